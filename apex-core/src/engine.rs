@@ -1,13 +1,113 @@
+pub mod affinity;
+pub mod auction;
+pub mod basket;
 pub mod book;
+pub mod bootstrap;
+pub mod builder;
+pub mod clock;
+pub mod config;
+pub mod correction;
+pub mod crossed;
+pub mod depth;
+pub mod digest;
+pub mod dropcopy;
 pub mod error;
+pub mod execution;
+pub mod expiry;
+pub mod fix;
+pub mod fuzz;
+pub mod history;
+pub mod id;
+pub mod idempotency;
+pub mod itch;
+pub mod latency;
+pub mod lifecycle;
+pub mod listener;
 pub mod matching;
+pub mod metrics;
+pub mod observer;
+pub mod offbook;
+pub mod position;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+pub mod queue;
+pub mod quote;
+pub mod raft;
+pub mod rate;
+pub mod reference;
+pub mod replay;
+pub mod replicated;
+pub mod rescale;
+pub mod resend;
+pub mod risk;
+pub mod scheduler;
+pub mod shadow;
+pub mod shard;
+pub mod sim;
+pub mod snapshot;
+pub mod speedbump;
+pub mod spread;
+pub mod standby;
+pub mod surveillance;
 pub mod syncer;
+pub mod tape;
+pub mod trigger;
 pub mod types;
 
 pub mod prelude {
+    pub use super::affinity::*;
+    pub use super::auction::*;
+    pub use super::basket::*;
     pub use super::book::*;
+    pub use super::bootstrap::*;
+    pub use super::builder::*;
+    pub use super::clock::*;
+    pub use super::config::*;
+    pub use super::correction::*;
+    pub use super::crossed::*;
+    pub use super::depth::*;
+    pub use super::digest::*;
+    pub use super::dropcopy::*;
     pub use super::error::*;
+    pub use super::execution::*;
+    pub use super::expiry::*;
+    pub use super::fix::*;
+    pub use super::fuzz::*;
+    pub use super::history::*;
+    pub use super::id::*;
+    pub use super::idempotency::*;
+    pub use super::itch::*;
+    pub use super::latency::*;
+    pub use super::lifecycle::*;
+    pub use super::listener::*;
     pub use super::matching::*;
+    pub use super::metrics::*;
+    pub use super::observer::*;
+    pub use super::offbook::*;
+    pub use super::position::*;
+    #[cfg(feature = "prometheus")]
+    pub use super::prometheus::*;
+    pub use super::queue::*;
+    pub use super::quote::*;
+    pub use super::raft::*;
+    pub use super::rate::*;
+    pub use super::reference::*;
+    pub use super::replay::*;
+    pub use super::replicated::*;
+    pub use super::rescale::*;
+    pub use super::resend::*;
+    pub use super::risk::*;
+    pub use super::scheduler::*;
+    pub use super::shadow::*;
+    pub use super::shard::*;
+    pub use super::sim::*;
+    pub use super::snapshot::*;
+    pub use super::speedbump::*;
+    pub use super::spread::*;
+    pub use super::standby::*;
+    pub use super::surveillance::*;
     pub use super::syncer::*;
+    pub use super::tape::*;
+    pub use super::trigger::*;
     pub use super::types::*;
 }
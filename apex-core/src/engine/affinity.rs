@@ -0,0 +1,100 @@
+use core_affinity::CoreId;
+
+/// AffinityConfig describes which cores the engine threads are pinned to.
+///
+/// Pinning keeps the matching threads from migrating between cores,
+/// which otherwise hurts the tail latency of the skiplist walks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AffinityConfig {
+    /// Cores for the matching threads; shard `i` is pinned to the `i`-th core (wrapping).
+    /// When empty, the cores of `numa_node` are used instead.
+    pub matching_cores: Vec<usize>,
+    /// Core for the syncer thread.
+    pub syncer_core: Option<usize>,
+    /// NUMA node whose cores are used for the matching threads when `matching_cores` is empty.
+    pub numa_node: Option<usize>,
+}
+
+impl AffinityConfig {
+    /// Gets the cores that matching threads can be pinned to
+    pub fn resolved_matching_cores(&self) -> Vec<usize> {
+        if !self.matching_cores.is_empty() {
+            return self.matching_cores.clone();
+        }
+        match self.numa_node {
+            Some(node) => numa_node_cores(node),
+            None => Vec::new(),
+        }
+    }
+
+    /// Gets the core for the matching thread of a shard
+    pub fn matching_core(&self, shard: usize) -> Option<usize> {
+        let cores = self.resolved_matching_cores();
+        if cores.is_empty() {
+            return None;
+        }
+        Some(cores[shard % cores.len()])
+    }
+
+    /// Pins the calling thread as the matching thread of a shard.
+    /// Returns false if no core is configured or pinning failed.
+    pub fn pin_matching_thread(&self, shard: usize) -> bool {
+        match self.matching_core(shard) {
+            Some(core) => pin_current_thread(core),
+            None => false,
+        }
+    }
+
+    /// Pins the calling thread as the syncer thread.
+    /// Returns false if no core is configured or pinning failed.
+    pub fn pin_syncer_thread(&self) -> bool {
+        match self.syncer_core {
+            Some(core) => pin_current_thread(core),
+            None => false,
+        }
+    }
+}
+
+/// Gets the ids of the cores the process is allowed to run on
+pub fn available_cores() -> Vec<usize> {
+    core_affinity::get_core_ids()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|core| core.id)
+        .collect()
+}
+
+/// Pins the calling thread to a core
+pub fn pin_current_thread(core: usize) -> bool {
+    core_affinity::set_for_current(CoreId { id: core })
+}
+
+/// Gets the cores of a NUMA node.
+/// Returns an empty list if the platform does not expose the NUMA topology.
+pub fn numa_node_cores(node: usize) -> Vec<usize> {
+    let path = format!("/sys/devices/system/node/node{node}/cpulist");
+    match std::fs::read_to_string(path) {
+        Ok(list) => parse_cpu_list(&list),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Parses a Linux cpu list such as `0-3,8,10-11`
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cores = Vec::new();
+    for part in list.trim().split(',').filter(|part| !part.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                    cores.extend(start..=end);
+                }
+            }
+            None => {
+                if let Ok(core) = part.parse() {
+                    cores.push(core);
+                }
+            }
+        }
+    }
+    cores
+}
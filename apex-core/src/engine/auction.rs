@@ -0,0 +1,112 @@
+use crate::prelude::*;
+use crypto_bigint::Zero;
+
+/// TieBreak is the rule that decided an auction price among the candidate prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// A single price executes the most volume.
+    MaxVolume,
+    /// Of the prices executing the most volume, a single one leaves the least imbalance.
+    MinImbalance,
+    /// Of those, a single one is closest to the reference price.
+    ReferencePrice,
+    /// The candidates are still tied, or there is no reference price,
+    /// and the lowest of them is taken.
+    LowestPrice,
+}
+
+/// AuctionPrice is the price an auction uncrosses at and what trades at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuctionPrice {
+    pub price: Price,
+    /// Quantity executed at the price.
+    pub volume: Quantity,
+    /// Quantity left unexecuted on the heavier side at the price.
+    pub imbalance: Quantity,
+    /// The heavier side, None when the price leaves no imbalance.
+    pub imbalance_side: Option<Side>,
+    /// The rule that decided the price.
+    pub rule: TieBreak,
+}
+
+/// Finds the price that uncrosses an auction book.
+///
+/// Every price of a level is a candidate. The candidate executing the most volume wins;
+/// ties go to the one leaving the least imbalance, then to the one closest to the reference
+/// price, and finally to the lowest. Levels are given best price first, as from
+/// `OrderBook::depth`. None if no bid crosses an ask.
+pub fn uncross_price(
+    bids: &[PriceLevel],
+    asks: &[PriceLevel],
+    reference: Option<Price>,
+) -> Option<AuctionPrice> {
+    let mut candidates: Vec<AuctionPrice> = bids
+        .iter()
+        .chain(asks.iter())
+        .map(|level| level.price)
+        .map(|price| {
+            let demand = bids
+                .iter()
+                .filter(|level| level.price >= price)
+                .fold(Quantity::ZERO, |total, level| {
+                    total.saturating_add(&level.quantity)
+                });
+            let supply = asks
+                .iter()
+                .filter(|level| level.price <= price)
+                .fold(Quantity::ZERO, |total, level| {
+                    total.saturating_add(&level.quantity)
+                });
+            let (imbalance, imbalance_side) = if demand > supply {
+                (demand.saturating_sub(&supply), Some(Side::Buy))
+            } else if supply > demand {
+                (supply.saturating_sub(&demand), Some(Side::Sell))
+            } else {
+                (Quantity::ZERO, None)
+            };
+            AuctionPrice {
+                price,
+                volume: demand.min(supply),
+                imbalance,
+                imbalance_side,
+                rule: TieBreak::MaxVolume,
+            }
+        })
+        .filter(|candidate| !bool::from(candidate.volume.is_zero()))
+        .collect();
+    candidates.sort_unstable_by_key(|candidate| candidate.price);
+    candidates.dedup_by_key(|candidate| candidate.price);
+
+    let volume = candidates.iter().map(|candidate| candidate.volume).max()?;
+    candidates.retain(|candidate| candidate.volume == volume);
+    let mut rule = TieBreak::MaxVolume;
+    if candidates.len() > 1 {
+        rule = TieBreak::MinImbalance;
+        let imbalance = candidates
+            .iter()
+            .map(|candidate| candidate.imbalance)
+            .min()?;
+        candidates.retain(|candidate| candidate.imbalance == imbalance);
+    }
+    if let Some(reference) = reference.filter(|_| candidates.len() > 1) {
+        rule = TieBreak::ReferencePrice;
+        let distance = |price: Price| {
+            if price > reference {
+                price.saturating_sub(&reference)
+            } else {
+                reference.saturating_sub(&price)
+            }
+        };
+        let closest = candidates
+            .iter()
+            .map(|candidate| distance(candidate.price))
+            .min()?;
+        candidates.retain(|candidate| distance(candidate.price) == closest);
+    }
+    if candidates.len() > 1 {
+        rule = TieBreak::LowestPrice;
+    }
+    candidates
+        .first()
+        .map(|candidate| AuctionPrice { rule, ..*candidate })
+}
@@ -0,0 +1,156 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// BasketChild is one order of a basket together with the symbol of the book it executes in.
+#[derive(Debug, Clone)]
+pub struct BasketChild {
+    pub symbol: SymbolID,
+    pub order: Order,
+}
+
+impl BasketChild {
+    /// Creates a new basket child
+    pub fn new(symbol: SymbolID, order: Order) -> Self {
+        Self { symbol, order }
+    }
+}
+
+/// BasketFill is the execution of one basket child.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasketFill {
+    pub symbol: SymbolID,
+    pub order_id: OrderID,
+    /// Both sides of every trade of the child.
+    pub trades: Vec<Trade>,
+}
+
+/// BasketRouter executes fill-or-kill baskets across the books of several symbols.
+///
+/// Either every child of a basket is filled for its whole quantity or none of them trades.
+/// Limit children fill at their limit price or better, market children within their slippage
+/// tolerance. Children never rest in a book, and a resting order claimed by one child
+/// is not shared with another child of the same basket.
+#[derive(Default)]
+pub struct BasketRouter {
+    books: HashMap<SymbolID, Arc<DefaultMatchingEngine>>,
+}
+
+impl BasketRouter {
+    /// Creates a new router without books
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the engine of a symbol's book
+    pub fn with_book(mut self, symbol: SymbolID, engine: Arc<DefaultMatchingEngine>) -> Self {
+        self.books.insert(symbol, engine);
+        self
+    }
+
+    /// Executes a basket and returns the fill of every child, in basket order.
+    ///
+    /// Every child is first admitted by its book, then the resting liquidity of all children is
+    /// claimed across the books, and only then are they filled. If any child is rejected or
+    /// cannot be filled in full, every claim is released and every child is rejected;
+    /// the failing child with its own reason, the others with `RejectReason::BasketRejected`.
+    /// The children of one book are synchronized as a single match.
+    pub fn execute(&self, children: &mut [BasketChild]) -> Result<Vec<BasketFill>, BasketError> {
+        let mut engines = Vec::with_capacity(children.len());
+        for child in children.iter() {
+            let engine = self
+                .books
+                .get(&child.symbol)
+                .ok_or(BasketError::UnknownSymbol(child.symbol))?;
+            engines.push(engine.as_ref());
+        }
+
+        for index in 0..children.len() {
+            if let Err(reason) = engines[index].admit(&mut children[index].order) {
+                return Err(Self::reject(children, &engines, index, reason));
+            }
+        }
+
+        // Claim the liquidity of every child before filling any
+        let mut claims: Vec<Vec<OrderID>> = Vec::with_capacity(children.len());
+        for (index, (child, engine)) in children.iter().zip(&engines).enumerate() {
+            match Self::claim(engine, &child.order) {
+                Some(order_id_list) => claims.push(order_id_list),
+                None => {
+                    for (order_id_list, engine) in claims.iter().zip(&engines) {
+                        engine.release_book_liquidity(order_id_list);
+                    }
+                    let reason = RejectReason::InsufficientLiquidity;
+                    return Err(Self::reject(children, &engines, index, reason));
+                }
+            }
+        }
+
+        let mut trades = vec![Vec::new(); children.len()];
+        let mut symbols: Vec<SymbolID> = Vec::new();
+        for child in children.iter() {
+            if !symbols.contains(&child.symbol) {
+                symbols.push(child.symbol);
+            }
+        }
+        for symbol in symbols {
+            let indices: Vec<usize> = (0..children.len())
+                .filter(|&index| children[index].symbol == symbol)
+                .collect();
+            let takers: Vec<(&Order, Vec<OrderID>)> = indices
+                .iter()
+                .map(|&index| (&children[index].order, std::mem::take(&mut claims[index])))
+                .collect();
+            let fills = self.books[&symbol].fill_locked_liquidity(&takers);
+            for (index, fill) in indices.into_iter().zip(fills) {
+                trades[index] = fill;
+            }
+        }
+
+        Ok(children
+            .iter()
+            .zip(trades)
+            .map(|(child, trades)| BasketFill {
+                symbol: child.symbol,
+                order_id: child.order.id,
+                trades,
+            })
+            .collect())
+    }
+
+    /// Claims the resting liquidity a child needs, None if its book cannot fill it in full
+    fn claim(engine: &DefaultMatchingEngine, order: &Order) -> Option<Vec<OrderID>> {
+        let (opposite_side, best_source) = match order.side {
+            Side::Buy => (Side::Sell, PriceSource::BestAsk),
+            Side::Sell => (Side::Buy, PriceSource::BestBid),
+        };
+        let limit_price = match order.order_type {
+            OrderType::Limit => Some(order.price),
+            OrderType::Market => engine
+                .reference_price(best_source)
+                .and_then(|price| order.slippage_bound_price(price)),
+        };
+        engine.lock_book_liquidity(opposite_side, order.quantity(), limit_price, order)
+    }
+
+    /// Rejects every child of a failed basket and returns the error of the failing one
+    fn reject(
+        children: &mut [BasketChild],
+        engines: &[&DefaultMatchingEngine],
+        failed: usize,
+        reason: RejectReason,
+    ) -> BasketError {
+        for (index, (child, engine)) in children.iter_mut().zip(engines).enumerate() {
+            let reason = if index == failed {
+                reason
+            } else {
+                RejectReason::BasketRejected
+            };
+            engine.reject_unplaced(&mut child.order, reason);
+        }
+        BasketError::Rejected {
+            order_id: children[failed].order.id,
+            reason,
+        }
+    }
+}
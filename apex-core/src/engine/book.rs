@@ -1,10 +1,14 @@
 use crate::prelude::*;
 use crossbeam::epoch;
+use crossbeam::epoch::Guard;
 use crossbeam::epoch::default_collector;
 use crossbeam_skiplist::SkipList;
-use flurry::HashMap;
+use crossbeam_skiplist::base::Entry;
+use crypto_bigint::NonZero;
+use flurry::{HashMap, HashMapRef, HashSet};
+use std::ops::{ControlFlow, RangeInclusive};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
 /// OrderBook is a trait for order book
 pub trait OrderBook {
@@ -17,14 +21,114 @@ pub trait OrderBook {
         new_price: Price,
         now_microseconds: u64,
     ) -> Result<(), UpdateOrderError>;
+    /// Amend the open quantity of an order in the order book
+    fn amend_quantity(
+        &self,
+        order_id: u64,
+        new_quantity: Quantity,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError>;
+    /// Apply an amend request to an order in the order book at once
+    fn amend_order(
+        &self,
+        order_id: u64,
+        request: &AmendRequest,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError>;
+    /// Return busted quantity to a resting order, keeping its time priority
+    fn restore_quantity(&self, order_id: u64, quantity: Quantity) -> Result<(), UpdateOrderError>;
     /// Remove an order from the order book
     fn remove(&self, order_id: u64) -> Result<(), CancelOrderError>;
+    /// Remove the resting limit order a user gave a client order id, and return its id.
+    /// Only the user's own orders are searched, so no user can cancel another's.
+    fn remove_client_order(
+        &self,
+        user_id: u64,
+        client_order_id: ClientOrderID,
+    ) -> Result<OrderID, CancelOrderError>;
+    /// Insert a batch of orders with one epoch pin and one syncer batch
+    fn insert_batch(&self, orders: &mut [Order]) -> Vec<Result<(), RejectReason>>;
+    /// Remove a batch of orders with one epoch pin and one syncer batch
+    fn remove_batch(&self, order_ids: &[OrderID]) -> Vec<Result<(), CancelOrderError>>;
+    /// Cancel every resting order on a side, optionally within a price band
+    fn cancel_where(&self, side: Side, price_range: Option<RangeInclusive<Price>>) -> Vec<OrderID>;
+    /// Reassign every resting limit order of a user to another user, keeping their priority,
+    /// and return the ids of the reassigned orders. Orders move one at a time, not atomically.
+    fn transfer_orders(&self, from_user_id: u64, to_user_id: u64) -> Vec<OrderID>;
+    /// Scale every price and quantity in the book at once, keeping the priority of every order,
+    /// and return the number of rescaled orders. Nothing changes if any order fails to scale.
+    fn rescale(&self, rescale: &Rescale) -> Result<usize, RescaleError>;
+    /// Re-key a resting order into another priority class, keeping its time priority
+    fn reprioritize(
+        &self,
+        order_id: OrderID,
+        priority_class: PriorityClass,
+    ) -> Result<(), UpdateOrderError>;
+    /// Re-key every resting regular order not refreshed for `max_quote_age_micros`
+    /// into `priority_class`, if it rests ahead of that class, and return the re-keyed ids
+    fn demote_stale(
+        &self,
+        now_microseconds: u64,
+        max_quote_age_micros: u64,
+        priority_class: PriorityClass,
+    ) -> Vec<OrderID>;
+    /// Expire every resting GoodTillDate order whose deadline is not after `now_microseconds`,
+    /// and every resting order accepted `max_resting_micros` or longer ago
+    fn expire_orders(&self, now_microseconds: u64, max_resting_micros: Option<u64>)
+    -> Vec<OrderID>;
+    /// Unlink finished orders that are still reachable from the queues,
+    /// drop index entries of orders that left the book, and hand the removed
+    /// entries to the epoch collector for reclamation.
+    /// Safe to run alongside inserts: an order being queued keeps its index entry.
+    fn compact(&self) -> CompactionReport;
+    /// Apply a batch of mixed commands with one epoch pin and one syncer batch
+    fn apply_batch(&self, commands: &mut [Command]) -> Vec<CommandResult>;
+    /// Get a resting limit order by id
+    fn get_order(&self, order_id: OrderID) -> Option<OrderView>;
+    /// Get the resting limit orders of a user
+    fn open_orders(&self, user_id: u64) -> Vec<OrderView>;
+    /// Visit the aggregated price levels of a side from the best price,
+    /// stopping early when `visit` breaks
+    fn for_each_level(&self, side: Side, visit: &mut dyn FnMut(&PriceLevel) -> ControlFlow<()>);
+    /// Visit the resting orders of a side in matching priority, stopping early when `visit` breaks.
+    /// TakerOnly orders are included; use `Order::provides_liquidity` to skip them.
+    fn for_each_order(&self, side: Side, visit: &mut dyn FnMut(&Order) -> ControlFlow<()>);
+    /// Get up to `max_levels` aggregated price levels of a side, best price first
+    fn depth(&self, side: Side, max_levels: usize) -> Vec<PriceLevel>;
+    /// Get up to `max_levels` aggregated price levels of both sides, best price first,
+    /// walked under one epoch pin
+    fn top_of_book(&self, max_levels: usize) -> TopOfBook;
+    /// Get the total quantity resting at a price
+    fn quantity_at(&self, side: Side, price: Price) -> Quantity;
+    /// Get the price level at a price, and the queue position of `order_id` at it if given
+    fn level_info(&self, side: Side, price: Price, order_id: Option<OrderID>) -> LevelInfo;
+    /// Get the total quantity resting from the best price up to and including `price`
+    fn cumulative_quantity_to(&self, side: Side, price: Price) -> Quantity;
+    /// Get a statistics snapshot of the book
+    fn stats(&self) -> BookStats;
+    /// Get an owned copy of the book that needs no epoch guard
+    fn snapshot(&self) -> BookSnapshot;
+    /// Get a full copy of the book that restores to an identical book
+    fn checkpoint(&self) -> BookCheckpoint;
+    /// Get the id the next synchronized event will carry
+    fn sequence(&self) -> u64;
+    /// Check the invariants of the book, describing the first one violated
+    fn check_invariants(&self) -> Result<(), String>;
+    /// Panic with a dump of the book if invariant checks are on and one is violated.
+    /// Called at quiescent points only, and skipped while a matching walk is in progress.
+    fn assert_invariants(&self);
     /// Get the best price for a side
     fn get_best_price(&self, side: Side) -> Option<Price>;
     /// Get the book
     fn get_book(&self, side: Side) -> &SkipList<BookKey, Order>;
     /// Sync orders that's matched and trades
     fn sync_matched(&self, updated: &[Order], trades: &[Trade]);
+    /// Sync a batch of order book events
+    fn sync_batch(&self, events: &[BookEvent]);
+    /// Sync an order that was rejected
+    fn sync_rejected(&self, order: &Order);
+    /// Sync a busted or price-corrected trade
+    fn sync_trade_corrected(&self, correction: &TradeCorrection);
 }
 
 /// WalkingResult is used for match engine walking results
@@ -99,6 +203,9 @@ pub trait MatchingEngineWalker {
 /// OrderBookWalker trait is used to walk the order book
 pub trait OrderBookWalker: Send + Sync + OrderBook + MatchingEngineWalker {}
 
+/// A claimed order together with its scaled price, quantity and filled quantity
+type RescaleClaim<'g, K> = (Entry<'g, 'g, K, Order>, (Price, Quantity, Quantity));
+
 /// DefaultOrderBook is the default implementation of the order book
 pub struct DefaultOrderBook {
     id: Arc<AtomicU64>,
@@ -111,6 +218,14 @@ pub struct DefaultOrderBook {
     sell_orders: SkipList<BookKey, Order>,
     // By order id for fast access order
     order_index: HashMap<OrderID, BookKey>,
+    // Ids claimed in the index whose orders are not queued yet
+    pending_inserts: HashSet<OrderID>,
+    // By user id and then order id for resting limit orders
+    user_orders: SkipList<(u64, OrderID), ()>,
+    // Whether quiescent points assert the invariants of the book
+    invariant_checks: bool,
+    // Walks in progress, which leave finished orders linked until they step past them
+    walks: AtomicUsize,
 }
 
 impl DefaultOrderBook {
@@ -120,6 +235,7 @@ impl DefaultOrderBook {
         let market_orders = SkipList::new(collector.clone());
         let buy_orders = SkipList::new(collector.clone());
         let sell_orders = SkipList::new(collector.clone());
+        let user_orders = SkipList::new(collector.clone());
         Self {
             id,
             syncer,
@@ -127,17 +243,158 @@ impl DefaultOrderBook {
             buy_orders,
             sell_orders,
             order_index: HashMap::new(),
+            pending_inserts: HashSet::new(),
+            user_orders,
+            invariant_checks: cfg!(feature = "invariants"),
+            walks: AtomicUsize::new(0),
         }
     }
-}
 
-impl OrderBook for DefaultOrderBook {
-    /// Insert order into the order book
-    fn insert(&self, order: &mut Order) -> Result<(), RejectReason> {
+    /// Sets whether the book asserts its invariants at quiescent points, panicking with a dump
+    /// of the book on the first violation. On by default with the `invariants` feature.
+    ///
+    /// Checks read the whole book, so they run only after batches, mass operations and
+    /// compaction, and at the end of every match cycle, never after single-order changes.
+    /// They are meant for tests, fuzzing and replays; with several threads mutating at once
+    /// a check may see another one halfway.
+    pub fn with_invariant_checks(mut self, enabled: bool) -> Self {
+        self.invariant_checks = enabled;
+        self
+    }
+
+    /// Restores an order book from a checkpoint.
+    ///
+    /// Every order is reinserted under the key it had, without syncing, and the next
+    /// synchronized event carries the sequence the checkpoint was taken at, so the restored
+    /// book matches and synchronizes exactly as the original would have.
+    pub fn restore(
+        id: Arc<AtomicU64>,
+        syncer: Arc<dyn OrderBookSyncer>,
+        checkpoint: &BookCheckpoint,
+    ) -> Self {
+        let book = Self::new(id, syncer);
+        book.id.store(checkpoint.sequence, Ordering::Release);
         let guard = &epoch::pin();
-        let order_index = self.order_index.pin();
+        let order_index = book.order_index.pin();
+        for (side, orders) in [
+            (&book.buy_orders, &checkpoint.bids),
+            (&book.sell_orders, &checkpoint.asks),
+        ] {
+            for (book_key, order) in orders {
+                side.insert(*book_key, order.clone(), guard);
+                order_index.insert(order.id, *book_key);
+                book.user_orders
+                    .insert((order.user_id, order.id), (), guard);
+            }
+        }
+        for (priority, order) in &checkpoint.market_orders {
+            book.market_orders.insert(*priority, order.clone(), guard);
+            order_index.insert(order.id, order.book_key());
+        }
+        drop(order_index);
+        book
+    }
+
+    /// Checks whether a price level is at or better than `price` for a side
+    #[inline(always)]
+    fn within(side: Side, level_price: Price, price: Price) -> bool {
+        match side {
+            Side::Buy => level_price >= price,
+            Side::Sell => level_price <= price,
+        }
+    }
+
+    /// Drops a limit order that left the book from the per-user index
+    #[inline(always)]
+    fn forget_user_order(&self, order: &Order, guard: &Guard) {
+        self.user_orders.remove(&(order.user_id, order.id), guard);
+    }
+
+    /// Visits the aggregated price levels of a side from the best price, under a pinned guard
+    fn visit_levels(
+        &self,
+        side: Side,
+        guard: &Guard,
+        visit: &mut dyn FnMut(&PriceLevel) -> ControlFlow<()>,
+    ) {
+        let mut level: Option<PriceLevel> = None;
+        for e in self.get_book(side).iter(guard) {
+            let (price, quantity) = (e.key().price, e.value().quantity());
+            match level.as_mut() {
+                Some(level) if level.price == price => {
+                    level.quantity = level.quantity.saturating_add(&quantity);
+                    level.orders += 1;
+                }
+                _ => {
+                    let next = PriceLevel {
+                        price,
+                        quantity,
+                        orders: 1,
+                    };
+                    let done = level.replace(next);
+                    if done.is_some_and(|done| visit(&done).is_break()) {
+                        return;
+                    }
+                }
+            }
+        }
+        if let Some(done) = level {
+            let _ = visit(&done);
+        }
+    }
+
+    /// Gets up to `max_levels` aggregated price levels of a side, under a pinned guard
+    fn top_levels(&self, side: Side, max_levels: usize, guard: &Guard) -> Vec<PriceLevel> {
+        let mut levels = Vec::new();
+        if max_levels == 0 {
+            return levels;
+        }
+        self.visit_levels(side, guard, &mut |level| {
+            levels.push(*level);
+            if levels.len() < max_levels {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        levels
+    }
+}
 
+impl DefaultOrderBook {
+    /// Inserts an order into the book without syncing it
+    fn insert_entry(
+        &self,
+        order: &mut Order,
+        guard: &Guard,
+        order_index: &HashMapRef<'_, OrderID, BookKey>,
+    ) -> Result<(), RejectReason> {
         let book_key = order.book_key();
+        let pending_inserts = self.pending_inserts.pin();
+        // Mark the insert pending so compaction keeps the index entry until it is queued
+        let marked = pending_inserts.insert(order.id);
+        // Claim the id first so a duplicate never reaches the book
+        if !marked || order_index.try_insert(order.id, book_key).is_err() {
+            if marked {
+                pending_inserts.remove(&order.id);
+            }
+            order.update_status(OrderStatus::Rejected);
+            order.update_reject_reason(RejectReason::DuplicateOrderId);
+            return Err(RejectReason::DuplicateOrderId);
+        }
+        let result = self.queue_entry(order, book_key, guard, order_index);
+        pending_inserts.remove(&order.id);
+        result
+    }
+
+    /// Queues an order whose id is already claimed in the index
+    fn queue_entry(
+        &self,
+        order: &mut Order,
+        book_key: BookKey,
+        guard: &Guard,
+        order_index: &HashMapRef<'_, OrderID, BookKey>,
+    ) -> Result<(), RejectReason> {
         match order.order_type {
             OrderType::Limit => {
                 let book = match order.side {
@@ -146,32 +403,109 @@ impl OrderBook for DefaultOrderBook {
                 };
 
                 order.update_status(OrderStatus::Placed);
-                book.get_or_insert(book_key, order.clone(), guard);
+                let entry = book.get_or_insert(book_key, order.clone(), guard);
+                if entry.value().id != order.id {
+                    return Err(Self::reject_conflict(order, order_index));
+                }
+                self.user_orders
+                    .get_or_insert((order.user_id, order.id), (), guard);
             }
             OrderType::Market => {
                 order.update_status(OrderStatus::Placed);
-                self.market_orders
-                    .get_or_insert(order.priority(), order.clone(), guard);
+                let entry =
+                    self.market_orders
+                        .get_or_insert(order.priority(), order.clone(), guard);
+                if entry.value().id != order.id {
+                    return Err(Self::reject_conflict(order, order_index));
+                }
             }
         };
-        order_index.insert(order.id, book_key);
-        let id = self.id.fetch_add(1, Ordering::Acquire);
-        self.syncer.add_order(id, order);
+        Ok(())
+    }
+
+    /// Rejects an order whose key is already taken by another order, releasing its id
+    fn reject_conflict(
+        order: &mut Order,
+        order_index: &HashMapRef<'_, OrderID, BookKey>,
+    ) -> RejectReason {
+        order_index.remove(&order.id);
+        order.update_status(OrderStatus::Rejected);
+        order.update_reject_reason(RejectReason::TimestampConflict);
+        RejectReason::TimestampConflict
+    }
+
+    /// Re-inserts a re-keyed limit order, moving it back a microsecond at a time
+    /// while its key collides with another resting order, and returns its final key
+    fn reinsert_entry(&self, order: &mut Order, guard: &Guard) -> BookKey {
+        let book = match order.side {
+            Side::Buy => &self.buy_orders,
+            Side::Sell => &self.sell_orders,
+        };
+        loop {
+            let book_key = order.book_key();
+            let entry = book.get_or_insert(book_key, order.clone(), guard);
+            if entry.value().id == order.id {
+                return book_key;
+            }
+            order.updated_at += 1;
+        }
+    }
 
+    /// Claims every live order of a queue and scales it, stopping at the first failure.
+    /// Claimed orders are pushed to `claimed` together with their scaled price and quantities.
+    fn claim_rescaled<'g, K: Ord + Send + 'static>(
+        queue: &'g SkipList<K, Order>,
+        rescale: &Rescale,
+        guard: &'g Guard,
+        claimed: &mut Vec<RescaleClaim<'g, K>>,
+    ) -> Result<(), RescaleError> {
+        for entry in queue.iter(guard) {
+            let order = entry.value();
+            if order.is_finished() {
+                continue;
+            }
+            if !order.enter_matched() {
+                return Err(RescaleError::OrderInFlight(order.id));
+            }
+            let Some(scaled) = rescale.apply(order) else {
+                order.exit_matched();
+                return Err(RescaleError::Inexact(order.id));
+            };
+            claimed.push((entry, scaled));
+        }
         Ok(())
     }
 
-    /// Updates an order in the order book
-    fn update_order(
+    /// Gets the update error of an indexed order missing from the limit queues,
+    /// telling a queued market order apart from one that left the book
+    fn missing_entry(
+        &self,
+        book_key: &BookKey,
+        order_id: OrderID,
+        guard: &Guard,
+    ) -> UpdateOrderError {
+        let queued = self
+            .market_orders
+            .get(&book_key.priority, guard)
+            .is_some_and(|e| e.value().id == order_id);
+        if queued {
+            UpdateOrderError::MarketOrderNotAmendable
+        } else {
+            UpdateOrderError::OrderNotFound
+        }
+    }
+
+    /// Re-prices an order in the book without syncing it, returning the re-inserted order
+    fn update_entry(
         &self,
         order_id: u64,
         new_price: Price,
         now_microseconds: u64,
-    ) -> Result<(), UpdateOrderError> {
-        let guard = &epoch::pin();
-        let order_index = self.order_index.pin();
+        guard: &Guard,
+        order_index: &HashMapRef<'_, OrderID, BookKey>,
+    ) -> Result<Order, UpdateOrderError> {
         let book_key = order_index.get(&order_id);
-        let mut book_key = match book_key {
+        let book_key = match book_key {
             Some(book_key) => *book_key,
             None => return Err(UpdateOrderError::OrderNotFound),
         };
@@ -182,12 +516,18 @@ impl OrderBook for DefaultOrderBook {
         };
         let order_entry = match order_entry_opt {
             Some(order_entry) => order_entry,
-            None => return Err(UpdateOrderError::OrderNotFound),
+            None => return Err(self.missing_entry(&book_key, order_id, guard)),
         };
 
         let book_order = order_entry.value();
+        if new_price == Price::ZERO {
+            return Err(UpdateOrderError::invalid(
+                AmendFailure::InvalidPrice,
+                OrderView::from(book_order),
+            ));
+        }
         if !book_order.enter_finished_from_active() {
-            return Err(UpdateOrderError::OrderNotModifiable);
+            return Err(UpdateOrderError::not_modifiable(book_order));
         }
 
         let mut book_order = book_order.clone();
@@ -196,30 +536,70 @@ impl OrderBook for DefaultOrderBook {
 
         // Set price、lifecycle before making visible in the book
         book_order.price = new_price;
-        book_order.updated_at = now_microseconds;
+        book_order.retime(now_microseconds);
         book_order.reset_lifecycle();
-        book_key = book_order.book_key();
 
         // Insert into the book after lifecycle is set
-        match book_order.side {
-            Side::Buy => self.buy_orders.insert(book_key, book_order.clone(), guard),
-            Side::Sell => self.sell_orders.insert(book_key, book_order.clone(), guard),
-        };
+        let book_key = self.reinsert_entry(&mut book_order, guard);
         order_index.insert(book_order.id, book_key);
-        let id = self.id.fetch_add(1, Ordering::Acquire);
-        self.syncer.update_order(id, &book_order);
+        Ok(book_order)
+    }
 
-        Ok(())
+    /// Re-keys an order into another priority class without syncing it,
+    /// returning the re-inserted order. Its time priority is kept.
+    fn rekey_entry(
+        &self,
+        order_id: u64,
+        priority_class: PriorityClass,
+        guard: &Guard,
+        order_index: &HashMapRef<'_, OrderID, BookKey>,
+    ) -> Result<Order, UpdateOrderError> {
+        let book_key = match order_index.get(&order_id) {
+            Some(book_key) => *book_key,
+            None => return Err(UpdateOrderError::OrderNotFound),
+        };
+        let order_entry_opt = match book_key.side {
+            Side::Buy => self.buy_orders.get(&book_key, guard),
+            Side::Sell => self.sell_orders.get(&book_key, guard),
+        };
+        let order_entry = match order_entry_opt {
+            Some(order_entry) => order_entry,
+            None => return Err(self.missing_entry(&book_key, order_id, guard)),
+        };
+
+        let book_order = order_entry.value();
+        if !book_order.enter_finished_from_active() {
+            return Err(UpdateOrderError::not_modifiable(book_order));
+        }
+
+        let mut book_order = book_order.clone();
+        order_index.remove(&order_id);
+        order_entry.remove();
+
+        book_order.priority_class = Some(priority_class);
+        book_order.reset_lifecycle();
+
+        let book_key = self.reinsert_entry(&mut book_order, guard);
+        order_index.insert(book_order.id, book_key);
+        Ok(book_order)
     }
 
-    /// remove an order from the order book
-    fn remove(&self, order_id: u64) -> Result<(), CancelOrderError> {
-        let guard = &epoch::pin();
-        let order_index = self.order_index.pin();
+    /// Amends the open quantity of an order without syncing it, returning the amended order.
+    ///
+    /// A decrease is applied in place and keeps the order's time priority,
+    /// while an increase re-times the order to the back of its price level.
+    fn amend_entry(
+        &self,
+        order_id: u64,
+        new_quantity: Quantity,
+        now_microseconds: u64,
+        guard: &Guard,
+        order_index: &HashMapRef<'_, OrderID, BookKey>,
+    ) -> Result<Order, UpdateOrderError> {
         let book_key = order_index.get(&order_id);
         let book_key = match book_key {
             Some(book_key) => *book_key,
-            None => return Err(CancelOrderError::OrderNotFound),
+            None => return Err(UpdateOrderError::OrderNotFound),
         };
 
         let order_entry_opt = match book_key.side {
@@ -228,81 +608,1151 @@ impl OrderBook for DefaultOrderBook {
         };
         let order_entry = match order_entry_opt {
             Some(order_entry) => order_entry,
-            None => return Err(CancelOrderError::OrderNotFound),
+            None => return Err(self.missing_entry(&book_key, order_id, guard)),
         };
 
         let book_order = order_entry.value();
+        if new_quantity == Quantity::ZERO {
+            return Err(UpdateOrderError::invalid(
+                AmendFailure::InvalidQuantity,
+                OrderView::from(book_order),
+            ));
+        }
+        if new_quantity <= book_order.quantity() {
+            // Claim the order so the matching thread cannot fill it mid-amend
+            if !book_order.enter_matched() {
+                return Err(UpdateOrderError::not_modifiable(book_order));
+            }
+            book_order.update_quantity(new_quantity);
+            book_order.exit_matched();
+            return Ok(book_order.clone());
+        }
+
         if !book_order.enter_finished_from_active() {
-            return Err(CancelOrderError::OrderNotCancellable);
+            return Err(UpdateOrderError::not_modifiable(book_order));
         }
 
-        order_entry.remove();
+        let mut book_order = book_order.clone();
         order_index.remove(&order_id);
-        let id = self.id.fetch_add(1, Ordering::Acquire);
-        self.syncer.cancel_order(id, book_order);
+        order_entry.remove();
 
-        Ok(())
+        // Set quantity、time、lifecycle before making visible in the book
+        book_order.update_quantity(new_quantity);
+        book_order.retime(now_microseconds);
+        book_order.reset_lifecycle();
+
+        let book_key = self.reinsert_entry(&mut book_order, guard);
+        order_index.insert(book_order.id, book_key);
+        Ok(book_order)
     }
 
-    /// Gets the best price for a side
-    fn get_best_price(&self, side: Side) -> Option<Price> {
-        let guard = &epoch::pin();
-        let entry = match side {
-            Side::Buy => self.buy_orders.front(guard),
-            Side::Sell => self.sell_orders.front(guard),
+    /// Applies an amend request to an order without syncing it, returning the amended order.
+    ///
+    /// A change that keeps the order's time priority and touches only the quantity is applied
+    /// in place; otherwise the order is re-inserted, re-timed only if it loses its priority.
+    fn modify_entry(
+        &self,
+        order_id: u64,
+        request: &AmendRequest,
+        now_microseconds: u64,
+        guard: &Guard,
+        order_index: &HashMapRef<'_, OrderID, BookKey>,
+    ) -> Result<Order, UpdateOrderError> {
+        let book_key = match order_index.get(&order_id) {
+            Some(book_key) => *book_key,
+            None => return Err(UpdateOrderError::OrderNotFound),
+        };
+        let order_entry_opt = match book_key.side {
+            Side::Buy => self.buy_orders.get(&book_key, guard),
+            Side::Sell => self.sell_orders.get(&book_key, guard),
+        };
+        let order_entry = match order_entry_opt {
+            Some(order_entry) => order_entry,
+            None => return Err(self.missing_entry(&book_key, order_id, guard)),
         };
-        entry.map(|entry| entry.key().price)
-    }
 
-    fn get_book(&self, side: Side) -> &SkipList<BookKey, Order> {
-        match side {
-            Side::Buy => &self.buy_orders,
-            Side::Sell => &self.sell_orders,
+        let book_order = order_entry.value();
+        let failure = if request.is_empty() {
+            Some(AmendFailure::EmptyRequest)
+        } else if request.price == Some(Price::ZERO) {
+            Some(AmendFailure::InvalidPrice)
+        } else if request.quantity == Some(Quantity::ZERO) {
+            Some(AmendFailure::InvalidQuantity)
+        } else if request.time_in_force.is_some_and(|time_in_force| {
+            !matches!(
+                time_in_force,
+                TimeInForce::GoodTillCancelled | TimeInForce::GoodTillDate(_)
+            )
+        }) {
+            Some(AmendFailure::InvalidTimeInForce)
+        } else {
+            None
+        };
+        if let Some(failure) = failure {
+            return Err(UpdateOrderError::invalid(
+                failure,
+                OrderView::from(book_order),
+            ));
+        }
+
+        let loses_priority = request.loses_priority(book_order);
+        if !loses_priority && request.time_in_force.is_none() {
+            // Claim the order so the matching thread cannot fill it mid-amend
+            if !book_order.enter_matched() {
+                return Err(UpdateOrderError::not_modifiable(book_order));
+            }
+            if let Some(quantity) = request.quantity {
+                book_order.update_quantity(quantity);
+            }
+            book_order.exit_matched();
+            return Ok(book_order.clone());
+        }
+
+        if !book_order.enter_finished_from_active() {
+            return Err(UpdateOrderError::not_modifiable(book_order));
+        }
+
+        let mut book_order = book_order.clone();
+        order_index.remove(&order_id);
+        order_entry.remove();
+
+        // Set every field、lifecycle before making visible in the book
+        if let Some(price) = request.price {
+            book_order.price = price;
+        }
+        if let Some(quantity) = request.quantity {
+            book_order.update_quantity(quantity);
+        }
+        if let Some(time_in_force) = request.time_in_force {
+            book_order.time_in_force = time_in_force;
+        }
+        if loses_priority {
+            book_order.retime(now_microseconds);
         }
+        book_order.reset_lifecycle();
+
+        let book_key = self.reinsert_entry(&mut book_order, guard);
+        order_index.insert(book_order.id, book_key);
+        Ok(book_order)
     }
 
-    /// Sync orders that are matched and trades
-    fn sync_matched(&self, updated: &[Order], trades: &[Trade]) {
-        let id = self.id.fetch_add(1, Ordering::Acquire);
-        self.syncer.matched(id, updated, trades);
+    /// Removes an order from the book without syncing it,
+    /// calling `removed` with the order before it is released.
+    /// With an `owner`, an order of another user is refused.
+    fn remove_entry<F: FnOnce(&Order)>(
+        &self,
+        order_id: u64,
+        owner: Option<u64>,
+        guard: &Guard,
+        order_index: &HashMapRef<'_, OrderID, BookKey>,
+        removed: F,
+    ) -> Result<(), CancelOrderError> {
+        let book_key = order_index.get(&order_id);
+        let book_key = match book_key {
+            Some(book_key) => *book_key,
+            None => return Err(CancelOrderError::OrderNotFound),
+        };
+
+        let order_entry_opt = match book_key.side {
+            Side::Buy => self.buy_orders.get(&book_key, guard),
+            Side::Sell => self.sell_orders.get(&book_key, guard),
+        };
+        let order_entry = match order_entry_opt {
+            Some(order_entry) => order_entry,
+            None => return Err(CancelOrderError::OrderNotFound),
+        };
+
+        let book_order = order_entry.value();
+        if owner.is_some_and(|user_id| user_id != book_order.user_id) {
+            return Err(CancelOrderError::NotOrderOwner);
+        }
+        if !book_order.enter_finished_from_active() {
+            return Err(CancelOrderError::OrderNotCancellable {
+                status: book_order.status(),
+                filled_quantity: book_order.filled_quantity(),
+            });
+        }
+
+        book_order.update_status(OrderStatus::Cancelled);
+        book_order.update_cancel_reason(CancelReason::UserRequest);
+        order_entry.remove();
+        order_index.remove(&order_id);
+        self.forget_user_order(book_order, guard);
+        removed(book_order);
+        Ok(())
     }
 }
 
-impl MatchingEngineWalker for DefaultOrderBook {
-    fn walking_market_book(&self, walk: &mut dyn FnMut(&Order) -> WalkingResult) {
+impl OrderBook for DefaultOrderBook {
+    /// Insert order into the order book
+    fn insert(&self, order: &mut Order) -> Result<(), RejectReason> {
         let guard = &epoch::pin();
-        let mut entry = self.market_orders.front(guard);
-        while let Some(e) = entry {
-            let order = e.value();
-            let result = walk(order);
-            if result.remove {
-                e.remove();
-            } else if result.exit {
-                break;
-            }
-            entry = e.next();
+        let order_index = self.order_index.pin();
+
+        if let Err(reason) = self.insert_entry(order, guard, &order_index) {
+            self.sync_rejected(order);
+            return Err(reason);
         }
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.add_order(id, order);
+
+        Ok(())
     }
 
-    fn walking_book_maker(
+    /// Updates an order in the order book
+    fn update_order(
         &self,
-        side: Side,
-        slip_price_option: Option<Price>,
-        walk: &mut dyn FnMut(&Order) -> WalkingResult,
-    ) {
+        order_id: u64,
+        new_price: Price,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError> {
         let guard = &epoch::pin();
-        let book = match side {
-            Side::Buy => &self.buy_orders,
-            Side::Sell => &self.sell_orders,
-        };
+        let order_index = self.order_index.pin();
 
-        let mut entry = book.front(guard);
-        while let Some(e) = entry {
-            let key = e.key();
-            let order = e.value();
+        let book_order =
+            self.update_entry(order_id, new_price, now_microseconds, guard, &order_index)?;
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.update_order(id, &book_order);
 
-            if order.liquidity_directive == LiquidityDirective::TakerOnly {
-                entry = e.next();
+        Ok(())
+    }
+
+    /// Amends the open quantity of an order in the order book
+    fn amend_quantity(
+        &self,
+        order_id: u64,
+        new_quantity: Quantity,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let book_order = self.amend_entry(
+            order_id,
+            new_quantity,
+            now_microseconds,
+            guard,
+            &order_index,
+        )?;
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.update_order(id, &book_order);
+
+        Ok(())
+    }
+
+    /// Applies an amend request to an order in the order book at once
+    fn amend_order(
+        &self,
+        order_id: u64,
+        request: &AmendRequest,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let book_order =
+            self.modify_entry(order_id, request, now_microseconds, guard, &order_index)?;
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.update_order(id, &book_order);
+
+        Ok(())
+    }
+
+    /// Returns busted quantity to a resting order, keeping its time priority
+    fn restore_quantity(&self, order_id: u64, quantity: Quantity) -> Result<(), UpdateOrderError> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let book_key = match order_index.get(&order_id) {
+            Some(book_key) => *book_key,
+            None => return Err(UpdateOrderError::OrderNotFound),
+        };
+        let order_entry_opt = match book_key.side {
+            Side::Buy => self.buy_orders.get(&book_key, guard),
+            Side::Sell => self.sell_orders.get(&book_key, guard),
+        };
+        let order_entry = match order_entry_opt {
+            Some(order_entry) => order_entry,
+            None => return Err(self.missing_entry(&book_key, order_id, guard)),
+        };
+
+        let book_order = order_entry.value();
+        // Claim the order so the matching thread cannot fill it mid-restore
+        if !book_order.enter_matched() {
+            return Err(UpdateOrderError::not_modifiable(book_order));
+        }
+        book_order.quantity_unfill(quantity);
+        if book_order.filled_quantity() == Quantity::ZERO {
+            book_order.update_status(OrderStatus::Placed);
+        } else {
+            book_order.update_status(OrderStatus::PartiallyFilled);
+        }
+        book_order.exit_matched();
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.update_order(id, book_order);
+
+        Ok(())
+    }
+
+    /// remove an order from the order book
+    fn remove(&self, order_id: u64) -> Result<(), CancelOrderError> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        self.remove_entry(order_id, None, guard, &order_index, |book_order| {
+            let id = self.id.fetch_add(1, Ordering::Acquire);
+            self.syncer.cancel_order(id, book_order);
+        })?;
+
+        Ok(())
+    }
+
+    /// Remove the resting limit order a user gave a client order id
+    fn remove_client_order(
+        &self,
+        user_id: u64,
+        client_order_id: ClientOrderID,
+    ) -> Result<OrderID, CancelOrderError> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let order_id = self
+            .user_orders
+            .range((user_id, OrderID::MIN)..=(user_id, OrderID::MAX), guard)
+            .map(|e| e.key().1)
+            .find(|order_id| {
+                let Some(book_key) = order_index.get(order_id) else {
+                    return false;
+                };
+                let order_entry_opt = match book_key.side {
+                    Side::Buy => self.buy_orders.get(book_key, guard),
+                    Side::Sell => self.sell_orders.get(book_key, guard),
+                };
+                order_entry_opt.is_some_and(|e| e.value().client_order_id == Some(client_order_id))
+            })
+            .ok_or(CancelOrderError::OrderNotFound)?;
+        self.remove_entry(order_id, Some(user_id), guard, &order_index, |book_order| {
+            let id = self.id.fetch_add(1, Ordering::Acquire);
+            self.syncer.cancel_order(id, book_order);
+        })?;
+        Ok(order_id)
+    }
+
+    /// Insert a batch of orders with one epoch pin and one syncer batch
+    fn insert_batch(&self, orders: &mut [Order]) -> Vec<Result<(), RejectReason>> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let mut events = Vec::with_capacity(orders.len());
+        let results = orders
+            .iter_mut()
+            .map(|order| {
+                let result = self.insert_entry(order, guard, &order_index);
+                match result {
+                    Ok(()) => events.push(BookEvent::Added(order.clone())),
+                    Err(_) => events.push(BookEvent::Rejected(order.clone())),
+                }
+                result
+            })
+            .collect();
+        self.sync_batch(&events);
+        self.assert_invariants();
+        results
+    }
+
+    /// Remove a batch of orders with one epoch pin and one syncer batch
+    fn remove_batch(&self, order_ids: &[OrderID]) -> Vec<Result<(), CancelOrderError>> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let mut events = Vec::with_capacity(order_ids.len());
+        let results = order_ids
+            .iter()
+            .map(|order_id| {
+                self.remove_entry(*order_id, None, guard, &order_index, |book_order| {
+                    events.push(BookEvent::Cancelled(book_order.clone()))
+                })
+            })
+            .collect();
+        self.sync_batch(&events);
+        self.assert_invariants();
+        results
+    }
+
+    /// Reassigns every resting limit order of a user to another user, keeping their priority.
+    /// Orders held by a concurrent match are skipped, so run it on the matching thread
+    /// for the transfer to be complete.
+    fn transfer_orders(&self, from_user_id: u64, to_user_id: u64) -> Vec<OrderID> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let order_ids: Vec<OrderID> = self
+            .user_orders
+            .range(
+                (from_user_id, OrderID::MIN)..=(from_user_id, OrderID::MAX),
+                guard,
+            )
+            .map(|e| e.key().1)
+            .collect();
+        let (mut events, mut transferred) = (Vec::new(), Vec::new());
+        for order_id in order_ids {
+            let book_key = match order_index.get(&order_id) {
+                Some(book_key) => *book_key,
+                None => continue,
+            };
+            let order_entry_opt = match book_key.side {
+                Side::Buy => self.buy_orders.get(&book_key, guard),
+                Side::Sell => self.sell_orders.get(&book_key, guard),
+            };
+            let order_entry = match order_entry_opt {
+                Some(order_entry) => order_entry,
+                None => continue,
+            };
+            let book_order = order_entry.value();
+            if !book_order.enter_finished_from_active() {
+                continue;
+            }
+
+            let mut book_order = book_order.clone();
+            order_entry.remove();
+            self.forget_user_order(&book_order, guard);
+
+            // The key does not depend on the owner, so the order goes back to its place
+            book_order.user_id = to_user_id;
+            book_order.reset_lifecycle();
+            let book_key = self.reinsert_entry(&mut book_order, guard);
+            order_index.insert(order_id, book_key);
+            self.user_orders
+                .get_or_insert((to_user_id, order_id), (), guard);
+            transferred.push(order_id);
+            events.push(BookEvent::Updated(book_order));
+        }
+        self.sync_batch(&events);
+        self.assert_invariants();
+        transferred
+    }
+
+    fn rescale(&self, rescale: &Rescale) -> Result<usize, RescaleError> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        // Claim and scale every order before changing any, so a failure leaves the book untouched
+        let (mut limits, mut markets) = (Vec::new(), Vec::new());
+        let claimed = Self::claim_rescaled(&self.buy_orders, rescale, guard, &mut limits)
+            .and_then(|()| Self::claim_rescaled(&self.sell_orders, rescale, guard, &mut limits))
+            .and_then(|()| Self::claim_rescaled(&self.market_orders, rescale, guard, &mut markets));
+        if let Err(error) = claimed {
+            for (entry, _) in &limits {
+                entry.value().exit_matched();
+            }
+            for (entry, _) in &markets {
+                entry.value().exit_matched();
+            }
+            return Err(error);
+        }
+        let rescaled = limits.len() + markets.len();
+
+        // Market orders are queued by time alone, so they are scaled in place
+        for (entry, (_, quantity, filled_quantity)) in markets {
+            let order = entry.value();
+            order.update_quantity(quantity);
+            order.filled_quantity.store(filled_quantity);
+            order.exit_matched();
+        }
+        // Exact scaling keeps prices apart and in order, so every limit order keeps its place.
+        // All of them leave the book first, so a scaled key never meets an unscaled one.
+        let mut orders = Vec::with_capacity(limits.len());
+        for (entry, (price, quantity, filled_quantity)) in limits {
+            let mut order = entry.value().clone();
+            entry.remove();
+            order.price = price;
+            order.update_quantity(quantity);
+            order.filled_quantity.store(filled_quantity);
+            order.reset_lifecycle();
+            orders.push(order);
+        }
+        for mut order in orders {
+            let book_key = self.reinsert_entry(&mut order, guard);
+            order_index.insert(order.id, book_key);
+        }
+
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.rescaled(id, rescale);
+        self.assert_invariants();
+        Ok(rescaled)
+    }
+
+    /// Cancel every resting order on a side, optionally within a price band
+    fn cancel_where(&self, side: Side, price_range: Option<RangeInclusive<Price>>) -> Vec<OrderID> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+        let book = match side {
+            Side::Buy => &self.buy_orders,
+            Side::Sell => &self.sell_orders,
+        };
+
+        let (mut events, mut cancelled) = (Vec::new(), Vec::new());
+        let mut entry = book.front(guard);
+        while let Some(e) = entry {
+            let price = e.key().price;
+            if let Some(range) = price_range.as_ref() {
+                // Buys are sorted by descending price and sells by ascending price,
+                // so once the walk leaves the band on the far side it can stop.
+                let past_band = match side {
+                    Side::Buy => price < *range.start(),
+                    Side::Sell => price > *range.end(),
+                };
+                if past_band {
+                    break;
+                }
+                if !range.contains(&price) {
+                    entry = e.next();
+                    continue;
+                }
+            }
+
+            let order = e.value();
+            if order.enter_finished_from_active() {
+                order.update_status(OrderStatus::Cancelled);
+                order.update_cancel_reason(CancelReason::MassCancel);
+                e.remove();
+                order_index.remove(&order.id);
+                self.forget_user_order(order, guard);
+                cancelled.push(order.id);
+                events.push(BookEvent::Cancelled(order.clone()));
+            }
+            entry = e.next();
+        }
+
+        self.sync_batch(&events);
+        self.assert_invariants();
+        cancelled
+    }
+
+    /// Re-keys a resting order into another priority class, keeping its time priority
+    fn reprioritize(
+        &self,
+        order_id: OrderID,
+        priority_class: PriorityClass,
+    ) -> Result<(), UpdateOrderError> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let book_order = self.rekey_entry(order_id, priority_class, guard, &order_index)?;
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.update_order(id, &book_order);
+
+        Ok(())
+    }
+
+    /// Re-keys stale regular orders behind fresher orders at their price.
+    /// Orders held by a concurrent match are skipped until the next pass.
+    fn demote_stale(
+        &self,
+        now_microseconds: u64,
+        max_quote_age_micros: u64,
+        priority_class: PriorityClass,
+    ) -> Vec<OrderID> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let stale: Vec<OrderID> = [&self.buy_orders, &self.sell_orders]
+            .into_iter()
+            .flat_map(|book| book.iter(guard))
+            .map(|e| e.value())
+            .filter(|order| {
+                order.class == OrderClass::Regular
+                    && order.priority_class() < priority_class
+                    && order.updated_at.saturating_add(max_quote_age_micros) <= now_microseconds
+            })
+            .map(|order| order.id)
+            .collect();
+        let (mut events, mut demoted) = (Vec::new(), Vec::new());
+        for order_id in stale {
+            if let Ok(book_order) = self.rekey_entry(order_id, priority_class, guard, &order_index)
+            {
+                demoted.push(order_id);
+                events.push(BookEvent::Updated(book_order));
+            }
+        }
+        self.sync_batch(&events);
+        self.assert_invariants();
+        demoted
+    }
+
+    /// Expire every resting GoodTillDate order whose deadline is not after `now_microseconds`
+    fn expire_orders(
+        &self,
+        now_microseconds: u64,
+        max_resting_micros: Option<u64>,
+    ) -> Vec<OrderID> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let (mut events, mut expired) = (Vec::new(), Vec::new());
+        for book in [&self.buy_orders, &self.sell_orders] {
+            for e in book.iter(guard) {
+                let order = e.value();
+                let reason = match order.time_in_force {
+                    TimeInForce::GoodTillDate(deadline) if deadline <= now_microseconds => {
+                        CancelReason::TimeInForceExpired
+                    }
+                    // The engine's own acceptance time, so a client cannot forge a longer life
+                    _ if max_resting_micros.is_some_and(|max| {
+                        let rested_from = order.accepted_at.unwrap_or(order.created_at);
+                        rested_from.saturating_add(max) <= now_microseconds
+                    }) =>
+                    {
+                        CancelReason::LifetimeExceeded
+                    }
+                    _ => continue,
+                };
+                if !order.enter_finished_from_active() {
+                    continue;
+                }
+                order.update_status(OrderStatus::Expired);
+                order.update_cancel_reason(reason);
+                e.remove();
+                order_index.remove(&order.id);
+                self.forget_user_order(order, guard);
+                expired.push(order.id);
+                events.push(BookEvent::Cancelled(order.clone()));
+            }
+        }
+
+        self.sync_batch(&events);
+        self.assert_invariants();
+        expired
+    }
+
+    fn compact(&self) -> CompactionReport {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+        let mut report = CompactionReport::default();
+
+        for book in [&self.buy_orders, &self.sell_orders] {
+            for e in book.iter(guard) {
+                let order = e.value();
+                if order.is_finished() && e.remove() {
+                    self.forget_user_order(order, guard);
+                    report.book_entries += 1;
+                }
+            }
+        }
+        for e in self.market_orders.iter(guard) {
+            if e.value().is_finished() && e.remove() {
+                report.book_entries += 1;
+            }
+        }
+
+        // Filled orders leave the queues during matching but keep their index entry
+        let pending_inserts = self.pending_inserts.pin();
+        let stale = order_index
+            .iter()
+            .filter(|(order_id, book_key)| {
+                if pending_inserts.contains(*order_id) {
+                    return false;
+                }
+                let is_live = |order: &Order| order.id == **order_id && !order.is_finished();
+                let resting = match book_key.side {
+                    Side::Buy => self.buy_orders.get(book_key, guard),
+                    Side::Sell => self.sell_orders.get(book_key, guard),
+                };
+                let live = match resting {
+                    Some(e) => is_live(e.value()),
+                    None => self
+                        .market_orders
+                        .get(&book_key.priority, guard)
+                        .is_some_and(|e| is_live(e.value())),
+                };
+                !live
+            })
+            .map(|(order_id, book_key)| (*order_id, *book_key))
+            .collect::<Vec<_>>();
+        for (order_id, book_key) in stale {
+            // Leave the entry alone if the order was re-keyed or re-inserted meanwhile
+            let mut removed = false;
+            order_index.compute_if_present(&order_id, |_, current| {
+                removed = *current == book_key;
+                (!removed).then_some(*current)
+            });
+            if removed {
+                report.index_entries += 1;
+            }
+        }
+
+        guard.flush();
+        self.assert_invariants();
+        report
+    }
+
+    /// Apply a batch of mixed commands with one epoch pin and one syncer batch
+    fn apply_batch(&self, commands: &mut [Command]) -> Vec<CommandResult> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let mut events = Vec::with_capacity(commands.len());
+        let results = commands
+            .iter_mut()
+            .map(|command| match command {
+                Command::Create(order) => {
+                    let result = self.insert_entry(order, guard, &order_index);
+                    match result {
+                        Ok(()) => events.push(BookEvent::Added(order.clone())),
+                        Err(_) => events.push(BookEvent::Rejected(order.clone())),
+                    }
+                    CommandResult::Created(result)
+                }
+                Command::Update {
+                    order_id,
+                    new_price,
+                    now_microseconds,
+                } => {
+                    let result = self.update_entry(
+                        *order_id,
+                        *new_price,
+                        *now_microseconds,
+                        guard,
+                        &order_index,
+                    );
+                    CommandResult::Updated(result.map(|book_order| {
+                        events.push(BookEvent::Updated(book_order));
+                    }))
+                }
+                Command::Amend {
+                    order_id,
+                    new_quantity,
+                    now_microseconds,
+                } => {
+                    let result = self.amend_entry(
+                        *order_id,
+                        *new_quantity,
+                        *now_microseconds,
+                        guard,
+                        &order_index,
+                    );
+                    CommandResult::Updated(result.map(|book_order| {
+                        events.push(BookEvent::Updated(book_order));
+                    }))
+                }
+                Command::AmendOrder {
+                    order_id,
+                    request,
+                    now_microseconds,
+                } => {
+                    let result = self.modify_entry(
+                        *order_id,
+                        request,
+                        *now_microseconds,
+                        guard,
+                        &order_index,
+                    );
+                    CommandResult::Updated(result.map(|book_order| {
+                        events.push(BookEvent::Updated(book_order));
+                    }))
+                }
+                Command::Cancel(order_id) => CommandResult::Cancelled(self.remove_entry(
+                    *order_id,
+                    None,
+                    guard,
+                    &order_index,
+                    |book_order| events.push(BookEvent::Cancelled(book_order.clone())),
+                )),
+            })
+            .collect();
+        self.sync_batch(&events);
+        self.assert_invariants();
+        results
+    }
+
+    /// Gets the best price for a side
+    fn get_best_price(&self, side: Side) -> Option<Price> {
+        let guard = &epoch::pin();
+        let entry = match side {
+            Side::Buy => self.buy_orders.front(guard),
+            Side::Sell => self.sell_orders.front(guard),
+        };
+        entry.map(|entry| entry.key().price)
+    }
+
+    fn get_book(&self, side: Side) -> &SkipList<BookKey, Order> {
+        match side {
+            Side::Buy => &self.buy_orders,
+            Side::Sell => &self.sell_orders,
+        }
+    }
+
+    /// Sync orders that are matched and trades
+    fn sync_matched(&self, updated: &[Order], trades: &[Trade]) {
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.matched(id, updated, trades);
+    }
+
+    /// Get a resting limit order by id
+    fn get_order(&self, order_id: OrderID) -> Option<OrderView> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+        let book_key = *order_index.get(&order_id)?;
+        let order_entry = match book_key.side {
+            Side::Buy => self.buy_orders.get(&book_key, guard),
+            Side::Sell => self.sell_orders.get(&book_key, guard),
+        }?;
+        Some(OrderView::from(order_entry.value()))
+    }
+
+    /// Get the resting limit orders of a user
+    fn open_orders(&self, user_id: u64) -> Vec<OrderView> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+
+        let mut views = Vec::new();
+        for e in self
+            .user_orders
+            .range((user_id, OrderID::MIN)..=(user_id, OrderID::MAX), guard)
+        {
+            let book_key = match order_index.get(&e.key().1) {
+                Some(book_key) => *book_key,
+                None => continue,
+            };
+            let order_entry_opt = match book_key.side {
+                Side::Buy => self.buy_orders.get(&book_key, guard),
+                Side::Sell => self.sell_orders.get(&book_key, guard),
+            };
+            if let Some(order_entry) = order_entry_opt {
+                views.push(OrderView::from(order_entry.value()));
+            }
+        }
+        views
+    }
+
+    /// Visits the aggregated price levels of a side from the best price
+    fn for_each_level(&self, side: Side, visit: &mut dyn FnMut(&PriceLevel) -> ControlFlow<()>) {
+        self.visit_levels(side, &epoch::pin(), visit);
+    }
+
+    /// Visits the resting orders of a side in matching priority
+    fn for_each_order(&self, side: Side, visit: &mut dyn FnMut(&Order) -> ControlFlow<()>) {
+        let guard = &epoch::pin();
+        for e in self.get_book(side).iter(guard) {
+            if visit(e.value()).is_break() {
+                return;
+            }
+        }
+    }
+
+    /// Get up to `max_levels` aggregated price levels of a side, best price first
+    fn depth(&self, side: Side, max_levels: usize) -> Vec<PriceLevel> {
+        self.top_levels(side, max_levels, &epoch::pin())
+    }
+
+    /// Get up to `max_levels` aggregated price levels of both sides, walked under one epoch pin
+    fn top_of_book(&self, max_levels: usize) -> TopOfBook {
+        let guard = &epoch::pin();
+        TopOfBook {
+            bids: self.top_levels(Side::Buy, max_levels, guard),
+            asks: self.top_levels(Side::Sell, max_levels, guard),
+        }
+    }
+
+    /// Get the total quantity resting at a price
+    fn quantity_at(&self, side: Side, price: Price) -> Quantity {
+        let mut quantity = Quantity::ZERO;
+        self.for_each_level(side, &mut |level| {
+            if level.price == price {
+                quantity = level.quantity;
+                return ControlFlow::Break(());
+            }
+            if Self::within(side, level.price, price) {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        quantity
+    }
+
+    /// Get the price level at a price, and the queue position of `order_id` at it if given
+    fn level_info(&self, side: Side, price: Price, order_id: Option<OrderID>) -> LevelInfo {
+        let mut info = LevelInfo {
+            level: PriceLevel {
+                price,
+                quantity: Quantity::ZERO,
+                orders: 0,
+            },
+            queue_position: None,
+        };
+        self.for_each_order(side, &mut |order| {
+            if order.price != price {
+                return if Self::within(side, order.price, price) {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                };
+            }
+            if order_id == Some(order.id) {
+                info.queue_position = Some(QueuePosition {
+                    orders_ahead: info.level.orders,
+                    quantity_ahead: info.level.quantity,
+                });
+            }
+            info.level.quantity = info.level.quantity.saturating_add(&order.quantity());
+            info.level.orders += 1;
+            ControlFlow::Continue(())
+        });
+        info
+    }
+
+    /// Get the total quantity resting from the best price up to and including `price`
+    fn cumulative_quantity_to(&self, side: Side, price: Price) -> Quantity {
+        let mut quantity = Quantity::ZERO;
+        self.for_each_level(side, &mut |level| {
+            if !Self::within(side, level.price, price) {
+                return ControlFlow::Break(());
+            }
+            quantity = quantity.saturating_add(&level.quantity);
+            ControlFlow::Continue(())
+        });
+        quantity
+    }
+
+    /// Get a statistics snapshot of the book
+    fn stats(&self) -> BookStats {
+        let guard = &epoch::pin();
+        let summarize = |book: &SkipList<BookKey, Order>| {
+            let best = book.front(guard).map(|e| e.key().price);
+            let (mut orders, mut volume) = (0, Quantity::ZERO);
+            for e in book.iter(guard) {
+                orders += 1;
+                volume = volume.saturating_add(&e.value().quantity());
+            }
+            (best, orders, volume)
+        };
+        let (best_bid, bid_orders, bid_volume) = summarize(&self.buy_orders);
+        let (best_ask, ask_orders, ask_volume) = summarize(&self.sell_orders);
+
+        let (spread, midpoint) = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => {
+                let midpoint = bid.shr_vartime(1) + ask.shr_vartime(1) + (bid & ask & Price::ONE);
+                (Some(ask.saturating_sub(&bid)), Some(midpoint))
+            }
+            _ => (None, None),
+        };
+
+        let total = bid_volume.saturating_add(&ask_volume);
+        let imbalance_bps = NonZero::new(total).into_option().map(|total| {
+            let bps = |volume: Quantity| {
+                let scaled = volume.saturating_mul(&Quantity::from(10_000u32)) / total;
+                scaled.as_words()[0] as i32
+            };
+            bps(bid_volume) - bps(ask_volume)
+        });
+
+        BookStats {
+            best_bid,
+            best_ask,
+            spread,
+            midpoint,
+            bid_orders,
+            ask_orders,
+            bid_volume,
+            ask_volume,
+            imbalance_bps,
+        }
+    }
+
+    /// Get the id the next synchronized event will carry
+    fn sequence(&self) -> u64 {
+        self.id.load(Ordering::Acquire)
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        let guard = &epoch::pin();
+        let order_index = self.order_index.pin();
+        let resting = |order: &Order, book_key: BookKey| {
+            if order.is_finished() {
+                return Err(format!(
+                    "order {} is finished but still reachable",
+                    order.id
+                ));
+            }
+            if !order.is_live() {
+                return Err(format!(
+                    "order {} rests with status {:?} and quantity {}",
+                    order.id,
+                    order.status(),
+                    order.quantity()
+                ));
+            }
+            if order_index.get(&order.id) != Some(&book_key) {
+                return Err(format!(
+                    "order {} is indexed as {:?} instead of {:?}",
+                    order.id,
+                    order_index.get(&order.id),
+                    book_key
+                ));
+            }
+            Ok(())
+        };
+
+        for side in [Side::Buy, Side::Sell] {
+            let mut previous: Option<BookKey> = None;
+            for e in self.get_book(side).iter(guard) {
+                let (book_key, order) = (*e.key(), e.value());
+                if book_key.side != side || order.side != side || order.price != book_key.price {
+                    return Err(format!(
+                        "order {} with side {:?} and price {} rests under {:?} on the {:?} side",
+                        order.id, order.side, order.price, book_key, side
+                    ));
+                }
+                if let Some(previous) = previous.filter(|previous| *previous >= book_key) {
+                    return Err(format!("{:?} is not after {:?}", book_key, previous));
+                }
+                previous = Some(book_key);
+                resting(order, book_key)?;
+                if self
+                    .user_orders
+                    .get(&(order.user_id, order.id), guard)
+                    .is_none()
+                {
+                    return Err(format!(
+                        "order {} is missing from the orders of user {}",
+                        order.id, order.user_id
+                    ));
+                }
+            }
+        }
+        for e in self.market_orders.iter(guard) {
+            resting(e.value(), e.value().book_key())?;
+        }
+
+        // Filled ids stay indexed until compaction, but must never resolve to another order
+        for (order_id, book_key) in order_index.iter() {
+            let found = match book_key.side {
+                Side::Buy => self.buy_orders.get(book_key, guard),
+                Side::Sell => self.sell_orders.get(book_key, guard),
+            };
+            if let Some(found) = found.filter(|e| e.value().id != *order_id) {
+                return Err(format!(
+                    "order {} is indexed as {:?}, which holds order {}",
+                    order_id,
+                    book_key,
+                    found.value().id
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn assert_invariants(&self) {
+        if !self.invariant_checks || self.walks.load(Ordering::Acquire) > 0 {
+            return;
+        }
+        if let Err(violation) = self.check_invariants() {
+            panic!(
+                "order book invariant violated at sequence {}: {}\n{:#?}",
+                self.sequence(),
+                violation,
+                self.snapshot()
+            );
+        }
+    }
+
+    fn checkpoint(&self) -> BookCheckpoint {
+        let guard = &epoch::pin();
+        let entries = |book: &SkipList<BookKey, Order>| {
+            book.iter(guard)
+                .map(|e| (*e.key(), e.value().clone()))
+                .collect()
+        };
+        BookCheckpoint {
+            sequence: self.sequence(),
+            bids: entries(&self.buy_orders),
+            asks: entries(&self.sell_orders),
+            market_orders: self
+                .market_orders
+                .iter(guard)
+                .map(|e| (*e.key(), e.value().clone()))
+                .collect(),
+        }
+    }
+
+    fn snapshot(&self) -> BookSnapshot {
+        let guard = &epoch::pin();
+        let views = |book: &SkipList<BookKey, Order>| {
+            book.iter(guard)
+                .map(|e| OrderView::from(e.value()))
+                .collect()
+        };
+        BookSnapshot {
+            bids: views(&self.buy_orders),
+            asks: views(&self.sell_orders),
+            market_orders: self
+                .market_orders
+                .iter(guard)
+                .map(|e| OrderView::from(e.value()))
+                .collect(),
+        }
+    }
+
+    /// Sync a batch of order book events
+    fn sync_batch(&self, events: &[BookEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.batch(id, events);
+    }
+
+    /// Sync an order that was rejected
+    fn sync_rejected(&self, order: &Order) {
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.reject_order(id, order);
+    }
+
+    fn sync_trade_corrected(&self, correction: &TradeCorrection) {
+        let id = self.id.fetch_add(1, Ordering::Acquire);
+        self.syncer.trade_corrected(id, correction);
+    }
+}
+
+impl MatchingEngineWalker for DefaultOrderBook {
+    fn walking_market_book(&self, walk: &mut dyn FnMut(&Order) -> WalkingResult) {
+        let guard = &epoch::pin();
+        self.walks.fetch_add(1, Ordering::AcqRel);
+        let mut entry = self.market_orders.front(guard);
+        while let Some(e) = entry {
+            let order = e.value();
+            let result = walk(order);
+            if result.remove {
+                e.remove();
+            } else if result.exit {
+                break;
+            }
+            entry = e.next();
+        }
+        self.walks.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    fn walking_book_maker(
+        &self,
+        side: Side,
+        slip_price_option: Option<Price>,
+        walk: &mut dyn FnMut(&Order) -> WalkingResult,
+    ) {
+        let guard = &epoch::pin();
+        self.walks.fetch_add(1, Ordering::AcqRel);
+        let book = match side {
+            Side::Buy => &self.buy_orders,
+            Side::Sell => &self.sell_orders,
+        };
+
+        let mut entry = book.front(guard);
+        while let Some(e) = entry {
+            let key = e.key();
+            let order = e.value();
+
+            if !order.provides_liquidity() {
+                entry = e.next();
                 continue;
             }
 
@@ -324,16 +1774,19 @@ impl MatchingEngineWalker for DefaultOrderBook {
             let result = walk(order);
             if result.remove {
                 e.remove();
+                self.forget_user_order(order, guard);
             } else if result.exit {
                 break;
             }
 
             entry = e.next();
         }
+        self.walks.fetch_sub(1, Ordering::AcqRel);
     }
 
     fn walking_cross_taker(&self, walk: &mut dyn FnMut(&Order) -> WalkingResult) {
         let guard = &epoch::pin();
+        self.walks.fetch_add(1, Ordering::AcqRel);
 
         let (mut buy_entry_opt, mut sell_entry_opt) =
             (self.buy_orders.front(guard), self.sell_orders.front(guard));
@@ -363,6 +1816,14 @@ impl MatchingEngineWalker for DefaultOrderBook {
                     let taker = match (buy_maker_only, sell_maker_only) {
                         (true, false) => sell_order,
                         (false, true) => buy_order,
+                        // A liquidation order always takes, otherwise the older order does
+                        _ if buy_order.class != sell_order.class => {
+                            if buy_order.class == OrderClass::Liquidation {
+                                buy_order
+                            } else {
+                                sell_order
+                            }
+                        }
                         _ => {
                             if buy_key.priority < sell_key.priority {
                                 buy_order
@@ -381,11 +1842,13 @@ impl MatchingEngineWalker for DefaultOrderBook {
                     if taker_is_buy {
                         if result.remove {
                             buy_entry.remove();
+                            self.forget_user_order(taker, guard);
                         }
                         buy_entry_opt = buy_entry.next();
                     } else {
                         if result.remove {
                             sell_entry.remove();
+                            self.forget_user_order(taker, guard);
                         }
                         sell_entry_opt = sell_entry.next();
                     }
@@ -412,6 +1875,7 @@ impl MatchingEngineWalker for DefaultOrderBook {
                     }
                     if result.remove {
                         buy_entry.remove();
+                        self.forget_user_order(buy_order, guard);
                     }
                     buy_entry_opt = buy_entry.next();
                 }
@@ -437,6 +1901,7 @@ impl MatchingEngineWalker for DefaultOrderBook {
                     }
                     if result.remove {
                         sell_entry.remove();
+                        self.forget_user_order(sell_order, guard);
                     }
                     sell_entry_opt = sell_entry.next();
                 }
@@ -444,6 +1909,7 @@ impl MatchingEngineWalker for DefaultOrderBook {
                 (None, None) => break, // unreachable theoretically
             }
         }
+        self.walks.fetch_sub(1, Ordering::AcqRel);
     }
 
     fn walking_by_order_id_list(
@@ -452,6 +1918,7 @@ impl MatchingEngineWalker for DefaultOrderBook {
         walk: &mut dyn FnMut(&Order) -> WalkingResult,
     ) {
         let guard = &epoch::pin();
+        self.walks.fetch_add(1, Ordering::AcqRel);
         let order_index = self.order_index.pin();
 
         for order_id in order_id_list {
@@ -474,10 +1941,12 @@ impl MatchingEngineWalker for DefaultOrderBook {
             let result = walk(order);
             if result.remove {
                 order_entry.remove();
+                self.forget_user_order(order, guard);
             } else if result.exit {
                 break;
             }
         }
+        self.walks.fetch_sub(1, Ordering::AcqRel);
     }
 }
 
@@ -0,0 +1,442 @@
+use crate::prelude::*;
+use crossbeam::epoch;
+use std::error::Error;
+use std::fmt;
+use std::ops::Bound;
+
+/// Leading bytes of an encoded snapshot chunk.
+const CHUNK_MAGIC: [u8; 4] = *b"APXS";
+/// Version of the chunk encoding.
+const CHUNK_VERSION: u8 = 1;
+/// Magic, version, section, last flag, sequence, index and order count.
+const HEADER_LEN: usize = 4 + 1 + 1 + 1 + 8 + 4 + 4;
+/// Ids, side, type, status, time in force, deadline, price, quantities and timestamps.
+const ORDER_LEN: usize = 8 + 8 + 1 + 1 + 1 + 1 + 8 + 32 * 3 + 8 + 8;
+
+/// SnapshotError is a failure to decode or assemble a snapshot stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The chunk ended before its declared content.
+    Truncated { length: usize },
+    /// The chunk does not start with the snapshot magic or has an unknown version.
+    InvalidHeader,
+    /// A field holds a value no variant is encoded as.
+    InvalidValue { field: &'static str, value: u8 },
+    /// A chunk arrived out of order or belongs to another stream.
+    OutOfOrder { expected: u32, received: u32 },
+    /// A chunk arrived after the final chunk of the stream.
+    AlreadyComplete,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Truncated { length } => {
+                write!(f, "snapshot chunk truncated at {length} bytes")
+            }
+            SnapshotError::InvalidHeader => f.write_str("invalid snapshot chunk header"),
+            SnapshotError::InvalidValue { field, value } => {
+                write!(f, "invalid {field} {value:#04x} in snapshot chunk")
+            }
+            SnapshotError::OutOfOrder { expected, received } => {
+                write!(f, "expected snapshot chunk {expected}, received {received}")
+            }
+            SnapshotError::AlreadyComplete => f.write_str("snapshot is already complete"),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+/// SnapshotSection is the part of the book a snapshot chunk holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotSection {
+    Bids,
+    Asks,
+    MarketOrders,
+}
+
+/// SnapshotChunk is one piece of a streamed book snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotChunk {
+    /// Book sequence when the stream started.
+    /// Live events from this sequence on complete the snapshot.
+    pub sequence: u64,
+    /// Position of the chunk in the stream, starting at 0.
+    pub index: u32,
+    pub section: SnapshotSection,
+    /// Orders of the section in book order.
+    pub orders: Vec<OrderView>,
+    /// Whether this is the final chunk of the stream.
+    pub last: bool,
+}
+
+impl SnapshotChunk {
+    /// Encodes the chunk into its binary wire format, integers little endian
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.orders.len() * ORDER_LEN);
+        bytes.extend_from_slice(&CHUNK_MAGIC);
+        bytes.push(CHUNK_VERSION);
+        bytes.push(match self.section {
+            SnapshotSection::Bids => 0,
+            SnapshotSection::Asks => 1,
+            SnapshotSection::MarketOrders => 2,
+        });
+        bytes.push(self.last as u8);
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        bytes.extend_from_slice(&self.index.to_le_bytes());
+        bytes.extend_from_slice(&(self.orders.len() as u32).to_le_bytes());
+        for order in &self.orders {
+            encode_order(order, &mut bytes);
+        }
+        bytes
+    }
+
+    /// Decodes a chunk from its binary wire format
+    pub fn decode(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(SnapshotError::Truncated {
+                length: bytes.len(),
+            });
+        }
+        if bytes[..4] != CHUNK_MAGIC || bytes[4] != CHUNK_VERSION {
+            return Err(SnapshotError::InvalidHeader);
+        }
+        let section = match bytes[5] {
+            0 => SnapshotSection::Bids,
+            1 => SnapshotSection::Asks,
+            2 => SnapshotSection::MarketOrders,
+            value => {
+                return Err(SnapshotError::InvalidValue {
+                    field: "section",
+                    value,
+                });
+            }
+        };
+        let count = read_u32(&bytes[19..]) as usize;
+        if bytes.len() != HEADER_LEN + count * ORDER_LEN {
+            return Err(SnapshotError::Truncated {
+                length: bytes.len(),
+            });
+        }
+        let orders = bytes[HEADER_LEN..]
+            .chunks_exact(ORDER_LEN)
+            .map(decode_order)
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            sequence: read_u64(&bytes[7..]),
+            index: read_u32(&bytes[15..]),
+            section,
+            orders,
+            last: bytes[6] != 0,
+        })
+    }
+}
+
+fn encode_order(order: &OrderView, bytes: &mut Vec<u8>) {
+    bytes.extend_from_slice(&order.id.to_le_bytes());
+    bytes.extend_from_slice(&order.user_id.to_le_bytes());
+    bytes.push(match order.side {
+        Side::Buy => 0,
+        Side::Sell => 1,
+    });
+    bytes.push(match order.order_type {
+        OrderType::Limit => 0,
+        OrderType::Market => 1,
+    });
+    bytes.push(match order.status {
+        OrderStatus::Pending => 0,
+        OrderStatus::Placed => 1,
+        OrderStatus::Filled => 2,
+        OrderStatus::PartiallyFilled => 3,
+        OrderStatus::Cancelled => 4,
+        OrderStatus::Rejected => 5,
+        OrderStatus::Expired => 6,
+    });
+    let (time_in_force, deadline) = match order.time_in_force {
+        TimeInForce::None => (0, 0),
+        TimeInForce::GoodTillCancelled => (1, 0),
+        TimeInForce::GoodTillDate(deadline) => (2, deadline),
+    };
+    bytes.push(time_in_force);
+    bytes.extend_from_slice(&deadline.to_le_bytes());
+    bytes.extend_from_slice(&order.price.to_le_bytes());
+    bytes.extend_from_slice(&order.quantity.to_le_bytes());
+    bytes.extend_from_slice(&order.filled_quantity.to_le_bytes());
+    bytes.extend_from_slice(&order.created_at.to_le_bytes());
+    bytes.extend_from_slice(&order.updated_at.to_le_bytes());
+}
+
+fn decode_order(bytes: &[u8]) -> Result<OrderView, SnapshotError> {
+    let invalid = |field, value| SnapshotError::InvalidValue { field, value };
+    let side = match bytes[16] {
+        0 => Side::Buy,
+        1 => Side::Sell,
+        value => return Err(invalid("side", value)),
+    };
+    let order_type = match bytes[17] {
+        0 => OrderType::Limit,
+        1 => OrderType::Market,
+        value => return Err(invalid("order type", value)),
+    };
+    let status = match bytes[18] {
+        0 => OrderStatus::Pending,
+        1 => OrderStatus::Placed,
+        2 => OrderStatus::Filled,
+        3 => OrderStatus::PartiallyFilled,
+        4 => OrderStatus::Cancelled,
+        5 => OrderStatus::Rejected,
+        6 => OrderStatus::Expired,
+        value => return Err(invalid("status", value)),
+    };
+    let time_in_force = match bytes[19] {
+        0 => TimeInForce::None,
+        1 => TimeInForce::GoodTillCancelled,
+        2 => TimeInForce::GoodTillDate(read_u64(&bytes[20..])),
+        value => return Err(invalid("time in force", value)),
+    };
+    Ok(OrderView {
+        id: read_u64(bytes),
+        user_id: read_u64(&bytes[8..]),
+        side,
+        order_type,
+        status,
+        time_in_force,
+        price: Price::from_le_slice(&bytes[28..60]),
+        quantity: Quantity::from_le_slice(&bytes[60..92]),
+        filled_quantity: Quantity::from_le_slice(&bytes[92..124]),
+        created_at: read_u64(&bytes[124..]),
+        updated_at: read_u64(&bytes[132..]),
+    })
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(buf)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(buf)
+}
+
+/// SnapshotStream emits the state of a book in chunks while the engine keeps matching.
+///
+/// Each chunk is read under its own epoch pin, so the chunks are not one consistent cut:
+/// an order changed between two chunks may be missed or sent twice. Every such change is
+/// synchronized with an event id of at least the stream's sequence, so a replica that applies
+/// the live events from that sequence on ends up with the exact book.
+/// Bids come first, then asks; market orders waiting to be matched form the final chunk.
+pub struct SnapshotStream<'a> {
+    book: &'a dyn OrderBookWalker,
+    chunk_size: usize,
+    sequence: u64,
+    index: u32,
+    section: Option<SnapshotSection>,
+    cursor: Option<BookKey>,
+}
+
+impl<'a> SnapshotStream<'a> {
+    /// Starts streaming a book in chunks of up to `chunk_size` resting orders
+    pub fn new(book: &'a dyn OrderBookWalker, chunk_size: usize) -> Self {
+        Self {
+            book,
+            chunk_size: chunk_size.max(1),
+            sequence: book.sequence(),
+            index: 0,
+            section: Some(SnapshotSection::Bids),
+            cursor: None,
+        }
+    }
+
+    /// Gets the book sequence the stream started at
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Reads the resting orders of a side that follow the cursor
+    fn read_side(&mut self, side: Side) -> Vec<OrderView> {
+        let guard = &epoch::pin();
+        let cursor = self.cursor;
+        let lower = match &cursor {
+            Some(key) => Bound::Excluded(key),
+            None => Bound::Unbounded,
+        };
+        let mut orders = Vec::with_capacity(self.chunk_size);
+        for entry in self
+            .book
+            .get_book(side)
+            .range((lower, Bound::Unbounded), guard)
+            .take(self.chunk_size)
+        {
+            orders.push(OrderView::from(entry.value()));
+            self.cursor = Some(*entry.key());
+        }
+        orders
+    }
+}
+
+impl Iterator for SnapshotStream<'_> {
+    type Item = SnapshotChunk;
+
+    fn next(&mut self) -> Option<SnapshotChunk> {
+        loop {
+            let section = self.section?;
+            let (orders, last) = match section {
+                SnapshotSection::Bids | SnapshotSection::Asks => {
+                    let (side, next) = if section == SnapshotSection::Bids {
+                        (Side::Buy, SnapshotSection::Asks)
+                    } else {
+                        (Side::Sell, SnapshotSection::MarketOrders)
+                    };
+                    let orders = self.read_side(side);
+                    if orders.len() < self.chunk_size {
+                        self.section = Some(next);
+                        self.cursor = None;
+                    }
+                    if orders.is_empty() {
+                        continue;
+                    }
+                    (orders, false)
+                }
+                SnapshotSection::MarketOrders => {
+                    let mut orders = Vec::new();
+                    self.book.walking_market_book(&mut |order| {
+                        orders.push(OrderView::from(order));
+                        WalkingResult::next()
+                    });
+                    self.section = None;
+                    (orders, true)
+                }
+            };
+            let chunk = SnapshotChunk {
+                sequence: self.sequence,
+                index: self.index,
+                section,
+                orders,
+                last,
+            };
+            self.index += 1;
+            return Some(chunk);
+        }
+    }
+}
+
+/// SnapshotReplica bootstraps a copy of a book from a snapshot stream
+/// and then follows the live event stream without gaps.
+///
+/// Live order changes are handed to `apply` with their event id from the moment the stream is
+/// requested. They are buffered until the final chunk is loaded, and only changes from the
+/// stream's sequence on are applied. Every event carries the full order state, so applying a
+/// change the snapshot already reflects is harmless.
+#[derive(Debug, Default)]
+pub struct SnapshotReplica {
+    book: BookSnapshot,
+    sequence: Option<u64>,
+    next_index: u32,
+    complete: bool,
+    pending: Vec<(u64, OrderView)>,
+}
+
+impl SnapshotReplica {
+    /// Creates a new empty replica waiting for the first chunk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the next chunk of the stream and returns whether the snapshot is complete
+    pub fn load_chunk(&mut self, chunk: SnapshotChunk) -> Result<bool, SnapshotError> {
+        if self.complete {
+            return Err(SnapshotError::AlreadyComplete);
+        }
+        if chunk.index != self.next_index || self.sequence.is_some_and(|s| s != chunk.sequence) {
+            return Err(SnapshotError::OutOfOrder {
+                expected: self.next_index,
+                received: chunk.index,
+            });
+        }
+        self.sequence = Some(chunk.sequence);
+        self.next_index += 1;
+        for order in chunk.orders {
+            self.upsert(order);
+        }
+        if chunk.last {
+            self.complete = true;
+            for (sequence, order) in std::mem::take(&mut self.pending) {
+                if sequence >= chunk.sequence {
+                    self.upsert(order);
+                }
+            }
+        }
+        Ok(self.complete)
+    }
+
+    /// Applies a live order change synchronized with event id `sequence`
+    pub fn apply(&mut self, sequence: u64, order: &Order) {
+        let view = OrderView::from(order);
+        if !self.complete {
+            self.pending.push((sequence, view));
+        } else if self.sequence.is_some_and(|s| sequence >= s) {
+            self.upsert(view);
+        }
+    }
+
+    /// Checks whether the final chunk has been loaded
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Gets the book sequence the snapshot was streamed from, once the first chunk is loaded
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    /// Gets the replicated book
+    pub fn book(&self) -> &BookSnapshot {
+        &self.book
+    }
+
+    /// Puts the latest state of an order into the book, keeping its priority while its price
+    /// is unchanged, or removes the order once it has left the book
+    fn upsert(&mut self, order: OrderView) {
+        let resting = matches!(
+            order.status,
+            OrderStatus::Pending | OrderStatus::Placed | OrderStatus::PartiallyFilled
+        );
+        if order.order_type == OrderType::Market {
+            let orders = &mut self.book.market_orders;
+            let position = orders.iter().position(|o| o.id == order.id);
+            match (position, resting) {
+                (Some(position), true) => orders[position] = order,
+                (Some(position), false) => {
+                    orders.remove(position);
+                }
+                (None, true) => orders.push(order),
+                (None, false) => {}
+            }
+            return;
+        }
+
+        let orders = match order.side {
+            Side::Buy => &mut self.book.bids,
+            Side::Sell => &mut self.book.asks,
+        };
+        if let Some(position) = orders.iter().position(|o| o.id == order.id) {
+            if resting && orders[position].price == order.price {
+                orders[position] = order;
+                return;
+            }
+            orders.remove(position);
+        }
+        if resting {
+            // A new or repriced order queues behind the orders at its price
+            let position = match order.side {
+                Side::Buy => orders.partition_point(|o| o.price >= order.price),
+                Side::Sell => orders.partition_point(|o| o.price <= order.price),
+            };
+            orders.insert(position, order);
+        }
+    }
+}
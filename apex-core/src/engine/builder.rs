@@ -0,0 +1,201 @@
+use crate::prelude::*;
+use crossbeam::atomic::AtomicCell;
+
+impl Order {
+    /// Creates a good-till-cancelled limit order with the standard match strategy
+    pub fn limit(
+        id: OrderID,
+        user_id: u64,
+        side: Side,
+        price: Price,
+        quantity: Quantity,
+        now_microseconds: u64,
+    ) -> Self {
+        Order {
+            id,
+            user_id,
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::GoodTillCancelled,
+            price,
+            quantity: AtomicCell::new(quantity),
+            created_at: now_microseconds,
+            updated_at: now_microseconds,
+            ..Order::default()
+        }
+    }
+
+    /// Creates an immediate-or-cancel market order
+    pub fn market(
+        id: OrderID,
+        user_id: u64,
+        side: Side,
+        quantity: Quantity,
+        now_microseconds: u64,
+    ) -> Self {
+        Order {
+            id,
+            user_id,
+            side,
+            order_type: OrderType::Market,
+            match_strategy: MatchStrategy::ImmediateOrCancel,
+            time_in_force: TimeInForce::None,
+            quantity: AtomicCell::new(quantity),
+            created_at: now_microseconds,
+            updated_at: now_microseconds,
+            ..Order::default()
+        }
+    }
+
+    /// Creates a builder for an order
+    pub fn builder(id: OrderID, side: Side) -> OrderBuilder {
+        OrderBuilder::new(id, side)
+    }
+}
+
+/// OrderBuilder constructs an order with typed setters and validates it on `build`,
+/// so callers never touch the interior-mutable fields or assemble invalid combinations.
+///
+/// The builder starts as a good-till-cancelled limit order;
+/// calling `market` switches it to an immediate-or-cancel market order.
+/// Call `limit` or `market` before the strategy and time-in-force setters,
+/// since both reset them to the defaults of the order type.
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    order: Order,
+}
+
+impl OrderBuilder {
+    /// Creates a new builder for a limit order
+    pub fn new(id: OrderID, side: Side) -> Self {
+        Self {
+            order: Order::limit(id, 0, side, Price::ZERO, Quantity::ZERO, 0),
+        }
+    }
+
+    /// Sets the owner of the order
+    pub fn user_id(mut self, user_id: u64) -> Self {
+        self.order.user_id = user_id;
+        self
+    }
+
+    /// Makes the order a limit order at a price
+    pub fn limit(mut self, price: Price) -> Self {
+        self.order.order_type = OrderType::Limit;
+        self.order.price = price;
+        if self.order.time_in_force == TimeInForce::None {
+            self.order.time_in_force = TimeInForce::GoodTillCancelled;
+        }
+        self.order.match_strategy = MatchStrategy::Standard;
+        self
+    }
+
+    /// Makes the order an immediate-or-cancel market order
+    pub fn market(mut self) -> Self {
+        self.order.order_type = OrderType::Market;
+        self.order.price = Price::ZERO;
+        self.order.match_strategy = MatchStrategy::ImmediateOrCancel;
+        self.order.time_in_force = TimeInForce::None;
+        self
+    }
+
+    /// Sets the quantity of the order
+    pub fn quantity(mut self, quantity: Quantity) -> Self {
+        self.order.quantity = AtomicCell::new(quantity);
+        self
+    }
+
+    /// Sets the match strategy of the order
+    pub fn match_strategy(mut self, match_strategy: MatchStrategy) -> Self {
+        self.order.match_strategy = match_strategy;
+        self
+    }
+
+    /// Sets the liquidity directive of the order
+    pub fn liquidity_directive(mut self, liquidity_directive: LiquidityDirective) -> Self {
+        self.order.liquidity_directive = liquidity_directive;
+        self
+    }
+
+    /// Sets the class of the order
+    pub fn class(mut self, class: OrderClass) -> Self {
+        self.order.class = class;
+        self
+    }
+
+    /// Sets the execution tier of the order, overriding the one derived from its class.
+    /// The engine only honors it on liquidation orders.
+    pub fn priority_class(mut self, priority_class: PriorityClass) -> Self {
+        self.order.priority_class = Some(priority_class);
+        self
+    }
+
+    /// Sets the broker or firm the order is entered through
+    pub fn firm_id(mut self, firm_id: u64) -> Self {
+        self.order.firm_id = Some(firm_id);
+        self
+    }
+
+    /// Sets the account the order is booked to
+    #[cfg(feature = "hierarchy")]
+    pub fn account_id(mut self, account_id: u64) -> Self {
+        self.order.account_id = Some(account_id);
+        self
+    }
+
+    /// Sets the clearing broker of the order's account
+    #[cfg(feature = "hierarchy")]
+    pub fn broker_id(mut self, broker_id: u64) -> Self {
+        self.order.broker_id = Some(broker_id);
+        self
+    }
+
+    /// Sets the self-trade prevention group of the order's owner
+    pub fn stp_group(mut self, stp_group: u64) -> Self {
+        self.order.stp_group = Some(stp_group);
+        self
+    }
+
+    /// Sets the id the user gave the order
+    pub fn client_order_id(mut self, client_order_id: ClientOrderID) -> Self {
+        self.order.client_order_id = Some(client_order_id);
+        self
+    }
+
+    /// Sets the opaque tag carried into the order's events and trades
+    pub fn tag(mut self, tag: OrderTag) -> Self {
+        self.order.tag = tag;
+        self
+    }
+
+    /// Sets the channel the order is entered through
+    pub fn source(mut self, source: OrderSource) -> Self {
+        self.order.source = source;
+        self
+    }
+
+    /// Sets the time in force of the order
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.order.time_in_force = time_in_force;
+        self
+    }
+
+    /// Sets the slippage tolerance of a market order
+    pub fn slippage_tolerance(mut self, slippage_tolerance: SlippageTolerance) -> Self {
+        self.order.slippage_tolerance = Some(slippage_tolerance);
+        self
+    }
+
+    /// Sets the creation and update time of the order
+    pub fn timestamp(mut self, now_microseconds: u64) -> Self {
+        self.order.created_at = now_microseconds;
+        self.order.updated_at = now_microseconds;
+        self
+    }
+
+    /// Validates and builds the order
+    pub fn build(self) -> Result<Order, OrderValidationError> {
+        self.order.validate()?;
+        Ok(self.order)
+    }
+}
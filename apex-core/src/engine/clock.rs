@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Clock trait is the engine's source of wall-clock time,
+/// so time can be controlled in simulations and tests
+pub trait Clock: Send + Sync {
+    /// Gets the current time in microseconds since the UNIX epoch
+    fn now_micros(&self) -> u64;
+
+    /// Gets the current time in milliseconds since the UNIX epoch
+    fn now_millis(&self) -> u64 {
+        self.now_micros() / 1000
+    }
+}
+
+/// SystemClock reads the operating system's wall clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock {}
+
+impl Clock for SystemClock {
+    fn now_micros(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_micros() as u64)
+            .unwrap_or_default()
+    }
+}
+
+/// ManualClock only moves when it is told to.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    micros: AtomicU64,
+}
+
+impl ManualClock {
+    /// Creates a new clock stopped at `micros` since the UNIX epoch
+    pub fn new(micros: u64) -> Self {
+        Self {
+            micros: AtomicU64::new(micros),
+        }
+    }
+
+    /// Sets the time in microseconds since the UNIX epoch
+    pub fn set(&self, micros: u64) {
+        self.micros.store(micros, Ordering::Release);
+    }
+
+    /// Moves the time forward by `micros` and returns the new time
+    pub fn advance(&self, micros: u64) -> u64 {
+        self.micros.fetch_add(micros, Ordering::AcqRel) + micros
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_micros(&self) -> u64 {
+        self.micros.load(Ordering::Acquire)
+    }
+}
@@ -0,0 +1,284 @@
+use crate::prelude::*;
+use crypto_bigint::{NonZero, Zero};
+use std::cell::Cell;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::time::{Duration, Instant};
+
+/// MatchingScheme decides how a book allocates an incoming quantity among resting orders.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchingScheme {
+    /// Orders fill at the best price first, and in time priority within a price.
+    #[default]
+    PriceTime,
+    /// Orders fill at the best price first, and within a price in proportion to their size;
+    /// quantity left over by rounding goes to the orders in time priority.
+    ProRata,
+    /// Orders are not matched continuously; each match cycle uncrosses the book at a single
+    /// auction price, and every trade of the cycle executes at it. Market orders are refused.
+    BatchAuction,
+}
+
+/// SelfTradePrevention decides what happens when a taker meets a resting order of its own owner,
+/// that is of the same user or the same STP group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelfTradePrevention {
+    /// Orders of the same owner trade with each other.
+    #[default]
+    Off,
+    /// The resting order is canceled and the taker continues down the book.
+    CancelResting,
+    /// The rest of the taker is canceled and the resting order is kept.
+    CancelTaker,
+    /// Both orders are canceled.
+    CancelBoth,
+}
+
+/// BookConfig holds the per-book limits the engine enforces before an order reaches the book.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookConfig {
+    /// Lowest accepted limit price, inclusive.
+    pub min_price: Option<Price>,
+    /// Highest accepted limit price, inclusive.
+    pub max_price: Option<Price>,
+    /// Maximum number of limit orders resting on both sides of the book.
+    pub max_resting_orders: Option<usize>,
+    /// Largest accepted order quantity, inclusive.
+    pub max_order_quantity: Option<Quantity>,
+    /// Largest accepted order notional (price times quantity), inclusive.
+    pub max_notional: Option<Quantity>,
+    /// Widest accepted distance of a limit price from the reference price, in basis points.
+    /// Orders are not banded while the reference price is unknown.
+    pub reference_band_bps: Option<u32>,
+    /// Price the reference band is centered on.
+    pub reference_source: PriceSource,
+    /// Longest time a limit order may rest, in microseconds, whatever its time in force.
+    /// Older orders are expired by the sweeper with `CancelReason::LifetimeExceeded`.
+    pub max_resting_micros: Option<u64>,
+    /// Longest time a regular limit order may rest without a refresh, in microseconds,
+    /// before the sweeper demotes it to `PriorityClass::STALE`, behind fresher orders at its price.
+    /// A price or size amend that re-times the order refreshes it and restores its derived tier.
+    pub max_quote_age_micros: Option<u64>,
+    /// Number of match cycles between automatic compaction passes over the book.
+    pub compaction_interval: Option<NonZeroU64>,
+    /// Longest time a market order may wait for a match cycle, in microseconds.
+    /// Older market orders are rejected with `RejectReason::StaleMarketOrder` instead of executing.
+    pub max_market_order_age_micros: Option<u64>,
+    /// Limit prices must be a multiple of the tick size.
+    pub tick_size: Option<NonZero<Price>>,
+    /// Order quantities must be a multiple of the lot size.
+    pub lot_size: Option<NonZero<Quantity>>,
+    /// Fee charged to the maker of a trade, in basis points of the notional.
+    /// A negative fee is a rebate.
+    pub maker_fee_bps: i32,
+    /// Fee charged to the taker of a trade, in basis points of the notional.
+    pub taker_fee_bps: i32,
+    /// Most price levels a single market order may sweep; the remainder is canceled
+    /// with `CancelReason::SweepLimit`. Applies on top of the order's slippage tolerance;
+    /// fill-or-kill market orders are bounded by their slippage tolerance only.
+    pub max_sweep_levels: Option<usize>,
+    /// Largest notional a single market order may trade; the remainder is canceled
+    /// with `CancelReason::SweepLimit`.
+    pub max_sweep_notional: Option<Price>,
+    /// Most trades a single match cycle executes. The cycle stops at the budget,
+    /// keeping partially filled takers in the book, and the next cycle resumes from there.
+    pub max_cycle_trades: Option<usize>,
+    /// Longest time a single match cycle keeps matching, in microseconds, checked before each fill.
+    pub max_cycle_micros: Option<u64>,
+    /// Most trades synchronized in one matched event. A taker sweeping more makers streams
+    /// its fills in chunks as they happen; its own final state comes with the last chunk.
+    pub match_chunk_trades: Option<NonZeroUsize>,
+    /// Orders of the same firm never match each other; a taker skips the makers of its own firm
+    /// and continues down the book. Orders without a firm are not restricted.
+    pub prevent_internalization: bool,
+    /// What happens when orders of the same owner would trade. Fill-or-kill orders and
+    /// previews only count the liquidity of other owners.
+    pub self_trade_prevention: SelfTradePrevention,
+    /// How the book matches resting orders.
+    pub matching_scheme: MatchingScheme,
+    /// Order types the book accepts; all of them when None.
+    pub allowed_order_types: Option<Vec<OrderType>>,
+}
+
+impl BookConfig {
+    /// Checks whether the book accepts orders of a type
+    pub fn allows_order_type(&self, order_type: OrderType) -> bool {
+        if self.matching_scheme == MatchingScheme::BatchAuction && order_type == OrderType::Market {
+            return false;
+        }
+        self.allowed_order_types
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(&order_type))
+    }
+
+    /// Checks whether a limit price is inside the configured price band
+    pub fn price_in_band(&self, price: Price) -> bool {
+        self.min_price.is_none_or(|min| price >= min)
+            && self.max_price.is_none_or(|max| price <= max)
+    }
+
+    /// Checks whether a limit price is a multiple of the tick size
+    pub fn price_on_tick(&self, price: Price) -> bool {
+        self.tick_size
+            .is_none_or(|tick| bool::from((price % tick).is_zero()))
+    }
+
+    /// Checks whether an order quantity is a multiple of the lot size
+    pub fn quantity_on_lot(&self, quantity: Quantity) -> bool {
+        self.lot_size
+            .is_none_or(|lot| bool::from((quantity % lot).is_zero()))
+    }
+
+    /// Gets the fee rate of a trade role, in basis points
+    pub fn fee_bps(&self, role: TradeRole) -> i32 {
+        match role {
+            TradeRole::Maker => self.maker_fee_bps,
+            TradeRole::Taker => self.taker_fee_bps,
+        }
+    }
+
+    /// Checks whether an order quantity is within the fat-finger size limit
+    pub fn quantity_in_limit(&self, quantity: Quantity) -> bool {
+        self.max_order_quantity.is_none_or(|max| quantity <= max)
+    }
+
+    /// Checks whether the notional of `quantity` at `price` is within the fat-finger notional limit
+    pub fn notional_in_limit(&self, price: Price, quantity: Quantity) -> bool {
+        self.max_notional
+            .is_none_or(|max| price.saturating_mul(&quantity) <= max)
+    }
+
+    /// Checks whether a limit price is within the reference band around `reference`
+    pub fn price_in_reference_band(&self, price: Price, reference: Price) -> bool {
+        let Some(bps) = self.reference_band_bps else {
+            return true;
+        };
+        let basis = NonZero::new(Price::from(10_000u32)).unwrap();
+        let width = reference.saturating_mul(&Price::from(bps)) / basis;
+        price >= reference.saturating_sub(&width) && price <= reference.saturating_add(&width)
+    }
+}
+
+/// SweepBudget tracks how much of a book one market order has swept against the sweep caps.
+pub(crate) struct SweepBudget {
+    max_levels: Option<usize>,
+    max_notional: Option<Price>,
+    levels: usize,
+    last_price: Option<Price>,
+    notional: Price,
+    exhausted: bool,
+}
+
+impl SweepBudget {
+    /// Creates the budget of one market order
+    pub(crate) fn new(config: &BookConfig) -> Self {
+        Self {
+            max_levels: config.max_sweep_levels,
+            max_notional: config.max_sweep_notional,
+            levels: 0,
+            last_price: None,
+            notional: Price::ZERO,
+            exhausted: false,
+        }
+    }
+
+    /// Takes a fill of `quantity` at `price` out of the budget.
+    /// Returns false, and stays exhausted, if the fill would exceed a cap.
+    pub(crate) fn take(&mut self, price: Price, quantity: Quantity) -> bool {
+        let levels = if self.last_price == Some(price) {
+            self.levels
+        } else {
+            self.levels + 1
+        };
+        let notional = self
+            .notional
+            .saturating_add(&price.saturating_mul(&quantity));
+        if self.exhausted
+            || self.max_levels.is_some_and(|max| levels > max)
+            || self.max_notional.is_some_and(|max| notional > max)
+        {
+            self.exhausted = true;
+            return false;
+        }
+        self.levels = levels;
+        self.last_price = Some(price);
+        self.notional = notional;
+        true
+    }
+
+    /// Checks whether a fill was refused because it would exceed a cap
+    pub(crate) fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+}
+
+/// CycleBudget tracks how much work one match cycle has done against the cycle caps,
+/// and whether the cycle was asked to pause.
+pub(crate) struct CycleBudget<'a> {
+    interrupted: &'a dyn Fn() -> bool,
+    max_trades: Option<usize>,
+    deadline: Option<Instant>,
+    trades: Cell<usize>,
+    exhausted: Cell<bool>,
+}
+
+impl<'a> CycleBudget<'a> {
+    /// Creates the budget of a match cycle started at `started_at`,
+    /// which stops as soon as `interrupted` returns true
+    pub(crate) fn new(
+        config: &BookConfig,
+        started_at: Instant,
+        interrupted: &'a dyn Fn() -> bool,
+    ) -> Self {
+        Self {
+            interrupted,
+            max_trades: config.max_cycle_trades,
+            deadline: config
+                .max_cycle_micros
+                .map(|micros| started_at + Duration::from_micros(micros)),
+            trades: Cell::new(0),
+            exhausted: Cell::new(false),
+        }
+    }
+
+    /// Checks whether the cycle was asked to pause, which exhausts it
+    pub(crate) fn interrupted(&self) -> bool {
+        if !self.exhausted.get() && (self.interrupted)() {
+            self.exhausted.set(true);
+        }
+        self.exhausted.get()
+    }
+
+    /// Checks whether the cycle has trades and time left and was not asked to pause.
+    /// Returns false, and stays exhausted, once it has not.
+    pub(crate) fn has_room(&self) -> bool {
+        if self.interrupted()
+            || self.max_trades.is_some_and(|max| self.trades.get() >= max)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.exhausted.set(true);
+            return false;
+        }
+        true
+    }
+
+    /// Takes one fill out of the budget, if there is room for it
+    pub(crate) fn take(&self) -> bool {
+        let room = self.has_room();
+        if room {
+            self.record(1);
+        }
+        room
+    }
+
+    /// Charges fills that were executed regardless of the budget
+    pub(crate) fn record(&self, trades: usize) {
+        self.trades.set(self.trades.get() + trades);
+    }
+
+    /// Checks whether the cycle stopped at its budget or a pause
+    pub(crate) fn exhausted(&self) -> bool {
+        self.exhausted.get()
+    }
+}
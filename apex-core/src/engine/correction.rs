@@ -0,0 +1,93 @@
+use crate::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// TradeCorrectionKind is what a correction does to a previously reported trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeCorrectionKind {
+    /// The trade is canceled as if it never happened.
+    Bust,
+    /// The trade stands at a corrected price.
+    Price(Price),
+}
+
+/// TradeCorrection is the compensating event of a busted or price-corrected trade.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeCorrection {
+    /// Id of the original trade.
+    pub trade_id: u64,
+    pub kind: TradeCorrectionKind,
+    /// Both sides of the trade as they were reported before the correction.
+    pub original: Vec<Trade>,
+    /// Orders that got the busted quantity back; orders that already left the book are not restored.
+    pub restored: Vec<OrderID>,
+    /// Time the correction was made, in microseconds since the UNIX epoch.
+    pub created_at: u64,
+}
+
+/// TradeJournal remembers the most recent trades of a book so they can be corrected.
+/// At most `capacity` trades are kept; older ones can no longer be corrected.
+pub struct TradeJournal {
+    capacity: usize,
+    trades: Mutex<VecDeque<Trade>>,
+}
+
+impl TradeJournal {
+    /// Creates a new journal remembering up to `capacity` trades
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            trades: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Records both sides of matched trades
+    pub fn record(&self, trades: &[Trade]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut journal = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        for trade in trades {
+            if journal.len() == self.capacity {
+                journal.pop_front();
+            }
+            journal.push_back(trade.clone());
+        }
+    }
+
+    /// Gets both sides of a trade
+    pub fn get(&self, trade_id: u64) -> Vec<Trade> {
+        let journal = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        journal
+            .iter()
+            .filter(|trade| trade.trade_id == trade_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Removes both sides of a trade and returns them
+    pub(crate) fn take(&self, trade_id: u64) -> Vec<Trade> {
+        let mut journal = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        let taken = journal
+            .iter()
+            .filter(|trade| trade.trade_id == trade_id)
+            .cloned()
+            .collect();
+        journal.retain(|trade| trade.trade_id != trade_id);
+        taken
+    }
+
+    /// Sets the price of both sides of a trade and returns them as they were
+    pub(crate) fn reprice(&self, trade_id: u64, price: Price) -> Vec<Trade> {
+        let mut journal = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        let mut original = Vec::new();
+        for trade in journal
+            .iter_mut()
+            .filter(|trade| trade.trade_id == trade_id)
+        {
+            original.push(trade.clone());
+            trade.price = price;
+        }
+        original
+    }
+}
@@ -0,0 +1,97 @@
+use crate::prelude::*;
+use std::ops::ControlFlow;
+use std::sync::{Mutex, MutexGuard};
+
+/// BookCondition is how the best bid and the best ask of a book overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookCondition {
+    /// The best bid equals the best ask.
+    Locked,
+    /// The best bid is above the best ask.
+    Crossed,
+}
+
+/// CrossedBook is a locked or crossed resting book, as found by a `CrossedBookMonitor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossedBook {
+    pub condition: BookCondition,
+    pub best_bid: Price,
+    pub best_ask: Price,
+    /// Resting bids at or above the best ask, in priority order.
+    pub bid_order_ids: Vec<OrderID>,
+    /// Resting asks at or below the best bid, in priority order.
+    pub ask_order_ids: Vec<OrderID>,
+    /// Time the book was first seen locked or crossed, in microseconds since the UNIX epoch.
+    pub since: u64,
+    /// Microseconds the book has stayed locked or crossed.
+    pub duration_micros: u64,
+}
+
+/// CrossedBookMonitor detects resting books whose best bid meets or passes the best ask,
+/// which MakerOnly orders on both sides can leave behind, and tracks how long it lasts.
+///
+/// The condition is measured between checks: it starts at the first check that finds it
+/// and ends at the first check that does not, so check at least as often as the precision needed.
+#[derive(Default)]
+pub struct CrossedBookMonitor {
+    since: Mutex<Option<u64>>,
+}
+
+impl CrossedBookMonitor {
+    /// Creates a new monitor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks whether the book is locked or crossed at `now_microseconds`,
+    /// None if its best bid is below its best ask or a side is empty
+    pub fn check(&self, book: &dyn OrderBook, now_microseconds: u64) -> Option<CrossedBook> {
+        let mut since = self.lock();
+        let (best_bid, best_ask) = match (
+            book.get_best_price(Side::Buy),
+            book.get_best_price(Side::Sell),
+        ) {
+            (Some(bid), Some(ask)) if bid >= ask => (bid, ask),
+            _ => {
+                *since = None;
+                return None;
+            }
+        };
+        let since = *since.get_or_insert(now_microseconds);
+
+        let overlapping = |side: Side, limit: Price| {
+            let mut order_ids = Vec::new();
+            book.for_each_order(side, &mut |order| {
+                let overlaps = match side {
+                    Side::Buy => order.price >= limit,
+                    Side::Sell => order.price <= limit,
+                };
+                if !overlaps {
+                    return ControlFlow::Break(());
+                }
+                order_ids.push(order.id);
+                ControlFlow::Continue(())
+            });
+            order_ids
+        };
+        Some(CrossedBook {
+            condition: if best_bid == best_ask {
+                BookCondition::Locked
+            } else {
+                BookCondition::Crossed
+            },
+            best_bid,
+            best_ask,
+            bid_order_ids: overlapping(Side::Buy, best_ask),
+            ask_order_ids: overlapping(Side::Sell, best_bid),
+            since,
+            duration_micros: now_microseconds.saturating_sub(since),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<u64>> {
+        self.since
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
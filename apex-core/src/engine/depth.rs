@@ -0,0 +1,443 @@
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+/// DepthUpdate is a change of the aggregated depth a subscriber follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthUpdate {
+    /// New state of a price level; zero quantity and orders once the level is empty.
+    Level { side: Side, level: PriceLevel },
+    /// New best bid and offer; None for a side without resting orders.
+    Bbo {
+        bid: Option<PriceLevel>,
+        ask: Option<PriceLevel>,
+    },
+}
+
+/// DeliveryMode sets how a subscription queues updates its consumer has not taken yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeliveryMode {
+    /// Every update is queued until it is taken.
+    #[default]
+    Buffered,
+    /// Only the latest state of each price level and of the BBO is queued.
+    /// A new update replaces a pending one for the same level in place, so a lagging
+    /// consumer still sees levels in the order they first changed and never more
+    /// pending updates than there are levels.
+    Conflated,
+}
+
+/// RestingTotals is the quantity and number of orders resting on one side of a book.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RestingTotals {
+    pub quantity: Quantity,
+    pub orders: usize,
+}
+
+/// TopOfBook is the best price levels of both sides of a book, taken in one pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopOfBook {
+    /// Bid levels, best price first.
+    pub bids: Vec<PriceLevel>,
+    /// Ask levels, best price first.
+    pub asks: Vec<PriceLevel>,
+}
+
+/// DepthLadder is the depth of one side as parallel arrays, best price first,
+/// ready to plot as a depth chart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DepthLadder {
+    pub prices: Vec<Price>,
+    /// Quantity resting at each price.
+    pub quantities: Vec<Quantity>,
+    /// Quantity resting at each price and every better one.
+    pub cumulative: Vec<Quantity>,
+}
+
+impl DepthLadder {
+    /// Creates a ladder from price levels, best price first
+    pub fn from_levels<'a>(levels: impl IntoIterator<Item = &'a PriceLevel>) -> Self {
+        let mut ladder = Self::default();
+        let mut total = Quantity::ZERO;
+        for level in levels {
+            total = total.saturating_add(&level.quantity);
+            ladder.prices.push(level.price);
+            ladder.quantities.push(level.quantity);
+            ladder.cumulative.push(total);
+        }
+        ladder
+    }
+
+    /// Gets the number of levels
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    /// Checks whether the ladder has no levels
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    /// Gets the total quantity of the ladder
+    pub fn total(&self) -> Quantity {
+        self.cumulative.last().copied().unwrap_or(Quantity::ZERO)
+    }
+
+    /// Gets the worst price an order for `quantity` would reach sweeping the ladder,
+    /// None if the ladder does not hold that much
+    pub fn price_for(&self, quantity: Quantity) -> Option<Price> {
+        let level = self.cumulative.partition_point(|total| *total < quantity);
+        self.prices.get(level).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ConflationKey {
+    Level(Side, Price),
+    Bbo,
+}
+
+impl From<&DepthUpdate> for ConflationKey {
+    fn from(update: &DepthUpdate) -> Self {
+        match update {
+            DepthUpdate::Level { side, level } => ConflationKey::Level(*side, level.price),
+            DepthUpdate::Bbo { .. } => ConflationKey::Bbo,
+        }
+    }
+}
+
+#[derive(Default)]
+struct PendingUpdates {
+    order: VecDeque<ConflationKey>,
+    latest: HashMap<ConflationKey, DepthUpdate>,
+    buffered: VecDeque<DepthUpdate>,
+    conflated: u64,
+}
+
+struct SubscriptionQueue {
+    mode: DeliveryMode,
+    pending: Mutex<PendingUpdates>,
+}
+
+impl SubscriptionQueue {
+    fn lock(&self) -> MutexGuard<'_, PendingUpdates> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn push(&self, update: DepthUpdate) {
+        let mut pending = self.lock();
+        match self.mode {
+            DeliveryMode::Buffered => pending.buffered.push_back(update),
+            DeliveryMode::Conflated => {
+                let key = ConflationKey::from(&update);
+                if pending.latest.insert(key, update).is_some() {
+                    pending.conflated += 1;
+                } else {
+                    pending.order.push_back(key);
+                }
+            }
+        }
+    }
+
+    fn pop(&self) -> Option<DepthUpdate> {
+        let mut pending = self.lock();
+        match self.mode {
+            DeliveryMode::Buffered => pending.buffered.pop_front(),
+            DeliveryMode::Conflated => {
+                let key = pending.order.pop_front()?;
+                pending.latest.remove(&key)
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        let pending = self.lock();
+        pending.buffered.len() + pending.order.len()
+    }
+}
+
+/// DepthSubscription receives the depth updates of a book.
+/// Dropping the subscription unsubscribes it.
+pub struct DepthSubscription {
+    queue: Arc<SubscriptionQueue>,
+}
+
+impl DepthSubscription {
+    /// Takes the oldest pending update
+    pub fn poll(&self) -> Option<DepthUpdate> {
+        self.queue.pop()
+    }
+
+    /// Takes every pending update
+    pub fn drain(&self) -> Vec<DepthUpdate> {
+        std::iter::from_fn(|| self.queue.pop()).collect()
+    }
+
+    /// Gets the number of pending updates
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Gets the number of updates replaced by a newer one before they were taken
+    pub fn conflated(&self) -> u64 {
+        self.queue.lock().conflated
+    }
+
+    /// Gets the delivery mode of the subscription
+    pub fn mode(&self) -> DeliveryMode {
+        self.queue.mode
+    }
+}
+
+#[derive(Default)]
+struct DepthState {
+    orders: HashMap<OrderID, (Side, Price, Quantity)>,
+    bids: BTreeMap<Price, PriceLevel>,
+    asks: BTreeMap<Price, PriceLevel>,
+    bbo: (Option<PriceLevel>, Option<PriceLevel>),
+    // Running totals of the bids and the asks
+    totals: (RestingTotals, RestingTotals),
+    subscribers: Vec<Weak<SubscriptionQueue>>,
+}
+
+impl DepthState {
+    fn levels(&mut self, side: Side) -> &mut BTreeMap<Price, PriceLevel> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+
+    fn totals(&mut self, side: Side) -> &mut RestingTotals {
+        match side {
+            Side::Buy => &mut self.totals.0,
+            Side::Sell => &mut self.totals.1,
+        }
+    }
+
+    /// Moves an order to its current level, or out of the book once it stops resting
+    fn track(&mut self, order: &Order, touched: &mut Vec<(Side, Price)>) {
+        if let Some((side, price, quantity)) = self.orders.remove(&order.id) {
+            self.remove(side, price, quantity);
+            touched.push((side, price));
+        }
+        if order.is_resting() {
+            self.insert(order.id, order.side, order.price, order.quantity());
+            touched.push((order.side, order.price));
+        }
+    }
+
+    fn insert(&mut self, order_id: OrderID, side: Side, price: Price, quantity: Quantity) {
+        self.orders.insert(order_id, (side, price, quantity));
+        let level = self.levels(side).entry(price).or_insert(PriceLevel {
+            price,
+            quantity: Quantity::ZERO,
+            orders: 0,
+        });
+        level.quantity = level.quantity.saturating_add(&quantity);
+        level.orders += 1;
+        let totals = self.totals(side);
+        totals.quantity = totals.quantity.saturating_add(&quantity);
+        totals.orders += 1;
+    }
+
+    fn remove(&mut self, side: Side, price: Price, quantity: Quantity) {
+        let totals = self.totals(side);
+        totals.quantity = totals.quantity.saturating_sub(&quantity);
+        totals.orders -= 1;
+        let levels = self.levels(side);
+        if let Some(level) = levels.get_mut(&price) {
+            level.quantity = level.quantity.saturating_sub(&quantity);
+            level.orders -= 1;
+            if level.orders == 0 {
+                levels.remove(&price);
+            }
+        }
+    }
+
+    fn rescale(&mut self, rescale: &Rescale, touched: &mut Vec<(Side, Price)>) {
+        let orders = std::mem::take(&mut self.orders);
+        self.bids.clear();
+        self.asks.clear();
+        self.totals = Default::default();
+        for (order_id, (side, price, quantity)) in orders {
+            touched.push((side, price));
+            let price = rescale.price.apply(price).unwrap_or(price);
+            let quantity = rescale.quantity.apply(quantity).unwrap_or(quantity);
+            self.insert(order_id, side, price, quantity);
+            touched.push((side, price));
+        }
+    }
+
+    /// Sends the new state of the touched levels, and of the BBO if it changed
+    fn publish(&mut self, touched: Vec<(Side, Price)>) {
+        let mut updates = Vec::new();
+        let mut seen = HashSet::new();
+        for (side, price) in touched {
+            if !seen.insert((side, price)) {
+                continue;
+            }
+            let level = self
+                .levels(side)
+                .get(&price)
+                .copied()
+                .unwrap_or(PriceLevel {
+                    price,
+                    quantity: Quantity::ZERO,
+                    orders: 0,
+                });
+            updates.push(DepthUpdate::Level { side, level });
+        }
+        let bbo = (
+            self.bids.values().next_back().copied(),
+            self.asks.values().next().copied(),
+        );
+        if bbo != self.bbo {
+            self.bbo = bbo;
+            updates.push(DepthUpdate::Bbo {
+                bid: bbo.0,
+                ask: bbo.1,
+            });
+        }
+        if updates.is_empty() {
+            return;
+        }
+        self.subscribers
+            .retain(|subscriber| match subscriber.upgrade() {
+                Some(queue) => {
+                    for update in &updates {
+                        queue.push(*update);
+                    }
+                    true
+                }
+                None => false,
+            });
+    }
+}
+
+/// DepthSyncer forwards every book change to the primary syncer
+/// and publishes the resulting price level and BBO changes to its subscribers.
+///
+/// Updates are queued per subscriber from the thread that changed the book,
+/// so a slow subscriber never blocks matching. A `Conflated` subscriber that lags
+/// keeps only the latest state of each level instead of buffering every change.
+pub struct DepthSyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    state: Mutex<DepthState>,
+}
+
+impl DepthSyncer {
+    /// Creates a new depth syncer in front of the primary syncer
+    pub fn new(primary: Arc<dyn OrderBookSyncer>) -> Self {
+        Self {
+            primary,
+            state: Mutex::new(DepthState::default()),
+        }
+    }
+
+    /// Subscribes to depth updates.
+    /// Subscribe before the first order is placed to be able to rebuild the whole book.
+    pub fn subscribe(&self, mode: DeliveryMode) -> DepthSubscription {
+        let queue = Arc::new(SubscriptionQueue {
+            mode,
+            pending: Mutex::new(PendingUpdates::default()),
+        });
+        self.lock().subscribers.push(Arc::downgrade(&queue));
+        DepthSubscription { queue }
+    }
+
+    /// Gets the number of live subscriptions
+    pub fn subscribers(&self) -> usize {
+        self.lock()
+            .subscribers
+            .iter()
+            .filter(|subscriber| subscriber.strong_count() > 0)
+            .count()
+    }
+
+    /// Gets up to `max_levels` levels of a side as a ladder, best price first,
+    /// from the levels the syncer maintains
+    pub fn depth_ladder(&self, side: Side, max_levels: usize) -> DepthLadder {
+        let state = self.lock();
+        match side {
+            Side::Buy => DepthLadder::from_levels(state.bids.values().rev().take(max_levels)),
+            Side::Sell => DepthLadder::from_levels(state.asks.values().take(max_levels)),
+        }
+    }
+
+    /// Gets the quantity and number of orders resting on a side,
+    /// kept as running totals so reading them is cheap
+    pub fn resting(&self, side: Side) -> RestingTotals {
+        *self.lock().totals(side)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DepthState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn track(&self, orders: &[&Order]) {
+        let mut state = self.lock();
+        let mut touched = Vec::new();
+        for order in orders {
+            state.track(order, &mut touched);
+        }
+        state.publish(touched);
+    }
+}
+
+impl OrderBookSyncer for DepthSyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+        self.track(&[order]);
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+        self.track(&[order]);
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+        self.track(&[order]);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+        self.track(&[order]);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        self.track(&updated.iter().collect::<Vec<_>>());
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+        let mut state = self.lock();
+        let mut touched = Vec::new();
+        state.rescale(rescale, &mut touched);
+        state.publish(touched);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        let orders: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                BookEvent::Added(order)
+                | BookEvent::Updated(order)
+                | BookEvent::Cancelled(order)
+                | BookEvent::Rejected(order) => order,
+            })
+            .collect();
+        self.track(&orders);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// BookDigest is a cheap fingerprint of the resting orders of a book.
+///
+/// The value is the wrapping sum of a 64-bit hash of every resting order's
+/// `(id, price, quantity)`, so it does not depend on the order the orders arrived in
+/// and can be updated in constant time per change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BookDigest {
+    /// Number of syncer events folded into the digest.
+    pub events: u64,
+    pub value: u64,
+}
+
+/// Computes the digest value of a snapshot from scratch, for example to verify
+/// a replica restored from a snapshot against the live digest of its primary
+pub fn snapshot_digest(snapshot: &BookSnapshot) -> u64 {
+    snapshot
+        .bids
+        .iter()
+        .chain(snapshot.asks.iter())
+        .fold(0u64, |digest, view| {
+            digest.wrapping_add(order_hash(view.id, view.price, view.quantity))
+        })
+}
+
+/// Hashes the tuple of a resting order with a fixed mixer, so every build agrees
+fn order_hash(id: OrderID, price: Price, quantity: Quantity) -> u64 {
+    price
+        .as_words()
+        .iter()
+        .chain(quantity.as_words().iter())
+        .fold(mix(id), |hash, word| mix(hash ^ word))
+}
+
+/// The splitmix64 finalizer
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[derive(Default)]
+struct DigestState {
+    orders: HashMap<OrderID, (Price, Quantity)>,
+    digest: BookDigest,
+    checkpoint: Option<BookDigest>,
+}
+
+impl DigestState {
+    fn track(&mut self, order: &Order) {
+        if let Some((price, quantity)) = self.orders.remove(&order.id) {
+            self.digest.value = self
+                .digest
+                .value
+                .wrapping_sub(order_hash(order.id, price, quantity));
+        }
+        if order.is_resting() {
+            self.orders
+                .insert(order.id, (order.price, order.quantity()));
+            self.digest.value =
+                self.digest
+                    .value
+                    .wrapping_add(order_hash(order.id, order.price, order.quantity()));
+        }
+    }
+
+    fn rescale(&mut self, rescale: &Rescale) {
+        self.digest.value = 0;
+        for (id, (price, quantity)) in self.orders.iter_mut() {
+            *price = rescale.price.apply(*price).unwrap_or(*price);
+            *quantity = rescale.quantity.apply(*quantity).unwrap_or(*quantity);
+            self.digest.value = self
+                .digest
+                .value
+                .wrapping_add(order_hash(*id, *price, *quantity));
+        }
+    }
+}
+
+/// DigestSyncer forwards every book change to the primary syncer
+/// and keeps a rolling `BookDigest` of the book up to date.
+///
+/// Replicas fed the same events reach the same digest after the same number of events.
+/// With an interval, the digest is also kept as a checkpoint every `interval` events,
+/// which replicas compare to detect divergence without stopping the book.
+pub struct DigestSyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    interval: u64,
+    state: Mutex<DigestState>,
+}
+
+impl DigestSyncer {
+    /// Creates a new digest syncer in front of the primary syncer
+    pub fn new(primary: Arc<dyn OrderBookSyncer>) -> Self {
+        Self {
+            primary,
+            interval: 0,
+            state: Mutex::new(DigestState::default()),
+        }
+    }
+
+    /// Sets the number of events between checkpoints; 0 takes none
+    pub fn with_interval(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Gets the current digest
+    pub fn digest(&self) -> BookDigest {
+        self.lock().digest
+    }
+
+    /// Gets the digest as of the latest checkpoint
+    pub fn checkpoint(&self) -> Option<BookDigest> {
+        self.lock().checkpoint
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DigestState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn track(&self, orders: &[&Order]) {
+        let mut state = self.lock();
+        for order in orders {
+            state.track(order);
+        }
+        self.advance(&mut state);
+    }
+
+    fn advance(&self, state: &mut DigestState) {
+        state.digest.events += 1;
+        let since = state.digest.events - state.checkpoint.map_or(0, |last| last.events);
+        if self.interval > 0 && since == self.interval {
+            state.checkpoint = Some(state.digest);
+        }
+    }
+}
+
+impl OrderBookSyncer for DigestSyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+        self.track(&[order]);
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+        self.track(&[order]);
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+        self.track(&[order]);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+        self.track(&[order]);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        self.track(&updated.iter().collect::<Vec<_>>());
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+        self.track(&[]);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+        let mut state = self.lock();
+        state.rescale(rescale);
+        self.advance(&mut state);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        let orders: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                BookEvent::Added(order)
+                | BookEvent::Updated(order)
+                | BookEvent::Cancelled(order)
+                | BookEvent::Rejected(order) => order,
+            })
+            .collect();
+        self.track(&orders);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::prelude::*;
+use std::sync::{Arc, Mutex};
+
+/// DropCopyKind is the normalized kind of a drop-copy record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropCopyKind {
+    /// The order book accepted a new order.
+    New,
+    /// The order book changed an order.
+    Update,
+    /// The order book canceled an order.
+    Cancel,
+    /// The engine rejected an order.
+    Reject,
+    /// An order was executed; the record carries the execution.
+    Execution,
+}
+
+/// DropCopyExecution is the fill side of an execution record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropCopyExecution {
+    pub trade_id: u64,
+    pub role: TradeRole,
+    pub price: Price,
+    pub quantity: Quantity,
+}
+
+/// DropCopyRecord is a self-contained audit record of an order event or execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropCopyRecord {
+    /// Gap-free sequence of the drop-copy feed, starting at 1.
+    pub sequence: u64,
+    /// Syncer id of the book change the record belongs to.
+    pub sync_id: u64,
+    /// Time the record was produced by the syncer's clock, in microseconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub kind: DropCopyKind,
+    pub order: OrderView,
+    pub execution: Option<DropCopyExecution>,
+}
+
+/// DropCopySink trait receives the drop-copy feed, typically for audit storage
+pub trait DropCopySink: Send + Sync {
+    /// This function is called for every record
+    fn record(&self, record: &DropCopyRecord);
+}
+
+/// DropCopySyncer forwards every book change to the primary syncer
+/// and then copies it, normalized, to an independent drop-copy sink.
+///
+/// Records are numbered and delivered under one lock, so the sink receives them in sequence
+/// order even when book changes are synchronized concurrently.
+pub struct DropCopySyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    sink: Arc<dyn DropCopySink>,
+    clock: Arc<dyn Clock>,
+    // Sequence of the next record
+    sequence: Mutex<u64>,
+}
+
+impl DropCopySyncer {
+    /// Creates a new drop-copy syncer in front of the primary syncer
+    pub fn new(primary: Arc<dyn OrderBookSyncer>, sink: Arc<dyn DropCopySink>) -> Self {
+        Self {
+            primary,
+            sink,
+            clock: Arc::new(SystemClock {}),
+            sequence: Mutex::new(1),
+        }
+    }
+
+    /// Sets the clock the record timestamps are read from
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn emit(
+        &self,
+        sync_id: u64,
+        kind: DropCopyKind,
+        order: &Order,
+        execution: Option<DropCopyExecution>,
+    ) {
+        let mut sequence = self.sequence.lock().unwrap_or_else(|e| e.into_inner());
+        self.sink.record(&DropCopyRecord {
+            sequence: *sequence,
+            sync_id,
+            timestamp: self.clock.now_micros(),
+            kind,
+            order: OrderView::from(order),
+            execution,
+        });
+        *sequence += 1;
+    }
+}
+
+impl OrderBookSyncer for DropCopySyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+        self.emit(id, DropCopyKind::New, order, None);
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+        self.emit(id, DropCopyKind::Update, order, None);
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+        self.emit(id, DropCopyKind::Cancel, order, None);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+        self.emit(id, DropCopyKind::Reject, order, None);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        for (trade, order) in trade_orders(updated, trades) {
+            let execution = DropCopyExecution {
+                trade_id: trade.trade_id,
+                role: trade.role,
+                price: trade.price,
+                quantity: trade.quantity,
+            };
+            self.emit(id, DropCopyKind::Execution, order, Some(execution));
+        }
+        for order in updated {
+            let kind = match order.status() {
+                OrderStatus::Rejected => DropCopyKind::Reject,
+                _ => DropCopyKind::Update,
+            };
+            self.emit(id, kind, order, None);
+        }
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        for event in events {
+            match event {
+                BookEvent::Added(order) => self.emit(id, DropCopyKind::New, order, None),
+                BookEvent::Updated(order) => self.emit(id, DropCopyKind::Update, order, None),
+                BookEvent::Cancelled(order) => self.emit(id, DropCopyKind::Cancel, order, None),
+                BookEvent::Rejected(order) => self.emit(id, DropCopyKind::Reject, order, None),
+            }
+        }
+    }
+}
@@ -1,21 +1,711 @@
+use crate::prelude::*;
+use std::error::Error;
+use std::fmt;
+
 /// Represents possible errors when trying to update an order.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UpdateOrderError {
     /// The order was not found in the book.
     OrderNotFound,
-    /// The order is not in a modifiable state (e.g., already matched or canceled).
-    OrderNotModifiable,
-    /// The requested update is invalid (e.g., price change not allowed).
-    InvalidUpdateRequest,
+    /// The order is not in a modifiable state (e.g., already matched or canceled),
+    /// with why and a snapshot of the order when the update was refused.
+    OrderNotModifiable {
+        reason: AmendFailure,
+        order: Box<OrderView>,
+    },
+    /// The requested update is invalid (e.g., price change not allowed),
+    /// with why and a snapshot of the order when the update was refused.
+    InvalidUpdateRequest {
+        reason: AmendFailure,
+        order: Box<OrderView>,
+    },
+    /// The engine is in cancel-only or halted mode and does not accept updates.
+    TradingSuspended,
+    /// The order is a market order waiting to match, which cannot be amended.
+    MarketOrderNotAmendable,
+    /// The order's owner is frozen and may not change their orders.
+    UserFrozen,
+    /// The order owner, or senders of unknown order ids together, sent requests faster than
+    /// the engine's rate limit allows.
+    RateLimited,
+}
+
+/// AmendFailure is why an update or amend of a resting order was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmendFailure {
+    /// The matching thread was filling the order when the update arrived.
+    MatchInFlight,
+    /// The order was already filled.
+    AlreadyFilled,
+    /// The order was already canceled, expired or rejected.
+    AlreadyClosed,
+    /// The new price is zero, off the tick size, or outside the price band.
+    InvalidPrice,
+    /// The new quantity is zero.
+    InvalidQuantity,
+    /// The new quantity is over the book's maximum order quantity.
+    QuantityTooLarge,
+    /// The new quantity is not a multiple of the book's lot size.
+    OffLot,
+    /// The notional of the order at its new price and quantity is over the book's limit.
+    NotionalTooLarge,
+    /// The new time in force does not let the order rest in the book.
+    InvalidTimeInForce,
+    /// The amend request changes nothing.
+    EmptyRequest,
 }
 
 /// Represents possible errors when trying to cancel an order.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CancelOrderError {
     /// The order was not found in the book.
     OrderNotFound,
-    /// The order is not in a cancellable state (e.g., already matched).
-    OrderNotCancellable,
+    /// The order is not in a cancellable state (e.g., already matched),
+    /// with its status and filled quantity when the cancel was refused.
+    OrderNotCancellable {
+        status: OrderStatus,
+        filled_quantity: Quantity,
+    },
     /// The requested cancel is invalid (e.g., order already canceled).
     InvalidCancelRequest,
+    /// The engine is halted and does not accept cancels.
+    EngineHalted,
+    /// The order owner, or senders of unknown order ids together, sent requests faster than
+    /// the engine's rate limit allows.
+    RateLimited,
+    /// The order belongs to another user than the one canceling it.
+    NotOrderOwner,
+}
+
+/// Represents possible errors when trying to submit a command to the engine queue.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SubmitError {
+    /// The command queue is full; the caller should shed load or retry later.
+    QueueFull,
+    /// The command queue stayed full until the submission timeout elapsed.
+    Timeout,
+    /// The symbol is not served by this engine.
+    UnknownSymbol,
+    /// The matching thread serving the command has stopped.
+    Disconnected,
+    /// The symbol is already served by this engine.
+    SymbolListed,
+    /// The session or the engine is over its throughput limit.
+    Throttled,
+}
+
+/// Represents possible errors when trying to bust or correct a trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeCorrectionError {
+    /// The trade is unknown or too old to be in the trade journal.
+    TradeNotFound,
+    /// The corrected price is zero.
+    InvalidPrice,
+    /// The engine keeps no trade journal.
+    JournalDisabled,
+}
+
+/// Represents possible errors when trying to execute a basket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasketError {
+    /// No book of the symbol is known to the router; no child was touched.
+    UnknownSymbol(SymbolID),
+    /// A child was rejected, so every child of the basket was rejected.
+    Rejected {
+        order_id: OrderID,
+        reason: RejectReason,
+    },
+}
+
+/// Represents possible errors of one entry of a mass quote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuoteError {
+    /// The new quote was rejected.
+    Rejected(RejectReason),
+    /// The resting quote could not be repriced or resized.
+    Update(UpdateOrderError),
+    /// The quote could not be pulled.
+    Cancel(CancelOrderError),
+    /// The resting order of the quote is on the other side of the book.
+    SideMismatch,
+}
+
+/// Represents possible errors when trying to rescale the prices and quantities of a book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RescaleError {
+    /// The engine must be halted so no order matches while the book is rescaled.
+    NotHalted,
+    /// The order's price or quantity does not scale to a valid whole value.
+    Inexact(OrderID),
+    /// The order is being matched or removed and cannot be rescaled.
+    OrderInFlight(OrderID),
+}
+
+impl UpdateOrderError {
+    /// Creates the error of an order the book could not claim for an update,
+    /// telling a match in flight apart from an order that already left the book
+    pub(crate) fn not_modifiable(order: &Order) -> Self {
+        let reason = match order.status() {
+            OrderStatus::Filled => AmendFailure::AlreadyFilled,
+            OrderStatus::Cancelled | OrderStatus::Expired | OrderStatus::Rejected => {
+                AmendFailure::AlreadyClosed
+            }
+            _ => AmendFailure::MatchInFlight,
+        };
+        UpdateOrderError::OrderNotModifiable {
+            reason,
+            order: Box::new(OrderView::from(order)),
+        }
+    }
+
+    /// Creates the error of an update whose new price or quantity is invalid
+    pub(crate) fn invalid(reason: AmendFailure, order: OrderView) -> Self {
+        UpdateOrderError::InvalidUpdateRequest {
+            reason,
+            order: Box::new(order),
+        }
+    }
+
+    /// Gets why the update was refused, None if the order was not found, trading is suspended,
+    /// its owner is frozen or rate limited
+    pub fn reason(&self) -> Option<AmendFailure> {
+        match self {
+            UpdateOrderError::OrderNotModifiable { reason, .. }
+            | UpdateOrderError::InvalidUpdateRequest { reason, .. } => Some(*reason),
+            UpdateOrderError::OrderNotFound
+            | UpdateOrderError::TradingSuspended
+            | UpdateOrderError::MarketOrderNotAmendable
+            | UpdateOrderError::UserFrozen
+            | UpdateOrderError::RateLimited => None,
+        }
+    }
+
+    /// Gets the snapshot of the order when the update was refused, if it was found
+    pub fn order(&self) -> Option<&OrderView> {
+        match self {
+            UpdateOrderError::OrderNotModifiable { order, .. }
+            | UpdateOrderError::InvalidUpdateRequest { order, .. } => Some(order),
+            UpdateOrderError::OrderNotFound
+            | UpdateOrderError::TradingSuspended
+            | UpdateOrderError::MarketOrderNotAmendable
+            | UpdateOrderError::UserFrozen
+            | UpdateOrderError::RateLimited => None,
+        }
+    }
+
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match self {
+            UpdateOrderError::OrderNotFound => 201,
+            UpdateOrderError::OrderNotModifiable { .. } => 202,
+            UpdateOrderError::InvalidUpdateRequest { .. } => 203,
+            UpdateOrderError::TradingSuspended => 204,
+            UpdateOrderError::MarketOrderNotAmendable => 205,
+            UpdateOrderError::UserFrozen => 206,
+            UpdateOrderError::RateLimited => 207,
+        }
+    }
+}
+
+impl fmt::Display for UpdateOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            UpdateOrderError::OrderNotFound => "order not found",
+            UpdateOrderError::OrderNotModifiable { .. } => "order is not modifiable",
+            UpdateOrderError::InvalidUpdateRequest { .. } => "invalid update request",
+            UpdateOrderError::TradingSuspended => "trading is suspended",
+            UpdateOrderError::MarketOrderNotAmendable => "market orders cannot be amended",
+            UpdateOrderError::UserFrozen => "user is frozen",
+            UpdateOrderError::RateLimited => "request rate limit exceeded",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for UpdateOrderError {}
+
+impl CancelOrderError {
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match self {
+            CancelOrderError::OrderNotFound => 301,
+            CancelOrderError::OrderNotCancellable { .. } => 302,
+            CancelOrderError::InvalidCancelRequest => 303,
+            CancelOrderError::EngineHalted => 304,
+            CancelOrderError::RateLimited => 305,
+            CancelOrderError::NotOrderOwner => 306,
+        }
+    }
+}
+
+impl fmt::Display for CancelOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            CancelOrderError::OrderNotFound => "order not found",
+            CancelOrderError::OrderNotCancellable { .. } => "order is not cancellable",
+            CancelOrderError::InvalidCancelRequest => "invalid cancel request",
+            CancelOrderError::EngineHalted => "engine is halted",
+            CancelOrderError::RateLimited => "request rate limit exceeded",
+            CancelOrderError::NotOrderOwner => "order belongs to another user",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for CancelOrderError {}
+
+impl SubmitError {
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match self {
+            SubmitError::QueueFull => 401,
+            SubmitError::Timeout => 402,
+            SubmitError::UnknownSymbol => 403,
+            SubmitError::Disconnected => 404,
+            SubmitError::SymbolListed => 405,
+            SubmitError::Throttled => 406,
+        }
+    }
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            SubmitError::QueueFull => "command queue is full",
+            SubmitError::Timeout => "timed out waiting for room in the command queue",
+            SubmitError::UnknownSymbol => "symbol is not served by this engine",
+            SubmitError::Disconnected => "matching thread has stopped",
+            SubmitError::SymbolListed => "symbol is already served by this engine",
+            SubmitError::Throttled => "throughput limit exceeded",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for SubmitError {}
+
+impl TradeCorrectionError {
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match self {
+            TradeCorrectionError::TradeNotFound => 601,
+            TradeCorrectionError::InvalidPrice => 602,
+            TradeCorrectionError::JournalDisabled => 603,
+        }
+    }
+}
+
+impl fmt::Display for TradeCorrectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            TradeCorrectionError::TradeNotFound => "trade not found",
+            TradeCorrectionError::InvalidPrice => "corrected price is zero",
+            TradeCorrectionError::JournalDisabled => "trade journal is disabled",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for TradeCorrectionError {}
+
+impl BasketError {
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match self {
+            BasketError::UnknownSymbol(_) => 801,
+            BasketError::Rejected { .. } => 802,
+        }
+    }
+}
+
+impl fmt::Display for BasketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BasketError::UnknownSymbol(symbol) => write!(f, "unknown symbol {symbol}"),
+            BasketError::Rejected { order_id, reason } => {
+                write!(f, "basket order {order_id} rejected: {reason}")
+            }
+        }
+    }
+}
+
+impl Error for BasketError {}
+
+impl QuoteError {
+    /// Gets the stable numeric code of the error.
+    /// Failed commands use the code of the underlying error.
+    pub fn code(&self) -> u32 {
+        match self {
+            QuoteError::Rejected(reason) => reason.code(),
+            QuoteError::Update(error) => error.code(),
+            QuoteError::Cancel(error) => error.code(),
+            QuoteError::SideMismatch => 901,
+        }
+    }
+
+    /// Gets the id and status of the resting quote a failed update refers to, if known
+    fn order_context(&self) -> Option<(OrderID, OrderStatus)> {
+        match self {
+            QuoteError::Update(error) => error.order().map(|order| (order.id, order.status)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteError::Rejected(reason) => write!(f, "quote rejected: {reason}"),
+            QuoteError::Update(error) => write!(f, "quote not updated: {error}"),
+            QuoteError::Cancel(error) => write!(f, "quote not canceled: {error}"),
+            QuoteError::SideMismatch => f.write_str("quote is on the other side of the book"),
+        }
+    }
+}
+
+impl Error for QuoteError {}
+
+impl RescaleError {
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match self {
+            RescaleError::NotHalted => 701,
+            RescaleError::Inexact(_) => 702,
+            RescaleError::OrderInFlight(_) => 703,
+        }
+    }
+}
+
+impl fmt::Display for RescaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RescaleError::NotHalted => f.write_str("engine is not halted"),
+            RescaleError::Inexact(order_id) => {
+                write!(f, "order {order_id} does not rescale exactly")
+            }
+            RescaleError::OrderInFlight(order_id) => {
+                write!(f, "order {order_id} is in flight")
+            }
+        }
+    }
+}
+
+impl Error for RescaleError {}
+
+impl OrderValidationError {
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match self {
+            OrderValidationError::InvalidMatchStrategy => 501,
+            OrderValidationError::InvalidTimeInForce => 502,
+            OrderValidationError::InvalidLiquidityDirective => 503,
+            OrderValidationError::SlippageNotApplicable => 504,
+            OrderValidationError::SlippageExceedsMaximum => 505,
+        }
+    }
+}
+
+impl fmt::Display for OrderValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            OrderValidationError::InvalidMatchStrategy => {
+                "match strategy is invalid for the order type"
+            }
+            OrderValidationError::InvalidTimeInForce => {
+                "time in force is invalid for the order type"
+            }
+            OrderValidationError::InvalidLiquidityDirective => {
+                "liquidity directive is invalid for the order type"
+            }
+            OrderValidationError::SlippageNotApplicable => {
+                "slippage tolerance is not applicable to the order type"
+            }
+            OrderValidationError::SlippageExceedsMaximum => {
+                "slippage tolerance exceeds the maximum allowed"
+            }
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for OrderValidationError {}
+
+impl RejectReason {
+    /// Gets the stable numeric code of the reason.
+    /// Validation failures use the code of the underlying validation error.
+    pub fn code(&self) -> u32 {
+        match self {
+            RejectReason::TimestampConflict => 101,
+            RejectReason::InsufficientLiquidity => 102,
+            RejectReason::DuplicateOrderId => 103,
+            RejectReason::InvalidOrder(error) => error.code(),
+            RejectReason::ZeroQuantity => 104,
+            RejectReason::InvalidPrice => 105,
+            RejectReason::PriceOutOfBand => 106,
+            RejectReason::CapacityExceeded => 107,
+            RejectReason::RiskRejected(_) => 110,
+            RejectReason::PositionLimitExceeded => 111,
+            RejectReason::RateLimited => 112,
+            RejectReason::QuantityTooLarge => 113,
+            RejectReason::NotionalTooLarge => 114,
+            RejectReason::StaleMarketOrder => 115,
+            RejectReason::UserFrozen => 116,
+            RejectReason::OffTick => 117,
+            RejectReason::OffLot => 118,
+            RejectReason::BasketRejected => 119,
+            RejectReason::OrderTypeNotAllowed => 120,
+            RejectReason::CancelOnly => 108,
+            RejectReason::Halted => 109,
+        }
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            RejectReason::TimestampConflict => "timestamp conflict",
+            RejectReason::InsufficientLiquidity => "insufficient liquidity",
+            RejectReason::DuplicateOrderId => "duplicate order id",
+            RejectReason::InvalidOrder(error) => return write!(f, "invalid order: {error}"),
+            RejectReason::ZeroQuantity => "quantity is zero",
+            RejectReason::InvalidPrice => "limit price is zero",
+            RejectReason::PriceOutOfBand => "limit price is outside the price band",
+            RejectReason::CapacityExceeded => "book is at capacity",
+            RejectReason::RiskRejected(code) => {
+                return write!(f, "rejected by risk check (code {code})");
+            }
+            RejectReason::PositionLimitExceeded => "position limit exceeded",
+            RejectReason::RateLimited => "submission rate limit exceeded",
+            RejectReason::QuantityTooLarge => "order quantity exceeds the maximum order size",
+            RejectReason::NotionalTooLarge => "order notional exceeds the maximum notional",
+            RejectReason::StaleMarketOrder => "market order waited too long for a match",
+            RejectReason::UserFrozen => "user is frozen",
+            RejectReason::OffTick => "limit price is not a multiple of the tick size",
+            RejectReason::OffLot => "quantity is not a multiple of the lot size",
+            RejectReason::BasketRejected => "another order of the basket was rejected",
+            RejectReason::OrderTypeNotAllowed => "order type is not allowed in the book",
+            RejectReason::CancelOnly => "engine only accepts cancels",
+            RejectReason::Halted => "engine is halted",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for RejectReason {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RejectReason::InvalidOrder(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// EngineErrorKind is the operation-specific failure wrapped by an `EngineError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineErrorKind {
+    /// An order was rejected.
+    Rejected(RejectReason),
+    /// An update or amend failed.
+    Update(UpdateOrderError),
+    /// A cancel failed.
+    Cancel(CancelOrderError),
+    /// A command could not be submitted.
+    Submit(SubmitError),
+    /// An order failed validation.
+    Validation(OrderValidationError),
+    /// A trade bust or correction failed.
+    Correction(TradeCorrectionError),
+    /// A rescale of the book failed.
+    Rescale(RescaleError),
+    /// A basket was not executed.
+    Basket(BasketError),
+    /// An entry of a mass quote failed.
+    Quote(QuoteError),
+    /// A replicated command could not be applied, shipped or taken over.
+    Failover(FailoverError),
+}
+
+/// EngineError is the unified error of the engine, carrying the failure and
+/// the context it happened in, so it can be boxed, logged, and mapped to a wire code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineError {
+    pub kind: EngineErrorKind,
+    /// The order the failure applies to, if known.
+    pub order_id: Option<OrderID>,
+    /// The status of the order when the failure happened, if known.
+    pub status: Option<OrderStatus>,
+}
+
+impl EngineError {
+    /// Creates a new engine error without context
+    pub fn new(kind: EngineErrorKind) -> Self {
+        Self {
+            kind,
+            order_id: None,
+            status: None,
+        }
+    }
+
+    /// Attaches the order id the failure applies to
+    pub fn with_order_id(mut self, order_id: OrderID) -> Self {
+        self.order_id = Some(order_id);
+        self
+    }
+
+    /// Attaches the order status at the time of the failure
+    pub fn with_status(mut self, status: OrderStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Creates an engine error from a rejected order, capturing its id and status
+    pub fn rejected(order: &Order, reason: RejectReason) -> Self {
+        Self::new(EngineErrorKind::Rejected(reason))
+            .with_order_id(order.id)
+            .with_status(order.status())
+    }
+
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match &self.kind {
+            EngineErrorKind::Rejected(reason) => reason.code(),
+            EngineErrorKind::Update(error) => error.code(),
+            EngineErrorKind::Cancel(error) => error.code(),
+            EngineErrorKind::Submit(error) => error.code(),
+            EngineErrorKind::Validation(error) => error.code(),
+            EngineErrorKind::Correction(error) => error.code(),
+            EngineErrorKind::Rescale(error) => error.code(),
+            EngineErrorKind::Basket(error) => error.code(),
+            EngineErrorKind::Quote(error) => error.code(),
+            EngineErrorKind::Failover(error) => error.code(),
+        }
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] ", self.code())?;
+        match &self.kind {
+            EngineErrorKind::Rejected(reason) => write!(f, "order rejected: {reason}")?,
+            EngineErrorKind::Update(error) => write!(f, "update failed: {error}")?,
+            EngineErrorKind::Cancel(error) => write!(f, "cancel failed: {error}")?,
+            EngineErrorKind::Submit(error) => write!(f, "submit failed: {error}")?,
+            EngineErrorKind::Validation(error) => write!(f, "validation failed: {error}")?,
+            EngineErrorKind::Correction(error) => write!(f, "trade correction failed: {error}")?,
+            EngineErrorKind::Rescale(error) => write!(f, "rescale failed: {error}")?,
+            EngineErrorKind::Basket(error) => write!(f, "basket failed: {error}")?,
+            EngineErrorKind::Quote(error) => write!(f, "quote failed: {error}")?,
+            EngineErrorKind::Failover(error) => write!(f, "failover failed: {error}")?,
+        }
+        if let Some(order_id) = self.order_id {
+            write!(f, " (order {order_id}")?;
+            if let Some(status) = self.status {
+                write!(f, ", status {status:?}")?;
+            }
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for EngineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            EngineErrorKind::Rejected(reason) => Some(reason),
+            EngineErrorKind::Update(error) => Some(error),
+            EngineErrorKind::Cancel(error) => Some(error),
+            EngineErrorKind::Submit(error) => Some(error),
+            EngineErrorKind::Validation(error) => Some(error),
+            EngineErrorKind::Correction(error) => Some(error),
+            EngineErrorKind::Rescale(error) => Some(error),
+            EngineErrorKind::Basket(error) => Some(error),
+            EngineErrorKind::Quote(error) => Some(error),
+            EngineErrorKind::Failover(error) => Some(error),
+        }
+    }
+}
+
+impl From<RejectReason> for EngineError {
+    fn from(reason: RejectReason) -> Self {
+        Self::new(EngineErrorKind::Rejected(reason))
+    }
+}
+
+impl From<UpdateOrderError> for EngineError {
+    fn from(error: UpdateOrderError) -> Self {
+        match error.order().map(|order| (order.id, order.status)) {
+            Some((order_id, status)) => Self::new(EngineErrorKind::Update(error))
+                .with_order_id(order_id)
+                .with_status(status),
+            None => Self::new(EngineErrorKind::Update(error)),
+        }
+    }
+}
+
+impl From<CancelOrderError> for EngineError {
+    fn from(error: CancelOrderError) -> Self {
+        match error {
+            CancelOrderError::OrderNotCancellable { status, .. } => {
+                Self::new(EngineErrorKind::Cancel(error)).with_status(status)
+            }
+            _ => Self::new(EngineErrorKind::Cancel(error)),
+        }
+    }
+}
+
+impl From<SubmitError> for EngineError {
+    fn from(error: SubmitError) -> Self {
+        Self::new(EngineErrorKind::Submit(error))
+    }
+}
+
+impl From<OrderValidationError> for EngineError {
+    fn from(error: OrderValidationError) -> Self {
+        Self::new(EngineErrorKind::Validation(error))
+    }
+}
+
+impl From<TradeCorrectionError> for EngineError {
+    fn from(error: TradeCorrectionError) -> Self {
+        Self::new(EngineErrorKind::Correction(error))
+    }
+}
+
+impl From<RescaleError> for EngineError {
+    fn from(error: RescaleError) -> Self {
+        match error {
+            RescaleError::Inexact(order_id) | RescaleError::OrderInFlight(order_id) => {
+                Self::new(EngineErrorKind::Rescale(error)).with_order_id(order_id)
+            }
+            RescaleError::NotHalted => Self::new(EngineErrorKind::Rescale(error)),
+        }
+    }
+}
+
+impl From<BasketError> for EngineError {
+    fn from(error: BasketError) -> Self {
+        match error {
+            BasketError::Rejected { order_id, .. } => {
+                Self::new(EngineErrorKind::Basket(error)).with_order_id(order_id)
+            }
+            BasketError::UnknownSymbol(_) => Self::new(EngineErrorKind::Basket(error)),
+        }
+    }
+}
+
+impl From<QuoteError> for EngineError {
+    fn from(error: QuoteError) -> Self {
+        match error.order_context() {
+            Some((order_id, status)) => Self::new(EngineErrorKind::Quote(error))
+                .with_order_id(order_id)
+                .with_status(status),
+            None => Self::new(EngineErrorKind::Quote(error)),
+        }
+    }
+}
+
+impl From<FailoverError> for EngineError {
+    fn from(error: FailoverError) -> Self {
+        Self::new(EngineErrorKind::Failover(error))
+    }
 }
@@ -0,0 +1,245 @@
+use crate::prelude::*;
+use crypto_bigint::{NonZero, U256, U512, Zero};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// ExecutionKind is the state change an execution report was generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionKind {
+    /// The order book accepted a new order.
+    New,
+    /// The order was amended or re-priced.
+    Replaced,
+    /// The order was executed; the report carries the fill.
+    Trade,
+    /// The order was canceled.
+    Cancelled,
+    /// The order expired due to its time-in-force or the book's maximum resting time.
+    Expired,
+    /// The engine rejected the order.
+    Rejected,
+}
+
+/// ExecutionFill is a single execution of an order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionFill {
+    pub trade_id: u64,
+    pub role: TradeRole,
+    pub price: Price,
+    pub quantity: Quantity,
+}
+
+/// ExecutionReport is the state of an order and its fills so far,
+/// generated on each change of the order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionReport {
+    /// Syncer id of the book change the report belongs to.
+    pub sync_id: u64,
+    pub kind: ExecutionKind,
+    /// Status of the order as of this report.
+    pub status: OrderStatus,
+    pub order: OrderView,
+    /// FIX `ExecType` of the report.
+    pub exec_type: FixExecType,
+    /// FIX `OrdStatus` of the order as of this report.
+    pub ord_status: FixOrdStatus,
+    /// Total quantity filled so far.
+    pub filled_quantity: Quantity,
+    /// Quantity that is still open as of this report.
+    pub remaining_quantity: Quantity,
+    /// Quantity-weighted average fill price, or `None` before the first fill.
+    pub average_price: Option<Price>,
+    /// The fill this report was generated for, on `Trade` reports.
+    pub last_fill: Option<ExecutionFill>,
+    pub cancel_reason: Option<CancelReason>,
+    pub reject_reason: Option<RejectReason>,
+}
+
+/// ExecutionReportSink trait receives every execution report
+pub trait ExecutionReportSink: Send + Sync {
+    /// This function is called for every report
+    fn report(&self, report: &ExecutionReport);
+}
+
+/// FillTotals accumulates the fills of an order while it is open.
+/// The notional is kept in 512 bits so price times quantity never overflows.
+#[derive(Debug, Clone, Copy, Default)]
+struct FillTotals {
+    quantity: Quantity,
+    notional: U512,
+}
+
+impl FillTotals {
+    fn add(&mut self, price: Price, quantity: Quantity) {
+        let notional: U512 = price.widening_mul(&quantity);
+        self.quantity = self.quantity.saturating_add(&quantity);
+        self.notional = self.notional.saturating_add(&notional);
+    }
+
+    fn average_price(&self) -> Option<Price> {
+        let quantity = NonZero::new(self.quantity.resize::<{ U512::LIMBS }>()).into_option()?;
+        Some((self.notional / quantity).resize::<{ U256::LIMBS }>())
+    }
+}
+
+/// ExecutionReportSyncer forwards every book change to the primary syncer
+/// and then reports it, with the order's aggregated fills, to an execution report sink.
+///
+/// Fill totals are kept per order until the order is filled, canceled, expired or rejected,
+/// and an order that leaves matching unfilled, like the remainder of an immediate-or-cancel
+/// order, is reported as canceled.
+pub struct ExecutionReportSyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    sink: Arc<dyn ExecutionReportSink>,
+    fills: Mutex<HashMap<OrderID, FillTotals>>,
+}
+
+impl ExecutionReportSyncer {
+    /// Creates a new execution report syncer in front of the primary syncer
+    pub fn new(primary: Arc<dyn OrderBookSyncer>, sink: Arc<dyn ExecutionReportSink>) -> Self {
+        Self {
+            primary,
+            sink,
+            fills: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the number of orders with fill totals being tracked
+    pub fn tracked_orders(&self) -> usize {
+        self.fills.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    fn emit(&self, sync_id: u64, kind: ExecutionKind, order: &Order) {
+        let totals = {
+            let mut fills = self.fills.lock().unwrap_or_else(|e| e.into_inner());
+            match kind {
+                ExecutionKind::Cancelled | ExecutionKind::Expired | ExecutionKind::Rejected => {
+                    fills.remove(&order.id)
+                }
+                _ => fills.get(&order.id).copied(),
+            }
+        }
+        .unwrap_or_default();
+        self.sink.report(&ExecutionReport {
+            sync_id,
+            kind,
+            status: order.status(),
+            order: OrderView::from(order),
+            exec_type: FixExecType::from(kind),
+            ord_status: FixOrdStatus::from(order),
+            filled_quantity: totals.quantity,
+            remaining_quantity: order.quantity(),
+            average_price: totals.average_price(),
+            last_fill: None,
+            cancel_reason: order.cancel_reason(),
+            reject_reason: order.reject_reason(),
+        });
+    }
+
+    fn emit_removed(&self, sync_id: u64, order: &Order) {
+        let kind = match order.status() {
+            OrderStatus::Expired => ExecutionKind::Expired,
+            OrderStatus::Rejected => ExecutionKind::Rejected,
+            _ => ExecutionKind::Cancelled,
+        };
+        self.emit(sync_id, kind, order);
+    }
+
+    fn emit_fill(&self, sync_id: u64, order: &Order, trade: &Trade) {
+        let total = order.quantity().saturating_add(&order.filled_quantity());
+        let totals = {
+            let mut fills = self.fills.lock().unwrap_or_else(|e| e.into_inner());
+            let totals = fills.entry(order.id).or_default();
+            totals.add(trade.price, trade.quantity);
+            let totals = *totals;
+            if totals.quantity >= total {
+                fills.remove(&order.id);
+            }
+            totals
+        };
+        let remaining_quantity = total.saturating_sub(&totals.quantity);
+        let status = if bool::from(remaining_quantity.is_zero()) {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        self.sink.report(&ExecutionReport {
+            sync_id,
+            kind: ExecutionKind::Trade,
+            status,
+            order: OrderView::from(order),
+            exec_type: FixExecType::Trade,
+            ord_status: FixOrdStatus::from(status),
+            filled_quantity: totals.quantity,
+            remaining_quantity,
+            average_price: totals.average_price(),
+            last_fill: Some(ExecutionFill {
+                trade_id: trade.trade_id,
+                role: trade.role,
+                price: trade.price,
+                quantity: trade.quantity,
+            }),
+            cancel_reason: None,
+            reject_reason: None,
+        });
+    }
+}
+
+impl OrderBookSyncer for ExecutionReportSyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+        self.emit(id, ExecutionKind::New, order);
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+        self.emit(id, ExecutionKind::Replaced, order);
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+        self.emit_removed(id, order);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+        self.emit(id, ExecutionKind::Rejected, order);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        for (trade, order) in trade_orders(updated, trades) {
+            self.emit_fill(id, order, trade);
+        }
+        for order in updated {
+            match order.status() {
+                OrderStatus::Rejected => self.emit(id, ExecutionKind::Rejected, order),
+                OrderStatus::Filled => {}
+                // Unfilled remainders, and orders canceled by self-trade prevention or the
+                // sweep limits, leave with the match
+                _ if order.is_finished() => self.emit_removed(id, order),
+                _ => {}
+            }
+        }
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        for event in events {
+            match event {
+                BookEvent::Added(order) => self.emit(id, ExecutionKind::New, order),
+                BookEvent::Updated(order) => self.emit(id, ExecutionKind::Replaced, order),
+                BookEvent::Cancelled(order) => self.emit_removed(id, order),
+                BookEvent::Rejected(order) => self.emit(id, ExecutionKind::Rejected, order),
+            }
+        }
+    }
+}
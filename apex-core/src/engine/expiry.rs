@@ -0,0 +1,123 @@
+use crate::prelude::*;
+use crossbeam::channel::{Receiver, bounded};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type ExpiryCallback = Box<dyn FnOnce(&CancelEvent) + Send>;
+
+/// ExpirySyncer forwards every book change to the primary syncer and fires the callback
+/// registered for an order when it expires, so strategy code can re-quote right away.
+///
+/// Only a GoodTillDate deadline or the book's maximum resting time fires the callback;
+/// a registration is dropped silently once its order is canceled, filled or rejected.
+/// Callbacks run on the thread expiring the order, after the primary syncer saw it.
+pub struct ExpirySyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    callbacks: Mutex<HashMap<OrderID, ExpiryCallback>>,
+}
+
+impl ExpirySyncer {
+    /// Creates a new expiry syncer in front of the primary syncer
+    pub fn new(primary: Arc<dyn OrderBookSyncer>) -> Self {
+        Self {
+            primary,
+            callbacks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers a callback fired once when an order expires, replacing any previous one
+    pub fn on_expiry(
+        &self,
+        order_id: OrderID,
+        callback: impl FnOnce(&CancelEvent) + Send + 'static,
+    ) {
+        self.lock().insert(order_id, Box::new(callback));
+    }
+
+    /// Registers for the expiry of an order through a channel, replacing any previous registration.
+    /// The receiver gets a single event, or disconnects once the order leaves the book otherwise.
+    pub fn notify_expiry(&self, order_id: OrderID) -> Receiver<CancelEvent> {
+        let (sender, receiver) = bounded(1);
+        self.on_expiry(order_id, move |event| {
+            let _ = sender.send(event.clone());
+        });
+        receiver
+    }
+
+    /// Removes the registration of an order, returning whether there was one
+    pub fn cancel_expiry(&self, order_id: OrderID) -> bool {
+        self.lock().remove(&order_id).is_some()
+    }
+
+    /// Gets the number of registered orders
+    pub fn registered(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<OrderID, ExpiryCallback>> {
+        self.callbacks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Fires the callback of an order that left the book if it expired, and drops it either way
+    fn removed(&self, order: &Order) {
+        let Some(callback) = self.lock().remove(&order.id) else {
+            return;
+        };
+        if order.status() != OrderStatus::Expired {
+            return;
+        }
+        if let Some(reason) = order.cancel_reason() {
+            callback(&CancelEvent {
+                reason,
+                order: OrderView::from(order),
+            });
+        }
+    }
+}
+
+impl OrderBookSyncer for ExpirySyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+        self.removed(order);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+        self.removed(order);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        for order in updated.iter().filter(|order| !order.is_live()) {
+            self.removed(order);
+        }
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        for event in events {
+            match event {
+                BookEvent::Cancelled(order) | BookEvent::Rejected(order) => self.removed(order),
+                BookEvent::Added(_) | BookEvent::Updated(_) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::prelude::*;
+
+/// FixExecType is the FIX `ExecType` (tag 150) of an execution report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixExecType {
+    New,
+    Canceled,
+    Replaced,
+    Rejected,
+    Expired,
+    Trade,
+}
+
+impl FixExecType {
+    /// Gets the FIX wire value
+    pub fn code(&self) -> char {
+        match self {
+            FixExecType::New => '0',
+            FixExecType::Canceled => '4',
+            FixExecType::Replaced => '5',
+            FixExecType::Rejected => '8',
+            FixExecType::Expired => 'C',
+            FixExecType::Trade => 'F',
+        }
+    }
+
+    /// Maps the reason an order left the book to a FIX `ExecType`
+    pub fn from_cancel_reason(reason: CancelReason) -> Self {
+        match reason {
+            CancelReason::UserRequest
+            | CancelReason::MassCancel
+            | CancelReason::SweepLimit
+            | CancelReason::SelfTradePrevention => FixExecType::Canceled,
+            CancelReason::TimeInForceExpired | CancelReason::LifetimeExceeded => {
+                FixExecType::Expired
+            }
+        }
+    }
+}
+
+impl From<ExecutionKind> for FixExecType {
+    fn from(kind: ExecutionKind) -> Self {
+        match kind {
+            ExecutionKind::New => FixExecType::New,
+            ExecutionKind::Replaced => FixExecType::Replaced,
+            ExecutionKind::Trade => FixExecType::Trade,
+            ExecutionKind::Cancelled => FixExecType::Canceled,
+            ExecutionKind::Expired => FixExecType::Expired,
+            ExecutionKind::Rejected => FixExecType::Rejected,
+        }
+    }
+}
+
+/// FixOrdStatus is the FIX `OrdStatus` (tag 39) of an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixOrdStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+    PendingNew,
+    Expired,
+}
+
+impl FixOrdStatus {
+    /// Gets the FIX wire value
+    pub fn code(&self) -> char {
+        match self {
+            FixOrdStatus::New => '0',
+            FixOrdStatus::PartiallyFilled => '1',
+            FixOrdStatus::Filled => '2',
+            FixOrdStatus::Canceled => '4',
+            FixOrdStatus::Rejected => '8',
+            FixOrdStatus::PendingNew => 'A',
+            FixOrdStatus::Expired => 'C',
+        }
+    }
+
+    /// Maps the full state of an order to a FIX `OrdStatus`.
+    ///
+    /// Unlike the plain status mapping, a partially filled order that has finished,
+    /// i.e. the unfilled remainder of an immediate-or-cancel order, is canceled,
+    /// and a cancellation for a time reason is an expiry.
+    pub fn from_order_state(
+        status: OrderStatus,
+        lifecycle: OrderLifecycle,
+        cancel_reason: Option<CancelReason>,
+    ) -> Self {
+        match status {
+            OrderStatus::PartiallyFilled if lifecycle == OrderLifecycle::Finished => {
+                FixOrdStatus::Canceled
+            }
+            OrderStatus::Cancelled => match cancel_reason.map(FixExecType::from_cancel_reason) {
+                Some(FixExecType::Expired) => FixOrdStatus::Expired,
+                _ => FixOrdStatus::Canceled,
+            },
+            status => FixOrdStatus::from(status),
+        }
+    }
+}
+
+impl From<OrderStatus> for FixOrdStatus {
+    fn from(status: OrderStatus) -> Self {
+        match status {
+            OrderStatus::Pending => FixOrdStatus::PendingNew,
+            OrderStatus::Placed => FixOrdStatus::New,
+            OrderStatus::PartiallyFilled => FixOrdStatus::PartiallyFilled,
+            OrderStatus::Filled => FixOrdStatus::Filled,
+            OrderStatus::Cancelled => FixOrdStatus::Canceled,
+            OrderStatus::Rejected => FixOrdStatus::Rejected,
+            OrderStatus::Expired => FixOrdStatus::Expired,
+        }
+    }
+}
+
+impl From<&Order> for FixOrdStatus {
+    fn from(order: &Order) -> Self {
+        FixOrdStatus::from_order_state(
+            order.status(),
+            order.lifecycle.load(),
+            order.cancel_reason(),
+        )
+    }
+}
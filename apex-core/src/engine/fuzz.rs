@@ -0,0 +1,263 @@
+use crate::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+
+/// FuzzCommand is a single step decoded from fuzzer input.
+// Orders are far larger than the other variants, and boxing them costs an allocation per step
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum FuzzCommand {
+    /// Execute a create, update, amend, or cancel command.
+    Execute(Command),
+    /// Cancel every order of a side.
+    CancelWhere(Side),
+    /// Expire good-till-date orders at the time in microseconds.
+    Expire(u64),
+    /// Switch the engine mode.
+    SetMode(EngineMode),
+    /// Run a match cycle.
+    Match,
+}
+
+/// Largest limit price produced by the decoder, kept small so orders cross often.
+const FUZZ_MAX_PRICE: u8 = 32;
+/// Largest quantity produced by the decoder.
+const FUZZ_MAX_QUANTITY: u8 = 16;
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.data.get(self.position).copied()?;
+        self.position += 1;
+        Some(byte)
+    }
+}
+
+struct Decoder {
+    next_id: OrderID,
+    now: u64,
+}
+
+impl Decoder {
+    fn side(byte: u8) -> Side {
+        if byte & 1 == 0 { Side::Buy } else { Side::Sell }
+    }
+
+    /// Picks an id that was handed out, or occasionally one that never was.
+    fn known_id(&self, byte: u8) -> OrderID {
+        byte as u64 % (self.next_id + 1)
+    }
+
+    fn create(&mut self, reader: &mut ByteReader, market: bool) -> Option<Command> {
+        let flags = reader.next()?;
+        let quantity = Quantity::from((reader.next()? % (FUZZ_MAX_QUANTITY + 1)) as u64);
+        let id = if flags & 0x80 != 0 && self.next_id > 1 {
+            // Reuse an earlier id to exercise duplicate detection.
+            self.known_id(flags)
+        } else {
+            self.next_id += 1;
+            self.next_id - 1
+        };
+        let user_id = 1 + (flags as u64 >> 1) % 4;
+        let side = Self::side(flags);
+        let mut order = if market {
+            Order::market(id, user_id, side, quantity, self.now)
+        } else {
+            let price = Price::from((reader.next()? % (FUZZ_MAX_PRICE + 1)) as u64);
+            Order::limit(id, user_id, side, price, quantity, self.now)
+        };
+        // The remaining options are decoded raw, so invalid combinations reach validation.
+        let options = reader.next()?;
+        order.match_strategy = match options & 0b11 {
+            0 => order.match_strategy,
+            1 => MatchStrategy::Standard,
+            2 => MatchStrategy::FillOrKill,
+            _ => MatchStrategy::ImmediateOrCancel,
+        };
+        order.liquidity_directive = match (options >> 2) & 0b11 {
+            1 => LiquidityDirective::MakerOnly,
+            2 => LiquidityDirective::TakerOnly,
+            _ => order.liquidity_directive,
+        };
+        order.time_in_force = match (options >> 4) & 0b11 {
+            1 => TimeInForce::None,
+            2 => TimeInForce::GoodTillDate(self.now + (options as u64) * 1000),
+            _ => order.time_in_force,
+        };
+        if options & 0x40 != 0 {
+            order.slippage_tolerance = Some(SlippageTolerance((options as u32) * 50));
+        }
+        Some(Command::Create(order))
+    }
+
+    fn decode(&mut self, reader: &mut ByteReader) -> Option<FuzzCommand> {
+        let opcode = reader.next()?;
+        self.now += 1 + (opcode >> 4) as u64;
+        let command = match opcode % 10 {
+            0 | 1 => FuzzCommand::Execute(self.create(reader, false)?),
+            2 => FuzzCommand::Execute(self.create(reader, true)?),
+            3 => FuzzCommand::Execute(Command::Cancel(self.known_id(reader.next()?))),
+            4 => FuzzCommand::Execute(Command::Update {
+                order_id: self.known_id(reader.next()?),
+                new_price: Price::from((reader.next()? % (FUZZ_MAX_PRICE + 1)) as u64),
+                now_microseconds: self.now,
+            }),
+            5 => FuzzCommand::Execute(Command::Amend {
+                order_id: self.known_id(reader.next()?),
+                new_quantity: Quantity::from((reader.next()? % (FUZZ_MAX_QUANTITY + 1)) as u64),
+                now_microseconds: self.now,
+            }),
+            6 => FuzzCommand::CancelWhere(Self::side(reader.next()?)),
+            7 => FuzzCommand::Expire(self.now + reader.next()? as u64 * 1000),
+            8 => FuzzCommand::SetMode(match reader.next()? % 8 {
+                // Weighted towards normal mode so most sequences keep trading.
+                0 => EngineMode::Halted,
+                1 => EngineMode::CancelOnly,
+                _ => EngineMode::Normal,
+            }),
+            _ => FuzzCommand::Match,
+        };
+        Some(command)
+    }
+}
+
+/// Decodes arbitrary bytes into a sequence of engine commands.
+/// Every input decodes without failing; a truncated trailing command is dropped.
+pub fn decode_commands(data: &[u8]) -> Vec<FuzzCommand> {
+    let mut reader = ByteReader::new(data);
+    let mut decoder = Decoder {
+        next_id: 1,
+        now: DEFAULT_EPOCH_MILLIS * 1000,
+    };
+    let mut commands = Vec::new();
+    while let Some(command) = decoder.decode(&mut reader) {
+        commands.push(command);
+    }
+    commands
+}
+
+/// Checks the structural invariants of a book snapshot.
+pub fn check_book_invariants(snapshot: &BookSnapshot) -> Result<(), String> {
+    for side in [Side::Buy, Side::Sell] {
+        let orders = snapshot.side(side);
+        for order in orders {
+            if order.quantity == Quantity::ZERO {
+                return Err(format!("order {} rests with zero quantity", order.id));
+            }
+            if !matches!(
+                order.status,
+                OrderStatus::Placed | OrderStatus::PartiallyFilled
+            ) {
+                return Err(format!(
+                    "order {} rests with status {:?}",
+                    order.id, order.status
+                ));
+            }
+            if order.side != side {
+                return Err(format!("order {} rests on the wrong side", order.id));
+            }
+        }
+        let sorted = orders.windows(2).all(|pair| match side {
+            Side::Buy => pair[0].price >= pair[1].price,
+            Side::Sell => pair[0].price <= pair[1].price,
+        });
+        if !sorted {
+            return Err(format!("{side:?} side is not in price priority"));
+        }
+    }
+    let mut ids = snapshot
+        .bids
+        .iter()
+        .chain(&snapshot.asks)
+        .chain(&snapshot.market_orders)
+        .map(|order| order.id)
+        .collect::<Vec<_>>();
+    let total = ids.len();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() != total {
+        return Err("an order id rests more than once".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that matched trades come in maker/taker pairs of the same price and quantity.
+pub fn check_trade_invariants(trades: &[Trade]) -> Result<(), String> {
+    let pairs = trades.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err(format!("{} trade legs do not form pairs", trades.len()));
+    }
+    for pair in pairs {
+        let (first, second) = (&pair[0], &pair[1]);
+        if first.trade_id != second.trade_id
+            || first.price != second.price
+            || first.quantity != second.quantity
+            || first.role == second.role
+        {
+            return Err(format!("trade {} legs do not agree", first.trade_id));
+        }
+        if first.quantity == Quantity::ZERO {
+            return Err(format!("trade {} has zero quantity", first.trade_id));
+        }
+    }
+    Ok(())
+}
+
+struct InvariantSyncer {}
+
+impl OrderBookSyncer for InvariantSyncer {
+    fn add_order(&self, _id: u64, _order: &Order) {}
+
+    fn update_order(&self, _id: u64, _order: &Order) {}
+
+    fn cancel_order(&self, _id: u64, _order: &Order) {}
+
+    fn matched(&self, _id: u64, _updated: &[Order], trades: &[Trade]) {
+        if let Err(violation) = check_trade_invariants(trades) {
+            panic!("trade invariant violated: {violation}");
+        }
+    }
+}
+
+/// Decodes arbitrary bytes into commands and applies them to a fresh engine,
+/// panicking when a book or trade invariant is violated.
+///
+/// Meant as the body of a cargo-fuzz target:
+/// `fuzz_target!(|data: &[u8]| apex_core::prelude::fuzz_apply(data));`
+pub fn fuzz_apply(data: &[u8]) {
+    let book = Arc::new(DefaultOrderBook::new(
+        Arc::new(AtomicU64::new(1)),
+        Arc::new(InvariantSyncer {}),
+    ));
+    let engine = DefaultMatchingEngine::new(book);
+    for command in decode_commands(data) {
+        match command {
+            FuzzCommand::Execute(command) => command.apply(&engine),
+            FuzzCommand::CancelWhere(side) => {
+                engine.cancel_where(side, None);
+            }
+            FuzzCommand::Expire(now) => {
+                engine.expire_orders(now);
+            }
+            FuzzCommand::SetMode(mode) => engine.set_mode(mode),
+            FuzzCommand::Match => {
+                engine.match_orders();
+                if let Err(violation) = check_book_invariants(&engine.snapshot()) {
+                    panic!("book invariant violated: {violation}");
+                }
+            }
+        }
+    }
+    engine.match_orders();
+    if let Err(violation) = check_book_invariants(&engine.snapshot()) {
+        panic!("book invariant violated: {violation}");
+    }
+}
@@ -0,0 +1,137 @@
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// UserTrade is one user's side of a trade.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserTrade {
+    pub side: Side,
+    pub trade: Trade,
+}
+
+/// TradeHistory keeps the most recent trades of every user in memory,
+/// so simple deployments can serve a user's fills without an external database.
+/// It also keeps the book's tape of prints, and computes VWAP and TWAP benchmarks over it.
+///
+/// At most `retention` trades are kept per user, and as many prints on the tape unless set
+/// with `with_tape_retention`; older ones are dropped first. Busted trades are dropped,
+/// price corrections and rescales are applied to the kept trades and prints.
+/// Feed trades with `record`, or attach it to the book through a `FanOutSyncer`.
+pub struct TradeHistory {
+    retention: usize,
+    trades: Mutex<HashMap<u64, VecDeque<UserTrade>>>,
+    tape: Tape,
+}
+
+impl TradeHistory {
+    /// Creates a new trade history keeping up to `retention` trades per user
+    pub fn new(retention: usize) -> Self {
+        Self {
+            retention,
+            trades: Mutex::new(HashMap::new()),
+            tape: Tape::new(retention),
+        }
+    }
+
+    /// Sets how many of the most recent prints the tape keeps
+    pub fn with_tape_retention(mut self, prints: usize) -> Self {
+        self.tape = Tape::new(prints);
+        self
+    }
+
+    /// Gets up to `limit` of the user's trades, most recent first
+    pub fn trades_for_user(&self, user_id: u64, limit: usize) -> Vec<UserTrade> {
+        let trades = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        trades.get(&user_id).map_or_else(Vec::new, |history| {
+            history.iter().rev().take(limit).cloned().collect()
+        })
+    }
+
+    /// Records each user's side of the trades of a match, and prints the match on the tape.
+    /// Trades are attributed through the orders the match reported as updated.
+    pub fn record(&self, updated: &[Order], trades: &[Trade]) {
+        self.tape.record(trades);
+        if self.retention == 0 {
+            return;
+        }
+        let mut history = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        for (trade, order) in trade_orders(updated, trades) {
+            let user_trades = history.entry(order.user_id).or_default();
+            if user_trades.len() == self.retention {
+                user_trades.pop_front();
+            }
+            user_trades.push_back(UserTrade {
+                side: order.side,
+                trade: trade.clone(),
+            });
+        }
+    }
+
+    /// Drops the sides of a busted trade, or sets the corrected price on them
+    pub fn correct(&self, correction: &TradeCorrection) {
+        self.tape.correct(correction);
+        let mut history = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        for user_trades in history.values_mut() {
+            match correction.kind {
+                TradeCorrectionKind::Bust => user_trades
+                    .retain(|user_trade| user_trade.trade.trade_id != correction.trade_id),
+                TradeCorrectionKind::Price(price) => user_trades
+                    .iter_mut()
+                    .filter(|user_trade| user_trade.trade.trade_id == correction.trade_id)
+                    .for_each(|user_trade| user_trade.trade.price = price),
+            }
+        }
+    }
+
+    /// Scales the prices and quantities of the kept trades to a rescaled book, rounding down
+    pub fn rescale(&self, rescale: &Rescale) {
+        self.tape.rescale(rescale);
+        let mut history = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        for user_trade in history.values_mut().flatten() {
+            let trade = &mut user_trade.trade;
+            trade.price = rescale.price.apply_rounded(trade.price);
+            trade.quantity = rescale.quantity.apply_rounded(trade.quantity);
+        }
+    }
+
+    /// Gets the prints of a window of the tape, oldest first
+    pub fn prints(&self, window: TapeWindow) -> Vec<TapePrint> {
+        self.tape.prints(window)
+    }
+
+    /// Computes the volume-weighted average price of a window of the tape,
+    /// None if it has no prints
+    pub fn vwap(&self, window: TapeWindow) -> Option<Price> {
+        self.tape.vwap(window)
+    }
+
+    /// Computes the time-weighted average price of a window of the tape, None if it has no
+    /// prints.
+    ///
+    /// Each print's price holds until the next print, and the last one until the end of a
+    /// range; a range also carries in the price of the latest print before it. When no time
+    /// passes between the prints, their prices are averaged evenly instead.
+    pub fn twap(&self, window: TapeWindow) -> Option<Price> {
+        self.tape.twap(window)
+    }
+}
+
+impl OrderBookSyncer for TradeHistory {
+    fn add_order(&self, _id: u64, _order: &Order) {}
+
+    fn update_order(&self, _id: u64, _order: &Order) {}
+
+    fn cancel_order(&self, _id: u64, _order: &Order) {}
+
+    fn matched(&self, _id: u64, updated: &[Order], trades: &[Trade]) {
+        self.record(updated, trades);
+    }
+
+    fn trade_corrected(&self, _id: u64, correction: &TradeCorrection) {
+        self.correct(correction);
+    }
+
+    fn rescaled(&self, _id: u64, rescale: &Rescale) {
+        self.rescale(rescale);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const WORKER_ID_BITS: u32 = 10;
+const SEQUENCE_BITS: u32 = 12;
+const MAX_WORKER_ID: u64 = (1 << WORKER_ID_BITS) - 1;
+const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+const TIMESTAMP_SHIFT: u32 = WORKER_ID_BITS + SEQUENCE_BITS;
+
+/// DEFAULT_EPOCH_MILLIS is 2024-01-01T00:00:00Z, the default origin of generated timestamps.
+pub const DEFAULT_EPOCH_MILLIS: u64 = 1_704_067_200_000;
+
+/// IdGenerator produces unique, roughly time-ordered 64-bit ids in the snowflake layout:
+/// 42 bits of milliseconds since the epoch, 10 bits of worker id, and a 12-bit sequence.
+///
+/// Generation is lock-free. When the sequence of a millisecond is exhausted or the
+/// wall clock steps backwards, the generator borrows the next millisecond instead of
+/// blocking, so ids stay unique and increasing for a given worker.
+pub struct IdGenerator {
+    worker_id: u64,
+    epoch_millis: u64,
+    clock: Arc<dyn Clock>,
+    // Last timestamp in the high bits and last sequence in the low bits
+    state: AtomicU64,
+}
+
+impl IdGenerator {
+    /// Creates a new id generator for a worker, counting from `DEFAULT_EPOCH_MILLIS`
+    pub fn new(worker_id: u16) -> Self {
+        Self::with_epoch(worker_id, DEFAULT_EPOCH_MILLIS)
+    }
+
+    /// Creates a new id generator for a worker with a custom epoch in Unix milliseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker_id` does not fit in 10 bits.
+    pub fn with_epoch(worker_id: u16, epoch_millis: u64) -> Self {
+        assert!(
+            worker_id as u64 <= MAX_WORKER_ID,
+            "worker id must be less than {}",
+            MAX_WORKER_ID + 1
+        );
+        Self {
+            worker_id: worker_id as u64,
+            epoch_millis,
+            clock: Arc::new(SystemClock {}),
+            state: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the clock the timestamps of generated ids are read from
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Gets the worker id of the generator
+    pub fn worker_id(&self) -> u16 {
+        self.worker_id as u16
+    }
+
+    /// Generates the next id
+    pub fn next_id(&self) -> u64 {
+        let now = self.now_millis();
+        let mut current = self.state.load(Ordering::Relaxed);
+        loop {
+            let (last, sequence) = (current >> SEQUENCE_BITS, current & MAX_SEQUENCE);
+            let next = if now > last {
+                now << SEQUENCE_BITS
+            } else if sequence < MAX_SEQUENCE {
+                current + 1
+            } else {
+                (last + 1) << SEQUENCE_BITS
+            };
+            match self.state.compare_exchange_weak(
+                current,
+                next,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    let (timestamp, sequence) = (next >> SEQUENCE_BITS, next & MAX_SEQUENCE);
+                    return timestamp << TIMESTAMP_SHIFT
+                        | self.worker_id << SEQUENCE_BITS
+                        | sequence;
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Creates an independent generator of the same worker and clock,
+    /// carrying on from the last id this one generated
+    pub fn fork(&self) -> Self {
+        Self {
+            worker_id: self.worker_id,
+            epoch_millis: self.epoch_millis,
+            clock: self.clock.clone(),
+            state: AtomicU64::new(self.state.load(Ordering::Acquire)),
+        }
+    }
+
+    /// Splits an id into its timestamp (milliseconds since the epoch), worker id, and sequence
+    pub fn decompose(id: u64) -> (u64, u16, u16) {
+        (
+            id >> TIMESTAMP_SHIFT,
+            ((id >> SEQUENCE_BITS) & MAX_WORKER_ID) as u16,
+            (id & MAX_SEQUENCE) as u16,
+        )
+    }
+
+    fn now_millis(&self) -> u64 {
+        self.clock.now_millis().saturating_sub(self.epoch_millis)
+    }
+}
+
+impl fmt::Debug for IdGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdGenerator")
+            .field("worker_id", &self.worker_id)
+            .field("epoch_millis", &self.epoch_millis)
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
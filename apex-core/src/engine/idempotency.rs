@@ -0,0 +1,75 @@
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// IdempotencyKey identifies a client request across gateway retries.
+pub type IdempotencyKey = u128;
+
+/// IdempotencyCache remembers the outcome of the most recent keyed commands,
+/// so a retried command is acknowledged with its original result instead of being applied twice.
+///
+/// The cache is bounded; once full, the oldest key is evicted first.
+pub struct IdempotencyCache {
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    results: HashMap<IdempotencyKey, CommandResult>,
+    order: VecDeque<IdempotencyKey>,
+}
+
+impl IdempotencyCache {
+    /// Creates a new cache holding up to `capacity` results
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// Gets the remembered result of a key
+    pub fn get(&self, key: IdempotencyKey) -> Option<CommandResult> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.results.get(&key).cloned()
+    }
+
+    /// Returns the remembered result of a key, or runs `apply` and remembers its result.
+    ///
+    /// The cache lock is held while `apply` runs, so concurrent retries of the same key
+    /// wait for the first attempt instead of racing it.
+    pub fn get_or_apply<F: FnOnce() -> CommandResult>(
+        &self,
+        key: IdempotencyKey,
+        apply: F,
+    ) -> CommandResult {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(result) = entries.results.get(&key) {
+            return result.clone();
+        }
+
+        let result = apply();
+        if entries.order.len() == self.capacity {
+            let oldest = entries
+                .order
+                .pop_front()
+                .expect("full cache has an oldest key");
+            entries.results.remove(&oldest);
+        }
+        entries.order.push_back(key);
+        entries.results.insert(key, result.clone());
+        result
+    }
+
+    /// Gets the number of remembered results
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.order.len()
+    }
+
+    /// Checks whether the cache remembers no results
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,278 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// ItchError is a failure to parse an ITCH message stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItchError {
+    /// The stream ended in the middle of a message.
+    Truncated { offset: usize },
+    /// A message is shorter than its type requires.
+    MessageTooShort { message_type: u8, length: usize },
+    /// An add order message has a side other than `B` or `S`.
+    InvalidSide { order_ref: u64, side: u8 },
+}
+
+impl fmt::Display for ItchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItchError::Truncated { offset } => {
+                write!(f, "stream truncated in message at offset {offset}")
+            }
+            ItchError::MessageTooShort {
+                message_type,
+                length,
+            } => write!(
+                f,
+                "message '{}' is too short ({length} bytes)",
+                *message_type as char
+            ),
+            ItchError::InvalidSide { order_ref, side } => {
+                write!(f, "order {order_ref} has invalid side {side:#04x}")
+            }
+        }
+    }
+}
+
+impl Error for ItchError {}
+
+/// ItchExecution selects how order executed messages are replayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ItchExecution {
+    /// Reduce the executed order's open quantity, so the book mirrors the feed.
+    #[default]
+    Reduce,
+    /// Send a market order against the executed order's side,
+    /// so the engine performs the match itself.
+    /// Market order ids count down from `u64::MAX` to stay clear of ITCH order references.
+    Aggress,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ItchOrder {
+    side: Side,
+    remaining: u64,
+}
+
+/// ItchLoader parses NASDAQ TotalView-ITCH 5.0 order messages into engine commands,
+/// so recorded exchange sessions can drive benches and soak tests.
+///
+/// Add (`A`, `F`), executed (`E`, `C`), cancel (`X`), delete (`D`), and replace (`U`)
+/// messages are translated; every other message type is skipped.
+/// ITCH order references become order ids and prices keep their four implied decimals.
+#[derive(Debug, Clone)]
+pub struct ItchLoader {
+    base_micros: u64,
+    stock: Option<[u8; 8]>,
+    execution: ItchExecution,
+    orders: HashMap<u64, ItchOrder>,
+    next_aggressor_id: OrderID,
+}
+
+impl ItchLoader {
+    /// Creates a new loader.
+    /// ITCH timestamps are nanoseconds since midnight and are added to `base_micros`,
+    /// the session date in microseconds since the UNIX epoch.
+    pub fn new(base_micros: u64) -> Self {
+        Self {
+            base_micros,
+            stock: None,
+            execution: ItchExecution::default(),
+            orders: HashMap::new(),
+            next_aggressor_id: u64::MAX,
+        }
+    }
+
+    /// Only loads orders of the stock symbol
+    pub fn with_stock(mut self, symbol: &str) -> Self {
+        let mut stock = [b' '; 8];
+        for (slot, byte) in stock.iter_mut().zip(symbol.bytes()) {
+            *slot = byte;
+        }
+        self.stock = Some(stock);
+        self
+    }
+
+    /// Sets how executions are replayed
+    pub fn with_execution(mut self, execution: ItchExecution) -> Self {
+        self.execution = execution;
+        self
+    }
+
+    /// Gets the number of orders the loader is tracking as open
+    pub fn open_orders(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Parses a stream of messages, each prefixed with its big-endian `u16` length
+    /// as in NASDAQ's binary ITCH files.
+    pub fn load(&mut self, data: &[u8]) -> Result<Vec<Command>, ItchError> {
+        let mut commands = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let header = data
+                .get(offset..offset + 2)
+                .ok_or(ItchError::Truncated { offset })?;
+            let length = u16::from_be_bytes([header[0], header[1]]) as usize;
+            let message = data
+                .get(offset + 2..offset + 2 + length)
+                .ok_or(ItchError::Truncated { offset })?;
+            commands.extend(self.parse_message(message)?);
+            offset += 2 + length;
+        }
+        Ok(commands)
+    }
+
+    /// Parses a single message without its length prefix into the commands it implies
+    pub fn parse_message(&mut self, message: &[u8]) -> Result<Vec<Command>, ItchError> {
+        let Some(&message_type) = message.first() else {
+            return Ok(Vec::new());
+        };
+        let required = match message_type {
+            b'A' => 36,
+            b'F' => 40,
+            b'E' => 31,
+            b'C' => 36,
+            b'X' => 23,
+            b'D' => 19,
+            b'U' => 35,
+            _ => return Ok(Vec::new()),
+        };
+        if message.len() < required {
+            return Err(ItchError::MessageTooShort {
+                message_type,
+                length: message.len(),
+            });
+        }
+        let now = self.base_micros + read_u48(&message[5..11]) / 1000;
+        let order_ref = read_u64(&message[11..19]);
+        let commands = match message_type {
+            b'A' | b'F' => {
+                if self.stock.is_some_and(|stock| stock[..] != message[24..32]) {
+                    return Ok(Vec::new());
+                }
+                let user_id = if message_type == b'F' {
+                    read_u32(&message[36..40]) as u64
+                } else {
+                    0
+                };
+                let side = match message[19] {
+                    b'B' => Side::Buy,
+                    b'S' => Side::Sell,
+                    side => return Err(ItchError::InvalidSide { order_ref, side }),
+                };
+                let shares = read_u32(&message[20..24]) as u64;
+                let price = Price::from(read_u32(&message[32..36]));
+                vec![self.add(order_ref, user_id, side, price, shares, now)]
+            }
+            b'E' | b'C' => self.execute(order_ref, read_u32(&message[19..23]) as u64, now),
+            b'X' => self.reduce(order_ref, read_u32(&message[19..23]) as u64, now),
+            b'D' => self.delete(order_ref),
+            _ => {
+                let Some(order) = self.orders.get(&order_ref).copied() else {
+                    return Ok(Vec::new());
+                };
+                let mut commands = self.delete(order_ref);
+                let new_ref = read_u64(&message[19..27]);
+                let shares = read_u32(&message[27..31]) as u64;
+                let price = Price::from(read_u32(&message[31..35]));
+                commands.push(self.add(new_ref, 0, order.side, price, shares, now));
+                commands
+            }
+        };
+        Ok(commands)
+    }
+
+    fn add(
+        &mut self,
+        order_ref: u64,
+        user_id: u64,
+        side: Side,
+        price: Price,
+        shares: u64,
+        now: u64,
+    ) -> Command {
+        self.orders.insert(
+            order_ref,
+            ItchOrder {
+                side,
+                remaining: shares,
+            },
+        );
+        Command::Create(Order::limit(
+            order_ref,
+            user_id,
+            side,
+            price,
+            Quantity::from(shares),
+            now,
+        ))
+    }
+
+    fn execute(&mut self, order_ref: u64, shares: u64, now: u64) -> Vec<Command> {
+        match self.execution {
+            ItchExecution::Reduce => self.reduce(order_ref, shares, now),
+            ItchExecution::Aggress => {
+                let Some(order) = self.orders.get_mut(&order_ref) else {
+                    return Vec::new();
+                };
+                order.remaining = order.remaining.saturating_sub(shares);
+                let side = match order.side {
+                    Side::Buy => Side::Sell,
+                    Side::Sell => Side::Buy,
+                };
+                if order.remaining == 0 {
+                    self.orders.remove(&order_ref);
+                }
+                let id = self.next_aggressor_id;
+                self.next_aggressor_id -= 1;
+                vec![Command::Create(Order::market(
+                    id,
+                    0,
+                    side,
+                    Quantity::from(shares),
+                    now,
+                ))]
+            }
+        }
+    }
+
+    fn reduce(&mut self, order_ref: u64, shares: u64, now: u64) -> Vec<Command> {
+        let Some(order) = self.orders.get_mut(&order_ref) else {
+            return Vec::new();
+        };
+        order.remaining = order.remaining.saturating_sub(shares);
+        if order.remaining == 0 {
+            return self.delete(order_ref);
+        }
+        vec![Command::Amend {
+            order_id: order_ref,
+            new_quantity: Quantity::from(order.remaining),
+            now_microseconds: now,
+        }]
+    }
+
+    fn delete(&mut self, order_ref: u64) -> Vec<Command> {
+        match self.orders.remove(&order_ref) {
+            Some(_) => vec![Command::Cancel(order_ref)],
+            None => Vec::new(),
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn read_u48(bytes: &[u8]) -> u64 {
+    bytes[..6]
+        .iter()
+        .fold(0u64, |value, byte| (value << 8) | *byte as u64)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_be_bytes(buf)
+}
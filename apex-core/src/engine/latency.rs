@@ -0,0 +1,117 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+struct PendingOrder {
+    accepted_at: Instant,
+    filled: bool,
+}
+
+/// LatencyTracker records when each order is accepted, first filled and completely filled,
+/// and keeps log-linear histograms of the accept-to-first-fill and accept-to-completion
+/// latencies, in nanoseconds.
+///
+/// Let it receive the book's events, directly or through a `FanOutSyncer`, or feed it with
+/// `record_accept` and `record_matched`.
+pub struct LatencyTracker {
+    pending: Mutex<HashMap<OrderID, PendingOrder>>,
+    first_fill: Histogram,
+    completion: Histogram,
+}
+
+impl LatencyTracker {
+    /// Creates a new latency tracker
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            first_fill: Histogram::log_linear(),
+            completion: Histogram::log_linear(),
+        }
+    }
+
+    /// Records that an order was accepted at `at`
+    pub fn record_accept(&self, order_id: OrderID, at: Instant) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.insert(
+            order_id,
+            PendingOrder {
+                accepted_at: at,
+                filled: false,
+            },
+        );
+    }
+
+    /// Records the fills of a match observed at `at`
+    pub fn record_matched(&self, updated: &[Order], trades: &[Trade], at: Instant) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        for trade in trades {
+            let unfilled = pending
+                .get_mut(&trade.order_id)
+                .filter(|order| !order.filled);
+            if let Some(order) = unfilled {
+                order.filled = true;
+                let latency = at.saturating_duration_since(order.accepted_at);
+                self.first_fill.observe(latency.as_nanos() as u64);
+            }
+        }
+        for order in updated {
+            match order.status() {
+                OrderStatus::Filled => {
+                    if let Some(done) = pending.remove(&order.id) {
+                        let latency = at.saturating_duration_since(done.accepted_at);
+                        self.completion.observe(latency.as_nanos() as u64);
+                    }
+                }
+                OrderStatus::Rejected | OrderStatus::Cancelled | OrderStatus::Expired => {
+                    pending.remove(&order.id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Forgets an order that left the book without completing
+    pub fn forget(&self, order_id: OrderID) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.remove(&order_id);
+    }
+
+    /// Gets the number of accepted orders that are not yet complete
+    pub fn pending(&self) -> usize {
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.len()
+    }
+
+    /// Gets the histogram of latencies from accept to first fill
+    pub fn accept_to_first_fill(&self) -> &Histogram {
+        &self.first_fill
+    }
+
+    /// Gets the histogram of latencies from accept to complete fill
+    pub fn accept_to_completion(&self) -> &Histogram {
+        &self.completion
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderBookSyncer for LatencyTracker {
+    fn add_order(&self, _id: u64, order: &Order) {
+        self.record_accept(order.id, Instant::now());
+    }
+
+    fn update_order(&self, _id: u64, _order: &Order) {}
+
+    fn cancel_order(&self, _id: u64, order: &Order) {
+        self.forget(order.id);
+    }
+
+    fn matched(&self, _id: u64, updated: &[Order], trades: &[Trade]) {
+        self.record_matched(updated, trades, Instant::now());
+    }
+}
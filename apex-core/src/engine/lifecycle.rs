@@ -0,0 +1,88 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicU8, Ordering};
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// LifecycleState is the atomic state machine behind an order's `OrderLifecycle`.
+///
+/// Every transition is a single strong compare-and-swap, so exactly one of a racing
+/// matching thread and cancellation thread claims an `Active` order.
+/// Under `cfg(loom)` it is built on loom atomics, so the protocol can be model-checked.
+pub struct LifecycleState {
+    state: AtomicU8,
+}
+
+impl LifecycleState {
+    /// Creates a new state machine in the given state
+    pub fn new(state: OrderLifecycle) -> Self {
+        Self {
+            state: AtomicU8::new(state.into()),
+        }
+    }
+
+    /// Gets the current state
+    #[inline(always)]
+    pub fn load(&self) -> OrderLifecycle {
+        self.state.load(Ordering::Acquire).into()
+    }
+
+    /// Forces the state back to `Active`, for orders that are not yet visible to other threads
+    #[inline(always)]
+    pub fn reset(&self) {
+        self.state
+            .store(OrderLifecycle::Active.into(), Ordering::Release);
+    }
+
+    /// Moves from `from` to `to`; returns false if the state was not `from`
+    #[inline(always)]
+    pub fn transition(&self, from: OrderLifecycle, to: OrderLifecycle) -> bool {
+        self.state
+            .compare_exchange(from.into(), to.into(), Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// `Active` → `Matched`: the matching thread claims the order
+    #[inline(always)]
+    pub fn enter_matched(&self) -> bool {
+        self.transition(OrderLifecycle::Active, OrderLifecycle::Matched)
+    }
+
+    /// `Matched` → `Active`: the matching thread releases a partially filled order
+    #[inline(always)]
+    pub fn exit_matched(&self) -> bool {
+        self.transition(OrderLifecycle::Matched, OrderLifecycle::Active)
+    }
+
+    /// `Active` → `Finished`: a cancellation thread removes the order
+    #[inline(always)]
+    pub fn enter_finished_from_active(&self) -> bool {
+        self.transition(OrderLifecycle::Active, OrderLifecycle::Finished)
+    }
+
+    /// `Matched` → `Finished`: the matching thread completes the order
+    #[inline(always)]
+    pub fn enter_finished_from_matched(&self) -> bool {
+        self.transition(OrderLifecycle::Matched, OrderLifecycle::Finished)
+    }
+}
+
+impl Default for LifecycleState {
+    fn default() -> Self {
+        Self::new(OrderLifecycle::Active)
+    }
+}
+
+impl Clone for LifecycleState {
+    fn clone(&self) -> Self {
+        Self::new(self.load())
+    }
+}
+
+impl fmt::Debug for LifecycleState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LifecycleState").field(&self.load()).finish()
+    }
+}
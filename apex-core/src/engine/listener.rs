@@ -0,0 +1,202 @@
+use crate::prelude::*;
+use crossbeam::channel::{Receiver, Sender, unbounded};
+use crypto_bigint::Zero;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// OrderStatusEvent is a change of a single order's status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatusEvent {
+    /// The order book accepted the order.
+    Accepted,
+    /// The order was executed and still has the remaining quantity.
+    PartiallyFilled {
+        quantity: Quantity,
+        remaining: Quantity,
+    },
+    /// The order was executed for the rest of its quantity.
+    Filled { quantity: Quantity },
+    /// The order was canceled.
+    Cancelled(CancelReason),
+    /// The order expired due to its time-in-force or the book's maximum resting time.
+    Expired(CancelReason),
+    /// The engine rejected the order.
+    Rejected(RejectReason),
+}
+
+impl OrderStatusEvent {
+    /// Checks if no further events follow this one
+    pub fn is_terminal(&self) -> bool {
+        !matches!(
+            self,
+            OrderStatusEvent::Accepted | OrderStatusEvent::PartiallyFilled { .. }
+        )
+    }
+}
+
+/// OrderStatusUpdate is an order status event together with the order it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderStatusUpdate {
+    /// Syncer id of the book change the event belongs to.
+    pub sync_id: u64,
+    pub event: OrderStatusEvent,
+    pub order: OrderView,
+}
+
+/// OrderListenerSyncer forwards every book change to the primary syncer
+/// and delivers the status changes of individual orders to their registered listeners.
+///
+/// Events are sent from the thread that changed the book, through unbounded channels,
+/// so a slow listener never blocks matching.
+/// A listener is dropped after its order's terminal event or once its receiver is gone.
+pub struct OrderListenerSyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    listeners: Mutex<HashMap<OrderID, Sender<OrderStatusUpdate>>>,
+}
+
+impl OrderListenerSyncer {
+    /// Creates a new listener syncer in front of the primary syncer
+    pub fn new(primary: Arc<dyn OrderBookSyncer>) -> Self {
+        Self {
+            primary,
+            listeners: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers a listener for an order, replacing any previous one.
+    /// Register before submitting the order to also receive its acceptance or rejection.
+    pub fn listen(&self, order_id: OrderID) -> Receiver<OrderStatusUpdate> {
+        let (sender, receiver) = unbounded();
+        self.lock().insert(order_id, sender);
+        receiver
+    }
+
+    /// Removes the listener of an order
+    pub fn unlisten(&self, order_id: OrderID) {
+        self.lock().remove(&order_id);
+    }
+
+    /// Gets the number of registered listeners
+    pub fn listeners(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<OrderID, Sender<OrderStatusUpdate>>> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn notify(&self, sync_id: u64, order: &Order, event: OrderStatusEvent) {
+        let mut listeners = self.lock();
+        let Some(sender) = listeners.get(&order.id) else {
+            return;
+        };
+        let update = OrderStatusUpdate {
+            sync_id,
+            event,
+            order: OrderView::from(order),
+        };
+        if sender.send(update).is_err() || event.is_terminal() {
+            listeners.remove(&order.id);
+        }
+    }
+
+    fn notify_removed(&self, sync_id: u64, order: &Order) {
+        match (order.status(), order.cancel_reason()) {
+            (OrderStatus::Expired, Some(reason)) => {
+                self.notify(sync_id, order, OrderStatusEvent::Expired(reason))
+            }
+            (_, Some(reason)) => self.notify(sync_id, order, OrderStatusEvent::Cancelled(reason)),
+            (_, None) => self.notify_rejected(sync_id, order),
+        }
+    }
+
+    /// Reports the end of an order that left matching without being filled
+    fn notify_finished(&self, sync_id: u64, order: &Order) {
+        match (order.status(), order.cancel_reason()) {
+            (OrderStatus::Rejected, _) | (_, Some(_)) => self.notify_removed(sync_id, order),
+            // The unfilled remainder of an immediate order lapses with its time in force
+            (_, None) => self.notify(
+                sync_id,
+                order,
+                OrderStatusEvent::Expired(CancelReason::TimeInForceExpired),
+            ),
+        }
+    }
+
+    fn notify_rejected(&self, sync_id: u64, order: &Order) {
+        if let Some(reason) = order.reject_reason() {
+            self.notify(sync_id, order, OrderStatusEvent::Rejected(reason));
+        }
+    }
+}
+
+impl OrderBookSyncer for OrderListenerSyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+        self.notify(id, order, OrderStatusEvent::Accepted);
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+        self.notify_removed(id, order);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+        self.notify_rejected(id, order);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        if self.lock().is_empty() {
+            return;
+        }
+        for order in updated {
+            let quantity = trades
+                .iter()
+                .filter(|trade| trade.order_id == order.id)
+                .fold(Quantity::ZERO, |total, trade| {
+                    total.saturating_add(&trade.quantity)
+                });
+            if !bool::from(quantity.is_zero()) {
+                let event = match order.status() {
+                    OrderStatus::Filled => OrderStatusEvent::Filled { quantity },
+                    _ => OrderStatusEvent::PartiallyFilled {
+                        quantity,
+                        remaining: order.quantity(),
+                    },
+                };
+                self.notify(id, order, event);
+            }
+            if order.is_finished() && order.status() != OrderStatus::Filled {
+                self.notify_finished(id, order);
+            }
+        }
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        for event in events {
+            match event {
+                BookEvent::Added(order) => self.notify(id, order, OrderStatusEvent::Accepted),
+                BookEvent::Cancelled(order) => self.notify_removed(id, order),
+                BookEvent::Rejected(order) => self.notify_rejected(id, order),
+                BookEvent::Updated(_) => {}
+            }
+        }
+    }
+}
@@ -1,8 +1,18 @@
 use crate::prelude::*;
-use crypto_bigint::Zero;
-use std::sync::Arc;
+use crossbeam::atomic::AtomicCell;
+use crypto_bigint::{NonZero, U256, U512, Zero};
+use flurry::HashSet;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::ops::{ControlFlow, RangeInclusive};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
 use std::time::Instant;
 
+/// Attempts to give a busted quantity back to an order held by a match in flight.
+const BUST_RESTORE_ATTEMPTS: usize = 1024;
+
 /// MatchingEngine is a trait for matching engine
 pub trait MatchingEngine {
     /// Creates a new order and then puts it into the order book
@@ -14,30 +24,1024 @@ pub trait MatchingEngine {
         new_price: Price,
         now_microseconds: u64,
     ) -> Result<(), UpdateOrderError>;
+    /// Amends the open quantity of an order in the order book.
+    /// Decreasing keeps the order's time priority, increasing loses it.
+    fn amend_quantity(
+        &self,
+        order_id: u64,
+        new_quantity: Quantity,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError>;
+    /// Amends any of the price, open quantity and time in force of an order at once.
+    /// The order keeps its time priority unless the price changes or the quantity increases.
+    fn amend_order(
+        &self,
+        order_id: u64,
+        request: AmendRequest,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError>;
     /// Cancels an order in the order book
     fn cancel_order(&self, order_id: u64) -> Result<(), CancelOrderError>;
-    /// Matches orders in the order book
+    /// Cancels the resting order a user gave a client order id, and returns its engine id.
+    /// Only the user's own orders are searched, so no user can cancel another's.
+    fn cancel_client_order(
+        &self,
+        user_id: u64,
+        client_order_id: ClientOrderID,
+    ) -> Result<OrderID, CancelOrderError>;
+    /// Creates a batch of orders with one epoch pin and one syncer batch
+    fn create_orders(&self, orders: &mut [Order]) -> Vec<Result<(), RejectReason>>;
+    /// Cancels a batch of orders with one epoch pin and one syncer batch
+    fn cancel_orders(&self, order_ids: &[OrderID]) -> Vec<Result<(), CancelOrderError>>;
+    /// Executes a batch of mixed commands with one epoch pin and one syncer batch
+    fn execute_batch(&self, commands: &mut [Command]) -> Vec<CommandResult>;
+    /// Executes a command at most once per idempotency key.
+    /// A retried key is acknowledged with the result of its first execution.
+    fn execute_idempotent(&self, key: IdempotencyKey, command: Command) -> CommandResult;
+    /// Cancels every resting order on a side, optionally within a price band,
+    /// and returns the ids of the canceled orders
+    fn cancel_where(&self, side: Side, price_range: Option<RangeInclusive<Price>>) -> Vec<OrderID>;
+    /// Reassigns every resting limit order of a user to another user, keeping their priority,
+    /// and returns the ids of the reassigned orders. Nothing moves while the engine is halted
+    /// or either user is frozen.
+    ///
+    /// The transfer is not atomic: orders move one at a time, each leaving the book and going
+    /// back to its place, so a concurrent match or reader may see some orders moved and others
+    /// not, and an order being matched when its turn comes is left with its owner.
+    fn transfer_orders(&self, from_user_id: u64, to_user_id: u64) -> Vec<OrderID>;
+    /// Expires every resting GoodTillDate order whose deadline has passed,
+    /// and every order resting longer than the book's maximum resting time since it was
+    /// accepted, and returns the ids of the expired orders
+    fn expire_orders(&self, now_microseconds: u64) -> Vec<OrderID>;
+    /// Demotes every regular order resting longer than the book's maximum quote age
+    /// without a refresh behind fresher orders at its price, and returns their ids
+    fn demote_stale_quotes(&self, now_microseconds: u64) -> Vec<OrderID>;
+    /// Removes finished orders and stale index entries left behind by matching and races,
+    /// and releases their memory to the epoch collector
+    fn compact(&self) -> CompactionReport;
+    /// Gets the resting limit orders of a user
+    fn open_orders(&self, user_id: u64) -> Vec<OrderView>;
+    /// Gets up to `max_levels` aggregated price levels of a side, best price first
+    fn depth(&self, side: Side, max_levels: usize) -> Vec<PriceLevel>;
+    /// Gets up to `max_levels` aggregated price levels of both sides in one pass,
+    /// best price first
+    fn top_of_book(&self, max_levels: usize) -> TopOfBook;
+    /// Gets up to `max_levels` levels of a side as price, quantity and cumulative quantity
+    /// arrays, best price first
+    fn depth_ladder(&self, side: Side, max_levels: usize) -> DepthLadder;
+    /// Gets the total quantity resting at a price
+    fn quantity_at(&self, side: Side, price: Price) -> Quantity;
+    /// Gets the order count and quantity resting at a price, and with `order_id`
+    /// the orders and quantity ahead of that order at the price, to estimate its fill odds
+    fn level_info(&self, side: Side, price: Price, order_id: Option<OrderID>) -> LevelInfo;
+    /// Gets the total quantity resting from the best price up to and including `price`
+    fn cumulative_quantity_to(&self, side: Side, price: Price) -> Quantity;
+    /// Simulates matching an order against the current book without changing any state.
+    /// Mode, admission and risk checks are not applied.
+    fn preview(&self, order: &Order) -> MatchPreview;
+    /// Estimates the execution of buying or selling `quantity` at market against the current
+    /// depth, None if the opposite side has no liquidity
+    fn estimate_fill(&self, side: Side, quantity: Quantity) -> Option<FillEstimate>;
+    /// Gets a statistics snapshot of the order book
+    fn stats(&self) -> BookStats;
+    /// Gets an owned copy of the order book that needs no epoch guard
+    fn snapshot(&self) -> BookSnapshot;
+    /// Gets the id the next synchronized event will carry
+    fn sequence(&self) -> u64;
+    /// Matches orders in the order book, within the configured cycle budget
     fn match_orders(&self);
+    /// Checks whether the last match cycle stopped at its budget or a pause
+    /// with matching left to do
+    fn match_budget_exhausted(&self) -> bool;
+    /// Gets the current engine mode
+    fn mode(&self) -> EngineMode;
+    /// Switches the engine mode at runtime
+    fn set_mode(&self, mode: EngineMode);
 }
 
 pub struct DefaultMatchingEngine {
     order_book: Arc<dyn OrderBookWalker>,
+    mode: AtomicU8,
+    /// Current configuration and its epoch, swapped together on reload.
+    config: RwLock<(u64, Arc<BookConfig>)>,
+    ids: Arc<IdGenerator>,
+    idempotency: Option<IdempotencyCache>,
+    risk: Arc<dyn RiskChecker>,
+    rate_limiter: Option<RateLimiter>,
+    metrics: Option<Arc<dyn Metrics>>,
+    clock: Arc<dyn Clock>,
+    /// Clock moved to the timestamp of each replicated command before it is applied.
+    log_clock: Option<Arc<ManualClock>>,
+    reference: Option<Arc<dyn ReferencePriceProvider>>,
+    last_trade_price: AtomicCell<Option<Price>>,
+    match_cycles: AtomicU64,
+    /// Whether the last match cycle stopped at its budget or a pause.
+    cycle_exhausted: AtomicBool,
+    matching_paused: AtomicBool,
+    speed_bump: Option<SpeedBump>,
+    journal: Option<TradeJournal>,
+    frozen_users: HashSet<u64>,
 }
 
 impl DefaultMatchingEngine {
     /// Creates a new matching engine
     pub fn new(order_book: Arc<dyn OrderBookWalker>) -> Self {
-        Self { order_book }
+        Self {
+            order_book,
+            mode: AtomicU8::new(EngineMode::Normal.into()),
+            config: RwLock::new((0, Arc::new(BookConfig::default()))),
+            ids: Arc::new(IdGenerator::default()),
+            idempotency: None,
+            risk: Arc::new(EmptyRiskChecker {}),
+            rate_limiter: None,
+            metrics: None,
+            clock: Arc::new(SystemClock {}),
+            log_clock: None,
+            reference: None,
+            last_trade_price: AtomicCell::new(None),
+            match_cycles: AtomicU64::new(0),
+            cycle_exhausted: AtomicBool::new(false),
+            matching_paused: AtomicBool::new(false),
+            speed_bump: None,
+            journal: None,
+            frozen_users: HashSet::new(),
+        }
+    }
+
+    /// Sets the provider of mark and index prices
+    pub fn with_reference_price(mut self, reference: Arc<dyn ReferencePriceProvider>) -> Self {
+        self.reference = Some(reference);
+        self
+    }
+
+    /// Gets the price of the most recent trade
+    pub fn last_trade_price(&self) -> Option<Price> {
+        self.last_trade_price.load()
+    }
+
+    /// Gets the current price of a source, if it is known
+    pub fn reference_price(&self, source: PriceSource) -> Option<Price> {
+        match source {
+            PriceSource::LastTrade => self.last_trade_price(),
+            PriceSource::Mark => self.reference.as_ref()?.mark_price(),
+            PriceSource::Index => self.reference.as_ref()?.index_price(),
+            PriceSource::BestBid => self.order_book.get_best_price(Side::Buy),
+            PriceSource::BestAsk => self.order_book.get_best_price(Side::Sell),
+        }
+    }
+
+    /// Gets the price an auction uncrossing the current book would execute at,
+    /// with the configured reference price breaking ties
+    pub fn indicative_auction_price(&self) -> Option<AuctionPrice> {
+        let bids = self.order_book.depth(Side::Buy, usize::MAX);
+        let asks = self.order_book.depth(Side::Sell, usize::MAX);
+        uncross_price(
+            &bids,
+            &asks,
+            self.reference_price(self.config().reference_source),
+        )
+    }
+
+    /// Sets the clock trades are timestamped with
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Drives the engine by the timestamps of the replicated commands it applies, so replicas
+    /// of one log see the same time: trade times and ids, acceptance times, rate limits, the
+    /// speed bump and market order staleness all read `clock`, which a `RaftNode`,
+    /// `PrimaryEngine` or `StandbyEngine` moves to each command's timestamp before applying it.
+    /// Replaces the clock of the engine and of a copy of its id generator.
+    pub fn with_log_clock(mut self, clock: Arc<ManualClock>) -> Self {
+        self.ids = Arc::new(self.ids.fork().with_clock(clock.clone()));
+        self.clock = clock.clone();
+        self.log_clock = Some(clock);
+        self
+    }
+
+    /// Moves the log clock to the timestamp of the replicated command about to be applied.
+    /// Engines without a log clock keep their own time.
+    pub(crate) fn set_log_time(&self, now_microseconds: u64) {
+        if let Some(clock) = &self.log_clock {
+            clock.set(now_microseconds);
+        }
+    }
+
+    /// Sets the metrics the engine reports to
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Limits how fast each user may create and cancel orders
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Sets the pre-trade risk checker consulted before insert and whenever a taker starts
+    /// matching
+    pub fn with_risk_checker(mut self, risk: Arc<dyn RiskChecker>) -> Self {
+        self.risk = risk;
+        self
+    }
+
+    /// Enables deduplication of keyed commands, remembering up to `capacity` results
+    pub fn with_idempotency(mut self, capacity: usize) -> Self {
+        self.idempotency = Some(IdempotencyCache::new(capacity));
+        self
+    }
+
+    /// Sets the generator of trade ids.
+    /// Engines of one process should share a generator, or use distinct worker ids.
+    pub fn with_id_generator(mut self, ids: Arc<IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Delays when new orders become eligible to match by a random interval
+    pub fn with_speed_bump(mut self, speed_bump: SpeedBump) -> Self {
+        self.speed_bump = Some(speed_bump);
+        self
+    }
+
+    /// Gets the speed bump new orders are delayed by
+    pub fn speed_bump(&self) -> Option<&SpeedBump> {
+        self.speed_bump.as_ref()
+    }
+
+    /// Freezes a user: their resting orders are skipped by matching and their new orders
+    /// are rejected, until the user is unfrozen. Returns false if the user was already frozen.
+    pub fn freeze_user(&self, user_id: u64) -> bool {
+        self.frozen_users.pin().insert(user_id)
+    }
+
+    /// Unfreezes a user. Returns false if the user was not frozen.
+    pub fn unfreeze_user(&self, user_id: u64) -> bool {
+        self.frozen_users.pin().remove(&user_id)
+    }
+
+    /// Checks whether a user is frozen
+    pub fn is_frozen(&self, user_id: u64) -> bool {
+        !self.frozen_users.is_empty() && self.frozen_users.pin().contains(&user_id)
+    }
+
+    /// Checks whether the owner of a resting order is frozen
+    fn owner_frozen(&self, order_id: OrderID) -> bool {
+        !self.frozen_users.is_empty()
+            && self
+                .order_book
+                .get_order(order_id)
+                .is_some_and(|order| self.is_frozen(order.user_id))
+    }
+
+    /// Pauses matching: a running match cycle stops at the next safe boundary, between two
+    /// fills, leaving every order consistent in the book, and later cycles do nothing until
+    /// matching is resumed. Halting the engine stops a running cycle the same way.
+    /// Returns false if matching was already paused.
+    pub fn pause_matching(&self) -> bool {
+        !self.matching_paused.swap(true, Ordering::AcqRel)
+    }
+
+    /// Resumes matching; the next cycle picks up where the paused one stopped.
+    /// Returns false if matching was not paused.
+    pub fn resume_matching(&self) -> bool {
+        self.matching_paused.swap(false, Ordering::AcqRel)
+    }
+
+    /// Checks whether matching is paused
+    pub fn is_matching_paused(&self) -> bool {
+        self.matching_paused.load(Ordering::Acquire)
+    }
+
+    /// Remembers up to `capacity` recent trades so they can be busted or price-corrected
+    pub fn with_trade_journal(mut self, capacity: usize) -> Self {
+        self.journal = Some(TradeJournal::new(capacity));
+        self
+    }
+
+    /// Busts a previously reported trade.
+    ///
+    /// The busted quantity is returned to each side that is still resting in the book,
+    /// keeping its time priority, and a compensating correction is synchronized.
+    pub fn bust_trade(&self, trade_id: u64) -> Result<TradeCorrection, TradeCorrectionError> {
+        let journal = self
+            .journal
+            .as_ref()
+            .ok_or(TradeCorrectionError::JournalDisabled)?;
+        let original = journal.take(trade_id);
+        if original.is_empty() {
+            return Err(TradeCorrectionError::TradeNotFound);
+        }
+        let restored = original
+            .iter()
+            .filter(|trade| self.restore_busted(trade))
+            .map(|trade| trade.order_id)
+            .collect();
+        let correction = TradeCorrection {
+            trade_id,
+            kind: TradeCorrectionKind::Bust,
+            original,
+            restored,
+            created_at: self.clock.now_micros(),
+        };
+        self.order_book.sync_trade_corrected(&correction);
+        Ok(correction)
+    }
+
+    /// Gives the quantity of a busted trade back to its order, waiting out a match in flight
+    /// on it, and returns whether the order got it back
+    fn restore_busted(&self, trade: &Trade) -> bool {
+        // A match holds an order only briefly; one held longer is left out of `restored`
+        for _ in 0..BUST_RESTORE_ATTEMPTS {
+            match self
+                .order_book
+                .restore_quantity(trade.order_id, trade.quantity)
+            {
+                Ok(()) => return true,
+                Err(error) if error.reason() == Some(AmendFailure::MatchInFlight) => {
+                    thread::yield_now()
+                }
+                Err(_) => return false,
+            }
+        }
+        false
+    }
+
+    /// Corrects the price of a previously reported trade, leaving the orders untouched
+    pub fn correct_trade_price(
+        &self,
+        trade_id: u64,
+        price: Price,
+    ) -> Result<TradeCorrection, TradeCorrectionError> {
+        let journal = self
+            .journal
+            .as_ref()
+            .ok_or(TradeCorrectionError::JournalDisabled)?;
+        if price.is_zero().into() {
+            return Err(TradeCorrectionError::InvalidPrice);
+        }
+        let original = journal.reprice(trade_id, price);
+        if original.is_empty() {
+            return Err(TradeCorrectionError::TradeNotFound);
+        }
+        let correction = TradeCorrection {
+            trade_id,
+            kind: TradeCorrectionKind::Price(price),
+            original,
+            restored: Vec::new(),
+            created_at: self.clock.now_micros(),
+        };
+        self.order_book.sync_trade_corrected(&correction);
+        Ok(correction)
+    }
+
+    /// Reports a trade executed away from the book.
+    ///
+    /// Both sides are published like matched trades, flagged as off-book,
+    /// and resting orders are left untouched.
+    /// Returns the maker (seller) and taker (buyer) sides of the trade.
+    pub fn report_off_book_trade(
+        &self,
+        report: &OffBookTrade,
+    ) -> Result<(Trade, Trade), RejectReason> {
+        if let Some(reason) = self.create_rejection() {
+            return Err(reason);
+        }
+        if report.quantity.is_zero().into() {
+            return Err(RejectReason::ZeroQuantity);
+        }
+        if report.price.is_zero().into() {
+            return Err(RejectReason::InvalidPrice);
+        }
+        if report.buy_order_id == report.sell_order_id {
+            return Err(RejectReason::DuplicateOrderId);
+        }
+        Ok(self.publish_off_book_trade(report))
+    }
+
+    /// Publishes both sides of a trade executed away from the book without checking it,
+    /// for trades that already happened elsewhere in the engine, like the legs of a spread
+    pub(crate) fn publish_off_book_trade(&self, report: &OffBookTrade) -> (Trade, Trade) {
+        let now_microseconds = self.clock.now_micros();
+        let trade_id = self.ids.next_id();
+        let sell = report.trade(trade_id, Side::Sell, now_microseconds);
+        let buy = report.trade(trade_id, Side::Buy, now_microseconds);
+        let updated = [
+            report.filled_order(Side::Sell, now_microseconds),
+            report.filled_order(Side::Buy, now_microseconds),
+        ];
+        self.publish_matched(&updated, &[sell.clone(), buy.clone()]);
+        (sell, buy)
+    }
+
+    /// Rescales every price and quantity in the book at once, e.g. for a token redenomination
+    /// or a contract split, and returns the number of rescaled orders.
+    ///
+    /// The engine must be halted. Every order keeps its priority and a single rescale event is
+    /// synchronized; nothing changes if any order's price or quantity fails to scale exactly.
+    /// Price bands and limits of the configuration are not rescaled; reload them separately.
+    pub fn rescale(&self, rescale: Rescale) -> Result<usize, RescaleError> {
+        if self.mode() != EngineMode::Halted {
+            return Err(RescaleError::NotHalted);
+        }
+        let rescaled = self.order_book.rescale(&rescale)?;
+        let last_trade_price = self.last_trade_price.load();
+        self.last_trade_price
+            .store(last_trade_price.and_then(|price| rescale.price.apply(price)));
+        Ok(rescaled)
+    }
+
+    /// Starts streaming the book in chunks of up to `chunk_size` resting orders,
+    /// to bootstrap a replica while the engine keeps matching
+    pub fn snapshot_stream(&self, chunk_size: usize) -> SnapshotStream<'_> {
+        SnapshotStream::new(self.order_book.as_ref(), chunk_size)
+    }
+
+    /// Forks an independent engine and book from the current state, so a simulation can
+    /// branch from it and run alternative futures in parallel.
+    ///
+    /// The book is restored from a checkpoint and synchronizes to `syncer`, carrying on from
+    /// the same sequence. The fork keeps the mode, configuration, last trade price, frozen
+    /// users and paused state, shares the clock and reference prices, and generates ids from
+    /// its own generator seeded with the live one, so the branch never advances the ids of the
+    /// live engine. The risk checker is left unset, since it usually tracks live positions;
+    /// supply one with `with_risk_checker`. Metrics, rate limits, the speed bump, the trade
+    /// journal and the idempotency cache are not carried over.
+    pub fn fork(
+        &self,
+        syncer: Arc<dyn OrderBookSyncer>,
+    ) -> (Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+        let book = Arc::new(DefaultOrderBook::restore(
+            Arc::new(AtomicU64::new(0)),
+            syncer,
+            &self.order_book.checkpoint(),
+        ));
+        let mut engine = DefaultMatchingEngine::new(book.clone())
+            .with_clock(self.clock.clone())
+            .with_id_generator(Arc::new(self.ids.fork()));
+        engine.log_clock = self.log_clock.clone();
+        engine.reference = self.reference.clone();
+        engine.config = RwLock::new(self.current_config());
+        engine.set_mode(self.mode());
+        engine.last_trade_price.store(self.last_trade_price());
+        engine
+            .match_cycles
+            .store(self.match_cycles.load(Ordering::Relaxed), Ordering::Relaxed);
+        engine
+            .matching_paused
+            .store(self.is_matching_paused(), Ordering::Release);
+        let frozen_users = engine.frozen_users.pin();
+        for user_id in self.frozen_users.pin().iter() {
+            frozen_users.insert(*user_id);
+        }
+        drop(frozen_users);
+        (book, engine)
+    }
+
+    /// Sets the limits enforced before orders reach the book
+    pub fn with_config(mut self, config: BookConfig) -> Self {
+        self.config = RwLock::new((0, Arc::new(config)));
+        self
+    }
+
+    /// Gets the limits enforced before orders reach the book
+    pub fn config(&self) -> Arc<BookConfig> {
+        self.current_config().1
+    }
+
+    /// Gets the epoch of the current configuration, starting at 0 and bumped on every reload
+    pub fn config_epoch(&self) -> u64 {
+        self.current_config().0
+    }
+
+    /// Replaces the configuration at runtime and returns its new epoch.
+    /// The new rules apply to orders accepted afterwards; resting orders are not revalidated.
+    pub fn reload_config(&self, config: BookConfig) -> u64 {
+        let mut current = self
+            .config
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *current = (current.0 + 1, Arc::new(config));
+        current.0
+    }
+
+    /// Gets the current configuration together with its epoch
+    fn current_config(&self) -> (u64, Arc<BookConfig>) {
+        self.config
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Gets the reason new orders are rejected in the current mode, if any
+    fn create_rejection(&self) -> Option<RejectReason> {
+        match self.mode() {
+            EngineMode::Normal => None,
+            EngineMode::CancelOnly => Some(RejectReason::CancelOnly),
+            EngineMode::Halted => Some(RejectReason::Halted),
+        }
+    }
+
+    /// Takes a request token of a user at the engine's time, if the engine is rate limited
+    fn within_rate(&self, user_id: u64) -> bool {
+        self.rate_limiter
+            .as_ref()
+            .is_none_or(|limiter| limiter.try_acquire_at_micros(user_id, self.clock.now_micros()))
+    }
+
+    /// Takes a request token for a cancel, update or amend from the owner of the order,
+    /// or from the shared bucket of unknown orders so floods of bogus ids are limited too
+    fn within_owner_rate(&self, order_id: OrderID) -> bool {
+        if self.rate_limiter.is_none() {
+            return true;
+        }
+        let owner = self.order_book.get_order(order_id);
+        self.within_rate(owner.map_or(UNKNOWN_ORDER_OWNER, |view| view.user_id))
+    }
+
+    /// Checks whether a new order may enter the book and stamps it with the epoch of the
+    /// configuration it was checked against and the time it was accepted.
+    /// Liquidation orders skip the rate limit and the risk checker, and only they keep
+    /// a priority class override.
+    pub(crate) fn admit(&self, order: &mut Order) -> Result<(), RejectReason> {
+        self.admit_queued(order, 0)
+    }
+
+    /// Checks whether a new order may enter the book behind `queued` limit orders
+    /// already admitted in the same batch but not inserted yet
+    fn admit_queued(&self, order: &mut Order, queued: usize) -> Result<(), RejectReason> {
+        let (epoch, config) = self.current_config();
+        order.config_epoch = epoch;
+        order.accepted_at = Some(self.clock.now_micros());
+        // Only venue-sent orders may choose their tier, so users cannot jump the queue
+        if order.class == OrderClass::Regular {
+            order.priority_class = None;
+        }
+        if let Some(reason) = self.create_rejection() {
+            return Err(reason);
+        }
+        let liquidation = order.class == OrderClass::Liquidation;
+        if !liquidation && !self.within_rate(order.user_id) {
+            return Err(RejectReason::RateLimited);
+        }
+        if self.is_frozen(order.user_id) {
+            return Err(RejectReason::UserFrozen);
+        }
+        order.validate().map_err(RejectReason::InvalidOrder)?;
+        if order.quantity().is_zero().into() {
+            return Err(RejectReason::ZeroQuantity);
+        }
+        if !config.allows_order_type(order.order_type) {
+            return Err(RejectReason::OrderTypeNotAllowed);
+        }
+        if !config.quantity_in_limit(order.quantity()) {
+            return Err(RejectReason::QuantityTooLarge);
+        }
+        if !config.quantity_on_lot(order.quantity()) {
+            return Err(RejectReason::OffLot);
+        }
+        if order.order_type == OrderType::Limit {
+            self.admit_limit(&config, order, queued)?;
+        }
+        // Market orders are valued at the best opposite price
+        let reference_price = match (order.order_type, order.side) {
+            (OrderType::Limit, _) => Some(order.price),
+            (OrderType::Market, Side::Buy) => self.order_book.get_best_price(Side::Sell),
+            (OrderType::Market, Side::Sell) => self.order_book.get_best_price(Side::Buy),
+        };
+        if reference_price.is_some_and(|price| !config.notional_in_limit(price, order.quantity())) {
+            return Err(RejectReason::NotionalTooLarge);
+        }
+        if liquidation {
+            return Ok(());
+        }
+        if let RiskDecision::Reject(reason) = self.risk.check(order) {
+            return Err(reason);
+        }
+        Ok(())
+    }
+
+    /// Checks whether a maker belongs to a taker's firm in a book that prevents internalization
+    fn is_internal(&self, taker: &Order, maker: &Order) -> bool {
+        taker.firm_id.is_some()
+            && taker.firm_id == maker.firm_id
+            && self.config().prevent_internalization
+    }
+
+    /// Gets the self-trade prevention policy that applies to a pair,
+    /// None if the orders have different owners or the book allows self-trades
+    fn self_trade_policy(&self, taker: &Order, maker: &Order) -> Option<SelfTradePrevention> {
+        if !taker.same_owner(maker) {
+            return None;
+        }
+        Some(self.config().self_trade_prevention)
+            .filter(|policy| *policy != SelfTradePrevention::Off)
+    }
+
+    /// Checks whether a maker can never fill a taker, because of internalization
+    /// or self-trade prevention
+    fn is_blocked(&self, taker: Option<&Order>, maker: &Order) -> bool {
+        taker.is_some_and(|taker| {
+            self.is_internal(taker, maker) || self.self_trade_policy(taker, maker).is_some()
+        })
+    }
+
+    /// Prevents a self-trade between a taker and a maker of the same owner, canceling the maker
+    /// if the policy says so. Returns the walking result for the maker, which exits the walk
+    /// when the taker's remainder must be canceled.
+    fn prevent_self_trade(
+        &self,
+        policy: SelfTradePrevention,
+        maker: &Order,
+        updated: &mut Vec<Order>,
+    ) -> WalkingResult {
+        let cancel_taker = matches!(
+            policy,
+            SelfTradePrevention::CancelTaker | SelfTradePrevention::CancelBoth
+        );
+        let cancel_maker = matches!(
+            policy,
+            SelfTradePrevention::CancelResting | SelfTradePrevention::CancelBoth
+        );
+        let removed = cancel_maker && maker.enter_matched();
+        if removed {
+            maker.update_status(OrderStatus::Cancelled);
+            maker.update_cancel_reason(CancelReason::SelfTradePrevention);
+            maker.enter_finished_from_matched();
+            updated.push(maker.clone());
+        }
+        WalkingResult::new(removed, cancel_taker)
+    }
+
+    /// Delays an order that entered the book from the time it was accepted.
+    /// Liquidation orders are never delayed.
+    fn delay_inserted(&self, order: &Order) {
+        if order.class == OrderClass::Liquidation {
+            return;
+        }
+        if let Some(speed_bump) = &self.speed_bump {
+            let accepted_at = order.accepted_at.unwrap_or_else(|| self.clock.now_micros());
+            speed_bump.delay(order.id, accepted_at);
+        }
+    }
+
+    /// Delays the orders of a batch that entered the book
+    fn delay_created(&self, orders: &[Order], results: &[Result<(), RejectReason>]) {
+        for (order, result) in orders.iter().zip(results) {
+            if result.is_ok() {
+                self.delay_inserted(order);
+            }
+        }
+    }
+
+    /// Forgets the speed bump delays of orders that left the book
+    fn forget_delays<'a>(&self, order_ids: impl IntoIterator<Item = &'a OrderID>) {
+        if let Some(speed_bump) = &self.speed_bump {
+            order_ids
+                .into_iter()
+                .for_each(|order_id| speed_bump.forget(*order_id));
+        }
+    }
+
+    /// Forgets the speed bump delays of the orders a batch canceled
+    fn forget_cancelled(&self, order_ids: &[OrderID], results: &[Result<(), CancelOrderError>]) {
+        self.forget_delays(
+            order_ids
+                .iter()
+                .zip(results)
+                .filter(|(_, result)| result.is_ok())
+                .map(|(order_id, _)| order_id),
+        );
+    }
+
+    /// Delays the orders a batch inserted and forgets the delays of the orders it canceled
+    fn track_batch_delays(&self, commands: &[Command], results: &[CommandResult]) {
+        if self.speed_bump.is_none() {
+            return;
+        }
+        for (command, result) in commands.iter().zip(results) {
+            match (command, result) {
+                (Command::Create(order), CommandResult::Created(Ok(()))) => {
+                    self.delay_inserted(order)
+                }
+                (Command::Cancel(order_id), CommandResult::Cancelled(Ok(()))) => {
+                    self.forget_delays([order_id])
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Checks whether an order is past its speed bump delay
+    fn is_eligible(&self, order: &Order) -> bool {
+        self.speed_bump
+            .as_ref()
+            .is_none_or(|speed_bump| speed_bump.is_eligible(order.id, self.clock.now_micros()))
+    }
+
+    /// Checks a new price or open quantity of a resting order against the tick size,
+    /// the price band, the size and lot limits and the notional limit,
+    /// valuing the order at its resting price or quantity for the one left unchanged
+    fn check_amend(
+        &self,
+        order_id: OrderID,
+        new_price: Option<Price>,
+        new_quantity: Option<Quantity>,
+    ) -> Result<(), UpdateOrderError> {
+        if new_price.is_none() && new_quantity.is_none() {
+            return Ok(());
+        }
+        let config = self.config();
+        let price_valid = new_price
+            .is_none_or(|price| config.price_on_tick(price) && config.price_in_band(price));
+        let size_valid = new_quantity.is_none_or(|quantity| {
+            config.quantity_in_limit(quantity) && config.quantity_on_lot(quantity)
+        });
+        if price_valid && size_valid && config.max_notional.is_none() {
+            return Ok(());
+        }
+        // Orders not resting at a price level are left for the book to report
+        let Some(order) = self.order_book.get_order(order_id) else {
+            return Ok(());
+        };
+        let failure = if !price_valid {
+            AmendFailure::InvalidPrice
+        } else if new_quantity.is_some_and(|quantity| !config.quantity_in_limit(quantity)) {
+            AmendFailure::QuantityTooLarge
+        } else if new_quantity.is_some_and(|quantity| !config.quantity_on_lot(quantity)) {
+            AmendFailure::OffLot
+        } else if !config.notional_in_limit(
+            new_price.unwrap_or(order.price),
+            new_quantity.unwrap_or(order.quantity),
+        ) {
+            AmendFailure::NotionalTooLarge
+        } else {
+            return Ok(());
+        };
+        Err(UpdateOrderError::invalid(failure, order))
+    }
+
+    /// Checks the book-level limits of a new limit order, counting the `queued` limit orders
+    /// ahead of it in its batch as resting
+    fn admit_limit(
+        &self,
+        config: &BookConfig,
+        order: &Order,
+        queued: usize,
+    ) -> Result<(), RejectReason> {
+        if order.price.is_zero().into() {
+            return Err(RejectReason::InvalidPrice);
+        }
+        if !config.price_on_tick(order.price) {
+            return Err(RejectReason::OffTick);
+        }
+        if !config.price_in_band(order.price) {
+            return Err(RejectReason::PriceOutOfBand);
+        }
+        if config.reference_band_bps.is_some()
+            && self
+                .reference_price(config.reference_source)
+                .is_some_and(|reference| !config.price_in_reference_band(order.price, reference))
+        {
+            return Err(RejectReason::PriceOutOfBand);
+        }
+        if let Some(max) = config.max_resting_orders {
+            let resting = self.order_book.get_book(Side::Buy).len()
+                + self.order_book.get_book(Side::Sell).len()
+                + queued;
+            if resting >= max {
+                return Err(RejectReason::CapacityExceeded);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects a taker that fails the pre-match risk check.
+    /// Returns None if the taker may be matched; liquidation orders are never checked.
+    fn reject_taker_by_risk(&self, taker: &Order) -> Option<WalkingResult> {
+        if taker.class == OrderClass::Liquidation {
+            return None;
+        }
+        match self.risk.check_match(taker) {
+            RiskDecision::Allow => None,
+            RiskDecision::Reject(reason) => Some(self.reject_taker(taker, reason)),
+        }
+    }
+
+    /// Rejects a market order that waited in the queue longer than the configured maximum age.
+    /// Returns None if the order is still fresh.
+    fn reject_stale_market_order(&self, taker: &Order) -> Option<WalkingResult> {
+        let max_age = self.config().max_market_order_age_micros?;
+        // The client's timestamp is not trusted; orders placed around the engine never age
+        let age = self.clock.now_micros().saturating_sub(taker.accepted_at?);
+        if age <= max_age {
+            return None;
+        }
+        Some(self.reject_taker(taker, RejectReason::StaleMarketOrder))
+    }
+
+    /// Rejects a taker held in the matched lifecycle and removes it from the walk
+    fn reject_taker(&self, taker: &Order, reason: RejectReason) -> WalkingResult {
+        taker.update_status(OrderStatus::Rejected);
+        taker.update_reject_reason(reason);
+        taker.enter_finished_from_matched();
+        self.publish_matched(std::slice::from_ref(taker), &[]);
+        WalkingResult::remove_and_next()
+    }
+
+    /// Syncs the outcome of a match and reports its trades and rejects
+    fn publish_matched(&self, updated: &[Order], matched: &[Trade]) {
+        // Off-book prices are reported, not discovered, so they do not move the last trade price
+        if let Some(trade) = matched.last().filter(|trade| !trade.off_book) {
+            self.last_trade_price.store(Some(trade.price));
+        }
+        if let Some(journal) = &self.journal {
+            journal.record(matched);
+        }
+        self.forget_delays(
+            updated
+                .iter()
+                .filter(|order| order.is_finished())
+                .map(|order| &order.id),
+        );
+        self.order_book.sync_matched(updated, matched);
+        if let Some(metrics) = &self.metrics {
+            let rejects = updated
+                .iter()
+                .filter(|order| order.status() == OrderStatus::Rejected)
+                .count();
+            metrics.record_trades(matched.len() as u64 / 2);
+            metrics.record_rejects(rejects as u64);
+        }
+    }
+
+    /// Publishes the fills of a sweep accumulated so far once they reach the chunk size,
+    /// so long sweeps are synchronized as they go. Every chunk carries the taker as it stands,
+    /// so consumers can join each trade to its order. Returns the number of trades published.
+    fn stream_chunk(
+        &self,
+        chunk: Option<NonZeroUsize>,
+        taker: &Order,
+        updated: &mut Vec<Order>,
+        matched: &mut Vec<Trade>,
+    ) -> usize {
+        let trades = matched.len() / 2;
+        if chunk.is_none_or(|size| trades < size.get()) {
+            return 0;
+        }
+        updated.push(taker.clone());
+        self.publish_matched(updated, matched);
+        updated.clear();
+        matched.clear();
+        trades
+    }
+
+    /// Reports the outcome of order creations
+    fn record_created(&self, results: &[Result<(), RejectReason>]) {
+        if let Some(metrics) = &self.metrics {
+            let inserts = results.iter().filter(|result| result.is_ok()).count();
+            metrics.record_inserts(inserts as u64);
+            metrics.record_rejects((results.len() - inserts) as u64);
+        }
+    }
+
+    /// Reports the outcome of a batch of commands
+    fn record_batch(&self, results: &[CommandResult]) {
+        if self.metrics.is_none() {
+            return;
+        }
+        let created: Vec<_> = results
+            .iter()
+            .filter_map(|result| match result {
+                CommandResult::Created(result) => Some(*result),
+                _ => None,
+            })
+            .collect();
+        self.record_created(&created);
+        let cancels = results
+            .iter()
+            .filter(|result| matches!(result, CommandResult::Cancelled(Ok(()))))
+            .count();
+        self.record_cancels(cancels);
+    }
+
+    /// Reports the number of resting orders that left the book without trading
+    fn record_cancels(&self, count: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_cancels(count as u64);
+        }
+    }
+
+    fn reject_order(order: &mut Order, reason: RejectReason) {
+        order.update_status(OrderStatus::Rejected);
+        order.update_reject_reason(reason);
+    }
+
+    /// Rejects an order that never reached the book and syncs the rejection
+    pub(crate) fn reject_unplaced(&self, order: &mut Order, reason: RejectReason) {
+        Self::reject_order(order, reason);
+        self.order_book.sync_rejected(order);
+    }
+
+    /// Gets the result of a command the engine refuses to pass to the book, if any.
+    /// Refused creates are marked rejected.
+    fn refuse(&self, command: &mut Command, queued: &mut usize) -> Option<CommandResult> {
+        match command {
+            Command::Create(order) => match self.admit_queued(order, *queued) {
+                Ok(()) => {
+                    if order.order_type == OrderType::Limit {
+                        *queued += 1;
+                    }
+                    None
+                }
+                Err(reason) => {
+                    Self::reject_order(order, reason);
+                    Some(CommandResult::Created(Err(reason)))
+                }
+            },
+            Command::Update { .. } | Command::Amend { .. } | Command::AmendOrder { .. }
+                if self.mode() != EngineMode::Normal =>
+            {
+                Some(CommandResult::Updated(Err(
+                    UpdateOrderError::TradingSuspended,
+                )))
+            }
+            Command::Update { order_id, .. }
+            | Command::Amend { order_id, .. }
+            | Command::AmendOrder { order_id, .. }
+                if !self.within_owner_rate(*order_id) =>
+            {
+                Some(CommandResult::Updated(Err(UpdateOrderError::RateLimited)))
+            }
+            Command::Update { order_id, .. }
+            | Command::Amend { order_id, .. }
+            | Command::AmendOrder { order_id, .. }
+                if self.owner_frozen(*order_id) =>
+            {
+                Some(CommandResult::Updated(Err(UpdateOrderError::UserFrozen)))
+            }
+            Command::Update {
+                order_id,
+                new_price,
+                ..
+            } => self
+                .check_amend(*order_id, Some(*new_price), None)
+                .err()
+                .map(|error| CommandResult::Updated(Err(error))),
+            Command::Amend {
+                order_id,
+                new_quantity,
+                ..
+            } => self
+                .check_amend(*order_id, None, Some(*new_quantity))
+                .err()
+                .map(|error| CommandResult::Updated(Err(error))),
+            Command::AmendOrder {
+                order_id, request, ..
+            } => self
+                .check_amend(*order_id, request.price, request.quantity)
+                .err()
+                .map(|error| CommandResult::Updated(Err(error))),
+            Command::Cancel(_) if self.mode() == EngineMode::Halted => Some(
+                CommandResult::Cancelled(Err(CancelOrderError::EngineHalted)),
+            ),
+            Command::Cancel(order_id) => (!self.within_owner_rate(*order_id))
+                .then_some(CommandResult::Cancelled(Err(CancelOrderError::RateLimited))),
+        }
     }
 
     fn process_order_pair(
+        &self,
+        taker: &Order,
+        maker: &Order,
+        updated: &mut Vec<Order>,
+        matched: &mut Vec<Trade>,
+    ) -> bool {
+        self.process_order_fill(taker, maker, Quantity::MAX, maker.price, updated, matched)
+    }
+
+    /// Matches a pair for at most `max_quantity` at `price`.
+    /// Returns whether the maker left the book.
+    fn process_order_fill(
+        &self,
         taker: &Order,
         maker: &Order,
+        max_quantity: Quantity,
+        price: Price,
         updated: &mut Vec<Order>,
         matched: &mut Vec<Trade>,
     ) -> bool {
-        let now_microseconds = Instant::now().elapsed().as_micros() as u64;
-        let trades = Trade::matched(now_microseconds, taker, maker);
+        let now_microseconds = self.clock.now_micros();
+        let trades = Trade::matched(
+            now_microseconds,
+            self.ids.next_id(),
+            taker,
+            maker,
+            max_quantity,
+            price,
+        );
 
         if trades.is_none() {
             maker.exit_matched();
@@ -56,20 +1060,173 @@ impl DefaultMatchingEngine {
         updated.push(cloned_order);
 
         let pair = trades.unwrap();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            trade_id = pair.0.trade_id,
+            taker_id = taker.id,
+            maker_id = maker.id,
+            user_id = taker.user_id,
+            side = ?taker.side,
+            qty = ?pair.0.quantity,
+            "orders matched",
+        );
         matched.push(pair.0);
         matched.push(pair.1);
         removed
     }
 
-    fn lock_book_liquidity(
+    /// Walks the resting orders of a side, from the best price up to `limit_price`,
+    /// and collects the fills `quantity` of a firm would get without claiming or changing any order
+    fn simulate_fills(
         &self,
+        side: Side,
+        quantity: Quantity,
+        limit_price: Option<Price>,
+        taker: Option<&Order>,
+    ) -> MatchPreview {
+        let mut preview = MatchPreview {
+            remaining_quantity: quantity,
+            ..Default::default()
+        };
+        let mut notional = Price::ZERO;
+        // Makers are only read; claimed or frozen ones are skipped as matching would
+        let mut walking = |maker: &Order| {
+            if self.is_frozen(maker.user_id)
+                || self.is_blocked(taker, maker)
+                || !self.is_eligible(maker)
+                || maker.lifecycle.load() != OrderLifecycle::Active
+            {
+                return WalkingResult::next();
+            }
+            let quantity = preview.remaining_quantity.min(maker.quantity());
+            if bool::from(quantity.is_zero()) {
+                return WalkingResult::next();
+            }
+            preview.fills.push(PreviewFill {
+                maker_order_id: maker.id,
+                price: maker.price,
+                quantity,
+            });
+            notional = notional.saturating_add(&maker.price.saturating_mul(&quantity));
+            preview.filled_quantity = preview.filled_quantity.saturating_add(&quantity);
+            preview.remaining_quantity = preview.remaining_quantity.saturating_sub(&quantity);
+            if preview.remaining_quantity.is_zero().into() {
+                WalkingResult::exit()
+            } else {
+                WalkingResult::next()
+            }
+        };
+        self.order_book
+            .walking_book_maker(side, limit_price, &mut walking);
+
+        preview.average_price = NonZero::new(preview.filled_quantity)
+            .into_option()
+            .map(|filled| notional / filled);
+        preview
+    }
+
+    /// Allocates `quantity` of a firm among the resting orders of a side, from the best price
+    /// up to `limit_price`, for a pro-rata book. Each price takes what is left of the quantity
+    /// in proportion to the size of its orders, and the remainder of the rounding goes
+    /// to them in time priority. None if the book matches in time priority.
+    fn pro_rata_allocation(
+        &self,
+        side: Side,
+        quantity: Quantity,
+        limit_price: Option<Price>,
+        taker: &Order,
+    ) -> Option<HashMap<OrderID, Quantity>> {
+        if self.config().matching_scheme != MatchingScheme::ProRata {
+            return None;
+        }
+        let mut levels: Vec<(Price, Vec<(OrderID, Quantity)>)> = Vec::new();
+        let mut covered = Quantity::ZERO;
+        // Makers are only read, and skipped as matching would skip them
+        let mut walking = |maker: &Order| {
+            if self.is_frozen(maker.user_id)
+                || self.is_blocked(Some(taker), maker)
+                || !self.is_eligible(maker)
+                || maker.lifecycle.load() != OrderLifecycle::Active
+            {
+                return WalkingResult::next();
+            }
+            match levels.last_mut() {
+                Some((price, orders)) if *price == maker.price => {
+                    orders.push((maker.id, maker.quantity()))
+                }
+                _ if covered >= quantity => return WalkingResult::exit(),
+                _ => levels.push((maker.price, vec![(maker.id, maker.quantity())])),
+            }
+            covered = covered.saturating_add(&maker.quantity());
+            WalkingResult::next()
+        };
+        self.order_book
+            .walking_book_maker(side, limit_price, &mut walking);
+
+        let mut allocation = HashMap::new();
+        let mut remaining = quantity;
+        for (_, orders) in levels {
+            let total = orders.iter().fold(Quantity::ZERO, |total, (_, size)| {
+                total.saturating_add(size)
+            });
+            let Some(divisor) = NonZero::new(total.resize::<{ U512::LIMBS }>()).into_option()
+            else {
+                continue;
+            };
+            let take = remaining.min(total);
+            let mut left = take;
+            let mut shares: Vec<_> = orders
+                .iter()
+                .map(|(id, size)| {
+                    let product: U512 = take.widening_mul(size);
+                    let share = (product / divisor).resize::<{ U256::LIMBS }>();
+                    left = left.saturating_sub(&share);
+                    (*id, *size, share)
+                })
+                .collect();
+            for (_, size, share) in shares.iter_mut() {
+                let extra = left.min(size.saturating_sub(share));
+                *share = share.saturating_add(&extra);
+                left = left.saturating_sub(&extra);
+            }
+            for (id, _, share) in shares {
+                if !bool::from(share.is_zero()) {
+                    allocation.insert(id, share);
+                }
+            }
+            remaining = remaining.saturating_sub(&take);
+        }
+        Some(allocation)
+    }
+
+    /// Gets the most a maker may fill under an allocation, None if it is allotted nothing
+    fn allowance(
+        allocation: Option<&HashMap<OrderID, Quantity>>,
+        maker: &Order,
+    ) -> Option<Quantity> {
+        match allocation {
+            None => Some(Quantity::MAX),
+            Some(allocation) => allocation.get(&maker.id).copied(),
+        }
+    }
+
+    /// Claims resting orders of a side, from the best price up to `slippage_price`,
+    /// until they cover `quantity` of a firm. Returns None and releases the claims if they fall short.
+    pub(crate) fn lock_book_liquidity(
+        &self,
+        side: Side,
         quantity: Quantity,
         slippage_price: Option<Price>,
+        taker: &Order,
     ) -> Option<Vec<OrderID>> {
         let mut order_id_list = Vec::new();
         let mut remaining_qty = quantity;
         let mut walking = |maker: &Order| {
-            if !maker.enter_matched() {
+            if self.is_frozen(maker.user_id)
+                || self.is_blocked(Some(taker), maker)
+                || !self.is_eligible(maker)
+                || !maker.enter_matched()
+            {
                 return WalkingResult::next();
             }
 
@@ -84,40 +1241,72 @@ impl DefaultMatchingEngine {
         };
 
         self.order_book
-            .walking_book_maker(Side::Sell, slippage_price, &mut walking);
+            .walking_book_maker(side, slippage_price, &mut walking);
 
         if remaining_qty.is_zero().into() {
             return Some(order_id_list);
         }
+        self.release_book_liquidity(&order_id_list);
+        None
+    }
 
+    /// Releases resting orders claimed by `lock_book_liquidity` without trading them
+    pub(crate) fn release_book_liquidity(&self, order_id_list: &[OrderID]) {
         self.order_book
-            .walking_by_order_id_list(order_id_list.as_slice(), &mut |o| {
+            .walking_by_order_id_list(order_id_list, &mut |o| {
                 o.exit_matched();
                 WalkingResult::next()
             });
-        None
+    }
+
+    /// Matches takers that never rest in this book against the resting orders each of them
+    /// claimed with `lock_book_liquidity`, syncs them as a single match,
+    /// and returns the trades of every taker
+    pub(crate) fn fill_locked_liquidity(
+        &self,
+        takers: &[(&Order, Vec<OrderID>)],
+    ) -> Vec<Vec<Trade>> {
+        let (mut updated, mut matched) = (Vec::new(), Vec::new());
+        let mut fills = Vec::with_capacity(takers.len());
+        for (taker, order_id_list) in takers {
+            let first = matched.len();
+            let mut process = |maker: &Order| {
+                let removed = self.process_order_pair(taker, maker, &mut updated, &mut matched);
+                WalkingResult::new(removed, taker.quantity().is_zero().into())
+            };
+            self.order_book
+                .walking_by_order_id_list(order_id_list, &mut process);
+            taker.enter_finished_from_active();
+            updated.push((*taker).clone());
+            fills.push(matched[first..].to_vec());
+        }
+        self.publish_matched(&updated, &matched);
+        fills
     }
 
     fn match_market_order_fok(
         &self,
+        opposite_side: Side,
         slippage_price: Option<Price>,
         taker: &Order,
     ) -> WalkingResult {
         let (mut updated, mut matched) = (Vec::new(), Vec::new());
 
-        let order_id_list_opt = self.lock_book_liquidity(taker.quantity(), slippage_price);
+        let order_id_list_opt =
+            self.lock_book_liquidity(opposite_side, taker.quantity(), slippage_price, taker);
         if order_id_list_opt.is_none() {
             taker.update_status(OrderStatus::Rejected);
             taker.update_reject_reason(RejectReason::InsufficientLiquidity);
             taker.enter_finished_from_matched();
             updated.push(taker.clone());
-            self.order_book.sync_matched(&updated, &matched);
+            self.publish_matched(&updated, &matched);
             return WalkingResult::remove_and_next();
         }
 
+        let chunk = self.config().match_chunk_trades;
         let mut process = |maker: &Order| {
-            let removed =
-                DefaultMatchingEngine::process_order_pair(taker, maker, &mut updated, &mut matched);
+            let removed = self.process_order_pair(taker, maker, &mut updated, &mut matched);
+            self.stream_chunk(chunk, taker, &mut updated, &mut matched);
             WalkingResult::new(removed, taker.quantity().is_zero().into())
         };
         self.order_book
@@ -126,15 +1315,29 @@ impl DefaultMatchingEngine {
         taker.enter_finished_from_matched();
         updated.push(taker.clone());
 
-        self.order_book.sync_matched(&updated, &matched);
+        self.publish_matched(&updated, &matched);
 
         WalkingResult::remove_and_next()
     }
 
-    fn match_market_order(&self, taker: &Order) -> WalkingResult {
-        if !taker.enter_matched() {
+    /// Matches a market order in full; market orders are not split across match cycles,
+    /// so the cycle budget is only checked before each one.
+    fn match_market_order(&self, taker: &Order, cycle: &CycleBudget) -> WalkingResult {
+        if !cycle.has_room() {
+            return WalkingResult::exit();
+        }
+        if !self.is_eligible(taker) || !taker.enter_matched() {
             return WalkingResult::next();
         }
+        if let Some(rejected) = self.reject_stale_market_order(taker) {
+            return rejected;
+        }
+        if self.is_frozen(taker.user_id) {
+            return self.reject_taker(taker, RejectReason::UserFrozen);
+        }
+        if let Some(rejected) = self.reject_taker_by_risk(taker) {
+            return rejected;
+        }
 
         let opposite_side = if taker.side == Side::Buy {
             Side::Sell
@@ -148,38 +1351,108 @@ impl DefaultMatchingEngine {
         };
 
         if taker.match_strategy == MatchStrategy::FillOrKill {
-            return self.match_market_order_fok(slippage_price, taker);
+            return self.match_market_order_fok(opposite_side, slippage_price, taker);
         }
 
         // Process market order as IOC
         let (mut updated, mut matched) = (Vec::new(), Vec::new());
+        let config = self.config();
+        let mut budget = SweepBudget::new(&config);
+        let allocation =
+            self.pro_rata_allocation(opposite_side, taker.quantity(), slippage_price, taker);
+        let (mut streamed, mut self_traded) = (0, false);
         let mut process = |maker: &Order| {
+            if self.is_frozen(maker.user_id)
+                || self.is_internal(taker, maker)
+                || !self.is_eligible(maker)
+            {
+                return WalkingResult::next();
+            }
+            if let Some(policy) = self.self_trade_policy(taker, maker) {
+                let result = self.prevent_self_trade(policy, maker, &mut updated);
+                self_traded |= result.exit;
+                return result;
+            }
+            let Some(allowance) = Self::allowance(allocation.as_ref(), maker) else {
+                return WalkingResult::next();
+            };
             if !maker.enter_matched() {
                 return WalkingResult::next();
             }
-            let removed =
-                DefaultMatchingEngine::process_order_pair(taker, maker, &mut updated, &mut matched);
+            let quantity = taker.quantity().min(maker.quantity()).min(allowance);
+            if !budget.take(maker.price, quantity) {
+                maker.exit_matched();
+                return WalkingResult::exit();
+            }
+            let removed = self.process_order_fill(
+                taker,
+                maker,
+                allowance,
+                maker.price,
+                &mut updated,
+                &mut matched,
+            );
+            streamed +=
+                self.stream_chunk(config.match_chunk_trades, taker, &mut updated, &mut matched);
             WalkingResult::new(removed, taker.quantity().is_zero().into())
         };
         self.order_book
             .walking_book_maker(opposite_side, slippage_price, &mut process);
 
-        if matched.is_empty() {
+        if self_traded {
+            taker.update_status(OrderStatus::Cancelled);
+            taker.update_cancel_reason(CancelReason::SelfTradePrevention);
+        } else if streamed == 0 && matched.is_empty() {
             taker.update_status(OrderStatus::Rejected);
             taker.update_reject_reason(RejectReason::InsufficientLiquidity);
+        } else if budget.exhausted() && !taker.is_filled() {
+            taker.update_status(OrderStatus::Cancelled);
+            taker.update_cancel_reason(CancelReason::SweepLimit);
         }
         taker.enter_finished_from_matched();
         updated.push(taker.clone());
 
-        self.order_book.sync_matched(&updated, &matched);
+        cycle.record(streamed + matched.len() / 2);
+        self.publish_matched(&updated, &matched);
 
         WalkingResult::remove_and_next()
     }
 
-    fn match_limit_order(&self, taker: &Order) -> WalkingResult {
-        if !taker.enter_matched() {
+    fn match_limit_order(&self, taker: &Order, cycle: &CycleBudget) -> WalkingResult {
+        self.match_limit_order_at(taker, taker.price, None, cycle)
+    }
+
+    /// Uncrosses the book at its auction price: the bids at or above it take the asks
+    /// at or below it in price-time priority, and every trade executes at the auction price
+    fn match_auction(&self, cycle: &CycleBudget) {
+        let Some(auction) = self.indicative_auction_price() else {
+            return;
+        };
+        let mut walking = |taker: &Order| {
+            self.match_limit_order_at(taker, auction.price, Some(auction.price), cycle)
+        };
+        self.order_book
+            .walking_book_maker(Side::Buy, Some(auction.price), &mut walking);
+    }
+
+    /// Matches a limit order against the makers up to `limit_price`,
+    /// at `trade_price` if given and otherwise at each maker's price
+    fn match_limit_order_at(
+        &self,
+        taker: &Order,
+        limit_price: Price,
+        trade_price: Option<Price>,
+        cycle: &CycleBudget,
+    ) -> WalkingResult {
+        if cycle.interrupted() {
+            return WalkingResult::exit();
+        }
+        if self.is_frozen(taker.user_id) || !self.is_eligible(taker) || !taker.enter_matched() {
             return WalkingResult::next();
         }
+        if let Some(rejected) = self.reject_taker_by_risk(taker) {
+            return rejected;
+        }
 
         let opposite_side = if taker.side == Side::Buy {
             Side::Sell
@@ -188,24 +1461,52 @@ impl DefaultMatchingEngine {
         };
 
         let (mut updated, mut matched) = (Vec::new(), Vec::new());
+        let chunk = self.config().match_chunk_trades;
+        let allocation =
+            self.pro_rata_allocation(opposite_side, taker.quantity(), Some(limit_price), taker);
+        let (mut streamed, mut self_traded) = (0, false);
         let mut process = |maker: &Order| {
+            if self.is_frozen(maker.user_id)
+                || self.is_internal(taker, maker)
+                || !self.is_eligible(maker)
+            {
+                return WalkingResult::next();
+            }
+            if let Some(policy) = self.self_trade_policy(taker, maker) {
+                let result = self.prevent_self_trade(policy, maker, &mut updated);
+                self_traded |= result.exit;
+                return result;
+            }
+            let Some(allowance) = Self::allowance(allocation.as_ref(), maker) else {
+                return WalkingResult::next();
+            };
             if !maker.enter_matched() {
                 return WalkingResult::next();
             }
+            if !cycle.take() {
+                maker.exit_matched();
+                return WalkingResult::exit();
+            }
+            let price = trade_price.unwrap_or(maker.price);
             let removed =
-                DefaultMatchingEngine::process_order_pair(taker, maker, &mut updated, &mut matched);
+                self.process_order_fill(taker, maker, allowance, price, &mut updated, &mut matched);
+            streamed += self.stream_chunk(chunk, taker, &mut updated, &mut matched);
             WalkingResult::new(removed, taker.quantity().is_zero().into())
         };
         self.order_book
-            .walking_book_maker(opposite_side, Some(taker.price), &mut process);
+            .walking_book_maker(opposite_side, Some(limit_price), &mut process);
 
-        if updated.is_empty() && matched.is_empty() {
+        if streamed == 0 && updated.is_empty() && matched.is_empty() && !self_traded {
             taker.exit_matched();
             return WalkingResult::next();
         }
 
         let cloned_order;
-        let removed = taker.is_filled();
+        let removed = taker.is_filled() || self_traded;
+        if self_traded {
+            taker.update_status(OrderStatus::Cancelled);
+            taker.update_cancel_reason(CancelReason::SelfTradePrevention);
+        }
         if !removed {
             cloned_order = taker.clone_reset_lifecycle();
             taker.exit_matched();
@@ -215,36 +1516,464 @@ impl DefaultMatchingEngine {
         }
         updated.push(cloned_order);
 
-        self.order_book.sync_matched(&updated, &matched);
+        self.publish_matched(&updated, &matched);
 
         WalkingResult::new(removed, false)
     }
 }
 
 impl MatchingEngine for DefaultMatchingEngine {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                order_id = order.id,
+                user_id = order.user_id,
+                side = ?order.side,
+                qty = ?order.quantity(),
+            ),
+        )
+    )]
     fn create_order(&self, order: &mut Order) -> Result<(), RejectReason> {
-        self.order_book.insert(order)
+        let result = match self.admit(order) {
+            Ok(()) => self.order_book.insert(order),
+            Err(reason) => {
+                self.reject_unplaced(order, reason);
+                Err(reason)
+            }
+        };
+        if result.is_ok() {
+            self.delay_inserted(order);
+        }
+        self.record_created(std::slice::from_ref(&result));
+        #[cfg(feature = "tracing")]
+        if let Err(reason) = &result {
+            tracing::debug!(code = reason.code(), %reason, "order rejected");
+        }
+        result
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(level = "debug"))
+    )]
     fn update_order(
         &self,
         order_id: u64,
         new_price: Price,
         now_microseconds: u64,
     ) -> Result<(), UpdateOrderError> {
+        if self.mode() != EngineMode::Normal {
+            return Err(UpdateOrderError::TradingSuspended);
+        }
+        if !self.within_owner_rate(order_id) {
+            return Err(UpdateOrderError::RateLimited);
+        }
+        if self.owner_frozen(order_id) {
+            return Err(UpdateOrderError::UserFrozen);
+        }
+        self.check_amend(order_id, Some(new_price), None)?;
         self.order_book
             .update_order(order_id, new_price, now_microseconds)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(level = "debug"))
+    )]
+    fn amend_quantity(
+        &self,
+        order_id: u64,
+        new_quantity: Quantity,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError> {
+        if self.mode() != EngineMode::Normal {
+            return Err(UpdateOrderError::TradingSuspended);
+        }
+        if !self.within_owner_rate(order_id) {
+            return Err(UpdateOrderError::RateLimited);
+        }
+        if self.owner_frozen(order_id) {
+            return Err(UpdateOrderError::UserFrozen);
+        }
+        self.check_amend(order_id, None, Some(new_quantity))?;
+        self.order_book
+            .amend_quantity(order_id, new_quantity, now_microseconds)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(level = "debug"))
+    )]
+    fn amend_order(
+        &self,
+        order_id: u64,
+        request: AmendRequest,
+        now_microseconds: u64,
+    ) -> Result<(), UpdateOrderError> {
+        if self.mode() != EngineMode::Normal {
+            return Err(UpdateOrderError::TradingSuspended);
+        }
+        if !self.within_owner_rate(order_id) {
+            return Err(UpdateOrderError::RateLimited);
+        }
+        if self.owner_frozen(order_id) {
+            return Err(UpdateOrderError::UserFrozen);
+        }
+        self.check_amend(order_id, request.price, request.quantity)?;
+        self.order_book
+            .amend_order(order_id, &request, now_microseconds)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(level = "debug"))
+    )]
     fn cancel_order(&self, order_id: u64) -> Result<(), CancelOrderError> {
-        self.order_book.remove(order_id)
+        if self.mode() == EngineMode::Halted {
+            return Err(CancelOrderError::EngineHalted);
+        }
+        if !self.within_owner_rate(order_id) {
+            return Err(CancelOrderError::RateLimited);
+        }
+        let result = self.order_book.remove(order_id);
+        if let Some(speed_bump) = self.speed_bump.as_ref().filter(|_| result.is_ok()) {
+            speed_bump.forget(order_id);
+        }
+        self.record_cancels(result.is_ok() as usize);
+        result
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), err(level = "debug"))
+    )]
+    fn cancel_client_order(
+        &self,
+        user_id: u64,
+        client_order_id: ClientOrderID,
+    ) -> Result<OrderID, CancelOrderError> {
+        if self.mode() == EngineMode::Halted {
+            return Err(CancelOrderError::EngineHalted);
+        }
+        if !self.within_rate(user_id) {
+            return Err(CancelOrderError::RateLimited);
+        }
+        let result = self
+            .order_book
+            .remove_client_order(user_id, client_order_id);
+        if let (Some(speed_bump), Ok(order_id)) = (self.speed_bump.as_ref(), &result) {
+            speed_bump.forget(*order_id);
+        }
+        self.record_cancels(result.is_ok() as usize);
+        result
+    }
+
+    fn create_orders(&self, orders: &mut [Order]) -> Vec<Result<(), RejectReason>> {
+        let mut queued = 0;
+        let mut results: Vec<_> = orders
+            .iter_mut()
+            .map(|order| {
+                let result = self.admit_queued(order, queued);
+                if result.is_ok() && order.order_type == OrderType::Limit {
+                    queued += 1;
+                }
+                result
+            })
+            .collect();
+        if results.iter().all(Result::is_ok) {
+            let results = self.order_book.insert_batch(orders);
+            self.delay_created(orders, &results);
+            self.record_created(&results);
+            return results;
+        }
+
+        // Rejected orders never reach the book; the rest are inserted as one batch
+        // and stitched back into their original positions.
+        let (mut admitted, mut positions, mut rejected) = (Vec::new(), Vec::new(), Vec::new());
+        for (position, (order, result)) in orders.iter_mut().zip(&results).enumerate() {
+            match result {
+                Ok(()) => {
+                    positions.push(position);
+                    admitted.push(order.clone());
+                }
+                Err(reason) => {
+                    Self::reject_order(order, *reason);
+                    rejected.push(BookEvent::Rejected(order.clone()));
+                }
+            }
+        }
+        self.order_book.sync_batch(&rejected);
+
+        let inserted = self.order_book.insert_batch(&mut admitted);
+        for ((position, order), result) in positions.into_iter().zip(admitted).zip(inserted) {
+            orders[position] = order;
+            results[position] = result;
+        }
+        self.delay_created(orders, &results);
+        self.record_created(&results);
+        results
+    }
+
+    fn cancel_orders(&self, order_ids: &[OrderID]) -> Vec<Result<(), CancelOrderError>> {
+        if self.mode() == EngineMode::Halted {
+            return vec![Err(CancelOrderError::EngineHalted); order_ids.len()];
+        }
+        let allowed: Vec<_> = order_ids
+            .iter()
+            .map(|order_id| self.within_owner_rate(*order_id))
+            .collect();
+        if allowed.iter().all(|allowed| *allowed) {
+            let results = self.order_book.remove_batch(order_ids);
+            self.forget_cancelled(order_ids, &results);
+            self.record_cancels(results.iter().filter(|result| result.is_ok()).count());
+            return results;
+        }
+
+        // Rate limited cancels never reach the book; the rest are removed as one batch
+        // and stitched back into their original positions.
+        let (mut admitted, mut positions) = (Vec::new(), Vec::new());
+        for (position, (order_id, allowed)) in order_ids.iter().zip(allowed).enumerate() {
+            if allowed {
+                positions.push(position);
+                admitted.push(*order_id);
+            }
+        }
+        let mut results = vec![Err(CancelOrderError::RateLimited); order_ids.len()];
+        let removed = self.order_book.remove_batch(&admitted);
+        for (position, result) in positions.into_iter().zip(removed) {
+            results[position] = result;
+        }
+        self.forget_cancelled(order_ids, &results);
+        self.record_cancels(results.iter().filter(|result| result.is_ok()).count());
+        results
+    }
+
+    fn execute_batch(&self, commands: &mut [Command]) -> Vec<CommandResult> {
+        let mut queued = 0;
+        let mut results: Vec<_> = commands
+            .iter_mut()
+            .map(|command| self.refuse(command, &mut queued))
+            .collect();
+        if results.iter().all(Option::is_none) {
+            let results = self.order_book.apply_batch(commands);
+            self.track_batch_delays(commands, &results);
+            self.record_batch(&results);
+            return results;
+        }
+
+        // Refused commands never reach the book; the rest are applied as one batch
+        // and stitched back into their original positions.
+        let (mut admitted, mut positions, mut rejected) = (Vec::new(), Vec::new(), Vec::new());
+        for (position, (command, result)) in commands.iter().zip(&results).enumerate() {
+            match (command, result) {
+                (_, None) => {
+                    positions.push(position);
+                    admitted.push(command.clone());
+                }
+                (Command::Create(order), Some(_)) => {
+                    rejected.push(BookEvent::Rejected(order.clone()));
+                }
+                _ => {}
+            }
+        }
+        self.order_book.sync_batch(&rejected);
+
+        let applied = self.order_book.apply_batch(&mut admitted);
+        for ((position, command), result) in positions.into_iter().zip(admitted).zip(applied) {
+            commands[position] = command;
+            results[position] = Some(result);
+        }
+        let results: Vec<_> = results.into_iter().flatten().collect();
+        self.track_batch_delays(commands, &results);
+        self.record_batch(&results);
+        results
+    }
+
+    fn execute_idempotent(&self, key: IdempotencyKey, command: Command) -> CommandResult {
+        match &self.idempotency {
+            Some(cache) => cache.get_or_apply(key, || command.execute(self)),
+            None => command.execute(self),
+        }
+    }
+
+    fn cancel_where(&self, side: Side, price_range: Option<RangeInclusive<Price>>) -> Vec<OrderID> {
+        let cancelled = self.order_book.cancel_where(side, price_range);
+        self.forget_delays(&cancelled);
+        self.record_cancels(cancelled.len());
+        cancelled
+    }
+
+    fn transfer_orders(&self, from_user_id: u64, to_user_id: u64) -> Vec<OrderID> {
+        if self.mode() == EngineMode::Halted
+            || self.is_frozen(from_user_id)
+            || self.is_frozen(to_user_id)
+        {
+            return Vec::new();
+        }
+        self.order_book.transfer_orders(from_user_id, to_user_id)
+    }
+
+    fn expire_orders(&self, now_microseconds: u64) -> Vec<OrderID> {
+        if self.mode() == EngineMode::Halted {
+            return Vec::new();
+        }
+        let expired = self
+            .order_book
+            .expire_orders(now_microseconds, self.config().max_resting_micros);
+        self.forget_delays(&expired);
+        self.record_cancels(expired.len());
+        expired
+    }
+
+    fn demote_stale_quotes(&self, now_microseconds: u64) -> Vec<OrderID> {
+        let Some(max_age) = self.config().max_quote_age_micros else {
+            return Vec::new();
+        };
+        if self.mode() == EngineMode::Halted {
+            return Vec::new();
+        }
+        self.order_book
+            .demote_stale(now_microseconds, max_age, PriorityClass::STALE)
+    }
+
+    fn compact(&self) -> CompactionReport {
+        // Delays of orders that left the book by any other path are dropped here
+        if let Some(speed_bump) = &self.speed_bump {
+            speed_bump.retain(|order_id| self.order_book.get_order(order_id).is_some());
+        }
+        self.order_book.compact()
+    }
+
+    fn open_orders(&self, user_id: u64) -> Vec<OrderView> {
+        self.order_book.open_orders(user_id)
+    }
+
+    fn depth(&self, side: Side, max_levels: usize) -> Vec<PriceLevel> {
+        self.order_book.depth(side, max_levels)
+    }
+
+    fn top_of_book(&self, max_levels: usize) -> TopOfBook {
+        self.order_book.top_of_book(max_levels)
+    }
+
+    fn depth_ladder(&self, side: Side, max_levels: usize) -> DepthLadder {
+        DepthLadder::from_levels(&self.order_book.depth(side, max_levels))
+    }
+
+    fn quantity_at(&self, side: Side, price: Price) -> Quantity {
+        self.order_book.quantity_at(side, price)
+    }
+
+    fn level_info(&self, side: Side, price: Price, order_id: Option<OrderID>) -> LevelInfo {
+        self.order_book.level_info(side, price, order_id)
+    }
+
+    fn cumulative_quantity_to(&self, side: Side, price: Price) -> Quantity {
+        self.order_book.cumulative_quantity_to(side, price)
+    }
+
+    fn preview(&self, order: &Order) -> MatchPreview {
+        let opposite_side = if order.side == Side::Buy {
+            Side::Sell
+        } else {
+            Side::Buy
+        };
+        let limit_price = match order.order_type {
+            OrderType::Limit => Some(order.price),
+            OrderType::Market => self
+                .order_book
+                .get_best_price(opposite_side)
+                .and_then(|price| order.slippage_bound_price(price)),
+        };
+
+        self.simulate_fills(opposite_side, order.quantity(), limit_price, Some(order))
+    }
+
+    fn estimate_fill(&self, side: Side, quantity: Quantity) -> Option<FillEstimate> {
+        let opposite_side = if side == Side::Buy {
+            Side::Sell
+        } else {
+            Side::Buy
+        };
+        let preview = self.simulate_fills(opposite_side, quantity, None, None);
+        Some(FillEstimate {
+            average_price: preview.average_price?,
+            worst_price: preview.fills.last()?.price,
+            filled_quantity: preview.filled_quantity,
+            unfilled_quantity: preview.remaining_quantity,
+        })
     }
 
+    fn stats(&self) -> BookStats {
+        self.order_book.stats()
+    }
+
+    fn snapshot(&self) -> BookSnapshot {
+        self.order_book.snapshot()
+    }
+
+    fn sequence(&self) -> u64 {
+        self.order_book.sequence()
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     fn match_orders(&self) {
-        let mut walking = |order: &Order| self.match_market_order(order);
-        self.order_book.walking_market_book(&mut walking);
+        if self.mode() == EngineMode::Halted || self.is_matching_paused() {
+            return;
+        }
+        let started_at = Instant::now();
+        let interrupted = || self.mode() == EngineMode::Halted || self.is_matching_paused();
+        let budget = CycleBudget::new(&self.config(), started_at, &interrupted);
+        if self.config().matching_scheme == MatchingScheme::BatchAuction {
+            self.match_auction(&budget);
+        } else {
+            let mut walking = |order: &Order| self.match_market_order(order, &budget);
+            self.order_book.walking_market_book(&mut walking);
+
+            let mut walking = |taker: &Order| self.match_limit_order(taker, &budget);
+            self.order_book.walking_cross_taker(&mut walking);
+        }
+        self.cycle_exhausted
+            .store(budget.exhausted(), Ordering::Release);
+
+        let cycle = self.match_cycles.fetch_add(1, Ordering::Relaxed) + 1;
+        if self
+            .config()
+            .compaction_interval
+            .is_some_and(|interval| cycle % interval == 0)
+        {
+            self.order_book.compact();
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_match_cycle(started_at.elapsed());
+            for side in [Side::Buy, Side::Sell] {
+                let mut levels = 0;
+                self.order_book.for_each_level(side, &mut |_| {
+                    levels += 1;
+                    ControlFlow::Continue(())
+                });
+                let resting = self.order_book.get_book(side).len();
+                metrics.set_resting_orders(side, resting as u64);
+                metrics.set_depth(side, levels);
+            }
+        }
+        // Walks leave finished orders linked until they pass them, so the book is checked after
+        self.order_book.assert_invariants();
+    }
+
+    fn match_budget_exhausted(&self) -> bool {
+        self.cycle_exhausted.load(Ordering::Acquire)
+    }
+
+    fn mode(&self) -> EngineMode {
+        self.mode.load(Ordering::Acquire).into()
+    }
 
-        let mut walking = |taker: &Order| self.match_limit_order(taker);
-        self.order_book.walking_cross_taker(&mut walking);
+    fn set_mode(&self, mode: EngineMode) {
+        self.mode.store(mode.into(), Ordering::Release);
     }
 }
@@ -0,0 +1,227 @@
+use crate::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Metrics trait is used to observe the engine's throughput and book shape
+pub trait Metrics: Send + Sync {
+    /// This function is called when orders are accepted into the book
+    fn record_inserts(&self, count: u64);
+    /// This function is called when resting orders are canceled or expired
+    fn record_cancels(&self, count: u64);
+    /// This function is called when trades are executed
+    fn record_trades(&self, count: u64);
+    /// This function is called when orders are rejected
+    fn record_rejects(&self, count: u64);
+    /// This function is called after each match cycle with the number of resting orders of a side
+    fn set_resting_orders(&self, side: Side, orders: u64);
+    /// This function is called after each match cycle with the number of price levels of a side
+    fn set_depth(&self, side: Side, levels: u64);
+    /// This function is called with the duration of each match cycle
+    fn observe_match_cycle(&self, duration: Duration);
+}
+
+/// Upper bounds, in microseconds, of the match cycle histogram buckets.
+pub const MATCH_CYCLE_BUCKETS_MICROS: [u64; 12] =
+    [1, 2, 5, 10, 20, 50, 100, 200, 500, 1_000, 5_000, 10_000];
+
+// Each power-of-two range of a log-linear histogram is split into 2^SUB_BUCKET_BITS linear
+// sub-buckets, which bounds the relative error of a counted value to about 6%.
+const SUB_BUCKET_BITS: u32 = 4;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+
+/// Histogram counts observations into cumulative buckets with fixed upper bounds.
+pub struct Histogram {
+    bounds: Vec<u64>,
+    // One count per bound plus the overflow bucket
+    counts: Vec<AtomicU64>,
+    sum: AtomicU64,
+    count: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Histogram {
+    /// Creates a new histogram with ascending bucket upper bounds
+    pub fn new(bounds: &[u64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates a new HDR-style histogram whose bounds grow log-linearly, 16 per power of two,
+    /// so every value is counted in a bucket within about 6% of it
+    pub fn log_linear() -> Self {
+        let linear = 0..SUB_BUCKETS;
+        let logarithmic = (0..=64 - SUB_BUCKET_BITS - 1).flat_map(|shift| {
+            (SUB_BUCKETS..2 * SUB_BUCKETS).map(move |sub| (sub << shift) + ((1u64 << shift) - 1))
+        });
+        Self::new(&linear.chain(logarithmic).collect::<Vec<_>>())
+    }
+
+    /// Records an observation
+    pub fn observe(&self, value: u64) {
+        let bucket = self.bounds.partition_point(|bound| *bound < value);
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+
+    /// Gets the bucket upper bounds
+    pub fn bounds(&self) -> &[u64] {
+        &self.bounds
+    }
+
+    /// Gets the cumulative count of observations at or below each bound, followed by the total
+    pub fn cumulative_counts(&self) -> Vec<u64> {
+        let mut total = 0;
+        self.counts
+            .iter()
+            .map(|count| {
+                total += count.load(Ordering::Relaxed);
+                total
+            })
+            .collect()
+    }
+
+    /// Gets the sum of all observations
+    pub fn sum(&self) -> u64 {
+        self.sum.load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of observations
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Gets the largest observation
+    pub fn max(&self) -> u64 {
+        self.max.load(Ordering::Relaxed)
+    }
+
+    /// Gets the bound at or below which `percentile` percent of the observations fall,
+    /// capped at the largest observation. Returns 0 when nothing was observed.
+    pub fn value_at_percentile(&self, percentile: f64) -> u64 {
+        let count = self.count();
+        if count == 0 {
+            return 0;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0) * count as f64).ceil() as u64;
+        let mut seen = 0;
+        for (bound, bucket) in self.bounds.iter().zip(&self.counts) {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= rank.max(1) {
+                return (*bound).min(self.max());
+            }
+        }
+        self.max()
+    }
+}
+
+/// EngineMetrics is the default lock-free implementation of Metrics.
+pub struct EngineMetrics {
+    inserts: AtomicU64,
+    cancels: AtomicU64,
+    trades: AtomicU64,
+    rejects: AtomicU64,
+    resting_orders: [AtomicU64; 2],
+    depth: [AtomicU64; 2],
+    match_cycle: Histogram,
+}
+
+impl EngineMetrics {
+    /// Creates a new set of engine metrics
+    pub fn new() -> Self {
+        Self {
+            inserts: AtomicU64::new(0),
+            cancels: AtomicU64::new(0),
+            trades: AtomicU64::new(0),
+            rejects: AtomicU64::new(0),
+            resting_orders: [AtomicU64::new(0), AtomicU64::new(0)],
+            depth: [AtomicU64::new(0), AtomicU64::new(0)],
+            match_cycle: Histogram::new(&MATCH_CYCLE_BUCKETS_MICROS),
+        }
+    }
+
+    #[inline(always)]
+    fn side_index(side: Side) -> usize {
+        match side {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        }
+    }
+
+    /// Gets the number of orders accepted into the book
+    pub fn inserts(&self) -> u64 {
+        self.inserts.load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of resting orders canceled or expired
+    pub fn cancels(&self) -> u64 {
+        self.cancels.load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of trades executed
+    pub fn trades(&self) -> u64 {
+        self.trades.load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of orders rejected
+    pub fn rejects(&self) -> u64 {
+        self.rejects.load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of resting orders of a side after the last match cycle
+    pub fn resting_orders(&self, side: Side) -> u64 {
+        self.resting_orders[Self::side_index(side)].load(Ordering::Relaxed)
+    }
+
+    /// Gets the number of price levels of a side after the last match cycle
+    pub fn depth(&self, side: Side) -> u64 {
+        self.depth[Self::side_index(side)].load(Ordering::Relaxed)
+    }
+
+    /// Gets the match cycle duration histogram, in microseconds
+    pub fn match_cycle(&self) -> &Histogram {
+        &self.match_cycle
+    }
+}
+
+impl Default for EngineMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics for EngineMetrics {
+    fn record_inserts(&self, count: u64) {
+        self.inserts.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn record_cancels(&self, count: u64) {
+        self.cancels.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn record_trades(&self, count: u64) {
+        self.trades.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn record_rejects(&self, count: u64) {
+        self.rejects.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn set_resting_orders(&self, side: Side, orders: u64) {
+        self.resting_orders[Self::side_index(side)].store(orders, Ordering::Relaxed);
+    }
+
+    fn set_depth(&self, side: Side, levels: u64) {
+        self.depth[Self::side_index(side)].store(levels, Ordering::Relaxed);
+    }
+
+    fn observe_match_cycle(&self, duration: Duration) {
+        self.match_cycle.observe(duration.as_micros() as u64);
+    }
+}
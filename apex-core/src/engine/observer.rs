@@ -0,0 +1,107 @@
+use crate::prelude::*;
+use std::sync::Arc;
+
+/// RejectEvent is a rejected order together with the reason it was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectEvent {
+    pub reason: RejectReason,
+    pub order: OrderView,
+}
+
+/// CancelEvent is a canceled or expired order together with the reason it left the book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancelEvent {
+    pub reason: CancelReason,
+    pub order: OrderView,
+}
+
+/// OrderObserver trait receives every rejection and cancellation,
+/// typically to feed alerting and customer-support tooling
+pub trait OrderObserver: Send + Sync {
+    /// This function is called when an order is rejected, before or during matching
+    fn on_reject(&self, _event: &RejectEvent) {}
+    /// This function is called when an order is canceled or expired, before or during matching
+    fn on_cancel(&self, _event: &CancelEvent) {}
+}
+
+/// ObservedSyncer forwards every book change to the primary syncer
+/// and reports rejections and cancellations to an observer.
+pub struct ObservedSyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    observer: Arc<dyn OrderObserver>,
+}
+
+impl ObservedSyncer {
+    /// Creates a new observed syncer in front of the primary syncer
+    pub fn new(primary: Arc<dyn OrderBookSyncer>, observer: Arc<dyn OrderObserver>) -> Self {
+        Self { primary, observer }
+    }
+
+    fn observe_reject(&self, order: &Order) {
+        if let Some(reason) = order.reject_reason() {
+            self.observer.on_reject(&RejectEvent {
+                reason,
+                order: OrderView::from(order),
+            });
+        }
+    }
+
+    fn observe_cancel(&self, order: &Order) {
+        if let Some(reason) = order.cancel_reason() {
+            self.observer.on_cancel(&CancelEvent {
+                reason,
+                order: OrderView::from(order),
+            });
+        }
+    }
+}
+
+impl OrderBookSyncer for ObservedSyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+        self.observe_cancel(order);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+        self.observe_reject(order);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        for order in updated {
+            match order.status() {
+                OrderStatus::Rejected => self.observe_reject(order),
+                OrderStatus::Cancelled => self.observe_cancel(order),
+                _ => {}
+            }
+        }
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        for event in events {
+            match event {
+                BookEvent::Cancelled(order) => self.observe_cancel(order),
+                BookEvent::Rejected(order) => self.observe_reject(order),
+                BookEvent::Added(_) | BookEvent::Updated(_) => {}
+            }
+        }
+    }
+}
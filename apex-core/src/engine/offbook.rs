@@ -0,0 +1,82 @@
+use crate::prelude::*;
+
+/// OffBookTrade is a block or OTC trade executed away from the book,
+/// reported so it reaches the same trade consumers as matched trades.
+///
+/// The order ids are the counterparties' references for the trade
+/// and never have to exist in the book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OffBookTrade {
+    pub buy_order_id: OrderID,
+    pub buy_user_id: u64,
+    pub sell_order_id: OrderID,
+    pub sell_user_id: u64,
+    pub price: Price,
+    pub quantity: Quantity,
+}
+
+impl OffBookTrade {
+    /// Creates a new off-book trade between two users
+    pub fn new(
+        (buy_order_id, buy_user_id): (OrderID, u64),
+        (sell_order_id, sell_user_id): (OrderID, u64),
+        price: Price,
+        quantity: Quantity,
+    ) -> Self {
+        Self {
+            buy_order_id,
+            buy_user_id,
+            sell_order_id,
+            sell_user_id,
+            price,
+            quantity,
+        }
+    }
+
+    /// Builds the filled order of one counterparty,
+    /// so consumers that attribute trades through the updated orders see the right user
+    pub(crate) fn filled_order(&self, side: Side, now_microseconds: u64) -> Order {
+        let (id, user_id) = match side {
+            Side::Buy => (self.buy_order_id, self.buy_user_id),
+            Side::Sell => (self.sell_order_id, self.sell_user_id),
+        };
+        let order = Order::limit(
+            id,
+            user_id,
+            side,
+            self.price,
+            self.quantity,
+            now_microseconds,
+        );
+        order.quantity_fill(self.quantity);
+        order.update_status(OrderStatus::Filled);
+        order.enter_finished_from_active();
+        order
+    }
+
+    /// Builds the trade of one counterparty.
+    /// The seller is reported as the maker and the buyer as the taker.
+    pub(crate) fn trade(&self, trade_id: u64, side: Side, now_microseconds: u64) -> Trade {
+        let (role, order_id) = match side {
+            Side::Buy => (TradeRole::Taker, self.buy_order_id),
+            Side::Sell => (TradeRole::Maker, self.sell_order_id),
+        };
+        Trade {
+            trade_id,
+            role,
+            order_id,
+            price: self.price,
+            quantity: self.quantity,
+            created_at: now_microseconds,
+            liquidation: false,
+            off_book: true,
+            tag: OrderTag::default(),
+            source: OrderSource::default(),
+            counterparty_source: OrderSource::default(),
+            #[cfg(feature = "hierarchy")]
+            account_id: None,
+            #[cfg(feature = "hierarchy")]
+            broker_id: None,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// PositionLimit bounds the net filled position a user may hold in a book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionLimit {
+    /// Largest net long position, i.e. bought minus sold.
+    pub max_long: Quantity,
+    /// Largest net short position, i.e. sold minus bought.
+    pub max_short: Quantity,
+}
+
+impl PositionLimit {
+    /// Creates a new position limit
+    pub fn new(max_long: Quantity, max_short: Quantity) -> Self {
+        Self {
+            max_long,
+            max_short,
+        }
+    }
+}
+
+/// Position is the filled quantity a user has bought and sold in a book.
+/// Both legs are kept unsigned, so the net position is `bought - sold`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub bought: Quantity,
+    pub sold: Quantity,
+}
+
+/// PositionLimits is a risk checker that tracks net filled positions per user
+/// from the trade stream and rejects orders that could breach the user's limit.
+///
+/// An order is checked as if its whole open quantity were filled.
+/// Feed trades with `record`, or let it receive the book's events, next to other syncers
+/// through a `FanOutSyncer`, to record them automatically.
+///
+/// The most recent fills are kept so a busted trade can be taken back out of the positions;
+/// busts of older trades leave the positions as they are. A rescale scales the positions
+/// but not the limits, which are reloaded separately like the book's configuration.
+pub struct PositionLimits {
+    default_limit: PositionLimit,
+    window: usize,
+    limits: Mutex<HashMap<u64, PositionLimit>>,
+    positions: Mutex<HashMap<u64, Position>>,
+    fills: Mutex<VecDeque<PositionFill>>,
+}
+
+/// PositionFill is one side of a recorded trade, kept so a bust can be reversed.
+#[derive(Debug, Clone, Copy)]
+struct PositionFill {
+    trade_id: u64,
+    user_id: u64,
+    side: Side,
+    quantity: Quantity,
+}
+
+impl PositionLimits {
+    /// Creates a new position limit module applying `default_limit` to every user
+    pub fn new(default_limit: PositionLimit) -> Self {
+        Self {
+            default_limit,
+            window: 1024,
+            limits: Mutex::new(HashMap::new()),
+            positions: Mutex::new(HashMap::new()),
+            fills: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Sets how many of the most recent fills are kept for reversing busted trades
+    pub fn with_correction_window(mut self, fills: usize) -> Self {
+        self.window = fills;
+        self
+    }
+
+    /// Overrides the limit of a user
+    pub fn set_limit(&self, user_id: u64, limit: PositionLimit) {
+        let mut limits = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+        limits.insert(user_id, limit);
+    }
+
+    /// Gets the limit applied to a user
+    pub fn limit(&self, user_id: u64) -> PositionLimit {
+        let limits = self.limits.lock().unwrap_or_else(|e| e.into_inner());
+        limits.get(&user_id).copied().unwrap_or(self.default_limit)
+    }
+
+    /// Gets the filled position of a user
+    pub fn position(&self, user_id: u64) -> Position {
+        let positions = self.positions.lock().unwrap_or_else(|e| e.into_inner());
+        positions.get(&user_id).copied().unwrap_or_default()
+    }
+
+    /// Records the fills of a match, as delivered to `OrderBookSyncer::matched`.
+    /// Trades of orders missing from `updated` are not counted.
+    pub fn record(&self, updated: &[Order], trades: &[Trade]) {
+        let mut positions = self.positions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut fills = self.fills.lock().unwrap_or_else(|e| e.into_inner());
+        for (trade, order) in trade_orders(updated, trades) {
+            let position = positions.entry(order.user_id).or_default();
+            match order.side {
+                Side::Buy => position.bought = position.bought.saturating_add(&trade.quantity),
+                Side::Sell => position.sold = position.sold.saturating_add(&trade.quantity),
+            }
+            if self.window == 0 {
+                continue;
+            }
+            if fills.len() == self.window {
+                fills.pop_front();
+            }
+            fills.push_back(PositionFill {
+                trade_id: trade.trade_id,
+                user_id: order.user_id,
+                side: order.side,
+                quantity: trade.quantity,
+            });
+        }
+    }
+
+    /// Takes the fills of a busted trade back out of the positions.
+    /// Price corrections leave the positions as they are.
+    pub fn correct(&self, correction: &TradeCorrection) {
+        if correction.kind != TradeCorrectionKind::Bust {
+            return;
+        }
+        let mut positions = self.positions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut fills = self.fills.lock().unwrap_or_else(|e| e.into_inner());
+        for fill in fills
+            .iter()
+            .filter(|fill| fill.trade_id == correction.trade_id)
+        {
+            let position = positions.entry(fill.user_id).or_default();
+            match fill.side {
+                Side::Buy => position.bought = position.bought.saturating_sub(&fill.quantity),
+                Side::Sell => position.sold = position.sold.saturating_sub(&fill.quantity),
+            }
+        }
+        fills.retain(|fill| fill.trade_id != correction.trade_id);
+    }
+
+    /// Scales the positions and the kept fills to a rescaled book, rounding down
+    pub fn rescale(&self, rescale: &Rescale) {
+        let mut positions = self.positions.lock().unwrap_or_else(|e| e.into_inner());
+        for position in positions.values_mut() {
+            position.bought = rescale.quantity.apply_rounded(position.bought);
+            position.sold = rescale.quantity.apply_rounded(position.sold);
+        }
+        let mut fills = self.fills.lock().unwrap_or_else(|e| e.into_inner());
+        for fill in fills.iter_mut() {
+            fill.quantity = rescale.quantity.apply_rounded(fill.quantity);
+        }
+    }
+}
+
+impl RiskChecker for PositionLimits {
+    fn check(&self, order: &Order) -> RiskDecision {
+        let position = self.position(order.user_id);
+        let limit = self.limit(order.user_id);
+        let quantity = order.quantity();
+        let breached = match order.side {
+            Side::Buy => {
+                position.bought.saturating_add(&quantity)
+                    > position.sold.saturating_add(&limit.max_long)
+            }
+            Side::Sell => {
+                position.sold.saturating_add(&quantity)
+                    > position.bought.saturating_add(&limit.max_short)
+            }
+        };
+        if breached {
+            RiskDecision::Reject(RejectReason::PositionLimitExceeded)
+        } else {
+            RiskDecision::Allow
+        }
+    }
+}
+
+impl OrderBookSyncer for PositionLimits {
+    fn add_order(&self, _id: u64, _order: &Order) {}
+
+    fn update_order(&self, _id: u64, _order: &Order) {}
+
+    fn cancel_order(&self, _id: u64, _order: &Order) {}
+
+    fn matched(&self, _id: u64, updated: &[Order], trades: &[Trade]) {
+        self.record(updated, trades);
+    }
+
+    fn trade_corrected(&self, _id: u64, correction: &TradeCorrection) {
+        self.correct(correction);
+    }
+
+    fn rescaled(&self, _id: u64, rescale: &Rescale) {
+        self.rescale(rescale);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::prelude::*;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Reads a per-side gauge from the engine metrics
+type SideGauge = fn(&EngineMetrics, Side) -> u64;
+
+/// PrometheusExporter renders engine metrics in the Prometheus text exposition format.
+pub struct PrometheusExporter {
+    metrics: Arc<EngineMetrics>,
+    prefix: String,
+}
+
+impl PrometheusExporter {
+    /// Creates a new exporter naming every metric `<prefix>_<name>`
+    pub fn new(metrics: Arc<EngineMetrics>, prefix: &str) -> Self {
+        Self {
+            metrics,
+            prefix: prefix.to_string(),
+        }
+    }
+
+    /// Renders the current metrics
+    pub fn render(&self) -> String {
+        let (m, p) = (&self.metrics, &self.prefix);
+        let mut out = String::new();
+        let counters = [
+            (
+                "inserts_total",
+                "Orders accepted into the book.",
+                m.inserts(),
+            ),
+            (
+                "cancels_total",
+                "Resting orders canceled or expired.",
+                m.cancels(),
+            ),
+            ("trades_total", "Trades executed.", m.trades()),
+            ("rejects_total", "Orders rejected.", m.rejects()),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {p}_{name} {help}");
+            let _ = writeln!(out, "# TYPE {p}_{name} counter");
+            let _ = writeln!(out, "{p}_{name} {value}");
+        }
+
+        let gauges: [(&str, &str, SideGauge); 2] = [
+            (
+                "resting_orders",
+                "Resting limit orders per side.",
+                EngineMetrics::resting_orders,
+            ),
+            (
+                "depth_levels",
+                "Price levels per side.",
+                EngineMetrics::depth,
+            ),
+        ];
+        for (name, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {p}_{name} {help}");
+            let _ = writeln!(out, "# TYPE {p}_{name} gauge");
+            for (side, label) in [(Side::Buy, "buy"), (Side::Sell, "sell")] {
+                let _ = writeln!(out, "{p}_{name}{{side=\"{label}\"}} {}", value(m, side));
+            }
+        }
+
+        let histogram = m.match_cycle();
+        let name = "match_cycle_microseconds";
+        let _ = writeln!(out, "# HELP {p}_{name} Duration of match cycles.");
+        let _ = writeln!(out, "# TYPE {p}_{name} histogram");
+        let counts = histogram.cumulative_counts();
+        for (bound, count) in histogram.bounds().iter().zip(&counts) {
+            let _ = writeln!(out, "{p}_{name}_bucket{{le=\"{bound}\"}} {count}");
+        }
+        let _ = writeln!(
+            out,
+            "{p}_{name}_bucket{{le=\"+Inf\"}} {}",
+            histogram.count()
+        );
+        let _ = writeln!(out, "{p}_{name}_sum {}", histogram.sum());
+        let _ = writeln!(out, "{p}_{name}_count {}", histogram.count());
+        out
+    }
+}
@@ -0,0 +1,378 @@
+use crate::prelude::*;
+use crossbeam::channel::{Receiver, Sender, bounded};
+use crossbeam::queue::ArrayQueue;
+use crossbeam::utils::Backoff;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Command is a request that is submitted to the matching engine.
+// Orders are far larger than the other variants, and boxing them costs an allocation per command
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Create a new order and put it into the order book.
+    Create(Order),
+    /// Update the price of a resting order.
+    Update {
+        order_id: OrderID,
+        new_price: Price,
+        now_microseconds: u64,
+    },
+    /// Amend the open quantity of a resting order.
+    Amend {
+        order_id: OrderID,
+        new_quantity: Quantity,
+        now_microseconds: u64,
+    },
+    /// Amend any of the price, open quantity and time in force of a resting order at once.
+    AmendOrder {
+        order_id: OrderID,
+        request: AmendRequest,
+        now_microseconds: u64,
+    },
+    /// Cancel a resting order.
+    Cancel(OrderID),
+}
+
+/// CommandResult is the outcome of a command applied in a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandResult {
+    /// Outcome of a `Command::Create`.
+    Created(Result<(), RejectReason>),
+    /// Outcome of a `Command::Update`, `Command::Amend` or `Command::AmendOrder`.
+    Updated(Result<(), UpdateOrderError>),
+    /// Outcome of a `Command::Cancel`.
+    Cancelled(Result<(), CancelOrderError>),
+}
+
+impl Command {
+    /// Executes the command on a matching engine and returns its outcome
+    pub fn execute(self, engine: &dyn MatchingEngine) -> CommandResult {
+        match self {
+            Command::Create(mut order) => CommandResult::Created(engine.create_order(&mut order)),
+            Command::Update {
+                order_id,
+                new_price,
+                now_microseconds,
+            } => CommandResult::Updated(engine.update_order(order_id, new_price, now_microseconds)),
+            Command::Amend {
+                order_id,
+                new_quantity,
+                now_microseconds,
+            } => CommandResult::Updated(engine.amend_quantity(
+                order_id,
+                new_quantity,
+                now_microseconds,
+            )),
+            Command::AmendOrder {
+                order_id,
+                request,
+                now_microseconds,
+            } => CommandResult::Updated(engine.amend_order(order_id, request, now_microseconds)),
+            Command::Cancel(order_id) => CommandResult::Cancelled(engine.cancel_order(order_id)),
+        }
+    }
+
+    /// Applies the command to a matching engine.
+    /// Outcomes are published through the order book syncer.
+    pub fn apply(self, engine: &dyn MatchingEngine) {
+        let _ = self.execute(engine);
+    }
+}
+
+/// QueueMetrics is a point-in-time view of the command queue,
+/// used by gateways to decide when to shed load.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueMetrics {
+    /// Number of commands waiting to be processed.
+    pub depth: usize,
+    /// Maximum number of commands the queue can hold.
+    pub capacity: usize,
+    /// Highest depth observed since the queue was created.
+    pub high_watermark: usize,
+    /// Number of commands accepted into the queue.
+    pub submitted: u64,
+    /// Number of commands refused because the queue was full.
+    pub rejected: u64,
+}
+
+/// CommandQueue is a bounded lock-free queue of commands waiting for the matching thread.
+pub struct CommandQueue {
+    queue: ArrayQueue<Command>,
+    high_watermark: AtomicUsize,
+    submitted: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl CommandQueue {
+    /// Creates a new command queue with the given capacity
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            queue: ArrayQueue::new(capacity.get()),
+            high_watermark: AtomicUsize::new(0),
+            submitted: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Submits a command, failing immediately with `QueueFull` if there is no room.
+    pub fn try_submit(&self, command: Command) -> Result<(), SubmitError> {
+        match self.queue.push(command) {
+            Ok(()) => {
+                self.submitted.fetch_add(1, Ordering::Relaxed);
+                self.high_watermark
+                    .fetch_max(self.queue.len(), Ordering::Relaxed);
+                Ok(())
+            }
+            Err(_) => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                Err(SubmitError::QueueFull)
+            }
+        }
+    }
+
+    /// Submits a command, waiting up to `timeout` for room in the queue.
+    pub fn submit_timeout(&self, command: Command, timeout: Duration) -> Result<(), SubmitError> {
+        let deadline = Instant::now() + timeout;
+        let backoff = Backoff::new();
+        let mut command = command;
+        loop {
+            match self.queue.push(command) {
+                Ok(()) => {
+                    self.submitted.fetch_add(1, Ordering::Relaxed);
+                    self.high_watermark
+                        .fetch_max(self.queue.len(), Ordering::Relaxed);
+                    return Ok(());
+                }
+                Err(returned) => {
+                    if Instant::now() >= deadline {
+                        self.rejected.fetch_add(1, Ordering::Relaxed);
+                        return Err(SubmitError::Timeout);
+                    }
+                    command = returned;
+                    backoff.snooze();
+                }
+            }
+        }
+    }
+
+    /// Pops the oldest command from the queue
+    pub fn pop(&self) -> Option<Command> {
+        self.queue.pop()
+    }
+
+    /// Gets the number of commands waiting in the queue
+    pub fn depth(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Gets the capacity of the queue
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Gets the queue metrics
+    pub fn metrics(&self) -> QueueMetrics {
+        QueueMetrics {
+            depth: self.queue.len(),
+            capacity: self.queue.capacity(),
+            high_watermark: self.high_watermark.load(Ordering::Relaxed),
+            submitted: self.submitted.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Session the submit methods without a session id are throttled as.
+pub const DEFAULT_SESSION: u64 = 0;
+
+/// QueuedMatchingEngine puts a bounded command queue in front of a matching engine.
+///
+/// Producers submit commands from any thread and get a typed `SubmitError`
+/// when the queue is full, while a single matching thread drains the queue with `process`.
+pub struct QueuedMatchingEngine {
+    engine: Arc<dyn MatchingEngine + Send + Sync>,
+    queue: CommandQueue,
+    throttle: Throttle,
+    /// Number of commands applied by `process`.
+    processed: AtomicU64,
+    /// Snapshot requests waiting for the commands submitted before them.
+    snapshots: Mutex<Vec<(u64, Sender<SequencedSnapshot>)>>,
+}
+
+impl QueuedMatchingEngine {
+    /// Creates a new queued matching engine
+    pub fn new(engine: Arc<dyn MatchingEngine + Send + Sync>, capacity: NonZeroUsize) -> Self {
+        Self {
+            engine,
+            queue: CommandQueue::new(capacity),
+            throttle: Throttle::default(),
+            processed: AtomicU64::new(0),
+            snapshots: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sets the throttle applied to submitted commands; commands submitted without
+    /// a session id count against `DEFAULT_SESSION`
+    pub fn with_throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Submits a command of a gateway session, failing with `Throttled` if the session
+    /// or the engine is over its throughput limit
+    pub fn submit(&self, session_id: u64, command: Command) -> Result<(), SubmitError> {
+        if !self.throttle.try_admit(session_id, &command) {
+            return Err(SubmitError::Throttled);
+        }
+        self.queue.try_submit(command)
+    }
+
+    /// Submits a create order command
+    pub fn create_order(&self, order: Order) -> Result<(), SubmitError> {
+        self.submit(DEFAULT_SESSION, Command::Create(order))
+    }
+
+    /// Submits a create order command, waiting up to `timeout` for room in the queue
+    pub fn create_order_timeout(&self, order: Order, timeout: Duration) -> Result<(), SubmitError> {
+        let command = Command::Create(order);
+        if !self.throttle.try_admit(DEFAULT_SESSION, &command) {
+            return Err(SubmitError::Throttled);
+        }
+        self.queue.submit_timeout(command, timeout)
+    }
+
+    /// Submits an update order command
+    pub fn update_order(
+        &self,
+        order_id: OrderID,
+        new_price: Price,
+        now_microseconds: u64,
+    ) -> Result<(), SubmitError> {
+        self.submit(
+            DEFAULT_SESSION,
+            Command::Update {
+                order_id,
+                new_price,
+                now_microseconds,
+            },
+        )
+    }
+
+    /// Submits an amend quantity command
+    pub fn amend_quantity(
+        &self,
+        order_id: OrderID,
+        new_quantity: Quantity,
+        now_microseconds: u64,
+    ) -> Result<(), SubmitError> {
+        self.submit(
+            DEFAULT_SESSION,
+            Command::Amend {
+                order_id,
+                new_quantity,
+                now_microseconds,
+            },
+        )
+    }
+
+    /// Submits an amend order command
+    pub fn amend_order(
+        &self,
+        order_id: OrderID,
+        request: AmendRequest,
+        now_microseconds: u64,
+    ) -> Result<(), SubmitError> {
+        self.submit(
+            DEFAULT_SESSION,
+            Command::AmendOrder {
+                order_id,
+                request,
+                now_microseconds,
+            },
+        )
+    }
+
+    /// Submits a cancel order command
+    pub fn cancel_order(&self, order_id: OrderID) -> Result<(), SubmitError> {
+        self.submit(DEFAULT_SESSION, Command::Cancel(order_id))
+    }
+
+    /// Requests a snapshot of the book once every command submitted so far is applied.
+    ///
+    /// The snapshot is taken by `process` on the matching thread, after it applied those
+    /// commands and matched the book, so no command or match interleaves with it and it
+    /// reflects a single sequence. The receiver gets it from the first `process` call that
+    /// catches up with the request, or disconnects if the engine is dropped first.
+    pub fn request_snapshot(&self) -> Receiver<SequencedSnapshot> {
+        let (sender, receiver) = bounded(1);
+        let submitted = self.queue.metrics().submitted;
+        self.lock_snapshots().push((submitted, sender));
+        receiver
+    }
+
+    /// Applies up to `max` queued commands and then matches the book,
+    /// and takes the snapshots requested once those commands are applied.
+    /// Returns the number of commands applied.
+    pub fn process(&self, max: usize) -> usize {
+        let mut applied = 0;
+        while applied < max {
+            let command = match self.queue.pop() {
+                Some(command) => command,
+                None => break,
+            };
+            command.apply(self.engine.as_ref());
+            applied += 1;
+        }
+        if applied > 0 {
+            self.engine.match_orders();
+        }
+        let processed =
+            self.processed.fetch_add(applied as u64, Ordering::Relaxed) + applied as u64;
+        self.take_snapshots(processed);
+        applied
+    }
+
+    /// Gets the number of commands waiting in the queue
+    pub fn depth(&self) -> usize {
+        self.queue.depth()
+    }
+
+    /// Gets the queue metrics
+    pub fn metrics(&self) -> QueueMetrics {
+        self.queue.metrics()
+    }
+
+    /// Sends a snapshot to every request whose commands are all applied
+    fn take_snapshots(&self, processed: u64) {
+        let due: Vec<_> = {
+            let mut snapshots = self.lock_snapshots();
+            if snapshots.is_empty() {
+                return;
+            }
+            let (due, waiting) = std::mem::take(&mut *snapshots)
+                .into_iter()
+                .partition(|(submitted, _)| *submitted <= processed);
+            *snapshots = waiting;
+            due
+        };
+        if due.is_empty() {
+            return;
+        }
+        let snapshot = SequencedSnapshot {
+            sequence: self.engine.sequence(),
+            book: self.engine.snapshot(),
+        };
+        for (_, sender) in due {
+            let _ = sender.send(snapshot.clone());
+        }
+    }
+
+    fn lock_snapshots(&self) -> MutexGuard<'_, Vec<(u64, Sender<SequencedSnapshot>)>> {
+        self.snapshots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::prelude::*;
+
+/// QuoteEntry is the desired state of one quote of a mass quote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteEntry {
+    /// Id of the resting limit order that carries the quote.
+    pub order_id: OrderID,
+    pub side: Side,
+    pub price: Price,
+    /// Open quantity of the quote; zero pulls the quote from the book.
+    pub quantity: Quantity,
+}
+
+/// QuoteAction is what a mass quote did to the book for one entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteAction {
+    /// A new quote was placed.
+    Created,
+    /// The price or the open quantity of a resting quote was changed.
+    Replaced,
+    /// The resting quote was pulled.
+    Cancelled,
+    /// The resting quote already matched the entry.
+    Unchanged,
+}
+
+/// MassQuote updates many quotes of one user in a single call.
+///
+/// Every entry names the order carrying one quote. Unknown ids place a new GoodTillCancelled
+/// limit order, resting quotes of the user are repriced and resized in place, and entries with
+/// a zero quantity pull their quote. The resulting commands are applied as one batch, and
+/// every entry is accepted or rejected on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MassQuote {
+    pub user_id: u64,
+    pub entries: Vec<QuoteEntry>,
+    pub now_microseconds: u64,
+}
+
+impl MassQuote {
+    /// Creates a new mass quote for a user
+    pub fn new(user_id: u64, entries: Vec<QuoteEntry>, now_microseconds: u64) -> Self {
+        Self {
+            user_id,
+            entries,
+            now_microseconds,
+        }
+    }
+
+    /// Applies the mass quote to a matching engine and returns the outcome of every entry,
+    /// in entry order
+    pub fn execute(&self, engine: &dyn MatchingEngine) -> Vec<Result<QuoteAction, QuoteError>> {
+        let resting = engine.open_orders(self.user_id);
+        let mut commands = Vec::new();
+        // For every entry, its planned outcome and the range of its commands in the batch
+        let mut planned = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            let start = commands.len();
+            let quote = resting.iter().find(|order| order.id == entry.order_id);
+            let outcome = match quote {
+                Some(order) if order.side != entry.side => Err(QuoteError::SideMismatch),
+                Some(_) if entry.quantity == Quantity::ZERO => {
+                    commands.push(Command::Cancel(entry.order_id));
+                    Ok(QuoteAction::Cancelled)
+                }
+                Some(order) => {
+                    if order.price != entry.price {
+                        commands.push(Command::Update {
+                            order_id: entry.order_id,
+                            new_price: entry.price,
+                            now_microseconds: self.now_microseconds,
+                        });
+                    }
+                    if order.quantity != entry.quantity {
+                        commands.push(Command::Amend {
+                            order_id: entry.order_id,
+                            new_quantity: entry.quantity,
+                            now_microseconds: self.now_microseconds,
+                        });
+                    }
+                    if commands.len() == start {
+                        Ok(QuoteAction::Unchanged)
+                    } else {
+                        Ok(QuoteAction::Replaced)
+                    }
+                }
+                None if entry.quantity == Quantity::ZERO => {
+                    Err(QuoteError::Cancel(CancelOrderError::OrderNotFound))
+                }
+                None => {
+                    commands.push(Command::Create(Order::limit(
+                        entry.order_id,
+                        self.user_id,
+                        entry.side,
+                        entry.price,
+                        entry.quantity,
+                        self.now_microseconds,
+                    )));
+                    Ok(QuoteAction::Created)
+                }
+            };
+            planned.push((outcome, start..commands.len()));
+        }
+
+        let results = if commands.is_empty() {
+            Vec::new()
+        } else {
+            engine.execute_batch(&mut commands)
+        };
+        planned
+            .into_iter()
+            .map(|(outcome, range)| {
+                // The first failing command of an entry decides its outcome
+                results[range]
+                    .iter()
+                    .find_map(|result| match result {
+                        CommandResult::Created(Err(reason)) => Some(QuoteError::Rejected(*reason)),
+                        CommandResult::Updated(Err(error)) => {
+                            Some(QuoteError::Update(error.clone()))
+                        }
+                        CommandResult::Cancelled(Err(error)) => {
+                            Some(QuoteError::Cancel(error.clone()))
+                        }
+                        _ => None,
+                    })
+                    .map_or(outcome, Err)
+            })
+            .collect()
+    }
+}
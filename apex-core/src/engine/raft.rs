@@ -0,0 +1,621 @@
+use crate::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// NodeID identifies a member of a replication group.
+pub type NodeID = u64;
+
+/// RaftError is a failure to propose a command to a replication group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaftError {
+    /// Only the leader accepts proposals; `leader` is the last leader this node heard from.
+    NotLeader { leader: Option<NodeID> },
+}
+
+impl fmt::Display for RaftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaftError::NotLeader {
+                leader: Some(leader),
+            } => {
+                write!(f, "not the leader, node {leader} is")
+            }
+            RaftError::NotLeader { leader: None } => f.write_str("not the leader, none known"),
+        }
+    }
+}
+
+impl Error for RaftError {}
+
+/// RaftConfig configures the timing of a replication node, in ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaftConfig {
+    /// Ticks without hearing from a leader before a follower starts an election.
+    /// Each node adds its id modulo this value, so elections rarely split.
+    pub election_ticks: u64,
+    /// Ticks between the heartbeats of a leader.
+    pub heartbeat_ticks: u64,
+}
+
+impl Default for RaftConfig {
+    fn default() -> Self {
+        Self {
+            election_ticks: 10,
+            heartbeat_ticks: 3,
+        }
+    }
+}
+
+/// RaftRole is the role a node currently plays in its group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaftRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+/// LogEntry is one slot of the replicated command log.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// Term of the leader that appended the entry.
+    pub term: u64,
+    /// Time the leader appended the entry, in microseconds since the UNIX epoch.
+    /// Every replica applies the entry as of this time; it never decreases along the log.
+    pub timestamp: u64,
+    /// The replicated command; None for the entry a new leader appends to commit its term.
+    pub command: Option<ReplicatedCommand>,
+}
+
+/// RaftMessage is exchanged between the nodes of a group by the caller's transport.
+#[derive(Debug, Clone)]
+pub enum RaftMessage {
+    RequestVote {
+        term: u64,
+        last_log_index: u64,
+        last_log_term: u64,
+    },
+    Vote {
+        term: u64,
+        granted: bool,
+    },
+    AppendEntries {
+        term: u64,
+        prev_log_index: u64,
+        prev_log_term: u64,
+        entries: Vec<LogEntry>,
+        leader_commit: u64,
+    },
+    AppendResponse {
+        term: u64,
+        success: bool,
+        /// Index of the last entry the follower holds in common with the leader.
+        match_index: u64,
+    },
+}
+
+/// RaftStorage keeps the state a node must not forget across a restart: its term, the vote
+/// it cast in that term and its log.
+///
+/// Every call must be durable when it returns; the node persists before it replies or sends,
+/// so a restarted node never votes twice in a term or loses an entry it acknowledged.
+pub trait RaftStorage: Send + Sync {
+    /// Stores the current term and the candidate voted for in it
+    fn save_vote(&self, term: u64, voted_for: Option<NodeID>);
+    /// Stores the log entries from index `from` on, replacing any stored at or after it
+    fn save_log(&self, from: u64, entries: &[LogEntry]);
+    /// Loads the term, vote and log stored before the restart
+    fn load(&self) -> (u64, Option<NodeID>, Vec<LogEntry>);
+}
+
+/// MemoryRaftStorage keeps the durable state of a node in memory,
+/// so it survives a restart of the node but not of the process.
+#[derive(Default)]
+pub struct MemoryRaftStorage {
+    state: Mutex<(u64, Option<NodeID>, Vec<LogEntry>)>,
+}
+
+impl MemoryRaftStorage {
+    /// Creates a new empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RaftStorage for MemoryRaftStorage {
+    fn save_vote(&self, term: u64, voted_for: Option<NodeID>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        (state.0, state.1) = (term, voted_for);
+    }
+
+    fn save_log(&self, from: u64, entries: &[LogEntry]) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.2.truncate(from.saturating_sub(1) as usize);
+        state.2.extend_from_slice(entries);
+    }
+
+    fn load(&self) -> (u64, Option<NodeID>, Vec<LogEntry>) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// RaftNode replicates the command log of a matching engine across a group of nodes.
+///
+/// Commands are proposed to the leader, stamped with the leader's time, appended to its log
+/// and replicated to the followers. A command is applied to a node's engine only once a
+/// majority of the group holds it, so every replica applies the same commands in the same
+/// order. The book is matched after every applied command, which keeps matching deterministic.
+///
+/// Replicas keep identical books only if nothing but the log changes them and they read
+/// the same time and state while applying it:
+///
+/// - Every book-changing operation, including expiry, stale quote demotion, mass cancels,
+///   transfers, freezes, mode switches, config reloads and rescales, is proposed as a
+///   `ReplicatedCommand` instead of being called on an engine directly.
+/// - Every engine is built `with_log_clock`, so trade times and ids, acceptance times, rate
+///   limits, the speed bump and market order staleness follow the entries' timestamps.
+///   Without it each replica reads its own clock and their books drift apart.
+/// - Engines start from the same book and config, with the same id generator worker id and
+///   the same speed bump seed.
+/// - A reference price provider or risk checker answers from state outside the log, so one
+///   that is not identical on every replica makes them diverge; leave them off or feed them
+///   only from the engine's own events.
+///
+/// The node is a pure state machine: the caller drives time with `tick`, delivers messages
+/// with `step` and carries the messages returned by `take_messages` to their nodes.
+///
+/// Without `with_storage` the term, vote and log live only in memory, and a restarted node
+/// may vote twice in a term or drop entries a majority counted on; give every node storage
+/// in production. The log is never truncated: it grows with every command, and a restarted
+/// or lagging node catches up by replaying it from the first entry, not from a book checkpoint.
+pub struct RaftNode {
+    id: NodeID,
+    peers: Vec<NodeID>,
+    config: RaftConfig,
+    engine: Arc<DefaultMatchingEngine>,
+    clock: Arc<dyn Clock>,
+    role: RaftRole,
+    term: u64,
+    voted_for: Option<NodeID>,
+    leader: Option<NodeID>,
+    log: Vec<LogEntry>,
+    commit_index: u64,
+    last_applied: u64,
+    elapsed: u64,
+    votes: HashSet<NodeID>,
+    next_index: HashMap<NodeID, u64>,
+    match_index: HashMap<NodeID, u64>,
+    outbox: Vec<(NodeID, RaftMessage)>,
+    storage: Option<Arc<dyn RaftStorage>>,
+}
+
+impl RaftNode {
+    /// Creates a new follower that applies committed commands to `engine`
+    pub fn new(
+        id: NodeID,
+        peers: Vec<NodeID>,
+        config: RaftConfig,
+        engine: Arc<DefaultMatchingEngine>,
+    ) -> Self {
+        Self {
+            id,
+            peers: peers.into_iter().filter(|peer| *peer != id).collect(),
+            config,
+            engine,
+            clock: Arc::new(SystemClock {}),
+            role: RaftRole::Follower,
+            term: 0,
+            voted_for: None,
+            leader: None,
+            log: Vec::new(),
+            commit_index: 0,
+            last_applied: 0,
+            elapsed: 0,
+            votes: HashSet::new(),
+            next_index: HashMap::new(),
+            match_index: HashMap::new(),
+            outbox: Vec::new(),
+            storage: None,
+        }
+    }
+
+    /// Persists the term, vote and log to `storage`, restoring what it holds from before a
+    /// restart. Committed entries are applied again once the node learns the commit index.
+    pub fn with_storage(mut self, storage: Arc<dyn RaftStorage>) -> Self {
+        (self.term, self.voted_for, self.log) = storage.load();
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets the clock the node stamps its entries with while it leads
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Gets the id of the node
+    pub fn id(&self) -> NodeID {
+        self.id
+    }
+
+    /// Gets the current role of the node
+    pub fn role(&self) -> RaftRole {
+        self.role
+    }
+
+    /// Gets the current term of the node
+    pub fn term(&self) -> u64 {
+        self.term
+    }
+
+    /// Gets the last leader this node heard from
+    pub fn leader(&self) -> Option<NodeID> {
+        self.leader
+    }
+
+    /// Gets the index of the last log entry known to be committed
+    pub fn commit_index(&self) -> u64 {
+        self.commit_index
+    }
+
+    /// Gets the index of the last log entry applied to the engine
+    pub fn last_applied(&self) -> u64 {
+        self.last_applied
+    }
+
+    /// Gets the engine the node applies committed commands to
+    pub fn engine(&self) -> &Arc<DefaultMatchingEngine> {
+        &self.engine
+    }
+
+    /// Proposes a command and returns the log index it will be committed at, if it is.
+    /// Only the leader accepts proposals.
+    pub fn propose(&mut self, command: impl Into<ReplicatedCommand>) -> Result<u64, RaftError> {
+        if self.role != RaftRole::Leader {
+            return Err(RaftError::NotLeader {
+                leader: self.leader,
+            });
+        }
+        self.log.push(LogEntry {
+            term: self.term,
+            timestamp: self.next_timestamp(),
+            command: Some(command.into()),
+        });
+        self.persist_log(self.last_log_index());
+        for peer in self.peers.clone() {
+            self.send_append(peer);
+        }
+        self.advance_commit();
+        Ok(self.last_log_index())
+    }
+
+    /// Advances the node's clock by one tick, starting an election or sending heartbeats when due
+    pub fn tick(&mut self) {
+        self.elapsed += 1;
+        match self.role {
+            RaftRole::Leader => {
+                if self.elapsed >= self.config.heartbeat_ticks {
+                    self.elapsed = 0;
+                    for peer in self.peers.clone() {
+                        self.send_append(peer);
+                    }
+                }
+            }
+            RaftRole::Follower | RaftRole::Candidate => {
+                if self.elapsed >= self.election_timeout() {
+                    self.campaign();
+                }
+            }
+        }
+    }
+
+    /// Handles a message from another node of the group
+    pub fn step(&mut self, from: NodeID, message: RaftMessage) {
+        let term = match &message {
+            RaftMessage::RequestVote { term, .. }
+            | RaftMessage::Vote { term, .. }
+            | RaftMessage::AppendEntries { term, .. }
+            | RaftMessage::AppendResponse { term, .. } => *term,
+        };
+        if term > self.term {
+            self.become_follower(term, None);
+        }
+
+        match message {
+            RaftMessage::RequestVote {
+                term,
+                last_log_index,
+                last_log_term,
+            } => {
+                let up_to_date = (last_log_term, last_log_index)
+                    >= (self.last_log_term(), self.last_log_index());
+                let granted = term == self.term
+                    && self.voted_for.is_none_or(|candidate| candidate == from)
+                    && up_to_date;
+                if granted {
+                    self.voted_for = Some(from);
+                    self.elapsed = 0;
+                    self.persist_vote();
+                }
+                self.send(
+                    from,
+                    RaftMessage::Vote {
+                        term: self.term,
+                        granted,
+                    },
+                );
+            }
+            RaftMessage::Vote { term, granted } => {
+                if self.role == RaftRole::Candidate && term == self.term && granted {
+                    self.votes.insert(from);
+                    if self.votes.len() >= self.quorum() {
+                        self.become_leader();
+                    }
+                }
+            }
+            RaftMessage::AppendEntries {
+                term,
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit,
+            } => self.handle_append(
+                from,
+                term,
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit,
+            ),
+            RaftMessage::AppendResponse {
+                term,
+                success,
+                match_index,
+            } => {
+                if self.role != RaftRole::Leader || term != self.term {
+                    return;
+                }
+                if success {
+                    let matched = self.match_index.entry(from).or_insert(0);
+                    *matched = (*matched).max(match_index);
+                    self.next_index.insert(from, *matched + 1);
+                    self.advance_commit();
+                } else {
+                    // Step back to the follower's hint and retry with the earlier entries
+                    let next = self.next_index.entry(from).or_insert(1);
+                    *next = (*next - 1).min(match_index + 1).max(1);
+                    self.send_append(from);
+                }
+            }
+        }
+    }
+
+    /// Takes the messages the node wants delivered, addressed by node id
+    pub fn take_messages(&mut self) -> Vec<(NodeID, RaftMessage)> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn handle_append(
+        &mut self,
+        from: NodeID,
+        term: u64,
+        prev_log_index: u64,
+        prev_log_term: u64,
+        entries: Vec<LogEntry>,
+        leader_commit: u64,
+    ) {
+        if term < self.term {
+            self.send(
+                from,
+                RaftMessage::AppendResponse {
+                    term: self.term,
+                    success: false,
+                    match_index: 0,
+                },
+            );
+            return;
+        }
+        self.become_follower(term, Some(from));
+
+        let consistent = prev_log_index <= self.last_log_index()
+            && (prev_log_index == 0 || self.term_at(prev_log_index) == prev_log_term);
+        if !consistent {
+            // Entries up to the hint may still match; the leader resends from after it
+            let hint = (prev_log_index - 1).min(self.last_log_index());
+            self.send(
+                from,
+                RaftMessage::AppendResponse {
+                    term: self.term,
+                    success: false,
+                    match_index: hint,
+                },
+            );
+            return;
+        }
+
+        let mut index = prev_log_index;
+        let mut changed_from = None;
+        for entry in entries {
+            index += 1;
+            if index <= self.last_log_index() {
+                if self.term_at(index) == entry.term {
+                    continue;
+                }
+                // A conflicting suffix was never committed; drop it
+                self.log.truncate(index as usize - 1);
+            }
+            self.log.push(entry);
+            changed_from.get_or_insert(index);
+        }
+        if let Some(from) = changed_from {
+            self.persist_log(from);
+        }
+        if leader_commit > self.commit_index {
+            self.commit_index = leader_commit.min(index);
+            self.apply_committed();
+        }
+        self.send(
+            from,
+            RaftMessage::AppendResponse {
+                term: self.term,
+                success: true,
+                match_index: index,
+            },
+        );
+    }
+
+    fn campaign(&mut self) {
+        self.term += 1;
+        self.role = RaftRole::Candidate;
+        self.voted_for = Some(self.id);
+        self.leader = None;
+        self.elapsed = 0;
+        self.votes = HashSet::from([self.id]);
+        self.persist_vote();
+        if self.votes.len() >= self.quorum() {
+            self.become_leader();
+            return;
+        }
+        for peer in self.peers.clone() {
+            self.send(
+                peer,
+                RaftMessage::RequestVote {
+                    term: self.term,
+                    last_log_index: self.last_log_index(),
+                    last_log_term: self.last_log_term(),
+                },
+            );
+        }
+    }
+
+    fn become_follower(&mut self, term: u64, leader: Option<NodeID>) {
+        if term > self.term {
+            self.term = term;
+            self.voted_for = None;
+            self.persist_vote();
+        }
+        self.role = RaftRole::Follower;
+        self.leader = leader;
+        self.elapsed = 0;
+    }
+
+    fn become_leader(&mut self) {
+        self.role = RaftRole::Leader;
+        self.leader = Some(self.id);
+        self.elapsed = 0;
+        let next = self.last_log_index() + 1;
+        self.next_index = self.peers.iter().map(|peer| (*peer, next)).collect();
+        self.match_index = self.peers.iter().map(|peer| (*peer, 0)).collect();
+        // Entries of earlier terms commit together with the first entry of this term
+        self.log.push(LogEntry {
+            term: self.term,
+            timestamp: self.next_timestamp(),
+            command: None,
+        });
+        self.persist_log(self.last_log_index());
+        for peer in self.peers.clone() {
+            self.send_append(peer);
+        }
+        self.advance_commit();
+    }
+
+    fn send_append(&mut self, peer: NodeID) {
+        let next = self
+            .next_index
+            .get(&peer)
+            .copied()
+            .unwrap_or(1)
+            .clamp(1, self.last_log_index() + 1);
+        let prev_log_index = next - 1;
+        self.send(
+            peer,
+            RaftMessage::AppendEntries {
+                term: self.term,
+                prev_log_index,
+                prev_log_term: self.term_at(prev_log_index),
+                entries: self.log[prev_log_index as usize..].to_vec(),
+                leader_commit: self.commit_index,
+            },
+        );
+    }
+
+    /// Commits the newest entry of the current term that a majority holds
+    fn advance_commit(&mut self) {
+        for index in (self.commit_index + 1..=self.last_log_index()).rev() {
+            if self.term_at(index) != self.term {
+                break;
+            }
+            let holders = 1 + self
+                .match_index
+                .values()
+                .filter(|matched| **matched >= index)
+                .count();
+            if holders >= self.quorum() {
+                self.commit_index = index;
+                self.apply_committed();
+                break;
+            }
+        }
+    }
+
+    fn apply_committed(&mut self) {
+        while self.last_applied < self.commit_index {
+            self.last_applied += 1;
+            let entry = &self.log[self.last_applied as usize - 1];
+            if let Some(command) = &entry.command {
+                self.engine.set_log_time(entry.timestamp);
+                command.clone().apply(&self.engine, entry.timestamp);
+                self.engine.match_orders();
+            }
+        }
+    }
+
+    /// Reads the leader's clock for a new entry, never going back before the last entry
+    fn next_timestamp(&self) -> u64 {
+        let last = self.log.last().map_or(0, |entry| entry.timestamp);
+        self.clock.now_micros().max(last)
+    }
+
+    fn persist_vote(&self) {
+        if let Some(storage) = &self.storage {
+            storage.save_vote(self.term, self.voted_for);
+        }
+    }
+
+    /// Persists the log from index `from` on
+    fn persist_log(&self, from: u64) {
+        if let Some(storage) = &self.storage {
+            storage.save_log(from, &self.log[from as usize - 1..]);
+        }
+    }
+
+    fn send(&mut self, to: NodeID, message: RaftMessage) {
+        self.outbox.push((to, message));
+    }
+
+    fn quorum(&self) -> usize {
+        let group = self.peers.len() + 1;
+        group / 2 + 1
+    }
+
+    fn election_timeout(&self) -> u64 {
+        let base = self.config.election_ticks.max(1);
+        base + self.id % base
+    }
+
+    fn last_log_index(&self) -> u64 {
+        self.log.len() as u64
+    }
+
+    fn last_log_term(&self) -> u64 {
+        self.term_at(self.last_log_index())
+    }
+
+    fn term_at(&self, index: u64) -> u64 {
+        match index {
+            0 => 0,
+            index => self.log[index as usize - 1].term,
+        }
+    }
+}
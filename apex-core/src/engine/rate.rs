@@ -0,0 +1,227 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// TokenBucket admits a burst of up to `capacity` requests and then
+/// `refill_per_second` requests per second.
+///
+/// A bucket keeps time in microseconds, either on a clock's timeline or, for the methods
+/// taking instants, since the instant it was created at; one bucket follows one of the two.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    /// Instant that microsecond 0 stands for when the bucket is driven by instants.
+    origin: Instant,
+    /// Time of the last refill, in microseconds.
+    refilled_at: u64,
+}
+
+impl TokenBucket {
+    /// Creates a new full bucket
+    pub fn new(capacity: u32, refill_per_second: u32) -> Self {
+        Self::new_at(capacity, refill_per_second, Instant::now())
+    }
+
+    /// Creates a new full bucket that starts refilling at `now`
+    pub fn new_at(capacity: u32, refill_per_second: u32, now: Instant) -> Self {
+        Self {
+            capacity: capacity as f64,
+            refill_per_second: refill_per_second as f64,
+            tokens: capacity as f64,
+            origin: now,
+            refilled_at: 0,
+        }
+    }
+
+    /// Creates a new full bucket that starts refilling at `now_micros` on a clock's timeline
+    pub fn new_at_micros(capacity: u32, refill_per_second: u32, now_micros: u64) -> Self {
+        Self {
+            refilled_at: now_micros,
+            ..Self::new(capacity, refill_per_second)
+        }
+    }
+
+    /// Takes a token if one is available at `now`
+    pub fn try_acquire_at(&mut self, now: Instant) -> bool {
+        self.try_acquire_at_micros(micros_since(self.origin, now))
+    }
+
+    /// Takes a token if one is available at `now_micros` on the bucket's clock
+    pub fn try_acquire_at_micros(&mut self, now_micros: u64) -> bool {
+        self.tokens = self.tokens_at(now_micros);
+        self.refilled_at = self.refilled_at.max(now_micros);
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    /// Takes a token if one is available now
+    pub fn try_acquire(&mut self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    /// Checks whether the bucket is full again at `now_micros`, so a new bucket would do
+    pub fn is_full_at_micros(&self, now_micros: u64) -> bool {
+        self.tokens_at(now_micros) >= self.capacity
+    }
+
+    /// Gets the tokens the bucket holds at `now_micros`
+    fn tokens_at(&self, now_micros: u64) -> f64 {
+        let elapsed = now_micros.saturating_sub(self.refilled_at) as f64 / 1_000_000.0;
+        (self.tokens + elapsed * self.refill_per_second).min(self.capacity)
+    }
+}
+
+/// Gets the microseconds from `origin` to `now`, zero if `now` is earlier
+fn micros_since(origin: Instant, now: Instant) -> u64 {
+    u64::try_from(now.saturating_duration_since(origin).as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Bucket charged for cancels of orders the book does not know, which have no owner to charge.
+pub const UNKNOWN_ORDER_OWNER: u64 = u64::MAX;
+
+struct RateLimiterState {
+    buckets: HashMap<u64, TokenBucket>,
+    /// Time buckets that refilled completely were last dropped, in microseconds.
+    swept_at: u64,
+}
+
+/// RateLimiter keeps one token bucket per user,
+/// so a single abusive client cannot monopolize the matching thread.
+///
+/// Buckets that refilled completely are dropped once per refill period, as a new bucket
+/// would be the same, so users who stopped sending take no memory.
+pub struct RateLimiter {
+    capacity: u32,
+    refill_per_second: u32,
+    /// Instant that microsecond 0 stands for when the limiter is driven by instants.
+    origin: Instant,
+    state: Mutex<RateLimiterState>,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter allowing each user a burst of `capacity` requests
+    /// and then `refill_per_second` requests per second
+    pub fn new(capacity: u32, refill_per_second: u32) -> Self {
+        Self {
+            capacity,
+            refill_per_second,
+            origin: Instant::now(),
+            state: Mutex::new(RateLimiterState {
+                buckets: HashMap::new(),
+                swept_at: 0,
+            }),
+        }
+    }
+
+    /// Takes a token from a user's bucket if one is available at `now`
+    pub fn try_acquire_at(&self, user_id: u64, now: Instant) -> bool {
+        self.try_acquire_at_micros(user_id, micros_since(self.origin, now))
+    }
+
+    /// Takes a token from a user's bucket if one is available at `now_micros` on a clock's
+    /// timeline; a limiter is driven either by a clock or by instants
+    pub fn try_acquire_at_micros(&self, user_id: u64, now_micros: u64) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.drop_full_buckets(&mut state, now_micros);
+        state
+            .buckets
+            .entry(user_id)
+            .or_insert_with(|| {
+                TokenBucket::new_at_micros(self.capacity, self.refill_per_second, now_micros)
+            })
+            .try_acquire_at_micros(now_micros)
+    }
+
+    /// Takes a token from a user's bucket if one is available now
+    pub fn try_acquire(&self, user_id: u64) -> bool {
+        self.try_acquire_at(user_id, Instant::now())
+    }
+
+    /// Gets the number of users with a bucket
+    pub fn tracked_users(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .buckets
+            .len()
+    }
+
+    /// Drops the buckets that refilled completely, if a refill period passed since the last time.
+    /// Buckets that never refill are kept, as they never fill up again.
+    fn drop_full_buckets(&self, state: &mut RateLimiterState, now_micros: u64) {
+        if self.refill_per_second == 0 {
+            return;
+        }
+        let refill_micros =
+            (u64::from(self.capacity) * 1_000_000).div_ceil(u64::from(self.refill_per_second));
+        if now_micros.saturating_sub(state.swept_at) < refill_micros {
+            return;
+        }
+        state.swept_at = now_micros;
+        state
+            .buckets
+            .retain(|_, bucket| !bucket.is_full_at_micros(now_micros));
+    }
+}
+
+/// Throttle limits the throughput of gateway sessions and of the whole engine,
+/// so a misbehaving gateway cannot starve the matching thread.
+///
+/// Every command takes a token from its session's bucket. Commands other than cancels also
+/// take a token from the global bucket, so a flood of new orders never holds back cancels.
+#[derive(Default)]
+pub struct Throttle {
+    sessions: Option<RateLimiter>,
+    global: Option<Mutex<TokenBucket>>,
+}
+
+impl Throttle {
+    /// Creates a new throttle without limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows each session a burst of `capacity` commands and then `refill_per_second`
+    /// commands per second
+    pub fn with_session_limit(mut self, capacity: u32, refill_per_second: u32) -> Self {
+        self.sessions = Some(RateLimiter::new(capacity, refill_per_second));
+        self
+    }
+
+    /// Allows all sessions together a burst of `capacity` commands other than cancels
+    /// and then `refill_per_second` such commands per second
+    pub fn with_global_limit(mut self, capacity: u32, refill_per_second: u32) -> Self {
+        self.global = Some(Mutex::new(TokenBucket::new(capacity, refill_per_second)));
+        self
+    }
+
+    /// Takes the tokens a command of a session needs if they are available at `now`.
+    /// A command refused by the global bucket still spends its session token.
+    pub fn try_admit_at(&self, session_id: u64, command: &Command, now: Instant) -> bool {
+        let session_admitted = self
+            .sessions
+            .as_ref()
+            .is_none_or(|sessions| sessions.try_acquire_at(session_id, now));
+        if !session_admitted {
+            return false;
+        }
+        match (&self.global, command) {
+            (None, _) | (_, Command::Cancel(_)) => true,
+            (Some(global), _) => global
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .try_acquire_at(now),
+        }
+    }
+
+    /// Takes the tokens a command of a session needs if they are available now
+    pub fn try_admit(&self, session_id: u64, command: &Command) -> bool {
+        self.try_admit_at(session_id, command, Instant::now())
+    }
+}
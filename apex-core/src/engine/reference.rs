@@ -0,0 +1,64 @@
+use crate::prelude::*;
+use crossbeam::atomic::AtomicCell;
+
+/// PriceSource selects which price a price-dependent check keys off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PriceSource {
+    /// The price of the book's most recent trade.
+    #[default]
+    LastTrade,
+    /// The mark price published by the reference price provider.
+    Mark,
+    /// The index price published by the reference price provider.
+    Index,
+    /// The book's best bid.
+    BestBid,
+    /// The book's best ask.
+    BestAsk,
+}
+
+/// ReferencePriceProvider supplies prices that come from outside the book,
+/// such as a mark price or an index built from other venues.
+pub trait ReferencePriceProvider: Send + Sync {
+    /// Gets the current mark price, if one is known
+    fn mark_price(&self) -> Option<Price>;
+    /// Gets the current index price, if one is known
+    fn index_price(&self) -> Option<Price> {
+        None
+    }
+}
+
+/// ManualReferencePrice is a reference price provider whose prices are pushed in,
+/// e.g. by a feed handler or a test.
+#[derive(Debug, Default)]
+pub struct ManualReferencePrice {
+    mark: AtomicCell<Option<Price>>,
+    index: AtomicCell<Option<Price>>,
+}
+
+impl ManualReferencePrice {
+    /// Creates a new provider without prices
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the mark price
+    pub fn set_mark(&self, price: Option<Price>) {
+        self.mark.store(price);
+    }
+
+    /// Sets the index price
+    pub fn set_index(&self, price: Option<Price>) {
+        self.index.store(price);
+    }
+}
+
+impl ReferencePriceProvider for ManualReferencePrice {
+    fn mark_price(&self) -> Option<Price> {
+        self.mark.load()
+    }
+
+    fn index_price(&self) -> Option<Price> {
+        self.index.load()
+    }
+}
@@ -0,0 +1,321 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// ReplayError is a failure to read a recorded event file.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The input could not be read.
+    Io(io::Error),
+    /// A line could not be turned into an event; `line` starts at 1.
+    Parse { line: usize, reason: String },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "failed to read replay input: {error}"),
+            ReplayError::Parse { line, reason } => write!(f, "line {line}: {reason}"),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::Io(error) => Some(error),
+            ReplayError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+/// ReplayEvent is a recorded command with the time it was sent.
+#[derive(Debug, Clone)]
+pub struct ReplayEvent {
+    /// Recorded time in microseconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub command: Command,
+}
+
+/// Loads events from CSV with a header row.
+///
+/// Columns are matched by name and may come in any order:
+/// `timestamp`, `action` (`new`, `cancel`, `amend`, `update`), `order_id`,
+/// and for the actions that need them `user_id`, `side` (`buy`, `sell`),
+/// `type` (`limit`, `market`), `price`, and `quantity`.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn load_csv(input: impl BufRead) -> Result<Vec<ReplayEvent>, ReplayError> {
+    let mut header: Option<Vec<String>> = None;
+    let mut events = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let cells = line.split(',').map(|cell| cell.trim().to_string());
+        let Some(columns) = &header else {
+            header = Some(cells.map(|cell| cell.to_ascii_lowercase()).collect());
+            continue;
+        };
+        let fields = columns.iter().cloned().zip(cells).collect();
+        events.push(event_from_fields(index + 1, &fields)?);
+    }
+    Ok(events)
+}
+
+/// Loads events from JSON lines, one flat object per line,
+/// using the same field names as `load_csv`.
+pub fn load_jsonl(input: impl BufRead) -> Result<Vec<ReplayEvent>, ReplayError> {
+    let mut events = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = parse_flat_object(&line).map_err(|reason| ReplayError::Parse {
+            line: index + 1,
+            reason,
+        })?;
+        events.push(event_from_fields(index + 1, &fields)?);
+    }
+    Ok(events)
+}
+
+fn event_from_fields(
+    line: usize,
+    fields: &HashMap<String, String>,
+) -> Result<ReplayEvent, ReplayError> {
+    let error = |reason: String| ReplayError::Parse { line, reason };
+    let text = |name: &str| -> Result<&str, ReplayError> {
+        fields
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| error(format!("missing field `{name}`")))
+    };
+    let number = |name: &str| -> Result<u64, ReplayError> {
+        let value = text(name)?;
+        value
+            .parse()
+            .map_err(|_| error(format!("field `{name}` is not a number: {value}")))
+    };
+    let timestamp = number("timestamp")?;
+    let order_id = number("order_id")?;
+    let command = match text("action")?.to_ascii_lowercase().as_str() {
+        "new" => {
+            let user_id = number("user_id")?;
+            let side = match text("side")?.to_ascii_lowercase().as_str() {
+                "buy" | "b" => Side::Buy,
+                "sell" | "s" => Side::Sell,
+                side => return Err(error(format!("unknown side: {side}"))),
+            };
+            let quantity = Quantity::from(number("quantity")?);
+            let order_type = fields.get("type").map(|value| value.to_ascii_lowercase());
+            match order_type.as_deref() {
+                Some("market") => {
+                    Command::Create(Order::market(order_id, user_id, side, quantity, timestamp))
+                }
+                None | Some("") | Some("limit") => {
+                    let price = Price::from(number("price")?);
+                    Command::Create(Order::limit(
+                        order_id, user_id, side, price, quantity, timestamp,
+                    ))
+                }
+                Some(order_type) => {
+                    return Err(error(format!("unknown order type: {order_type}")));
+                }
+            }
+        }
+        "cancel" => Command::Cancel(order_id),
+        "amend" => Command::Amend {
+            order_id,
+            new_quantity: Quantity::from(number("quantity")?),
+            now_microseconds: timestamp,
+        },
+        "update" => Command::Update {
+            order_id,
+            new_price: Price::from(number("price")?),
+            now_microseconds: timestamp,
+        },
+        action => return Err(error(format!("unknown action: {action}"))),
+    };
+    Ok(ReplayEvent { timestamp, command })
+}
+
+/// Parses a single-level JSON object whose values are strings, numbers, booleans, or null.
+/// Values are kept as their text; null becomes an empty string.
+fn parse_flat_object(line: &str) -> Result<HashMap<String, String>, String> {
+    let mut chars = line.trim().chars().peekable();
+    let mut fields = HashMap::new();
+    if chars.next() != Some('{') {
+        return Err("expected a JSON object".to_string());
+    }
+    loop {
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some('}') if fields.is_empty() => break,
+            Some('"') => {}
+            _ => return Err("expected a field name".to_string()),
+        }
+        let key = parse_string(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(format!("expected `:` after `{key}`"));
+        }
+        skip_whitespace(&mut chars);
+        let value = if chars.peek() == Some(&'"') {
+            chars.next();
+            parse_string(&mut chars)?
+        } else {
+            let mut value = String::new();
+            while let Some(c) = chars.next_if(|c| !matches!(c, ',' | '}') && !c.is_whitespace()) {
+                value.push(c);
+            }
+            match value.as_str() {
+                "" => return Err(format!("missing value for `{key}`")),
+                "null" => String::new(),
+                _ => value,
+            }
+        };
+        fields.insert(key.to_ascii_lowercase(), value);
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => break,
+            _ => return Err("expected `,` or `}`".to_string()),
+        }
+    }
+    skip_whitespace(&mut chars);
+    if chars.next().is_some() {
+        return Err("unexpected text after the object".to_string());
+    }
+    Ok(fields)
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(value),
+            Some('\\') => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(c @ ('"' | '\\' | '/')) => value.push(c),
+                _ => return Err("unsupported escape in string".to_string()),
+            },
+            Some(c) => value.push(c),
+            None => return Err("unterminated string".to_string()),
+        }
+    }
+}
+
+/// ReplaySpeed sets how fast recorded events are fed to the engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReplaySpeed {
+    /// Apply events back to back, for capacity planning.
+    #[default]
+    Unpaced,
+    /// Wait out the recorded gaps between events.
+    Recorded,
+    /// Wait out the recorded gaps divided by the factor. Factors that are not positive apply
+    /// events unpaced, and gaps too long for a `Duration` are waited out as `Duration::MAX`.
+    Accelerated(f64),
+}
+
+/// ReplayReport summarizes a replay run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Number of events applied.
+    pub events: usize,
+    /// Number of events the engine refused.
+    pub failed: usize,
+    /// Wall time the replay took.
+    pub elapsed: Duration,
+}
+
+/// ReplayDriver feeds recorded events to a matching engine
+/// and runs a match cycle after each one.
+pub struct ReplayDriver<'a> {
+    engine: &'a dyn MatchingEngine,
+    speed: ReplaySpeed,
+    clock: Option<Arc<ManualClock>>,
+}
+
+impl<'a> ReplayDriver<'a> {
+    /// Creates a new unpaced replay driver
+    pub fn new(engine: &'a dyn MatchingEngine) -> Self {
+        Self {
+            engine,
+            speed: ReplaySpeed::default(),
+            clock: None,
+        }
+    }
+
+    /// Sets the replay speed
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the engine's manual clock to each event's recorded time before applying it,
+    /// so trades carry recorded rather than wall-clock timestamps.
+    pub fn with_clock(mut self, clock: Arc<ManualClock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Replays the events in order
+    pub fn run(&self, events: &[ReplayEvent]) -> ReplayReport {
+        let started = Instant::now();
+        let first = events.first().map_or(0, |event| event.timestamp);
+        let mut report = ReplayReport::default();
+        for event in events {
+            self.pace(started, event.timestamp.saturating_sub(first));
+            if let Some(clock) = &self.clock {
+                clock.set(event.timestamp);
+            }
+            let failed = match event.command.clone().execute(self.engine) {
+                CommandResult::Created(result) => result.is_err(),
+                CommandResult::Updated(result) => result.is_err(),
+                CommandResult::Cancelled(result) => result.is_err(),
+            };
+            self.engine.match_orders();
+            report.events += 1;
+            report.failed += failed as usize;
+        }
+        report.elapsed = started.elapsed();
+        report
+    }
+
+    fn pace(&self, started: Instant, offset_micros: u64) {
+        let offset = match self.speed {
+            ReplaySpeed::Unpaced => return,
+            ReplaySpeed::Recorded => Duration::from_micros(offset_micros),
+            ReplaySpeed::Accelerated(factor) if factor > 0.0 => {
+                let seconds = offset_micros as f64 / 1_000_000.0 / factor;
+                Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX)
+            }
+            ReplaySpeed::Accelerated(_) => return,
+        };
+        let elapsed = started.elapsed();
+        if offset > elapsed {
+            thread::sleep(offset - elapsed);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::prelude::*;
+use std::ops::RangeInclusive;
+
+/// ReplicatedCommand is an operation on a matching engine that a replication log carries,
+/// so every replica changes its book the same way and at the same point in the log.
+///
+/// Client commands are only part of what changes a book: expiry, mass cancels, transfers,
+/// freezes, mode switches, config reloads and rescales change it too, and a replica that
+/// runs them on its own schedule diverges from the others.
+#[derive(Debug, Clone)]
+pub enum ReplicatedCommand {
+    /// A client command.
+    Command(Command),
+    /// Expire the orders past their deadline or resting time at the entry's timestamp.
+    ExpireOrders,
+    /// Demote the quotes past their maximum age at the entry's timestamp.
+    DemoteStaleQuotes,
+    /// Cancel the resting orders of a side, optionally within a price range.
+    CancelWhere {
+        side: Side,
+        price_range: Option<RangeInclusive<Price>>,
+    },
+    /// Reassign the resting limit orders of a user to another user.
+    TransferOrders {
+        from_user_id: u64,
+        to_user_id: u64,
+    },
+    FreezeUser(u64),
+    UnfreezeUser(u64),
+    SetMode(EngineMode),
+    PauseMatching,
+    ResumeMatching,
+    ReloadConfig(BookConfig),
+    Rescale(Rescale),
+    BustTrade(u64),
+    CorrectTradePrice {
+        trade_id: u64,
+        price: Price,
+    },
+}
+
+/// ReplicatedOutcome is the outcome of a replicated command on the engine that applied it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplicatedOutcome {
+    /// Outcome of a `ReplicatedCommand::Command`.
+    Command(CommandResult),
+    /// Ids of the orders expired, demoted, cancelled or transferred.
+    Orders(Vec<OrderID>),
+    /// Whether a freeze, unfreeze, pause or resume changed anything.
+    Toggled(bool),
+    /// The mode was switched.
+    ModeSet,
+    /// Epoch of the reloaded configuration.
+    ConfigReloaded(u64),
+    /// Outcome of a `ReplicatedCommand::Rescale`.
+    Rescaled(Result<usize, RescaleError>),
+    /// Outcome of a `ReplicatedCommand::BustTrade` or `ReplicatedCommand::CorrectTradePrice`.
+    Corrected(Result<TradeCorrection, TradeCorrectionError>),
+}
+
+impl ReplicatedCommand {
+    /// Executes the command on a matching engine as of `now_microseconds`, the timestamp of
+    /// its log entry, and returns its outcome
+    pub fn execute(
+        self,
+        engine: &DefaultMatchingEngine,
+        now_microseconds: u64,
+    ) -> ReplicatedOutcome {
+        match self {
+            ReplicatedCommand::Command(command) => {
+                ReplicatedOutcome::Command(command.execute(engine))
+            }
+            ReplicatedCommand::ExpireOrders => {
+                ReplicatedOutcome::Orders(engine.expire_orders(now_microseconds))
+            }
+            ReplicatedCommand::DemoteStaleQuotes => {
+                ReplicatedOutcome::Orders(engine.demote_stale_quotes(now_microseconds))
+            }
+            ReplicatedCommand::CancelWhere { side, price_range } => {
+                ReplicatedOutcome::Orders(engine.cancel_where(side, price_range))
+            }
+            ReplicatedCommand::TransferOrders {
+                from_user_id,
+                to_user_id,
+            } => ReplicatedOutcome::Orders(engine.transfer_orders(from_user_id, to_user_id)),
+            ReplicatedCommand::FreezeUser(user_id) => {
+                ReplicatedOutcome::Toggled(engine.freeze_user(user_id))
+            }
+            ReplicatedCommand::UnfreezeUser(user_id) => {
+                ReplicatedOutcome::Toggled(engine.unfreeze_user(user_id))
+            }
+            ReplicatedCommand::SetMode(mode) => {
+                engine.set_mode(mode);
+                ReplicatedOutcome::ModeSet
+            }
+            ReplicatedCommand::PauseMatching => ReplicatedOutcome::Toggled(engine.pause_matching()),
+            ReplicatedCommand::ResumeMatching => {
+                ReplicatedOutcome::Toggled(engine.resume_matching())
+            }
+            ReplicatedCommand::ReloadConfig(config) => {
+                ReplicatedOutcome::ConfigReloaded(engine.reload_config(config))
+            }
+            ReplicatedCommand::Rescale(rescale) => {
+                ReplicatedOutcome::Rescaled(engine.rescale(rescale))
+            }
+            ReplicatedCommand::BustTrade(trade_id) => {
+                ReplicatedOutcome::Corrected(engine.bust_trade(trade_id))
+            }
+            ReplicatedCommand::CorrectTradePrice { trade_id, price } => {
+                ReplicatedOutcome::Corrected(engine.correct_trade_price(trade_id, price))
+            }
+        }
+    }
+
+    /// Applies the command to a matching engine as of `now_microseconds`.
+    /// Outcomes are published through the order book syncer.
+    pub fn apply(self, engine: &DefaultMatchingEngine, now_microseconds: u64) {
+        let _ = self.execute(engine, now_microseconds);
+    }
+}
+
+impl From<Command> for ReplicatedCommand {
+    fn from(command: Command) -> Self {
+        ReplicatedCommand::Command(command)
+    }
+}
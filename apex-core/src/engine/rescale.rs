@@ -0,0 +1,66 @@
+use crate::prelude::*;
+use crypto_bigint::{CheckedMul, NonZero, U256, Zero};
+
+/// ScaleFactor multiplies a value by `numerator / denominator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScaleFactor {
+    pub numerator: NonZero<U256>,
+    pub denominator: NonZero<U256>,
+}
+
+impl ScaleFactor {
+    /// Leaves values unchanged.
+    pub const IDENTITY: ScaleFactor = ScaleFactor {
+        numerator: NonZero::<U256>::ONE,
+        denominator: NonZero::<U256>::ONE,
+    };
+
+    /// Creates a factor of `numerator / denominator`, None if either is zero
+    pub fn new(numerator: u64, denominator: u64) -> Option<Self> {
+        Some(Self {
+            numerator: Option::from(NonZero::new(U256::from(numerator)))?,
+            denominator: Option::from(NonZero::new(U256::from(denominator)))?,
+        })
+    }
+
+    /// Scales a value, None if the result is not a whole number or overflows
+    pub fn apply(&self, value: U256) -> Option<U256> {
+        let product: U256 = Option::from(value.checked_mul(&*self.numerator))?;
+        let (scaled, remainder) = product.div_rem(&self.denominator);
+        bool::from(remainder.is_zero()).then_some(scaled)
+    }
+
+    /// Scales a value, rounding down and saturating on overflow, for figures that are
+    /// reported rather than matched and need not scale exactly
+    pub(crate) fn apply_rounded(&self, value: U256) -> U256 {
+        value.saturating_mul(&*self.numerator) / self.denominator
+    }
+}
+
+/// Rescale is a redenomination of a book: every resting price and quantity is scaled at once,
+/// e.g. a token redenomination or a contract split.
+///
+/// Prices and quantities are scaled independently, so a 1:10 split
+/// scales prices by 1/10 and quantities by 10.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rescale {
+    pub price: ScaleFactor,
+    pub quantity: ScaleFactor,
+}
+
+impl Rescale {
+    /// Scales the price and quantities of an order, None if any of them cannot be scaled exactly.
+    /// Live orders must keep a nonzero quantity, and limit orders a nonzero price.
+    pub(crate) fn apply(&self, order: &Order) -> Option<(Price, Quantity, Quantity)> {
+        let price = self.price.apply(order.price)?;
+        let quantity = self.quantity.apply(order.quantity())?;
+        let filled_quantity = self.quantity.apply(order.filled_quantity())?;
+        if order.order_type == OrderType::Limit && bool::from(price.is_zero()) {
+            return None;
+        }
+        if bool::from(quantity.is_zero()) {
+            return None;
+        }
+        Some((price, quantity, filled_quantity))
+    }
+}
@@ -0,0 +1,245 @@
+use crate::prelude::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// ResendError is a failure to replay retained syncer events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResendError {
+    /// The requested event is older than the oldest one still retained;
+    /// the consumer has to rebuild its state from a snapshot instead.
+    Evicted { requested: u64, oldest: u64 },
+}
+
+impl fmt::Display for ResendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResendError::Evicted { requested, oldest } => {
+                write!(
+                    f,
+                    "event {requested} is no longer retained, oldest is {oldest}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ResendError {}
+
+/// SyncEvent is an owned copy of a single syncer call.
+#[derive(Debug, Clone)]
+pub enum SyncEvent {
+    Added(Order),
+    Updated(Order),
+    Cancelled(Order),
+    Rejected(Order),
+    Matched {
+        updated: Vec<Order>,
+        trades: Vec<Trade>,
+    },
+    TradeCorrected(TradeCorrection),
+    Rescaled(Rescale),
+    Batch(Vec<BookEvent>),
+}
+
+impl SyncEvent {
+    /// Delivers the event to a syncer as the original call did
+    pub fn deliver(&self, id: u64, syncer: &dyn OrderBookSyncer) {
+        match self {
+            SyncEvent::Added(order) => syncer.add_order(id, order),
+            SyncEvent::Updated(order) => syncer.update_order(id, order),
+            SyncEvent::Cancelled(order) => syncer.cancel_order(id, order),
+            SyncEvent::Rejected(order) => syncer.reject_order(id, order),
+            SyncEvent::Matched { updated, trades } => syncer.matched(id, updated, trades),
+            SyncEvent::TradeCorrected(correction) => syncer.trade_corrected(id, correction),
+            SyncEvent::Rescaled(rescale) => syncer.rescaled(id, rescale),
+            SyncEvent::Batch(events) => syncer.batch(id, events),
+        }
+    }
+}
+
+/// RetainedEvent is a syncer event together with its sequence number, the syncer id.
+#[derive(Debug, Clone)]
+pub struct RetainedEvent {
+    pub sequence: u64,
+    pub event: SyncEvent,
+}
+
+/// RetentionSyncer forwards every book change to the primary syncer
+/// and retains the latest `capacity` events so consumers can ask for a resend.
+///
+/// A consumer that detects a gap, for example with a `GapDetector`, calls `replay_from`
+/// with the first sequence it is missing and gets every retained event from there on.
+pub struct RetentionSyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    capacity: usize,
+    events: Mutex<BTreeMap<u64, SyncEvent>>,
+}
+
+impl RetentionSyncer {
+    /// Creates a new retention syncer in front of the primary syncer
+    /// that keeps up to `capacity` events
+    pub fn new(primary: Arc<dyn OrderBookSyncer>, capacity: usize) -> Self {
+        Self {
+            primary,
+            capacity: capacity.max(1),
+            events: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Gets the sequence of the oldest retained event
+    pub fn oldest(&self) -> Option<u64> {
+        self.lock().keys().next().copied()
+    }
+
+    /// Gets the sequence of the newest retained event
+    pub fn latest(&self) -> Option<u64> {
+        self.lock().keys().next_back().copied()
+    }
+
+    /// Gets every retained event from `sequence` on, oldest first.
+    /// A sequence past the newest event gives nothing.
+    pub fn replay_from(&self, sequence: u64) -> Result<Vec<RetainedEvent>, ResendError> {
+        let events = self.lock();
+        let oldest = events.keys().next().copied();
+        if let Some(oldest) = oldest.filter(|oldest| sequence < *oldest) {
+            return Err(ResendError::Evicted {
+                requested: sequence,
+                oldest,
+            });
+        }
+        Ok(events
+            .range(sequence..)
+            .map(|(sequence, event)| RetainedEvent {
+                sequence: *sequence,
+                event: event.clone(),
+            })
+            .collect())
+    }
+
+    /// Delivers every retained event from `sequence` on to a syncer, oldest first.
+    /// Returns the number of events delivered.
+    pub fn replay_into(
+        &self,
+        sequence: u64,
+        syncer: &dyn OrderBookSyncer,
+    ) -> Result<usize, ResendError> {
+        let events = self.replay_from(sequence)?;
+        for retained in &events {
+            retained.event.deliver(retained.sequence, syncer);
+        }
+        Ok(events.len())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<u64, SyncEvent>> {
+        self.events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn retain(&self, id: u64, event: SyncEvent) {
+        let mut events = self.lock();
+        events.insert(id, event);
+        while events.len() > self.capacity {
+            events.pop_first();
+        }
+    }
+}
+
+impl OrderBookSyncer for RetentionSyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+        self.retain(id, SyncEvent::Added(order.clone()));
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+        self.retain(id, SyncEvent::Updated(order.clone()));
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+        self.retain(id, SyncEvent::Cancelled(order.clone()));
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+        self.retain(id, SyncEvent::Rejected(order.clone()));
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        self.retain(
+            id,
+            SyncEvent::Matched {
+                updated: updated.to_vec(),
+                trades: trades.to_vec(),
+            },
+        );
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+        self.retain(id, SyncEvent::TradeCorrected(correction.clone()));
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+        self.retain(id, SyncEvent::Rescaled(*rescale));
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        self.retain(id, SyncEvent::Batch(events.to_vec()));
+    }
+}
+
+/// SequenceCheck is what a `GapDetector` concluded about a received sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceCheck {
+    /// The sequence was the next one expected.
+    InOrder,
+    /// The sequence was already received.
+    Duplicate,
+    /// The sequences from `from` up to but not including `to` were skipped.
+    Gap { from: u64, to: u64 },
+}
+
+/// GapDetector tracks the sequences a consumer has received
+/// and reports which ones it has to ask to be resent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GapDetector {
+    next: u64,
+}
+
+impl GapDetector {
+    /// Creates a new detector that expects `next` as the first sequence
+    pub fn new(next: u64) -> Self {
+        Self { next }
+    }
+
+    /// Gets the next sequence the detector expects
+    pub fn next(&self) -> u64 {
+        self.next
+    }
+
+    /// Records a received sequence.
+    /// After a gap the detector moves past the received sequence, so resend the gap
+    /// before handing the detector the next live event.
+    pub fn observe(&mut self, sequence: u64) -> SequenceCheck {
+        if sequence < self.next {
+            return SequenceCheck::Duplicate;
+        }
+        let check = if sequence == self.next {
+            SequenceCheck::InOrder
+        } else {
+            SequenceCheck::Gap {
+                from: self.next,
+                to: sequence,
+            }
+        };
+        self.next = sequence + 1;
+        check
+    }
+}
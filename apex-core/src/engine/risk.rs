@@ -0,0 +1,36 @@
+use crate::prelude::*;
+
+/// RiskDecision is the verdict of a pre-trade risk check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskDecision {
+    /// The order may proceed.
+    Allow,
+    /// The order must be rejected with the given reason.
+    Reject(RejectReason),
+}
+
+/// RiskChecker trait is used to plug pre-trade checks (margin, balance, compliance) into the engine
+pub trait RiskChecker: Send + Sync {
+    /// This function is called before a new order is inserted into the book
+    fn check(&self, order: &Order) -> RiskDecision;
+    /// This function is called once per match cycle for each taker, before it walks the book.
+    /// The fills of the walk are not checked one by one: a taker that passes may trade with
+    /// every maker it crosses, up to its whole open quantity, so checks must budget for that.
+    /// By default, it applies the same check as insertion.
+    fn check_match(&self, taker: &Order) -> RiskDecision {
+        self.check(taker)
+    }
+}
+
+/// EmptyRiskChecker is a no-op implementation of RiskChecker that allows every order
+pub struct EmptyRiskChecker {}
+
+impl RiskChecker for EmptyRiskChecker {
+    fn check(&self, _order: &Order) -> RiskDecision {
+        RiskDecision::Allow
+    }
+
+    fn check_match(&self, _taker: &Order) -> RiskDecision {
+        RiskDecision::Allow
+    }
+}
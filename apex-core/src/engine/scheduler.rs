@@ -0,0 +1,420 @@
+use crate::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle, Thread};
+use std::time::Duration;
+
+/// TaskID identifies a task of a scheduler.
+pub type TaskID = usize;
+
+/// Longest the scheduler thread sleeps before checking whether it was stopped.
+const MAX_IDLE: Duration = Duration::from_millis(10);
+
+type Task = Box<dyn FnMut(u64) -> bool + Send>;
+
+/// Cadence bounds the interval of an adaptive task.
+///
+/// The task runs every `min` while it finds work and backs off by doubling
+/// the interval up to `max` while it is idle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cadence {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Cadence {
+    /// Creates a new cadence between `min` and `max`
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+        }
+    }
+}
+
+/// Wakeup is shared between a scheduler and the wakers of its tasks.
+#[derive(Default)]
+struct Wakeup {
+    thread: Mutex<Option<Thread>>,
+}
+
+impl Wakeup {
+    fn unpark(&self) {
+        let thread = self
+            .thread
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(thread) = thread.as_ref() {
+            thread.unpark();
+        }
+    }
+}
+
+/// TaskWaker makes a task due immediately, for example when an order arrives.
+#[derive(Clone)]
+pub struct TaskWaker {
+    woken: Arc<AtomicBool>,
+    wakeup: Arc<Wakeup>,
+}
+
+impl TaskWaker {
+    /// Makes the task due at the next tick and wakes a spawned scheduler
+    pub fn wake(&self) {
+        self.woken.store(true, Ordering::Release);
+        self.wakeup.unpark();
+    }
+}
+
+struct ScheduledTask {
+    name: String,
+    interval: u64,
+    jitter: u64,
+    /// Bounds of the interval in microseconds, for adaptive tasks.
+    cadence: Option<(u64, u64)>,
+    woken: Arc<AtomicBool>,
+    /// Time the current period started; the task runs once per period.
+    period: u64,
+    next_run: u64,
+    runs: u64,
+    run: Option<Task>,
+}
+
+/// TaskInfo describes a scheduled task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+    pub id: TaskID,
+    pub name: String,
+    /// Time of the next run in microseconds since the UNIX epoch.
+    pub next_run: u64,
+    /// Number of times the task has run.
+    pub runs: u64,
+    /// Current interval in microseconds.
+    pub interval: u64,
+}
+
+/// Scheduler runs the periodic tasks of an engine runtime, such as the expiry sweep,
+/// metrics flushes, and snapshots, from a single thread.
+///
+/// Time is read from a `Clock`. With a `ManualClock` nothing runs on its own: the test moves
+/// the clock and calls `tick`, which runs every task that is due. `spawn` drives the same
+/// `tick` from a background thread.
+///
+/// A task runs at most once per interval; if the scheduler falls behind, missed runs are
+/// skipped rather than run back to back. Jitter delays each run by a random amount below it,
+/// drawn from a seeded generator, so tasks of many engines do not fire in lockstep while the
+/// schedule stays reproducible. Jitter never accumulates into drift.
+///
+/// Adaptive tasks follow a `Cadence` instead: their interval shrinks to the minimum
+/// whenever they find work or are woken through their `TaskWaker`, and doubles up to the
+/// maximum while they stay idle. `schedule_matching` runs the matching sweep this way,
+/// with a `WakeSyncer` waking it as soon as an order is inserted.
+pub struct Scheduler {
+    clock: Arc<dyn Clock>,
+    rng: SimRng,
+    tasks: Vec<ScheduledTask>,
+    wakeup: Arc<Wakeup>,
+}
+
+impl Scheduler {
+    /// Creates a new scheduler reading time from a clock
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            rng: SimRng::new(0),
+            tasks: Vec::new(),
+            wakeup: Arc::new(Wakeup::default()),
+        }
+    }
+
+    /// Sets the seed of the generator the jitter is drawn from
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = SimRng::new(seed);
+        self
+    }
+
+    /// Schedules a task to run every `interval`, first one interval from now.
+    /// The task gets the time it runs at, in microseconds since the UNIX epoch.
+    pub fn schedule(
+        &mut self,
+        name: &str,
+        interval: Duration,
+        task: impl FnMut(u64) + Send + 'static,
+    ) -> TaskID {
+        self.schedule_with_jitter(name, interval, Duration::ZERO, task)
+    }
+
+    /// Schedules a task to run every `interval`, each run delayed by up to `jitter`
+    pub fn schedule_with_jitter(
+        &mut self,
+        name: &str,
+        interval: Duration,
+        jitter: Duration,
+        mut task: impl FnMut(u64) + Send + 'static,
+    ) -> TaskID {
+        let interval = (interval.as_micros() as u64).max(1);
+        let jitter = (jitter.as_micros() as u64).min(interval - 1);
+        let period = self.clock.now_micros() + interval;
+        let next_run = period + self.rng.below(jitter + 1);
+        self.push(ScheduledTask {
+            name: name.to_string(),
+            interval,
+            jitter,
+            cadence: None,
+            woken: Arc::new(AtomicBool::new(false)),
+            period,
+            next_run,
+            runs: 0,
+            run: Some(Box::new(move |now| {
+                task(now);
+                true
+            })),
+        })
+    }
+
+    /// Schedules a task whose interval adapts to its activity within a cadence.
+    /// The task returns whether it found work; it first runs one minimum interval from now.
+    pub fn schedule_adaptive(
+        &mut self,
+        name: &str,
+        cadence: Cadence,
+        task: impl FnMut(u64) -> bool + Send + 'static,
+    ) -> TaskID {
+        let waker = self.new_waker();
+        self.schedule_adaptive_with_waker(name, cadence, &waker, task)
+    }
+
+    /// Schedules an adaptive task woken through a waker created beforehand with `new_waker`
+    pub fn schedule_adaptive_with_waker(
+        &mut self,
+        name: &str,
+        cadence: Cadence,
+        waker: &TaskWaker,
+        task: impl FnMut(u64) -> bool + Send + 'static,
+    ) -> TaskID {
+        let min = (cadence.min.as_micros() as u64).max(1);
+        let max = (cadence.max.as_micros() as u64).max(min);
+        let next_run = self.clock.now_micros() + min;
+        self.push(ScheduledTask {
+            name: name.to_string(),
+            interval: min,
+            jitter: 0,
+            cadence: Some((min, max)),
+            woken: waker.woken.clone(),
+            period: next_run,
+            next_run,
+            runs: 0,
+            run: Some(Box::new(task)),
+        })
+    }
+
+    /// Schedules the matching sweep of an engine within a cadence.
+    /// The book's syncer is expected to be wrapped in a `WakeSyncer` with the same waker,
+    /// so the sweep runs as soon as an order is inserted and backs off while none are.
+    /// A cycle that stops at its matching budget keeps the sweep at the minimum interval.
+    pub fn schedule_matching(
+        &mut self,
+        engine: Arc<dyn MatchingEngine + Send + Sync>,
+        cadence: Cadence,
+        waker: &TaskWaker,
+    ) -> TaskID {
+        self.schedule_adaptive_with_waker("matching", cadence, waker, move |_| {
+            engine.match_orders();
+            engine.match_budget_exhausted()
+        })
+    }
+
+    /// Creates a waker for a task that is yet to be scheduled,
+    /// for example one whose syncer has to exist before its engine
+    pub fn new_waker(&self) -> TaskWaker {
+        TaskWaker {
+            woken: Arc::new(AtomicBool::new(false)),
+            wakeup: self.wakeup.clone(),
+        }
+    }
+
+    /// Schedules the expiry sweep of an engine, which also demotes stale quotes
+    pub fn schedule_expiry(
+        &mut self,
+        engine: Arc<dyn MatchingEngine + Send + Sync>,
+        interval: Duration,
+    ) -> TaskID {
+        self.schedule("expiry", interval, move |now| {
+            engine.expire_orders(now);
+            engine.demote_stale_quotes(now);
+        })
+    }
+
+    /// Gets a waker that makes a task due immediately
+    pub fn waker(&self, id: TaskID) -> Option<TaskWaker> {
+        self.tasks
+            .get(id)
+            .filter(|task| task.run.is_some())
+            .map(|task| TaskWaker {
+                woken: task.woken.clone(),
+                wakeup: self.wakeup.clone(),
+            })
+    }
+
+    /// Cancels a task; returns false if it was already canceled
+    pub fn cancel(&mut self, id: TaskID) -> bool {
+        self.tasks
+            .get_mut(id)
+            .is_some_and(|task| task.run.take().is_some())
+    }
+
+    /// Gets the scheduled tasks
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.run.is_some())
+            .map(|(id, task)| TaskInfo {
+                id,
+                name: task.name.clone(),
+                next_run: task.next_run,
+                runs: task.runs,
+                interval: task.interval,
+            })
+            .collect()
+    }
+
+    /// Gets the time the next task is due, in microseconds since the UNIX epoch
+    pub fn next_due(&self) -> Option<u64> {
+        self.tasks
+            .iter()
+            .filter(|task| task.run.is_some())
+            .map(|task| {
+                if task.woken.load(Ordering::Acquire) {
+                    0
+                } else {
+                    task.next_run
+                }
+            })
+            .min()
+    }
+
+    /// Runs every task that is due, earliest first, and returns how many ran
+    pub fn tick(&mut self) -> usize {
+        let now = self.clock.now_micros();
+        let mut due: Vec<_> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.run.is_some())
+            .filter(|(_, task)| task.next_run <= now || task.woken.load(Ordering::Acquire))
+            .map(|(id, task)| (task.next_run.min(now), id))
+            .collect();
+        due.sort_unstable();
+        for (_, id) in &due {
+            let task = &mut self.tasks[*id];
+            let woken = task.woken.swap(false, Ordering::AcqRel);
+            let busy = task.run.as_mut().is_some_and(|run| run(now));
+            task.runs += 1;
+            match task.cadence {
+                Some((min, max)) => {
+                    task.interval = if woken || busy {
+                        min
+                    } else {
+                        task.interval.saturating_mul(2).min(max)
+                    };
+                    task.period = now + task.interval;
+                    task.next_run = task.period;
+                }
+                None => {
+                    // Skip the periods that were missed while the scheduler was behind
+                    if task.period <= now {
+                        let missed = (now - task.period) / task.interval;
+                        task.period += (missed + 1) * task.interval;
+                    }
+                    task.next_run = task.period + self.rng.below(task.jitter + 1);
+                }
+            }
+        }
+        due.len()
+    }
+
+    /// Runs the scheduler on a background thread until `stop` is set,
+    /// then hands the scheduler back through the join handle
+    pub fn spawn(mut self, stop: Arc<AtomicBool>) -> JoinHandle<Self> {
+        thread::spawn(move || {
+            *self
+                .wakeup
+                .thread
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(thread::current());
+            while !stop.load(Ordering::Acquire) {
+                self.tick();
+                let idle = self
+                    .next_due()
+                    .map_or(MAX_IDLE, |due| {
+                        let now = self.clock.now_micros();
+                        Duration::from_micros(due.saturating_sub(now))
+                    })
+                    .min(MAX_IDLE);
+                thread::park_timeout(idle);
+            }
+            self
+        })
+    }
+
+    fn push(&mut self, task: ScheduledTask) -> TaskID {
+        self.tasks.push(task);
+        self.tasks.len() - 1
+    }
+}
+
+/// WakeSyncer forwards every book change to the primary syncer
+/// and wakes a task, typically the matching sweep, whenever an order is added or updated.
+pub struct WakeSyncer {
+    primary: Arc<dyn OrderBookSyncer>,
+    waker: TaskWaker,
+}
+
+impl WakeSyncer {
+    /// Creates a new wake syncer in front of the primary syncer
+    pub fn new(primary: Arc<dyn OrderBookSyncer>, waker: TaskWaker) -> Self {
+        Self { primary, waker }
+    }
+}
+
+impl OrderBookSyncer for WakeSyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+        self.waker.wake();
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+        self.waker.wake();
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+        if events
+            .iter()
+            .any(|event| matches!(event, BookEvent::Added(_) | BookEvent::Updated(_)))
+        {
+            self.waker.wake();
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::ControlFlow;
+
+/// ShadowBook is a copy-on-write view of an order book for "what if" questions.
+///
+/// It shares every resting order with the base book and keeps only its own hypothetical
+/// inserts and cancels, so creating one costs nothing however deep the book is, and nothing
+/// it absorbs reaches the base book or its syncer. Only limit orders rest in a shadow, and
+/// nothing matches in it. The base book is read live, so changes made to it since the shadow
+/// was created show through, except for orders the shadow canceled.
+pub struct ShadowBook<'a> {
+    base: &'a dyn OrderBook,
+    bids: BTreeMap<BookKey, Order>,
+    asks: BTreeMap<BookKey, Order>,
+    /// Keys of the orders inserted into the shadow, by order id.
+    inserted: HashMap<OrderID, BookKey>,
+    /// Base orders canceled in the shadow.
+    canceled: HashSet<OrderID>,
+}
+
+impl<'a> ShadowBook<'a> {
+    /// Creates a new shadow of the base book with no changes of its own
+    pub fn new(base: &'a dyn OrderBook) -> Self {
+        Self {
+            base,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            inserted: HashMap::new(),
+            canceled: HashSet::new(),
+        }
+    }
+
+    /// Inserts a hypothetical limit order into the shadow
+    pub fn insert(&mut self, order: &Order) -> Result<(), RejectReason> {
+        if order.order_type != OrderType::Limit {
+            return Err(RejectReason::OrderTypeNotAllowed);
+        }
+        if self.get_order(order.id).is_some() {
+            return Err(RejectReason::DuplicateOrderId);
+        }
+        let book_key = order.book_key();
+        if self.book(order.side).contains_key(&book_key) {
+            return Err(RejectReason::DuplicateOrderId);
+        }
+        let order = order.clone();
+        order.update_status(OrderStatus::Placed);
+        self.inserted.insert(order.id, book_key);
+        self.book_mut(book_key.side).insert(book_key, order);
+        Ok(())
+    }
+
+    /// Cancels an order in the shadow, whether it rests in the base book or was inserted
+    /// into the shadow, returning whether it was resting
+    pub fn cancel(&mut self, order_id: OrderID) -> bool {
+        if let Some(book_key) = self.inserted.remove(&order_id) {
+            self.book_mut(book_key.side).remove(&book_key);
+            return true;
+        }
+        if self.canceled.contains(&order_id) || self.base.get_order(order_id).is_none() {
+            return false;
+        }
+        self.canceled.insert(order_id)
+    }
+
+    /// Drops every hypothetical change, leaving a plain view of the base book
+    pub fn reset(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.inserted.clear();
+        self.canceled.clear();
+    }
+
+    /// Checks whether the shadow holds no changes of its own
+    pub fn is_unchanged(&self) -> bool {
+        self.inserted.is_empty() && self.canceled.is_empty()
+    }
+
+    /// Gets a resting limit order by id, as the shadow sees it
+    pub fn get_order(&self, order_id: OrderID) -> Option<OrderView> {
+        if let Some(book_key) = self.inserted.get(&order_id) {
+            return self.book(book_key.side).get(book_key).map(OrderView::from);
+        }
+        if self.canceled.contains(&order_id) {
+            return None;
+        }
+        self.base.get_order(order_id)
+    }
+
+    /// Visits the resting orders of a side in matching priority, stopping early when `visit`
+    /// breaks. Orders inserted into the shadow take their place among the base orders.
+    pub fn for_each_order(&self, side: Side, visit: &mut dyn FnMut(&Order) -> ControlFlow<()>) {
+        let mut inserted = self.book(side).iter().peekable();
+        let mut stopped = false;
+        self.base.for_each_order(side, &mut |order| {
+            if self.canceled.contains(&order.id) {
+                return ControlFlow::Continue(());
+            }
+            let book_key = order.book_key();
+            while let Some((_, ahead)) = inserted.next_if(|(key, _)| **key < book_key) {
+                if visit(ahead).is_break() {
+                    stopped = true;
+                    return ControlFlow::Break(());
+                }
+            }
+            let flow = visit(order);
+            stopped = flow.is_break();
+            flow
+        });
+        if stopped {
+            return;
+        }
+        for (_, order) in inserted {
+            if visit(order).is_break() {
+                return;
+            }
+        }
+    }
+
+    /// Gets up to `max_levels` aggregated price levels of a side, best price first
+    pub fn depth(&self, side: Side, max_levels: usize) -> Vec<PriceLevel> {
+        let mut levels: Vec<PriceLevel> = Vec::new();
+        if max_levels == 0 {
+            return levels;
+        }
+        self.for_each_order(side, &mut |order| {
+            let quantity = order.quantity();
+            if let Some(level) = levels.last_mut().filter(|level| level.price == order.price) {
+                level.quantity = level.quantity.saturating_add(&quantity);
+                level.orders += 1;
+                return ControlFlow::Continue(());
+            }
+            if levels.len() == max_levels {
+                return ControlFlow::Break(());
+            }
+            levels.push(PriceLevel {
+                price: order.price,
+                quantity,
+                orders: 1,
+            });
+            ControlFlow::Continue(())
+        });
+        levels
+    }
+
+    /// Gets the best price of a side, as the shadow sees it
+    pub fn get_best_price(&self, side: Side) -> Option<Price> {
+        self.depth(side, 1).first().map(|level| level.price)
+    }
+
+    /// Gets the total quantity resting at a price, as the shadow sees it
+    pub fn quantity_at(&self, side: Side, price: Price) -> Quantity {
+        let mut quantity = Quantity::ZERO;
+        self.for_each_order(side, &mut |order| {
+            let past = match side {
+                Side::Buy => order.price < price,
+                Side::Sell => order.price > price,
+            };
+            if past {
+                return ControlFlow::Break(());
+            }
+            if order.price == price {
+                quantity = quantity.saturating_add(&order.quantity());
+            }
+            ControlFlow::Continue(())
+        });
+        quantity
+    }
+
+    fn book(&self, side: Side) -> &BTreeMap<BookKey, Order> {
+        match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        }
+    }
+
+    fn book_mut(&mut self, side: Side) -> &mut BTreeMap<BookKey, Order> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+}
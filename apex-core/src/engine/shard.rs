@@ -0,0 +1,284 @@
+use crate::prelude::*;
+use crossbeam::channel::{Receiver, Sender, TryRecvError, TrySendError, bounded};
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering, fence};
+use std::thread::JoinHandle;
+
+/// SymbolID identifies a market, which is served by exactly one order book.
+pub type SymbolID = u32;
+
+/// ShardConfig configures how a sharded engine partitions its books.
+#[derive(Debug, Clone)]
+pub struct ShardConfig {
+    /// Number of matching threads.
+    pub shards: usize,
+    /// Capacity of each shard's command channel.
+    pub queue_capacity: NonZeroUsize,
+    /// Optional core pinning for the shard threads.
+    pub affinity: Option<AffinityConfig>,
+}
+
+impl Default for ShardConfig {
+    fn default() -> Self {
+        Self {
+            shards: 1,
+            queue_capacity: NonZeroUsize::new(4096).unwrap(),
+            affinity: None,
+        }
+    }
+}
+
+/// Delisting is what is left of a delisted symbol's book.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Delisting {
+    /// The book as it was when the delisting was applied, before its orders were canceled.
+    pub snapshot: BookSnapshot,
+    /// Ids of the resting orders the delisting canceled.
+    pub cancelled: Vec<OrderID>,
+}
+
+/// ShardMessage is delivered to a shard thread through its command channel.
+// Commands carry orders, far larger than the other variants, and boxing them costs an allocation per command
+#[allow(clippy::large_enum_variant)]
+enum ShardMessage {
+    Command(SymbolID, Command),
+    List(SymbolID, Box<DefaultMatchingEngine>),
+    Delist(SymbolID, Sender<Delisting>),
+    Barrier(Sender<()>),
+    Shutdown,
+}
+
+/// ShardedEngine partitions symbols across N matching threads.
+///
+/// Each shard thread exclusively owns the books of its symbols, so books are never
+/// touched by more than one matching thread. Commands are routed by symbol to the
+/// owning shard through bounded lock-free channels.
+/// Symbols can be listed and delisted at runtime; listing and delisting travel
+/// through the same channels, so they are ordered with the symbol's commands.
+/// Submitting takes no lock: the listed symbols are a concurrent set, and a delisting
+/// waits for submits already past the listing check, so no accepted command is dropped.
+pub struct ShardedEngine {
+    senders: Vec<Sender<ShardMessage>>,
+    handles: Vec<JoinHandle<()>>,
+    symbols: flurry::HashSet<SymbolID>,
+    /// Submits of each shard between their listing check and their send.
+    in_flight: Vec<AtomicUsize>,
+    /// Serializes listing and delisting.
+    admin: Mutex<()>,
+}
+
+impl ShardedEngine {
+    /// Creates a new sharded engine and starts one matching thread per shard.
+    pub fn new(config: ShardConfig, books: Vec<(SymbolID, DefaultMatchingEngine)>) -> Self {
+        let shards = config.shards.max(1);
+        let symbols = flurry::HashSet::new();
+        for (symbol, _) in &books {
+            symbols.pin().insert(*symbol);
+        }
+
+        let mut partitions: Vec<HashMap<SymbolID, DefaultMatchingEngine>> =
+            (0..shards).map(|_| HashMap::new()).collect();
+        for (symbol, engine) in books {
+            partitions[Self::route(symbol, shards)].insert(symbol, engine);
+        }
+
+        let mut senders = Vec::with_capacity(shards);
+        let mut handles = Vec::with_capacity(shards);
+        for (index, engines) in partitions.into_iter().enumerate() {
+            let (sender, receiver) = bounded(config.queue_capacity.get());
+            let affinity = config.affinity.clone();
+            let handle = std::thread::Builder::new()
+                .name(format!("apex-shard-{index}"))
+                .spawn(move || {
+                    if let Some(affinity) = affinity {
+                        affinity.pin_matching_thread(index);
+                    }
+                    Self::run_shard(engines, receiver)
+                })
+                .expect("failed to spawn shard thread");
+            senders.push(sender);
+            handles.push(handle);
+        }
+
+        Self {
+            senders,
+            handles,
+            symbols,
+            in_flight: (0..shards).map(|_| AtomicUsize::new(0)).collect(),
+            admin: Mutex::new(()),
+        }
+    }
+
+    /// Gets the shard index that owns a symbol
+    #[inline(always)]
+    fn route(symbol: SymbolID, shards: usize) -> usize {
+        symbol as usize % shards
+    }
+
+    /// Gets the number of shards
+    pub fn shard_count(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Gets the shard index that owns a symbol
+    pub fn shard_of(&self, symbol: SymbolID) -> usize {
+        Self::route(symbol, self.senders.len())
+    }
+
+    /// Checks whether a symbol is listed
+    pub fn is_listed(&self, symbol: SymbolID) -> bool {
+        self.symbols.pin().contains(&symbol)
+    }
+
+    /// Lists a new symbol served by `engine` on its owning shard
+    pub fn list(&self, symbol: SymbolID, engine: DefaultMatchingEngine) -> Result<(), SubmitError> {
+        let _admin = self.admin.lock().unwrap_or_else(|e| e.into_inner());
+        if self.is_listed(symbol) {
+            return Err(SubmitError::SymbolListed);
+        }
+        // The book reaches the shard before any command can be routed to it
+        self.senders[self.shard_of(symbol)]
+            .send(ShardMessage::List(symbol, Box::new(engine)))
+            .map_err(|_| SubmitError::Disconnected)?;
+        self.symbols.pin().insert(symbol);
+        Ok(())
+    }
+
+    /// Delists a symbol: new commands for it are refused at once,
+    /// commands already queued are applied and matched,
+    /// and then its resting orders are canceled and its book is dropped.
+    /// Blocks until the owning shard has done so and returns the book's final state.
+    pub fn delist(&self, symbol: SymbolID) -> Result<Delisting, SubmitError> {
+        let _admin = self.admin.lock().unwrap_or_else(|e| e.into_inner());
+        if !self.symbols.pin().remove(&symbol) {
+            return Err(SubmitError::UnknownSymbol);
+        }
+        // Submits that saw the symbol listed send their command ahead of the delisting
+        let shard = self.shard_of(symbol);
+        fence(Ordering::SeqCst);
+        while self.in_flight[shard].load(Ordering::SeqCst) > 0 {
+            std::thread::yield_now();
+        }
+        let (done, wait) = bounded(1);
+        self.senders[shard]
+            .send(ShardMessage::Delist(symbol, done))
+            .map_err(|_| SubmitError::Disconnected)?;
+        wait.recv().map_err(|_| SubmitError::Disconnected)
+    }
+
+    /// Submits a command for a symbol to its owning shard without taking a lock
+    pub fn submit(&self, symbol: SymbolID, command: Command) -> Result<(), SubmitError> {
+        let shard = self.shard_of(symbol);
+        self.in_flight[shard].fetch_add(1, Ordering::SeqCst);
+        fence(Ordering::SeqCst);
+        let result = if !self.is_listed(symbol) {
+            Err(SubmitError::UnknownSymbol)
+        } else {
+            match self.senders[shard].try_send(ShardMessage::Command(symbol, command)) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => Err(SubmitError::QueueFull),
+                Err(TrySendError::Disconnected(_)) => Err(SubmitError::Disconnected),
+            }
+        };
+        self.in_flight[shard].fetch_sub(1, Ordering::SeqCst);
+        result
+    }
+
+    /// Gets the number of commands waiting in each shard's channel
+    pub fn shard_depths(&self) -> Vec<usize> {
+        self.senders.iter().map(|sender| sender.len()).collect()
+    }
+
+    /// Blocks until every shard has applied all commands submitted before this call.
+    pub fn flush(&self) {
+        let mut waits = Vec::with_capacity(self.senders.len());
+        for sender in &self.senders {
+            let (done, wait) = bounded(1);
+            if sender.send(ShardMessage::Barrier(done)).is_ok() {
+                waits.push(wait);
+            }
+        }
+        for wait in waits {
+            let _ = wait.recv();
+        }
+    }
+
+    /// Takes the final snapshot of a delisted book and cancels its resting orders
+    fn close_book(engine: &DefaultMatchingEngine) -> Delisting {
+        let snapshot = engine.snapshot();
+        let mut cancelled = engine.cancel_where(Side::Buy, None);
+        cancelled.extend(engine.cancel_where(Side::Sell, None));
+        Delisting {
+            snapshot,
+            cancelled,
+        }
+    }
+
+    fn run_shard(
+        mut engines: HashMap<SymbolID, DefaultMatchingEngine>,
+        receiver: Receiver<ShardMessage>,
+    ) {
+        let mut dirty = HashSet::new();
+        let mut barriers = Vec::new();
+        while let Ok(message) = receiver.recv() {
+            let mut next = Some(message);
+            let mut shutdown = false;
+            // Drain everything that is already queued before matching once per touched book.
+            while let Some(message) = next.take() {
+                match message {
+                    ShardMessage::Command(symbol, command) => {
+                        if let Some(engine) = engines.get(&symbol) {
+                            command.apply(engine);
+                            dirty.insert(symbol);
+                        }
+                    }
+                    ShardMessage::List(symbol, engine) => {
+                        engines.insert(symbol, *engine);
+                    }
+                    ShardMessage::Delist(symbol, done) => {
+                        if let Some(engine) = engines.remove(&symbol) {
+                            if dirty.remove(&symbol) {
+                                engine.match_orders();
+                            }
+                            let _ = done.send(Self::close_book(&engine));
+                        }
+                    }
+                    ShardMessage::Barrier(done) => barriers.push(done),
+                    ShardMessage::Shutdown => shutdown = true,
+                }
+                if shutdown {
+                    break;
+                }
+                next = match receiver.try_recv() {
+                    Ok(message) => Some(message),
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+                };
+            }
+
+            for symbol in dirty.drain() {
+                if let Some(engine) = engines.get(&symbol) {
+                    engine.match_orders();
+                }
+            }
+            for done in barriers.drain(..) {
+                let _ = done.send(());
+            }
+            if shutdown {
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for ShardedEngine {
+    fn drop(&mut self) {
+        for sender in &self.senders {
+            let _ = sender.send(ShardMessage::Shutdown);
+        }
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
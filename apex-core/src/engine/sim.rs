@@ -0,0 +1,245 @@
+use crate::prelude::*;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+
+/// SimRng is a small seeded pseudo-random generator (SplitMix64),
+/// so a scenario produces the same events on every platform.
+#[derive(Debug, Clone)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    /// Creates a new generator from a seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Gets the next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Gets a random number in `0..bound`, or 0 if `bound` is 0
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        self.next_u64() % bound
+    }
+
+    /// Returns true with a probability of `percent` in 100
+    pub fn chance(&mut self, percent: u8) -> bool {
+        self.below(100) < percent as u64
+    }
+}
+
+/// Scenario describes a seeded stream of order flow for a simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scenario {
+    pub seed: u64,
+    /// Number of order arrivals or cancels.
+    pub steps: usize,
+    /// Orders are spread over user ids `1..=users`.
+    pub users: u64,
+    /// Limit prices are drawn from `mid_price ± price_spread`.
+    pub mid_price: u64,
+    pub price_spread: u64,
+    /// Quantities are drawn from `1..=max_quantity`.
+    pub max_quantity: u64,
+    /// Chance in 100 that a step cancels an earlier order instead of sending a new one.
+    pub cancel_percent: u8,
+    /// Chance in 100 that a new order is a market order.
+    pub market_percent: u8,
+    /// Time advanced before each step, in microseconds.
+    pub tick_micros: u64,
+    /// Time the simulation starts at, in microseconds since the UNIX epoch.
+    pub start_micros: u64,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            steps: 1000,
+            users: 10,
+            mid_price: 1000,
+            price_spread: 10,
+            max_quantity: 100,
+            cancel_percent: 20,
+            market_percent: 10,
+            tick_micros: 1000,
+            start_micros: DEFAULT_EPOCH_MILLIS * 1000,
+        }
+    }
+}
+
+/// SimEvent is a single input of a simulation.
+// Orders are far larger than the other variants, and boxing them costs an allocation per step
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum SimEvent {
+    /// Submit a new order.
+    Create(Order),
+    /// Cancel an order by id.
+    Cancel(OrderID),
+    /// Move the simulation clock forward by microseconds.
+    Advance(u64),
+    /// Run a match cycle.
+    Match,
+}
+
+impl Scenario {
+    /// Generates the events of the scenario.
+    /// Each step advances the clock, sends a create or cancel, and runs a match cycle.
+    pub fn events(&self) -> Vec<SimEvent> {
+        let mut rng = SimRng::new(self.seed);
+        let mut now = self.start_micros;
+        let mut events = Vec::with_capacity(self.steps * 3);
+        let mut next_id: OrderID = 1;
+        for _ in 0..self.steps {
+            now += self.tick_micros;
+            events.push(SimEvent::Advance(self.tick_micros));
+            if next_id > 1 && rng.chance(self.cancel_percent) {
+                events.push(SimEvent::Cancel(1 + rng.below(next_id - 1)));
+            } else {
+                events.push(SimEvent::Create(self.random_order(&mut rng, next_id, now)));
+                next_id += 1;
+            }
+            events.push(SimEvent::Match);
+        }
+        events
+    }
+
+    fn random_order(&self, rng: &mut SimRng, id: OrderID, now: u64) -> Order {
+        let user_id = 1 + rng.below(self.users.max(1));
+        let side = if rng.chance(50) {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        let quantity = Quantity::from(1 + rng.below(self.max_quantity.max(1)));
+        if rng.chance(self.market_percent) {
+            return Order::market(id, user_id, side, quantity, now);
+        }
+        let offset = rng.below(2 * self.price_spread + 1);
+        let price = (self.mid_price + offset)
+            .saturating_sub(self.price_spread)
+            .max(1);
+        Order::limit(id, user_id, side, Price::from(price), quantity, now)
+    }
+}
+
+/// SimReport is the outcome of a simulation run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimReport {
+    /// Every trade in execution order.
+    pub trades: Vec<Trade>,
+    /// The book after every match cycle.
+    pub books: Vec<BookSnapshot>,
+    /// Number of orders the engine rejected.
+    pub rejected: usize,
+}
+
+#[derive(Default)]
+struct TradeRecorder {
+    trades: Mutex<Vec<Trade>>,
+}
+
+impl OrderBookSyncer for TradeRecorder {
+    fn add_order(&self, _id: u64, _order: &Order) {}
+
+    fn update_order(&self, _id: u64, _order: &Order) {}
+
+    fn cancel_order(&self, _id: u64, _order: &Order) {}
+
+    fn matched(&self, _id: u64, _updated: &[Order], trades: &[Trade]) {
+        let mut recorded = self.trades.lock().unwrap_or_else(|e| e.into_inner());
+        recorded.extend_from_slice(trades);
+    }
+}
+
+/// Simulation drives a matching engine with a manual clock,
+/// so the same events always produce the same trades and book states.
+pub struct Simulation {
+    clock: Arc<ManualClock>,
+    engine: DefaultMatchingEngine,
+    recorder: Arc<TradeRecorder>,
+}
+
+impl Simulation {
+    /// Creates a new simulation with its clock stopped at `start_micros`
+    pub fn new(start_micros: u64) -> Self {
+        let clock = Arc::new(ManualClock::new(start_micros));
+        let recorder = Arc::new(TradeRecorder::default());
+        let book = Arc::new(DefaultOrderBook::new(
+            Arc::new(AtomicU64::new(1)),
+            recorder.clone(),
+        ));
+        let ids = IdGenerator::new(0).with_clock(clock.clone());
+        let engine = DefaultMatchingEngine::new(book)
+            .with_clock(clock.clone())
+            .with_id_generator(Arc::new(ids));
+        Self {
+            clock,
+            engine,
+            recorder,
+        }
+    }
+
+    /// Gets the simulation clock
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    /// Gets the simulated engine
+    pub fn engine(&self) -> &DefaultMatchingEngine {
+        &self.engine
+    }
+
+    /// Applies an event and returns whether the engine accepted it.
+    /// Clock advances and match cycles are always accepted.
+    pub fn apply(&self, event: &SimEvent) -> bool {
+        match event {
+            SimEvent::Create(order) => self.engine.create_order(&mut order.clone()).is_ok(),
+            SimEvent::Cancel(order_id) => self.engine.cancel_order(*order_id).is_ok(),
+            SimEvent::Advance(micros) => {
+                self.clock.advance(*micros);
+                true
+            }
+            SimEvent::Match => {
+                self.engine.match_orders();
+                true
+            }
+        }
+    }
+
+    /// Runs a scenario from the simulation's current state
+    pub fn run(&self, scenario: &Scenario) -> SimReport {
+        let mut report = SimReport::default();
+        for event in scenario.events() {
+            let accepted = self.apply(&event);
+            match event {
+                SimEvent::Create(_) if !accepted => report.rejected += 1,
+                SimEvent::Match => report.books.push(self.engine.snapshot()),
+                _ => {}
+            }
+        }
+        let mut trades = self
+            .recorder
+            .trades
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        report.trades = std::mem::take(&mut *trades);
+        report
+    }
+
+    /// Runs a scenario on a fresh simulation
+    pub fn run_scenario(scenario: &Scenario) -> SimReport {
+        Self::new(scenario.start_micros).run(scenario)
+    }
+}
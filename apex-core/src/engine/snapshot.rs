@@ -0,0 +1,297 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Position of every order of a snapshot: its queue and its view
+type OrderIndex<'a> = HashMap<OrderID, (usize, &'a OrderView)>;
+
+/// BookSnapshot is an owned, plain-data copy of an order book.
+///
+/// It is taken under a single epoch pin, so consumers never deal with
+/// crossbeam guards or skiplist entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookSnapshot {
+    /// Resting buy orders, best price and earliest priority first.
+    pub bids: Vec<OrderView>,
+    /// Resting sell orders, best price and earliest priority first.
+    pub asks: Vec<OrderView>,
+    /// Market orders waiting to be matched, earliest first.
+    pub market_orders: Vec<OrderView>,
+}
+
+impl BookSnapshot {
+    /// Gets the resting orders of a side
+    pub fn side(&self, side: Side) -> &[OrderView] {
+        match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        }
+    }
+
+    /// Gets the best price of a side
+    pub fn best_price(&self, side: Side) -> Option<Price> {
+        self.side(side).first().map(|order| order.price)
+    }
+
+    /// Finds a resting or waiting market order by id
+    pub fn order(&self, order_id: OrderID) -> Option<&OrderView> {
+        self.bids
+            .iter()
+            .chain(self.asks.iter())
+            .chain(self.market_orders.iter())
+            .find(|order| order.id == order_id)
+    }
+
+    /// Checks whether the snapshot holds no orders
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty() && self.market_orders.is_empty()
+    }
+
+    /// Compares the snapshot, taken as the reference, with another book state,
+    /// for example a replica or a book rebuilt after recovery
+    pub fn diff(&self, other: &BookSnapshot) -> BookDiff {
+        let expected = self.index();
+        let actual = other.index();
+        let mut diff = BookDiff::default();
+        for (_, views) in self.queues() {
+            for view in views {
+                match actual.get(&view.id) {
+                    None => diff.missing.push(view.clone()),
+                    Some((_, other_view)) if view != *other_view => {
+                        diff.mismatched.push(OrderMismatch {
+                            expected: view.clone(),
+                            actual: (*other_view).clone(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        for (_, views) in other.queues() {
+            diff.unexpected.extend(
+                views
+                    .iter()
+                    .filter(|view| !expected.contains_key(&view.id))
+                    .cloned(),
+            );
+        }
+        // Compare positions only among the orders both hold in the same queue
+        let shared = |queue: usize, views: &[OrderView], index: &OrderIndex| {
+            views
+                .iter()
+                .map(|view| view.id)
+                .filter(|id| index.get(id).is_some_and(|(other, _)| *other == queue))
+                .collect::<Vec<_>>()
+        };
+        for ((queue, views), (_, other_views)) in self.queues().zip(other.queues()) {
+            let order = shared(queue, views, &actual);
+            let other_order = shared(queue, other_views, &expected);
+            diff.reordered.extend(
+                order
+                    .iter()
+                    .zip(other_order.iter())
+                    .filter(|(id, other_id)| id != other_id)
+                    .map(|(id, _)| *id),
+            );
+        }
+        diff
+    }
+
+    fn queues(&self) -> impl Iterator<Item = (usize, &[OrderView])> {
+        [
+            self.bids.as_slice(),
+            self.asks.as_slice(),
+            self.market_orders.as_slice(),
+        ]
+        .into_iter()
+        .enumerate()
+    }
+
+    fn index(&self) -> OrderIndex<'_> {
+        self.queues()
+            .flat_map(|(queue, views)| views.iter().map(move |view| (view.id, (queue, view))))
+            .collect()
+    }
+}
+
+/// SequencedSnapshot is a copy of an order book taken between two synchronized events,
+/// so it reflects exactly the events before `sequence` and none after.
+///
+/// A plain `BookSnapshot` is read while matching may go on, so an order it holds can be a fill
+/// ahead of another; a sequenced snapshot is taken by the thread that applies every command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SequencedSnapshot {
+    /// Id the next synchronized event after the snapshot carries.
+    pub sequence: u64,
+    pub book: BookSnapshot,
+}
+
+impl SequencedSnapshot {
+    /// Iterates over every order of the snapshot: bids and asks in priority order,
+    /// then market orders
+    pub fn orders(&self) -> impl Iterator<Item = &OrderView> {
+        self.book.queues().flat_map(|(_, views)| views.iter())
+    }
+}
+
+/// OrderMismatch is an order two book states both hold, with a different state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderMismatch {
+    /// The order as the reference holds it.
+    pub expected: OrderView,
+    /// The order as the compared book holds it.
+    pub actual: OrderView,
+}
+
+/// BookDiff lists the differences of a book state from a reference snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookDiff {
+    /// Orders the reference holds and the compared book does not.
+    pub missing: Vec<OrderView>,
+    /// Orders the compared book holds and the reference does not.
+    pub unexpected: Vec<OrderView>,
+    /// Orders both hold with a different price, quantity, status, or other field.
+    pub mismatched: Vec<OrderMismatch>,
+    /// Orders both hold in the same queue, at a different position relative to the
+    /// other orders they share; listed in the order of the reference.
+    pub reordered: Vec<OrderID>,
+}
+
+impl BookDiff {
+    /// Checks whether the two book states are identical
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.unexpected.is_empty()
+            && self.mismatched.is_empty()
+            && self.reordered.is_empty()
+    }
+}
+
+/// CheckpointMismatch is the first difference a restored book has from its checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointMismatch {
+    /// The next synchronized event would carry another id.
+    Sequence { expected: u64, actual: u64 },
+    /// The entry at a position of a queue differs in key or order state, or is missing;
+    /// `side` is None for the market order queue.
+    Entry { side: Option<Side>, position: usize },
+}
+
+impl fmt::Display for CheckpointMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointMismatch::Sequence { expected, actual } => {
+                write!(f, "expected sequence {expected}, found {actual}")
+            }
+            CheckpointMismatch::Entry {
+                side: Some(side),
+                position,
+            } => write!(f, "{side:?} entry {position} differs"),
+            CheckpointMismatch::Entry {
+                side: None,
+                position,
+            } => write!(f, "market order entry {position} differs"),
+        }
+    }
+}
+
+impl Error for CheckpointMismatch {}
+
+/// NormalizationReport lists the orders whose interrupted lifecycle a normalization pass fixed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizationReport {
+    /// Orders released from `Matched` back to `Active`.
+    pub reactivated: Vec<OrderID>,
+    /// Orders dropped because they were leaving the book.
+    pub removed: Vec<OrderID>,
+}
+
+impl NormalizationReport {
+    /// Checks whether no order was affected
+    pub fn is_empty(&self) -> bool {
+        self.reactivated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// BookCheckpoint is a full copy of an order book that restores to an identical book.
+///
+/// Unlike a `BookSnapshot`, it keeps every order with all of its fields and the exact key
+/// it rests under, along with the sequence of the book's synchronized events, so matching
+/// on a restored book proceeds exactly as it would have on the original.
+#[derive(Debug, Clone, Default)]
+pub struct BookCheckpoint {
+    /// Id the next synchronized event carries.
+    pub sequence: u64,
+    /// Resting buy orders with their keys, in matching priority.
+    pub bids: Vec<(BookKey, Order)>,
+    /// Resting sell orders with their keys, in matching priority.
+    pub asks: Vec<(BookKey, Order)>,
+    /// Market orders with their priorities, earliest first.
+    pub market_orders: Vec<(Priority, Order)>,
+}
+
+impl BookCheckpoint {
+    /// Verifies that another checkpoint, typically taken from the restored book,
+    /// holds the same orders under the same keys and the same sequence
+    pub fn verify(&self, other: &BookCheckpoint) -> Result<(), CheckpointMismatch> {
+        if self.sequence != other.sequence {
+            return Err(CheckpointMismatch::Sequence {
+                expected: self.sequence,
+                actual: other.sequence,
+            });
+        }
+        Self::verify_queue(Some(Side::Buy), &self.bids, &other.bids)?;
+        Self::verify_queue(Some(Side::Sell), &self.asks, &other.asks)?;
+        Self::verify_queue(None, &self.market_orders, &other.market_orders)
+    }
+
+    /// Resets the lifecycles a checkpoint taken mid-match or mid-cancel left behind,
+    /// so every order of a book restored from it is claimable again.
+    ///
+    /// A `Matched` order that still has open quantity and a live status is released back
+    /// to `Active`; one that was being completed, and any `Finished` order whose removal
+    /// was interrupted, is dropped. Orders are visited in priority order, bids first,
+    /// so the report is the same for every copy of the checkpoint.
+    pub fn normalize(&mut self) -> NormalizationReport {
+        let mut report = NormalizationReport::default();
+        Self::normalize_queue(&mut self.bids, &mut report);
+        Self::normalize_queue(&mut self.asks, &mut report);
+        Self::normalize_queue(&mut self.market_orders, &mut report);
+        report
+    }
+
+    fn normalize_queue<K>(entries: &mut Vec<(K, Order)>, report: &mut NormalizationReport) {
+        entries.retain(|(_, order)| match order.lifecycle.load() {
+            OrderLifecycle::Active => true,
+            OrderLifecycle::Matched if order.is_live() => {
+                order.reset_lifecycle();
+                report.reactivated.push(order.id);
+                true
+            }
+            OrderLifecycle::Matched | OrderLifecycle::Finished => {
+                report.removed.push(order.id);
+                false
+            }
+        });
+    }
+
+    fn verify_queue<K: PartialEq>(
+        side: Option<Side>,
+        expected: &[(K, Order)],
+        actual: &[(K, Order)],
+    ) -> Result<(), CheckpointMismatch> {
+        let same = |(key, order): &(K, Order), (other_key, other_order): &(K, Order)| {
+            key == other_key
+                && OrderView::from(order) == OrderView::from(other_order)
+                && order.lifecycle.load() == other_order.lifecycle.load()
+        };
+        for position in 0..expected.len().max(actual.len()) {
+            match (expected.get(position), actual.get(position)) {
+                (Some(entry), Some(other)) if same(entry, other) => {}
+                _ => return Err(CheckpointMismatch::Entry { side, position }),
+            }
+        }
+        Ok(())
+    }
+}
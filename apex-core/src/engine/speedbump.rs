@@ -0,0 +1,90 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+struct SpeedBumpState {
+    rng: SimRng,
+    /// Time each delayed order becomes eligible to match, in microseconds since the UNIX epoch.
+    eligible_at: HashMap<OrderID, u64>,
+}
+
+/// SpeedBump delays when new orders become eligible to match by a random interval,
+/// so being a few microseconds faster than other participants stops deciding who trades.
+///
+/// Each accepted order is held back by up to `max_delay`; until then it neither takes nor is
+/// taken by other orders, but it rests, can be canceled, and shows in the depth. Delays come
+/// from a seeded generator and are measured with the engine's clock, so a run with the same
+/// seed and clock replays identically. Liquidation orders are never delayed.
+pub struct SpeedBump {
+    max_delay: u64,
+    state: Mutex<SpeedBumpState>,
+}
+
+impl SpeedBump {
+    /// Creates a new speed bump delaying orders by up to `max_delay`,
+    /// drawn from a generator seeded with `seed`
+    pub fn new(max_delay: Duration, seed: u64) -> Self {
+        Self {
+            max_delay: u64::try_from(max_delay.as_micros()).unwrap_or(u64::MAX),
+            state: Mutex::new(SpeedBumpState {
+                rng: SimRng::new(seed),
+                eligible_at: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Gets the time an order becomes eligible to match, None if it is not delayed
+    pub fn eligible_at(&self, order_id: OrderID) -> Option<u64> {
+        self.lock().eligible_at.get(&order_id).copied()
+    }
+
+    /// Gets the number of orders still delayed, or not yet checked since their delay passed
+    pub fn pending(&self) -> usize {
+        self.lock().eligible_at.len()
+    }
+
+    /// Delays an order that just entered the book from `now` and returns the time it becomes
+    /// eligible. An order already delayed keeps its delay.
+    pub(crate) fn delay(&self, order_id: OrderID, now: u64) -> u64 {
+        let mut state = self.lock();
+        if let Some(eligible_at) = state.eligible_at.get(&order_id) {
+            return *eligible_at;
+        }
+        let delay = state.rng.below(self.max_delay.saturating_add(1));
+        let eligible_at = now.saturating_add(delay);
+        state.eligible_at.insert(order_id, eligible_at);
+        eligible_at
+    }
+
+    /// Checks whether an order may match at `now`, forgetting its delay once it passed
+    pub(crate) fn is_eligible(&self, order_id: OrderID, now: u64) -> bool {
+        let mut state = self.lock();
+        match state.eligible_at.get(&order_id) {
+            Some(eligible_at) if now < *eligible_at => false,
+            Some(_) => {
+                state.eligible_at.remove(&order_id);
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Forgets the delay of an order that left the book
+    pub(crate) fn forget(&self, order_id: OrderID) {
+        self.lock().eligible_at.remove(&order_id);
+    }
+
+    /// Forgets the delays of orders for which `keep` returns false
+    pub(crate) fn retain(&self, mut keep: impl FnMut(OrderID) -> bool) {
+        self.lock()
+            .eligible_at
+            .retain(|order_id, _| keep(*order_id));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SpeedBumpState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
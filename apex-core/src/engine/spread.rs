@@ -0,0 +1,429 @@
+use crate::prelude::*;
+use crypto_bigint::{CheckedSub, Zero};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+
+/// SpreadDefinition describes a synthetic instrument over two leg books.
+///
+/// Buying one lot of the spread buys one lot of the front leg and sells one lot of the back leg;
+/// selling it does the opposite. Spread prices are quoted as `front - back + price_offset`,
+/// so a spread whose front leg trades below its back leg still has a positive price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpreadDefinition {
+    pub front: SymbolID,
+    pub back: SymbolID,
+    pub price_offset: Price,
+}
+
+impl SpreadDefinition {
+    /// Gets the spread price of a pair of leg prices, None if it would be negative
+    pub fn spread_price(&self, front: Price, back: Price) -> Option<Price> {
+        Option::from(front.saturating_add(&self.price_offset).checked_sub(&back))
+    }
+
+    /// Splits a spread price into positive leg prices.
+    /// The front leg keeps `front_reference` unless the back leg would not be positive.
+    pub fn leg_prices(&self, spread_price: Price, front_reference: Price) -> (Price, Price) {
+        let lowest_front = spread_price
+            .saturating_add(&Price::ONE)
+            .saturating_sub(&self.price_offset);
+        let front = front_reference.max(lowest_front).max(Price::ONE);
+        let back = front
+            .saturating_add(&self.price_offset)
+            .saturating_sub(&spread_price);
+        (front, back)
+    }
+}
+
+/// ImpliedQuote is the spread price the best prices of both leg books imply for one side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImpliedQuote {
+    pub price: Price,
+    /// Quantity available at both leg prices.
+    pub quantity: Quantity,
+    pub front_price: Price,
+    pub back_price: Price,
+}
+
+/// SpreadMatch is how a spread order was executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpreadMatch {
+    /// Against another spread order in the spread book.
+    Direct,
+    /// Against resting orders of both leg books at their implied spread price.
+    Implied,
+}
+
+/// SpreadExecution is a spread trade together with the leg trades it produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpreadExecution {
+    pub kind: SpreadMatch,
+    pub price: Price,
+    pub quantity: Quantity,
+    /// Both sides of a direct match, or the spread order's side of an implied one.
+    pub spread_trades: Vec<Trade>,
+    pub front_trades: Vec<Trade>,
+    pub back_trades: Vec<Trade>,
+}
+
+/// A spread book trade together with the user and side of its order
+struct SpreadFill {
+    trade: Trade,
+    user_id: u64,
+    side: Side,
+}
+
+/// SpreadFillCollector forwards every spread book change to the primary syncer
+/// and keeps the trades of the spread book until they are priced into legs.
+struct SpreadFillCollector {
+    primary: Arc<dyn OrderBookSyncer>,
+    fills: Mutex<Vec<SpreadFill>>,
+}
+
+impl SpreadFillCollector {
+    fn take(&self) -> Vec<SpreadFill> {
+        std::mem::take(&mut *self.fills.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl OrderBookSyncer for SpreadFillCollector {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.primary.add_order(id, order);
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.primary.update_order(id, order);
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.primary.cancel_order(id, order);
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.primary.reject_order(id, order);
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.primary.matched(id, updated, trades);
+        let mut fills = self.fills.lock().unwrap_or_else(|e| e.into_inner());
+        for (trade, order) in trade_orders(updated, trades) {
+            fills.push(SpreadFill {
+                trade: trade.clone(),
+                user_id: order.user_id,
+                side: order.side,
+            });
+        }
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.primary.trade_corrected(id, correction);
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.primary.rescaled(id, rescale);
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.primary.batch(id, events);
+    }
+}
+
+/// SpreadEngine matches spread orders of a synthetic instrument over two leg engines.
+///
+/// Spread orders rest in their own book. Each `match_orders` cycle first matches them directly
+/// against each other, then executes the remaining crossing ones against the best prices
+/// of the leg books. Both legs of a spread trade are always produced together:
+/// direct matches are reported to the legs as off-book trades priced around the front leg's
+/// reference price, and implied executions claim the liquidity of both legs before filling either.
+///
+/// The engine expects to be the only one matching the leg books while it runs a cycle.
+pub struct SpreadEngine {
+    definition: SpreadDefinition,
+    spread: DefaultMatchingEngine,
+    book: Arc<DefaultOrderBook>,
+    fills: Arc<SpreadFillCollector>,
+    front: Arc<DefaultMatchingEngine>,
+    back: Arc<DefaultMatchingEngine>,
+    ids: Arc<IdGenerator>,
+    clock: Arc<dyn Clock>,
+}
+
+impl SpreadEngine {
+    /// Creates a new spread engine with an empty spread book synchronized to `syncer`
+    pub fn new(
+        definition: SpreadDefinition,
+        syncer: Arc<dyn OrderBookSyncer>,
+        front: Arc<DefaultMatchingEngine>,
+        back: Arc<DefaultMatchingEngine>,
+    ) -> Self {
+        let fills = Arc::new(SpreadFillCollector {
+            primary: syncer,
+            fills: Mutex::new(Vec::new()),
+        });
+        let book = Arc::new(DefaultOrderBook::new(
+            Arc::new(AtomicU64::new(1)),
+            fills.clone(),
+        ));
+        let ids = Arc::new(IdGenerator::default());
+        let spread = DefaultMatchingEngine::new(book.clone()).with_id_generator(ids.clone());
+        Self {
+            definition,
+            spread,
+            book,
+            fills,
+            front,
+            back,
+            ids,
+            clock: Arc::new(SystemClock {}),
+        }
+    }
+
+    /// Sets the clock spread trades are timestamped with
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.spread = self.spread.with_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    /// Gets the definition of the spread instrument
+    pub fn definition(&self) -> &SpreadDefinition {
+        &self.definition
+    }
+
+    /// Gets the engine of the spread book, e.g. to cancel spread orders or switch its mode
+    pub fn spread(&self) -> &DefaultMatchingEngine {
+        &self.spread
+    }
+
+    /// Submits a spread order, priced in spread terms, to the spread book.
+    /// Only resting limit orders are executed against the leg books;
+    /// market orders match directly in the spread book.
+    pub fn create_order(&self, order: &mut Order) -> Result<(), RejectReason> {
+        self.spread.create_order(order)
+    }
+
+    /// Gets the spread price and quantity the best prices of the leg books offer
+    /// to an incoming spread order of `side`
+    pub fn implied_quote(&self, side: Side) -> Option<ImpliedQuote> {
+        // Buying the spread lifts the front offer and hits the back bid
+        let (front_resting, back_resting) = match side {
+            Side::Buy => (Side::Sell, Side::Buy),
+            Side::Sell => (Side::Buy, Side::Sell),
+        };
+        let front_price = self.front.reference_price(best_source(front_resting))?;
+        let back_price = self.back.reference_price(best_source(back_resting))?;
+        let quantity = self
+            .front
+            .quantity_at(front_resting, front_price)
+            .min(self.back.quantity_at(back_resting, back_price));
+        Some(ImpliedQuote {
+            price: self.definition.spread_price(front_price, back_price)?,
+            quantity,
+            front_price,
+            back_price,
+        })
+    }
+
+    /// Runs a match cycle of the spread book and returns the executed spread trades
+    pub fn match_orders(&self) -> Vec<SpreadExecution> {
+        let mut executions = Vec::new();
+        // Spread trades are only possible while both legs can trade
+        if self.front.mode() != EngineMode::Normal || self.back.mode() != EngineMode::Normal {
+            return executions;
+        }
+        self.match_direct(&mut executions);
+        if self.spread.mode() == EngineMode::Normal {
+            for side in [Side::Buy, Side::Sell] {
+                let mut walking = |order: &Order| self.match_implied(order, &mut executions);
+                self.book.walking_book_maker(side, None, &mut walking);
+            }
+        }
+        // Implied fills have no counterpart in the spread book and are already priced
+        self.fills.take();
+        executions
+    }
+
+    /// Matches spread orders against each other and reports both legs of every match.
+    /// Must only run while both leg engines accept trades.
+    fn match_direct(&self, executions: &mut Vec<SpreadExecution>) {
+        self.spread.match_orders();
+        let front_reference = self
+            .front
+            .last_trade_price()
+            .or_else(|| self.front.reference_price(PriceSource::BestBid))
+            .or_else(|| self.front.reference_price(PriceSource::BestAsk))
+            .unwrap_or(Price::ONE);
+
+        // Both sides of a match are synchronized together, maker first
+        let mut fills = self.fills.take().into_iter().peekable();
+        while let Some(first) = fills.next() {
+            let Some(second) = fills.next_if(|fill| fill.trade.trade_id == first.trade.trade_id)
+            else {
+                continue;
+            };
+            let (buyer, seller) = match first.side {
+                Side::Buy => (&first, &second),
+                Side::Sell => (&second, &first),
+            };
+            let (price, quantity) = (first.trade.price, first.trade.quantity);
+            let (front_price, back_price) = self.definition.leg_prices(price, front_reference);
+            let buyer_leg = (buyer.trade.order_id, buyer.user_id);
+            let seller_leg = (seller.trade.order_id, seller.user_id);
+            // The spread trade already happened, so both legs are booked even if a leg
+            // engine left normal mode since the check; leg prices are always positive
+            let (front_sell, front_buy) = self.front.publish_off_book_trade(&OffBookTrade::new(
+                buyer_leg,
+                seller_leg,
+                front_price,
+                quantity,
+            ));
+            let (back_sell, back_buy) = self.back.publish_off_book_trade(&OffBookTrade::new(
+                seller_leg, buyer_leg, back_price, quantity,
+            ));
+            executions.push(SpreadExecution {
+                kind: SpreadMatch::Direct,
+                price,
+                quantity,
+                spread_trades: vec![first.trade.clone(), second.trade.clone()],
+                front_trades: vec![front_sell, front_buy],
+                back_trades: vec![back_sell, back_buy],
+            });
+        }
+    }
+
+    /// Executes a resting spread order against the leg books for as long as their prices cross it
+    fn match_implied(&self, order: &Order, executions: &mut Vec<SpreadExecution>) -> WalkingResult {
+        if self.spread.is_frozen(order.user_id) || !order.enter_matched() {
+            return WalkingResult::next();
+        }
+        let mut trades = Vec::new();
+        while !bool::from(order.quantity().is_zero()) {
+            let Some(execution) = self.execute_implied(order) else {
+                break;
+            };
+            trades.extend(execution.spread_trades.iter().cloned());
+            executions.push(execution);
+        }
+
+        let filled = order.is_filled();
+        let updated = if filled {
+            order.enter_finished_from_matched();
+            order.clone()
+        } else {
+            let cloned = order.clone_reset_lifecycle();
+            order.exit_matched();
+            cloned
+        };
+        if !trades.is_empty() {
+            self.book.sync_matched(&[updated], &trades);
+        }
+        // Orders behind an unfilled one are priced no better, so they cannot cross either
+        WalkingResult::new(filled, !filled)
+    }
+
+    /// Executes one implied trade of a claimed spread order at the best leg prices, if they cross it
+    fn execute_implied(&self, order: &Order) -> Option<SpreadExecution> {
+        let quote = self.implied_quote(order.side)?;
+        let crosses = match order.side {
+            Side::Buy => quote.price <= order.price,
+            Side::Sell => quote.price >= order.price,
+        };
+        if !crosses || bool::from(quote.quantity.is_zero()) {
+            return None;
+        }
+        let quantity = order.quantity().min(quote.quantity);
+        let (front_side, back_side) = match order.side {
+            Side::Buy => (Side::Buy, Side::Sell),
+            Side::Sell => (Side::Sell, Side::Buy),
+        };
+
+        // Claim both legs before filling either, so a leg never trades alone
+        let front_makers = self.front.lock_book_liquidity(
+            opposite(front_side),
+            quantity,
+            Some(quote.front_price),
+            order,
+        )?;
+        let Some(back_makers) = self.back.lock_book_liquidity(
+            opposite(back_side),
+            quantity,
+            Some(quote.back_price),
+            order,
+        ) else {
+            self.front.release_book_liquidity(&front_makers);
+            return None;
+        };
+
+        let now_microseconds = self.clock.now_micros();
+        let leg = |side: Side, price: Price| Order {
+            tag: order.tag,
+            source: order.source,
+            ..Order::limit(
+                order.id,
+                order.user_id,
+                side,
+                price,
+                quantity,
+                now_microseconds,
+            )
+        };
+        let front_taker = leg(front_side, quote.front_price);
+        let front_trades = self
+            .front
+            .fill_locked_liquidity(&[(&front_taker, front_makers)])
+            .concat();
+        let back_taker = leg(back_side, quote.back_price);
+        let back_trades = self
+            .back
+            .fill_locked_liquidity(&[(&back_taker, back_makers)])
+            .concat();
+
+        let remaining = order.quantity_fill(quantity);
+        order.update_status(if bool::from(remaining.is_zero()) {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        });
+        let trade = Trade {
+            trade_id: self.ids.next_id(),
+            role: TradeRole::Taker,
+            order_id: order.id,
+            price: quote.price,
+            quantity,
+            created_at: now_microseconds,
+            liquidation: order.class == OrderClass::Liquidation,
+            off_book: false,
+            tag: order.tag,
+            source: order.source,
+            // The spread side is implied from the legs rather than entered
+            counterparty_source: OrderSource::System,
+            #[cfg(feature = "hierarchy")]
+            account_id: order.account_id,
+            #[cfg(feature = "hierarchy")]
+            broker_id: order.broker_id,
+        };
+        Some(SpreadExecution {
+            kind: SpreadMatch::Implied,
+            price: quote.price,
+            quantity,
+            spread_trades: vec![trade],
+            front_trades,
+            back_trades,
+        })
+    }
+}
+
+/// Gets the price source of the best resting price of a side
+fn best_source(side: Side) -> PriceSource {
+    match side {
+        Side::Buy => PriceSource::BestBid,
+        Side::Sell => PriceSource::BestAsk,
+    }
+}
+
+fn opposite(side: Side) -> Side {
+    match side {
+        Side::Buy => Side::Sell,
+        Side::Sell => Side::Buy,
+    }
+}
@@ -0,0 +1,325 @@
+use crate::prelude::*;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// FailoverError is a failure to apply, ship, or take over a replicated command stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailoverError {
+    /// The fence moved past the epoch of this primary; another node has taken over.
+    Fenced { epoch: u64, current: u64 },
+    /// A record skipped ahead of the next sequence number the standby expects.
+    SequenceGap { expected: u64, received: u64 },
+    /// A record came from a primary that has since been fenced.
+    StaleEpoch { epoch: u64, current: u64 },
+    /// The fence was advanced by another node before this standby could take over.
+    PromotionConflict { expected: u64, current: u64 },
+    /// The fence moved while the primary applied a command. The command changed the
+    /// primary's book but no standby will apply it, so its outcome must not be acknowledged.
+    Unreplicated { epoch: u64, current: u64 },
+}
+
+impl FailoverError {
+    /// Gets the stable numeric code of the error
+    pub fn code(&self) -> u32 {
+        match self {
+            FailoverError::Fenced { .. } => 1001,
+            FailoverError::SequenceGap { .. } => 1002,
+            FailoverError::StaleEpoch { .. } => 1003,
+            FailoverError::PromotionConflict { .. } => 1004,
+            FailoverError::Unreplicated { .. } => 1005,
+        }
+    }
+}
+
+impl fmt::Display for FailoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailoverError::Fenced { epoch, current } => {
+                write!(f, "primary of epoch {epoch} is fenced by epoch {current}")
+            }
+            FailoverError::SequenceGap { expected, received } => {
+                write!(f, "expected sequence {expected}, received {received}")
+            }
+            FailoverError::StaleEpoch { epoch, current } => {
+                write!(f, "record of epoch {epoch} is older than epoch {current}")
+            }
+            FailoverError::PromotionConflict { expected, current } => {
+                write!(f, "fence moved from epoch {expected} to {current}")
+            }
+            FailoverError::Unreplicated { epoch, current } => {
+                write!(
+                    f,
+                    "primary of epoch {epoch} was fenced by epoch {current} while applying"
+                )
+            }
+        }
+    }
+}
+
+impl Error for FailoverError {}
+
+/// EpochFence holds the epoch of the node allowed to act as primary.
+/// It must be shared by every node of a deployment, for example through a lease service.
+#[derive(Debug, Default)]
+pub struct EpochFence {
+    epoch: AtomicU64,
+}
+
+impl EpochFence {
+    /// Creates a new fence at the given epoch
+    pub fn new(epoch: u64) -> Self {
+        Self {
+            epoch: AtomicU64::new(epoch),
+        }
+    }
+
+    /// Gets the current epoch
+    pub fn current(&self) -> u64 {
+        self.epoch.load(Ordering::Acquire)
+    }
+
+    /// Advances the fence from `from` to the next epoch and returns it,
+    /// or returns the current epoch if the fence is no longer at `from`.
+    pub fn advance(&self, from: u64) -> Result<u64, u64> {
+        self.epoch
+            .compare_exchange(from, from + 1, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| from + 1)
+    }
+}
+
+/// SequencedCommand is a command applied by a primary, as shipped to its standbys.
+#[derive(Debug, Clone)]
+pub struct SequencedCommand {
+    /// Epoch of the primary that applied the command.
+    pub epoch: u64,
+    /// Position of the command in the stream, starting at 1.
+    pub sequence: u64,
+    /// Time the primary applied the command at, in microseconds since the UNIX epoch.
+    /// It never decreases along the stream.
+    pub timestamp: u64,
+    pub command: ReplicatedCommand,
+}
+
+/// PrimaryEngine applies commands to a matching engine while its epoch holds the fence.
+pub struct PrimaryEngine {
+    engine: Arc<DefaultMatchingEngine>,
+    clock: Arc<dyn Clock>,
+    fence: Arc<EpochFence>,
+    epoch: u64,
+    sequence: u64,
+    timestamp: u64,
+}
+
+impl PrimaryEngine {
+    /// Creates a new primary for the current epoch of the fence
+    pub fn new(engine: Arc<DefaultMatchingEngine>, fence: Arc<EpochFence>) -> Self {
+        let epoch = fence.current();
+        Self {
+            engine,
+            clock: Arc::new(SystemClock {}),
+            fence,
+            epoch,
+            sequence: 0,
+            timestamp: 0,
+        }
+    }
+
+    /// Sets the clock the primary stamps commands with
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Gets the epoch of the primary
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Gets the sequence number of the last applied command
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Gets the engine the primary applies commands to
+    pub fn engine(&self) -> &Arc<DefaultMatchingEngine> {
+        &self.engine
+    }
+
+    /// Applies a client command and matches the book.
+    /// Returns the outcome and the record to ship to the standbys.
+    pub fn execute(
+        &mut self,
+        command: Command,
+    ) -> Result<(CommandResult, SequencedCommand), FailoverError> {
+        let (outcome, record) = self.execute_replicated(command.into())?;
+        let ReplicatedOutcome::Command(result) = outcome else {
+            unreachable!("a client command has a command outcome");
+        };
+        Ok((result, record))
+    }
+
+    /// Applies any book-changing operation, stamped with the primary's time, and matches
+    /// the book. Returns the outcome and the record to ship to the standbys.
+    ///
+    /// The fence is checked again after applying, and a command it moved under is reported
+    /// as `FailoverError::Unreplicated`; the primary's book has then diverged and must be
+    /// discarded. The fence can still move between that check and the record reaching the
+    /// standbys, so outcomes may only be acknowledged while the primary holds a lease that
+    /// outlives the promotion of a standby.
+    pub fn execute_replicated(
+        &mut self,
+        command: ReplicatedCommand,
+    ) -> Result<(ReplicatedOutcome, SequencedCommand), FailoverError> {
+        let current = self.fence.current();
+        if current != self.epoch {
+            return Err(FailoverError::Fenced {
+                epoch: self.epoch,
+                current,
+            });
+        }
+        self.sequence += 1;
+        self.timestamp = self.clock.now_micros().max(self.timestamp);
+        let record = SequencedCommand {
+            epoch: self.epoch,
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+            command: command.clone(),
+        };
+        self.engine.set_log_time(self.timestamp);
+        let result = command.execute(&self.engine, self.timestamp);
+        self.engine.match_orders();
+        let current = self.fence.current();
+        if current != self.epoch {
+            return Err(FailoverError::Unreplicated {
+                epoch: self.epoch,
+                current,
+            });
+        }
+        Ok((result, record))
+    }
+}
+
+/// StandbyEngine follows the command stream of a primary and keeps a copy of its book.
+///
+/// A primary stamps every command it applies with its epoch, a gapless sequence number and
+/// its time, and ships the resulting `SequencedCommand` to its standbys. A standby applies the
+/// stream in sequence order as of each record's time, matching after every command exactly as
+/// the primary does.
+///
+/// The standby's book stays identical to the primary's only if nothing but the stream changes
+/// either of them: expiry, stale quote demotion, mass cancels, transfers, freezes, mode
+/// switches, config reloads and rescales must go through `PrimaryEngine::execute_replicated`,
+/// never straight to an engine. Both engines must also be built `with_log_clock`, so trade
+/// times and ids, acceptance times, rate limits, the speed bump and market order staleness
+/// follow the records' timestamps, and start from the same book and config with the same id
+/// generator worker id and speed bump seed. A reference price provider or risk checker
+/// answers from state outside the stream and must be identical on both, or left off.
+///
+/// Failover follows a fencing protocol built on a shared `EpochFence`:
+///
+/// 1. The primary checks the fence before applying each command. Once the fence has moved
+///    past its epoch it refuses every command with `FailoverError::Fenced`, and it reports
+///    a command the fence moved under while it was applied as `FailoverError::Unreplicated`.
+/// 2. A standby is promoted by advancing the fence from the epoch it has been following to
+///    the next one. The advance is a compare-and-swap, so of two standbys racing to take
+///    over only one succeeds; the other gets `FailoverError::PromotionConflict`.
+/// 3. The promoted standby continues the sequence from the last command it applied, and
+///    never stamps a command earlier than that command's time.
+/// 4. Standbys reject records stamped with an epoch older than the newest they have seen,
+///    so a command the old primary shipped after it was fenced never reaches a book.
+///
+/// A standby that sees a gap in the sequence can no longer follow the stream and must be
+/// rebuilt, for example from a snapshot stream.
+pub struct StandbyEngine {
+    engine: Arc<DefaultMatchingEngine>,
+    clock: Arc<dyn Clock>,
+    fence: Arc<EpochFence>,
+    epoch: u64,
+    sequence: u64,
+    timestamp: u64,
+}
+
+impl StandbyEngine {
+    /// Creates a new standby that follows the primary of the current epoch of the fence
+    pub fn new(engine: Arc<DefaultMatchingEngine>, fence: Arc<EpochFence>) -> Self {
+        let epoch = fence.current();
+        Self {
+            engine,
+            clock: Arc::new(SystemClock {}),
+            fence,
+            epoch,
+            sequence: 0,
+            timestamp: 0,
+        }
+    }
+
+    /// Sets the clock the standby stamps commands with once it is promoted
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Gets the newest epoch the standby has followed
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Gets the sequence number of the last applied command
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Gets the engine the standby applies commands to
+    pub fn engine(&self) -> &Arc<DefaultMatchingEngine> {
+        &self.engine
+    }
+
+    /// Applies a record from the primary and matches the book.
+    /// Records already applied are ignored, so a primary may resend after a reconnect.
+    pub fn apply(&mut self, record: &SequencedCommand) -> Result<(), FailoverError> {
+        if record.epoch < self.epoch {
+            return Err(FailoverError::StaleEpoch {
+                epoch: record.epoch,
+                current: self.epoch,
+            });
+        }
+        if record.sequence <= self.sequence {
+            return Ok(());
+        }
+        if record.sequence != self.sequence + 1 {
+            return Err(FailoverError::SequenceGap {
+                expected: self.sequence + 1,
+                received: record.sequence,
+            });
+        }
+        self.epoch = record.epoch;
+        self.sequence = record.sequence;
+        self.timestamp = record.timestamp;
+        self.engine.set_log_time(record.timestamp);
+        record.command.clone().apply(&self.engine, record.timestamp);
+        self.engine.match_orders();
+        Ok(())
+    }
+
+    /// Takes over as primary by fencing off the epoch the standby has been following.
+    /// Every record of the old primary must be applied first; later ones are lost.
+    pub fn promote(self) -> Result<PrimaryEngine, FailoverError> {
+        let epoch =
+            self.fence
+                .advance(self.epoch)
+                .map_err(|current| FailoverError::PromotionConflict {
+                    expected: self.epoch,
+                    current,
+                })?;
+        Ok(PrimaryEngine {
+            engine: self.engine,
+            clock: self.clock,
+            fence: self.fence,
+            epoch,
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+        })
+    }
+}
@@ -0,0 +1,173 @@
+use crate::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// WashTradeKind is the pattern a surveillance alert was raised for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WashTradeKind {
+    /// Both sides of the trade belong to the same user.
+    SameUser,
+    /// Both sides of the trade belong to users of the same group.
+    SameGroup(u64),
+    /// The two users traded with each other in both directions
+    /// `trades` times within the monitor's window.
+    RepeatedCrossing { trades: usize },
+}
+
+/// WashTradeAlert describes a suspicious execution for compliance review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WashTradeAlert {
+    pub kind: WashTradeKind,
+    pub trade_id: u64,
+    pub buy_order_id: OrderID,
+    pub sell_order_id: OrderID,
+    pub buy_user_id: u64,
+    pub sell_user_id: u64,
+    pub price: Price,
+    pub quantity: Quantity,
+}
+
+/// SurveillanceSink trait receives the alerts raised by a `WashTradeMonitor`
+pub trait SurveillanceSink: Send + Sync {
+    /// This function is called when a suspicious execution is detected
+    fn alert(&self, alert: &WashTradeAlert);
+}
+
+/// WashTradeMonitor inspects the trade stream for wash trading:
+/// same-user and same-group executions, and pairs of users repeatedly crossing each other.
+///
+/// Call `inspect` with each match, or add the monitor to a `FanOutSyncer` so it sees every match.
+/// Busted trades no longer count towards repeated crossing; the monitor keeps no prices or
+/// quantities, so a rescale does not concern it.
+pub struct WashTradeMonitor {
+    sink: Arc<dyn SurveillanceSink>,
+    window: usize,
+    threshold: usize,
+    groups: Mutex<HashMap<u64, u64>>,
+    // (trade id, buy user, sell user) of the most recent cross-user executions
+    recent: Mutex<VecDeque<(u64, u64, u64)>>,
+}
+
+impl WashTradeMonitor {
+    /// Creates a new monitor that alerts on repeated crossing
+    /// when a pair of users trades 3 times within the last 100 executions
+    pub fn new(sink: Arc<dyn SurveillanceSink>) -> Self {
+        Self {
+            sink,
+            window: 100,
+            threshold: 3,
+            groups: Mutex::new(HashMap::new()),
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Sets the repeated crossing pattern: `threshold` trades between the same two users,
+    /// in both directions, within the last `window` cross-user executions
+    pub fn with_pattern(mut self, window: usize, threshold: usize) -> Self {
+        self.window = window.max(1);
+        self.threshold = threshold.max(2);
+        self
+    }
+
+    /// Assigns a user to a surveillance group
+    pub fn set_group(&self, user_id: u64, group_id: u64) {
+        let mut groups = self.groups.lock().unwrap_or_else(|e| e.into_inner());
+        groups.insert(user_id, group_id);
+    }
+
+    /// Inspects the trades of a match. Executions are skipped unless both of their orders
+    /// are among `updated`.
+    pub fn inspect(&self, updated: &[Order], trades: &[Trade]) {
+        let fills: Vec<_> = trade_orders(updated, trades).collect();
+        for (maker_trade, maker) in fills.iter().filter(|(t, _)| t.role == TradeRole::Maker) {
+            let taker = fills
+                .iter()
+                .find(|(t, _)| t.role == TradeRole::Taker && t.trade_id == maker_trade.trade_id);
+            let Some((_, taker)) = taker else {
+                continue;
+            };
+            let (buy, sell) = match maker.side {
+                Side::Buy => (maker, taker),
+                Side::Sell => (taker, maker),
+            };
+            if let Some(kind) = self.classify(maker_trade.trade_id, buy.user_id, sell.user_id) {
+                self.sink.alert(&WashTradeAlert {
+                    kind,
+                    trade_id: maker_trade.trade_id,
+                    buy_order_id: buy.id,
+                    sell_order_id: sell.id,
+                    buy_user_id: buy.user_id,
+                    sell_user_id: sell.user_id,
+                    price: maker_trade.price,
+                    quantity: maker_trade.quantity,
+                });
+            }
+        }
+    }
+
+    /// Takes a busted trade out of the repeated crossing window.
+    /// Alerts already raised stand, and price corrections change nothing.
+    pub fn correct(&self, correction: &TradeCorrection) {
+        if correction.kind != TradeCorrectionKind::Bust {
+            return;
+        }
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.retain(|(trade_id, _, _)| *trade_id != correction.trade_id);
+    }
+
+    /// Classifies an execution between a buyer and a seller, recording cross-user executions
+    fn classify(
+        &self,
+        trade_id: u64,
+        buy_user_id: u64,
+        sell_user_id: u64,
+    ) -> Option<WashTradeKind> {
+        if buy_user_id == sell_user_id {
+            return Some(WashTradeKind::SameUser);
+        }
+        {
+            let groups = self.groups.lock().unwrap_or_else(|e| e.into_inner());
+            let group = groups.get(&buy_user_id);
+            if group.is_some() && group == groups.get(&sell_user_id) {
+                return group.copied().map(WashTradeKind::SameGroup);
+            }
+        }
+
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == self.window {
+            recent.pop_front();
+        }
+        recent.push_back((trade_id, buy_user_id, sell_user_id));
+
+        let (mut forward, mut backward) = (0, 0);
+        for (_, buy, sell) in recent.iter() {
+            let pair = (*buy, *sell);
+            if pair == (buy_user_id, sell_user_id) {
+                forward += 1;
+            } else if pair == (sell_user_id, buy_user_id) {
+                backward += 1;
+            }
+        }
+        (forward > 0 && backward > 0 && forward + backward >= self.threshold).then_some(
+            WashTradeKind::RepeatedCrossing {
+                trades: forward + backward,
+            },
+        )
+    }
+}
+
+impl OrderBookSyncer for WashTradeMonitor {
+    fn add_order(&self, _id: u64, _order: &Order) {}
+
+    fn update_order(&self, _id: u64, _order: &Order) {}
+
+    fn cancel_order(&self, _id: u64, _order: &Order) {}
+
+    fn matched(&self, _id: u64, updated: &[Order], trades: &[Trade]) {
+        self.inspect(updated, trades);
+    }
+
+    fn trade_corrected(&self, _id: u64, correction: &TradeCorrection) {
+        self.correct(correction);
+    }
+}
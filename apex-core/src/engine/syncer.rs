@@ -1,6 +1,24 @@
 use crate::prelude::*;
+use std::sync::Arc;
+
+/// BookEvent is a single order book change delivered as part of a syncer batch.
+#[derive(Debug, Clone)]
+pub enum BookEvent {
+    /// The order book accepted a new order.
+    Added(Order),
+    /// The order book updated an order.
+    Updated(Order),
+    /// The order book canceled an order.
+    Cancelled(Order),
+    /// The engine rejected an order before it reached the book.
+    Rejected(Order),
+}
 
 /// OrderBookSyncer trait is used to synchronize the order book with the nodes
+///
+/// Every call carries the id of the book change it belongs to. Ids form a gap-free
+/// sequence per book, so a consumer that sees an id skipped has missed an event.
+/// Concurrent changes may be delivered out of order.
 pub trait OrderBookSyncer: Send + Sync {
     /// This function is called when the order book accepts a new order
     fn add_order(&self, id: u64, order: &Order);
@@ -8,8 +26,31 @@ pub trait OrderBookSyncer: Send + Sync {
     fn update_order(&self, id: u64, order: &Order);
     /// This function is called when the order book cancels an order
     fn cancel_order(&self, id: u64, order: &Order);
+    /// This function is called when the engine rejects an order.
+    /// By default, rejections are not synchronized.
+    fn reject_order(&self, _id: u64, _order: &Order) {}
     /// This function is called when the order engine matches an order
     fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]);
+    /// This function is called when a previously reported trade is busted or price-corrected.
+    /// Orders whose quantity was restored are synchronized separately as updates.
+    /// By default, corrections are not synchronized.
+    fn trade_corrected(&self, _id: u64, _correction: &TradeCorrection) {}
+    /// This function is called when every price and quantity of the book is rescaled at once.
+    /// Rescaled orders are not synchronized one by one.
+    /// By default, rescales are not synchronized.
+    fn rescaled(&self, _id: u64, _rescale: &Rescale) {}
+    /// This function is called when the order book applies a batch of changes at once.
+    /// By default, every event is forwarded to its single-event callback with the batch id.
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        for event in events {
+            match event {
+                BookEvent::Added(order) => self.add_order(id, order),
+                BookEvent::Updated(order) => self.update_order(id, order),
+                BookEvent::Cancelled(order) => self.cancel_order(id, order),
+                BookEvent::Rejected(order) => self.reject_order(id, order),
+            }
+        }
+    }
 }
 
 /// EmptyOrderBookSyncer is a no-op implementation of OrderBookSyncer
@@ -24,3 +65,72 @@ impl OrderBookSyncer for EmptyOrderBookSyncer {
 
     fn matched(&self, _id: u64, _updated: &[Order], _trades: &[Trade]) {}
 }
+
+/// FanOutSyncer delivers every event to several syncers in turn,
+/// so trade-fed modules can run alongside the syncer that replicates the book.
+pub struct FanOutSyncer {
+    syncers: Vec<Arc<dyn OrderBookSyncer>>,
+}
+
+impl FanOutSyncer {
+    /// Creates a new fan-out delivering to `syncers` in order
+    pub fn new(syncers: Vec<Arc<dyn OrderBookSyncer>>) -> Self {
+        Self { syncers }
+    }
+
+    /// Appends a syncer that receives every event after the existing ones
+    pub fn with_syncer(mut self, syncer: Arc<dyn OrderBookSyncer>) -> Self {
+        self.syncers.push(syncer);
+        self
+    }
+}
+
+impl OrderBookSyncer for FanOutSyncer {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.syncers.iter().for_each(|s| s.add_order(id, order));
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.syncers.iter().for_each(|s| s.update_order(id, order));
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.syncers.iter().for_each(|s| s.cancel_order(id, order));
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.syncers.iter().for_each(|s| s.reject_order(id, order));
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.syncers
+            .iter()
+            .for_each(|s| s.matched(id, updated, trades));
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.syncers
+            .iter()
+            .for_each(|s| s.trade_corrected(id, correction));
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.syncers.iter().for_each(|s| s.rescaled(id, rescale));
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.syncers.iter().for_each(|s| s.batch(id, events));
+    }
+}
+
+/// Pairs each trade of a match with the order it filled, skipping trades whose order
+/// is not among `updated`, as delivered to `OrderBookSyncer::matched`
+pub fn trade_orders<'a>(
+    updated: &'a [Order],
+    trades: &'a [Trade],
+) -> impl Iterator<Item = (&'a Trade, &'a Order)> {
+    trades.iter().filter_map(|trade| {
+        let order = updated.iter().find(|order| order.id == trade.order_id)?;
+        Some((trade, order))
+    })
+}
@@ -0,0 +1,175 @@
+use crate::prelude::*;
+use crypto_bigint::{NonZero, U256, U512};
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+/// TapePrint is one trade as printed on the tape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TapePrint {
+    pub trade_id: u64,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub created_at: u64,
+}
+
+/// TapeWindow selects the prints a benchmark price is computed over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapeWindow {
+    /// The latest `n` prints.
+    Last(usize),
+    /// The prints from `from` up to and including `to`, in microseconds since the UNIX epoch.
+    Range { from: u64, to: u64 },
+}
+
+/// Tape is the book-wide record of prints a `TradeHistory` keeps next to the trades of each
+/// user, and computes benchmark prices over.
+///
+/// Every match is printed once, at the taker's side; off-book trades are reported rather
+/// than discovered and are left off the tape. At most `retention` prints are kept.
+/// Sums are taken in 512 bits so price times quantity or duration never overflows.
+pub(crate) struct Tape {
+    retention: usize,
+    prints: Mutex<VecDeque<TapePrint>>,
+}
+
+impl Tape {
+    /// Creates a new tape keeping up to `retention` prints
+    pub(crate) fn new(retention: usize) -> Self {
+        Self {
+            retention,
+            prints: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Records the trades of a match
+    pub(crate) fn record(&self, trades: &[Trade]) {
+        if self.retention == 0 {
+            return;
+        }
+        let mut prints = self.lock();
+        for trade in trades
+            .iter()
+            .filter(|trade| trade.role == TradeRole::Taker && !trade.off_book)
+        {
+            if prints.len() == self.retention {
+                prints.pop_front();
+            }
+            prints.push_back(TapePrint {
+                trade_id: trade.trade_id,
+                price: trade.price,
+                quantity: trade.quantity,
+                created_at: trade.created_at,
+            });
+        }
+    }
+
+    /// Takes a busted trade off the tape, or sets the corrected price on its print
+    pub(crate) fn correct(&self, correction: &TradeCorrection) {
+        let mut prints = self.lock();
+        match correction.kind {
+            TradeCorrectionKind::Bust => {
+                prints.retain(|print| print.trade_id != correction.trade_id)
+            }
+            TradeCorrectionKind::Price(price) => prints
+                .iter_mut()
+                .filter(|print| print.trade_id == correction.trade_id)
+                .for_each(|print| print.price = price),
+        }
+    }
+
+    /// Scales the prices and quantities on the tape to a rescaled book, rounding down
+    pub(crate) fn rescale(&self, rescale: &Rescale) {
+        for print in self.lock().iter_mut() {
+            print.price = rescale.price.apply_rounded(print.price);
+            print.quantity = rescale.quantity.apply_rounded(print.quantity);
+        }
+    }
+
+    /// Gets the prints of a window, oldest first
+    pub(crate) fn prints(&self, window: TapeWindow) -> Vec<TapePrint> {
+        let prints = self.lock();
+        match window {
+            TapeWindow::Last(n) => prints
+                .iter()
+                .skip(prints.len().saturating_sub(n))
+                .copied()
+                .collect(),
+            TapeWindow::Range { from, to } => prints
+                .iter()
+                .filter(|print| (from..=to).contains(&print.created_at))
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Computes the volume-weighted average price of a window,
+    /// None if it has no prints
+    pub(crate) fn vwap(&self, window: TapeWindow) -> Option<Price> {
+        let (notional, volume) = self.prints(window).iter().fold(
+            (U512::ZERO, U512::ZERO),
+            |(notional, volume), print| {
+                let value: U512 = print.price.widening_mul(&print.quantity);
+                (
+                    notional.saturating_add(&value),
+                    volume.saturating_add(&print.quantity.resize()),
+                )
+            },
+        );
+        let volume = NonZero::new(volume).into_option()?;
+        Some((notional / volume).resize::<{ U256::LIMBS }>())
+    }
+
+    /// Computes the time-weighted average price of a window, None if it has no prints
+    pub(crate) fn twap(&self, window: TapeWindow) -> Option<Price> {
+        let (prints, end) = match window {
+            TapeWindow::Last(_) => {
+                let prints = self.prints(window);
+                let end = prints.last()?.created_at;
+                (prints, end)
+            }
+            TapeWindow::Range { from, to } => {
+                let mut prints = self.prints(window);
+                let carried = self
+                    .lock()
+                    .iter()
+                    .rev()
+                    .find(|print| print.created_at < from)
+                    .map(|print| TapePrint {
+                        created_at: from,
+                        ..*print
+                    });
+                if let Some(carried) = carried {
+                    prints.insert(0, carried);
+                }
+                (prints, to)
+            }
+        };
+        if prints.is_empty() {
+            return None;
+        }
+        let mut sum = U512::ZERO;
+        let mut duration = 0u64;
+        for (index, print) in prints.iter().enumerate() {
+            let until = prints.get(index + 1).map_or(end, |next| next.created_at);
+            let held = until.saturating_sub(print.created_at);
+            let value: U512 = print.price.widening_mul(&U256::from(held));
+            sum = sum.saturating_add(&value);
+            duration += held;
+        }
+        if duration == 0 {
+            // Every print happened at once, so each counts the same
+            duration = prints.len() as u64;
+            sum = prints.iter().fold(U512::ZERO, |sum, print| {
+                sum.saturating_add(&print.price.resize())
+            });
+        }
+        let duration = NonZero::new(U512::from(duration)).into_option()?;
+        Some((sum / duration).resize::<{ U256::LIMBS }>())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<TapePrint>> {
+        self.prints
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,91 @@
+use crate::prelude::*;
+use std::sync::Mutex;
+
+/// StopOrder is an order held back from the book until its trigger price is reached.
+/// A buy stop triggers when the source price rises to the trigger price or above,
+/// a sell stop when it falls to the trigger price or below.
+#[derive(Debug, Clone)]
+pub struct StopOrder {
+    pub order: Order,
+    pub trigger_price: Price,
+    /// Price stream the trigger is evaluated against.
+    pub source: PriceSource,
+}
+
+impl StopOrder {
+    /// Creates a new stop order
+    pub fn new(order: Order, trigger_price: Price, source: PriceSource) -> Self {
+        Self {
+            order,
+            trigger_price,
+            source,
+        }
+    }
+
+    /// Checks whether the stop triggers at a source price
+    pub fn is_triggered(&self, price: Price) -> bool {
+        match self.order.side {
+            Side::Buy => price >= self.trigger_price,
+            Side::Sell => price <= self.trigger_price,
+        }
+    }
+}
+
+/// TriggerEngine holds pending stop orders and releases them into a matching engine
+/// once the price stream each order chose reaches its trigger price.
+#[derive(Debug, Default)]
+pub struct TriggerEngine {
+    pending: Mutex<Vec<StopOrder>>,
+}
+
+impl TriggerEngine {
+    /// Creates a new trigger engine without pending orders
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Holds a stop order until it triggers
+    pub fn submit(&self, stop: StopOrder) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.push(stop);
+    }
+
+    /// Cancels a pending stop order and returns it
+    pub fn cancel(&self, order_id: OrderID) -> Option<StopOrder> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let index = pending.iter().position(|stop| stop.order.id == order_id)?;
+        Some(pending.remove(index))
+    }
+
+    /// Gets the number of stop orders waiting for their trigger
+    pub fn pending(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Evaluates every pending stop against the current price of its source
+    /// and creates the triggered orders in submission order.
+    /// Returns the ids of the triggered orders with their create results.
+    pub fn evaluate(
+        &self,
+        engine: &DefaultMatchingEngine,
+    ) -> Vec<(OrderID, Result<(), RejectReason>)> {
+        let triggered = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let mut triggered = Vec::new();
+            pending.retain(|stop| {
+                let fired = engine
+                    .reference_price(stop.source)
+                    .is_some_and(|price| stop.is_triggered(price));
+                if fired {
+                    triggered.push(stop.order.clone());
+                }
+                !fired
+            });
+            triggered
+        };
+        triggered
+            .into_iter()
+            .map(|mut order| (order.id, engine.create_order(&mut order)))
+            .collect()
+    }
+}
@@ -1,8 +1,8 @@
+use super::lifecycle::LifecycleState;
+use crossbeam::atomic::AtomicCell;
 use crypto_bigint::{Limb, NonZero, Reciprocal, U256, U512, Zero};
 use mimalloc::MiMalloc;
-use std::cell::UnsafeCell;
 use std::ops::Mul;
-use std::sync::atomic::{AtomicU8, Ordering};
 
 /// Global allocator
 /// Requires the `mimalloc` feature to be enabled in the `Cargo.toml` file.
@@ -12,6 +12,9 @@ static GLOBAL: MiMalloc = MiMalloc;
 /// OrderID is the type used for order IDs.
 pub type OrderID = u64;
 
+/// ClientOrderID is the id a user gives an order, expected to be unique among its resting orders.
+pub type ClientOrderID = u64;
+
 /// Price is the type used for prices in the order.
 /// This is a 256-bit unsigned integer.
 pub type Price = U256;
@@ -24,7 +27,7 @@ pub type Quantity = U256;
 pub type Priority = u64;
 
 /// Side indicates the direction of the order.
-#[derive(PartialEq, Eq, Default, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Default, Copy, Clone, Debug)]
 pub enum Side {
     /// Buy means the user wants to acquire the asset, typically matching against sell orders.
     #[default]
@@ -97,6 +100,14 @@ pub enum CancelReason {
     UserRequest,
     /// The order was canceled due to a timeout or expiration.
     TimeInForceExpired,
+    /// The order was canceled by an operator mass cancel.
+    MassCancel,
+    /// The order rested longer than the book's maximum resting time.
+    LifetimeExceeded,
+    /// The remainder of a market order was canceled at the book's sweep limit.
+    SweepLimit,
+    /// The order would have traded with an order of the same owner.
+    SelfTradePrevention,
 }
 
 /// RejectReason indicates the reason for rejecting an order.
@@ -108,6 +119,57 @@ pub enum RejectReason {
     /// The order was rejected due to insufficient liquidity.
     /// This can happen if the order is a market order and there are not enough matching orders.
     InsufficientLiquidity,
+    /// The order was rejected because its id is already used by another order.
+    DuplicateOrderId,
+    /// The order was rejected because its parameters failed validation.
+    InvalidOrder(OrderValidationError),
+    /// The order was rejected because its quantity is zero.
+    ZeroQuantity,
+    /// The order was rejected because its limit price is zero.
+    InvalidPrice,
+    /// The order was rejected because its limit price is outside the book's price band.
+    PriceOutOfBand,
+    /// The order was rejected because the book holds the maximum number of resting orders.
+    CapacityExceeded,
+    /// The order was rejected by a pre-trade risk check, with an integrator-defined code.
+    RiskRejected(u32),
+    /// The order could breach the user's position limit if filled.
+    PositionLimitExceeded,
+    /// The user submitted orders faster than the engine's rate limit allows.
+    RateLimited,
+    /// The order quantity is above the book's maximum order size.
+    QuantityTooLarge,
+    /// The order notional is above the book's maximum notional.
+    NotionalTooLarge,
+    /// The market order waited longer than the book's maximum market order age.
+    StaleMarketOrder,
+    /// The owner of the order is frozen.
+    UserFrozen,
+    /// The limit price is not a multiple of the book's tick size.
+    OffTick,
+    /// The order quantity is not a multiple of the book's lot size.
+    OffLot,
+    /// Another order of the same basket was rejected.
+    BasketRejected,
+    /// The order type is not accepted by the book's configuration.
+    OrderTypeNotAllowed,
+    /// The order was rejected because the engine only accepts cancels.
+    CancelOnly,
+    /// The order was rejected because the engine is halted.
+    Halted,
+}
+
+/// EngineMode controls which requests the matching engine accepts.
+/// It can be toggled at runtime to act as a kill switch.
+#[derive(PartialEq, Eq, Default, Clone, Copy, Debug)]
+pub enum EngineMode {
+    /// All requests are accepted and orders are matched.
+    #[default]
+    Normal = 0,
+    /// New orders and updates are rejected, while cancels and expiry keep working.
+    CancelOnly = 1,
+    /// Every request is rejected and matching is stopped.
+    Halted = 2,
 }
 
 /// MatchStrategy represents the strategy used to match an order.
@@ -123,6 +185,69 @@ pub enum MatchStrategy {
     ImmediateOrCancel,
 }
 
+/// OrderClass classifies who an order is sent on behalf of.
+#[derive(PartialEq, Eq, Default, Copy, Clone, Debug)]
+pub enum OrderClass {
+    /// Forced close-out of a position, sent by the venue.
+    /// Liquidation orders bypass the user rate limit and risk checks,
+    /// rest in the liquidation priority tier, always take liquidity when they cross,
+    /// and flag their trades.
+    Liquidation,
+    /// A regular user order.
+    #[default]
+    Regular,
+}
+
+/// PriorityClass is the execution tier of an order within a price level.
+/// Lower tiers rest ahead of higher tiers at the same price, and time priority
+/// only decides between orders of the same tier.
+/// Venues may define their own tiers between the predefined ones.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug, Hash)]
+pub struct PriorityClass(pub u8);
+
+impl PriorityClass {
+    /// Tier of liquidation orders.
+    pub const LIQUIDATION: PriorityClass = PriorityClass(0);
+    /// Tier of regular orders.
+    pub const NORMAL: PriorityClass = PriorityClass(128);
+    /// Tier of regular orders demoted for resting too long without a refresh.
+    pub const STALE: PriorityClass = PriorityClass(160);
+}
+
+impl Default for PriorityClass {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+/// OrderSource is the channel an order was entered through.
+#[derive(PartialEq, Eq, Default, Copy, Clone, Debug, Hash)]
+pub enum OrderSource {
+    /// A client of the programmatic API.
+    #[default]
+    Api,
+    /// A FIX session.
+    Fix,
+    /// A user interface.
+    Ui,
+    /// The liquidation engine.
+    Liquidation,
+    /// The venue itself, such as an operator or an implied order.
+    System,
+}
+
+/// OrderTag is opaque integrator data carried with an order.
+/// The engine never reads it; it is copied unchanged into every event and trade of the order,
+/// so a client can correlate them with its own records.
+#[derive(PartialEq, Eq, Default, Copy, Clone, Debug, Hash)]
+pub struct OrderTag(pub [u8; 16]);
+
+impl From<u128> for OrderTag {
+    fn from(value: u128) -> Self {
+        Self(value.to_be_bytes())
+    }
+}
+
 /// LiquidityDirective specifies whether the order is allowed to take or must provide liquidity.
 /// It determines whether an order can match against existing orders
 /// (taker) or only rest in the book (maker).
@@ -134,7 +259,7 @@ pub enum LiquidityDirective {
     /// MakerOnly means the order must only add liquidity; if it matches, it is canceled.
     MakerOnly,
     /// TakerOnly means the order must immediately match against resting orders;
-    /// it will be rejected if it rests on the book.
+    /// only market orders may carry it, and a limit order with it is rejected.
     TakerOnly,
 }
 
@@ -181,12 +306,15 @@ const RECIPROCAL_10000: Reciprocal = Reciprocal::new(NonZero::<Limb>::new_unwrap
 ///   and for the same price, earlier orders (lower priority values) are prioritized.
 /// - For Sell orders: lower prices are prioritized (sorted ascending),
 ///   and for the same price, earlier orders (lower priority values) are prioritized.
+/// - Within a price, the priority class is compared before the priority,
+///   so orders of a lower tier rest ahead regardless of their time.
 ///
 /// This allows a single skip list to sort all orders per side correctly,
 /// without needing a secondary level of price grouping.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BookKey {
     pub price: Price,
+    pub priority_class: PriorityClass,
     pub priority: Priority,
     pub side: Side,
 }
@@ -199,12 +327,14 @@ impl Ord for BookKey {
                 self.price
                     .cmp(&other.price)
                     .reverse()
+                    .then(self.priority_class.cmp(&other.priority_class))
                     .then(self.priority.cmp(&other.priority))
             }
             Side::Sell => {
                 // Lower price first for sells, then earlier priority
                 self.price
                     .cmp(&other.price)
+                    .then(self.priority_class.cmp(&other.priority_class))
                     .then(self.priority.cmp(&other.priority))
             }
         }
@@ -220,35 +350,198 @@ impl PartialOrd for BookKey {
 /// `Order` represents a single order in the book.
 ///
 /// Certain fields (quantity, filled_quantity, status, cancel_reason, reject_reason)
-/// are wrapped with `UnsafeCell` to allow safe internal mutability.
+/// are wrapped with `AtomicCell` so the matching thread can mutate them through
+/// shared references while other threads read tear-free values.
 ///
-/// SAFETY: All unsafe mutations are controlled within the matching engine thread
-/// context to prevent data races and maintain logical soundness.
+/// Only the matching engine thread writes these fields, so each field is always
+/// consistent on its own, but a concurrent reader may observe a fill between the
+/// `quantity` and `filled_quantity` stores.
 #[derive(Debug)]
 pub struct Order {
     pub id: OrderID,
     pub user_id: u64,
     pub side: Side,
-    pub lifecycle: AtomicU8,
+    pub lifecycle: LifecycleState,
     pub order_type: OrderType,
-    pub status: UnsafeCell<OrderStatus>,
+    pub status: AtomicCell<OrderStatus>,
     pub match_strategy: MatchStrategy,
     pub liquidity_directive: LiquidityDirective,
+    pub class: OrderClass,
+    /// Overrides the priority class derived from the order class.
+    /// The engine only honors it on liquidation orders and clears it on regular ones.
+    pub priority_class: Option<PriorityClass>,
+    /// Broker or firm the order is entered through.
+    pub firm_id: Option<u64>,
+    /// Self-trade prevention group of the order's beneficial owner.
+    /// Orders of the same group are treated as orders of the same user.
+    pub stp_group: Option<u64>,
+    /// Id the user gave the order, so it can be canceled without the engine id.
+    pub client_order_id: Option<ClientOrderID>,
+    /// Integrator data the engine carries but never interprets.
+    pub tag: OrderTag,
+    /// Channel the order was entered through.
+    pub source: OrderSource,
+    /// Account the order is booked to, for clearing.
+    #[cfg(feature = "hierarchy")]
+    pub account_id: Option<u64>,
+    /// Clearing broker of the account.
+    #[cfg(feature = "hierarchy")]
+    pub broker_id: Option<u64>,
     pub time_in_force: TimeInForce,
     pub price: Price,
     pub slippage_tolerance: Option<SlippageTolerance>,
-    pub quantity: UnsafeCell<Quantity>,
+    pub quantity: AtomicCell<Quantity>,
     // TODO: iceberg orders design
     // pub visible_quantity: Option<Quantity>, // if None, fully visible
-    pub filled_quantity: UnsafeCell<Quantity>,
-    pub cancel_reason: UnsafeCell<Option<CancelReason>>,
-    pub reject_reason: UnsafeCell<Option<RejectReason>>,
+    pub filled_quantity: AtomicCell<Quantity>,
+    pub cancel_reason: AtomicCell<Option<CancelReason>>,
+    pub reject_reason: AtomicCell<Option<RejectReason>>,
+    /// Epoch of the book configuration the order was accepted under.
+    pub config_epoch: u64,
+    /// Time the engine accepted the order by its own clock, in microseconds;
+    /// None for orders that did not enter through the engine.
+    pub accepted_at: Option<u64>,
     pub created_at: u64, // In microseconds
     pub updated_at: u64, // In microseconds
 }
 
+/// `OrderView` is a plain-data snapshot of an order,
+/// safe to hand out to callers outside the matching thread.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct OrderView {
+    pub id: OrderID,
+    pub user_id: u64,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub status: OrderStatus,
+    pub time_in_force: TimeInForce,
+    pub price: Price,
+    /// Remaining quantity that is still open.
+    pub quantity: Quantity,
+    pub filled_quantity: Quantity,
+    pub created_at: u64, // In microseconds
+    pub updated_at: u64, // In microseconds
+}
+
+/// `AmendRequest` changes any of the price, open quantity and time in force
+/// of a resting order at once. Fields left as None are kept.
+///
+/// The order keeps its time priority unless the price changes or the quantity increases.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct AmendRequest {
+    pub price: Option<Price>,
+    /// New remaining quantity that is still open.
+    pub quantity: Option<Quantity>,
+    /// New time in force, GoodTillCancelled or GoodTillDate.
+    pub time_in_force: Option<TimeInForce>,
+}
+
+impl AmendRequest {
+    /// Checks whether the request changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.price.is_none() && self.quantity.is_none() && self.time_in_force.is_none()
+    }
+
+    /// Checks whether applying the request moves an order to the back of its price level
+    pub fn loses_priority(&self, order: &Order) -> bool {
+        self.price.is_some_and(|price| price != order.price)
+            || self
+                .quantity
+                .is_some_and(|quantity| quantity > order.quantity())
+    }
+}
+
+/// `PriceLevel` aggregates the resting orders of one side at a single price.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct PriceLevel {
+    pub price: Price,
+    /// Total remaining quantity resting at the price.
+    pub quantity: Quantity,
+    /// Number of orders resting at the price.
+    pub orders: usize,
+}
+
+/// `QueuePosition` is where an order stands in the time priority of its price level.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct QueuePosition {
+    /// Number of orders ahead of it at the level.
+    pub orders_ahead: usize,
+    /// Quantity resting ahead of it at the level.
+    pub quantity_ahead: Quantity,
+}
+
+/// `LevelInfo` is a price level together with an order's queue position at it.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct LevelInfo {
+    /// The level; zero quantity and orders when nothing rests at the price.
+    pub level: PriceLevel,
+    /// None when no order was asked for, or it does not rest at the level.
+    pub queue_position: Option<QueuePosition>,
+}
+
+/// `PreviewFill` is one fill a previewed order would get against a resting order.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct PreviewFill {
+    /// Id of the resting order that would be traded against.
+    pub maker_order_id: OrderID,
+    pub price: Price,
+    pub quantity: Quantity,
+}
+
+/// `MatchPreview` is the outcome matching an order against the current book would have.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct MatchPreview {
+    /// Expected fills, best price first.
+    pub fills: Vec<PreviewFill>,
+    pub filled_quantity: Quantity,
+    /// Volume weighted average price of the fills, rounded down; None without fills.
+    pub average_price: Option<Price>,
+    /// Quantity that would be left to rest or to be canceled.
+    pub remaining_quantity: Quantity,
+}
+
+/// `FillEstimate` is the expected execution of a size against the current depth of a book.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct FillEstimate {
+    /// Volume weighted average execution price, rounded down.
+    pub average_price: Price,
+    /// Price of the last level the size reaches.
+    pub worst_price: Price,
+    pub filled_quantity: Quantity,
+    /// Part of the size the book is too thin to fill.
+    pub unfilled_quantity: Quantity,
+}
+
+/// `CompactionReport` counts what a compaction pass removed from the order book.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct CompactionReport {
+    /// Finished orders unlinked from the price and market queues.
+    pub book_entries: usize,
+    /// Order index entries that no longer pointed at a live order.
+    pub index_entries: usize,
+}
+
+/// `BookStats` is a point-in-time summary of the order book
+/// for monitoring and strategy code.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct BookStats {
+    pub best_bid: Option<Price>,
+    pub best_ask: Option<Price>,
+    /// Best ask minus best bid, zero when the book is locked or crossed.
+    pub spread: Option<Price>,
+    /// Average of the best bid and best ask, rounded down.
+    pub midpoint: Option<Price>,
+    pub bid_orders: usize,
+    pub ask_orders: usize,
+    pub bid_volume: Quantity,
+    pub ask_volume: Quantity,
+    /// `(bid_volume - ask_volume) / (bid_volume + ask_volume)` in basis points,
+    /// from -10000 (only asks) to 10000 (only bids); `None` for an empty book.
+    pub imbalance_bps: Option<i32>,
+}
+
 /// OrderValidationError represents possible validation failures for order parameters.
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum OrderValidationError {
     /// The match strategy used is invalid for an order.
     InvalidMatchStrategy,
@@ -275,13 +568,31 @@ pub enum TradeRole {
 }
 
 /// Trade represents a trade matched in the orders.
-#[derive(Default, Clone, Debug)]
+#[derive(PartialEq, Eq, Default, Clone, Debug)]
 pub struct Trade {
+    /// Shared by the maker and taker side of the same match.
+    pub trade_id: u64,
     pub role: TradeRole,
     pub order_id: u64,
     pub price: Price,
     pub quantity: Quantity,
     pub created_at: u64,
+    /// Set when either order of the match is a liquidation order.
+    pub liquidation: bool,
+    /// Set when the trade was executed away from the book and only reported to it.
+    pub off_book: bool,
+    /// Tag of the order on this side of the trade.
+    pub tag: OrderTag,
+    /// Source of the order on this side of the trade.
+    pub source: OrderSource,
+    /// Source of the order on the other side of the trade.
+    pub counterparty_source: OrderSource,
+    /// Account of the order on this side of the trade.
+    #[cfg(feature = "hierarchy")]
+    pub account_id: Option<u64>,
+    /// Clearing broker of the order on this side of the trade.
+    #[cfg(feature = "hierarchy")]
+    pub broker_id: Option<u64>,
 }
 
 impl From<u8> for OrderLifecycle {
@@ -301,24 +612,72 @@ impl From<OrderLifecycle> for u8 {
     }
 }
 
+impl From<u8> for EngineMode {
+    fn from(val: u8) -> Self {
+        match val {
+            0 => Self::Normal,
+            1 => Self::CancelOnly,
+            2 => Self::Halted,
+            _ => unreachable!("Invalid engine mode"),
+        }
+    }
+}
+
+impl From<EngineMode> for u8 {
+    fn from(m: EngineMode) -> u8 {
+        m as u8
+    }
+}
+
+impl From<&Order> for OrderView {
+    fn from(order: &Order) -> Self {
+        OrderView {
+            id: order.id,
+            user_id: order.user_id,
+            side: order.side,
+            order_type: order.order_type,
+            status: order.status(),
+            time_in_force: order.time_in_force,
+            price: order.price,
+            quantity: order.quantity(),
+            filled_quantity: order.filled_quantity(),
+            created_at: order.created_at,
+            updated_at: order.updated_at,
+        }
+    }
+}
+
 impl Default for Order {
     fn default() -> Self {
         Order {
             id: 0,
             user_id: 0,
             side: Side::default(),
-            lifecycle: AtomicU8::new(OrderLifecycle::Active.into()),
+            lifecycle: LifecycleState::default(),
             order_type: OrderType::default(),
-            status: UnsafeCell::new(OrderStatus::default()),
+            status: AtomicCell::new(OrderStatus::default()),
             match_strategy: MatchStrategy::default(),
             liquidity_directive: LiquidityDirective::default(),
+            class: OrderClass::default(),
+            priority_class: None,
+            firm_id: None,
+            stp_group: None,
+            client_order_id: None,
+            tag: OrderTag::default(),
+            source: OrderSource::default(),
+            #[cfg(feature = "hierarchy")]
+            account_id: None,
+            #[cfg(feature = "hierarchy")]
+            broker_id: None,
             time_in_force: TimeInForce::default(),
             price: U256::ZERO,
             slippage_tolerance: None,
-            quantity: UnsafeCell::new(U256::ZERO),
-            filled_quantity: UnsafeCell::new(U256::ZERO),
-            cancel_reason: UnsafeCell::new(None),
-            reject_reason: UnsafeCell::new(None),
+            quantity: AtomicCell::new(U256::ZERO),
+            filled_quantity: AtomicCell::new(U256::ZERO),
+            cancel_reason: AtomicCell::new(None),
+            reject_reason: AtomicCell::new(None),
+            config_epoch: 0,
+            accepted_at: None,
             created_at: 0,
             updated_at: 0,
         }
@@ -331,31 +690,42 @@ impl Clone for Order {
             id: self.id,
             user_id: self.user_id,
             side: self.side,
-            lifecycle: AtomicU8::new(self.lifecycle.load(Ordering::Acquire)),
+            lifecycle: self.lifecycle.clone(),
             order_type: self.order_type,
-            status: UnsafeCell::new(unsafe { *self.status.get() }),
+            status: AtomicCell::new(self.status.load()),
             match_strategy: self.match_strategy,
             liquidity_directive: self.liquidity_directive,
+            class: self.class,
+            priority_class: self.priority_class,
+            firm_id: self.firm_id,
+            stp_group: self.stp_group,
+            client_order_id: self.client_order_id,
+            tag: self.tag,
+            source: self.source,
+            #[cfg(feature = "hierarchy")]
+            account_id: self.account_id,
+            #[cfg(feature = "hierarchy")]
+            broker_id: self.broker_id,
             time_in_force: self.time_in_force,
             price: self.price,
             slippage_tolerance: self.slippage_tolerance,
-            quantity: UnsafeCell::new(unsafe { *self.quantity.get() }),
-            filled_quantity: UnsafeCell::new(unsafe { *self.filled_quantity.get() }),
-            cancel_reason: UnsafeCell::new(unsafe { *self.cancel_reason.get() }),
-            reject_reason: UnsafeCell::new(unsafe { *self.reject_reason.get() }),
+            quantity: AtomicCell::new(self.quantity.load()),
+            filled_quantity: AtomicCell::new(self.filled_quantity.load()),
+            cancel_reason: AtomicCell::new(self.cancel_reason.load()),
+            reject_reason: AtomicCell::new(self.reject_reason.load()),
+            config_epoch: self.config_epoch,
+            accepted_at: self.accepted_at,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
     }
 }
 
-unsafe impl Sync for Order {}
-
 impl Order {
     /// Get the order's status.
     #[inline(always)]
     pub fn status(&self) -> OrderStatus {
-        unsafe { *self.status.get() }
+        self.status.load()
     }
 
     /// Check the order status is filled.
@@ -367,13 +737,73 @@ impl Order {
     /// Get the quantity of the order.
     #[inline(always)]
     pub fn quantity(&self) -> Quantity {
-        unsafe { *self.quantity.get() }
+        self.quantity.load()
+    }
+
+    /// Get the cancel reason of the order.
+    #[inline(always)]
+    pub fn cancel_reason(&self) -> Option<CancelReason> {
+        self.cancel_reason.load()
+    }
+
+    /// Get the reject reason of the order.
+    #[inline(always)]
+    pub fn reject_reason(&self) -> Option<RejectReason> {
+        self.reject_reason.load()
     }
 
     /// Get the filled quantity of the order.
     #[inline(always)]
     pub fn filled_quantity(&self) -> Quantity {
-        unsafe { *self.filled_quantity.get() }
+        self.filled_quantity.load()
+    }
+
+    /// Check whether the order still has open quantity and a non-terminal status.
+    #[inline(always)]
+    pub(crate) fn is_live(&self) -> bool {
+        matches!(
+            self.status(),
+            OrderStatus::Pending | OrderStatus::Placed | OrderStatus::PartiallyFilled
+        ) && !bool::from(self.quantity().is_zero())
+    }
+
+    /// Check whether the order rests at a price level of the book.
+    #[inline(always)]
+    pub(crate) fn is_resting(&self) -> bool {
+        self.order_type == OrderType::Limit && self.is_live()
+    }
+
+    /// Check whether the order can be matched as a maker.
+    /// TakerOnly orders never provide liquidity, even if inserted into a book directly.
+    #[inline(always)]
+    pub fn provides_liquidity(&self) -> bool {
+        self.liquidity_directive != LiquidityDirective::TakerOnly
+    }
+
+    /// Check whether two orders have the same owner: the same user, or the same STP group.
+    #[inline(always)]
+    pub fn same_owner(&self, other: &Order) -> bool {
+        self.user_id == other.user_id
+            || (self.stp_group.is_some() && self.stp_group == other.stp_group)
+    }
+
+    /// Get the execution tier of the order within its price level.
+    #[inline(always)]
+    pub fn priority_class(&self) -> PriorityClass {
+        self.priority_class.unwrap_or(match self.class {
+            OrderClass::Liquidation => PriorityClass::LIQUIDATION,
+            OrderClass::Regular => PriorityClass::NORMAL,
+        })
+    }
+
+    /// Move the order to the back of its tier at `now_microseconds`.
+    /// A quote demoted as stale is fresh again and regains its derived tier.
+    #[inline(always)]
+    pub(crate) fn retime(&mut self, now_microseconds: u64) {
+        self.updated_at = now_microseconds;
+        if self.priority_class == Some(PriorityClass::STALE) {
+            self.priority_class = None;
+        }
     }
 
     /// Get the book key for the order.
@@ -381,75 +811,46 @@ impl Order {
     pub fn book_key(&self) -> BookKey {
         BookKey {
             price: self.price,
+            priority_class: self.priority_class(),
             priority: self.priority(),
             side: self.side,
         }
     }
 
     /// Get the current lifecycle state is `Finished`.
-    #[allow(dead_code)]
     #[inline(always)]
     pub(crate) fn is_finished(&self) -> bool {
-        self.lifecycle.load(Ordering::Acquire) == OrderLifecycle::Finished.into()
+        self.lifecycle.load() == OrderLifecycle::Finished
     }
 
     /// Reset lifecycle state to `Active`.
     #[inline(always)]
     pub(crate) fn reset_lifecycle(&self) {
-        self.lifecycle
-            .store(OrderLifecycle::Active.into(), Ordering::Release);
+        self.lifecycle.reset();
     }
 
     /// Enter matched lifecycle state.
     #[inline(always)]
     pub(crate) fn enter_matched(&self) -> bool {
-        self.lifecycle
-            .compare_exchange_weak(
-                OrderLifecycle::Active.into(),
-                OrderLifecycle::Matched.into(),
-                Ordering::AcqRel,
-                Ordering::Relaxed,
-            )
-            .is_ok()
+        self.lifecycle.enter_matched()
     }
 
     /// Exit from matched to active lifecycle state.
     #[inline(always)]
     pub(crate) fn exit_matched(&self) -> bool {
-        self.lifecycle
-            .compare_exchange_weak(
-                OrderLifecycle::Matched.into(),
-                OrderLifecycle::Active.into(),
-                Ordering::AcqRel,
-                Ordering::Relaxed,
-            )
-            .is_ok()
+        self.lifecycle.exit_matched()
     }
 
     /// Enter the finished lifecycle state from active.
     #[inline(always)]
     pub(crate) fn enter_finished_from_active(&self) -> bool {
-        self.lifecycle
-            .compare_exchange_weak(
-                OrderLifecycle::Active.into(),
-                OrderLifecycle::Finished.into(),
-                Ordering::AcqRel,
-                Ordering::Relaxed,
-            )
-            .is_ok()
+        self.lifecycle.enter_finished_from_active()
     }
 
     /// Enter the finished lifecycle state from matched.
     #[inline(always)]
     pub(crate) fn enter_finished_from_matched(&self) -> bool {
-        self.lifecycle
-            .compare_exchange_weak(
-                OrderLifecycle::Matched.into(),
-                OrderLifecycle::Finished.into(),
-                Ordering::AcqRel,
-                Ordering::Relaxed,
-            )
-            .is_ok()
+        self.lifecycle.enter_finished_from_matched()
     }
 
     /// Get the order priority of the order book.
@@ -460,47 +861,52 @@ impl Order {
         self.updated_at * 100 + self.id % 100
     }
 
-    /// SAFETY:
     /// Only the matching engine thread modifies quantity and filled_quantity,
-    /// ensuring no data race even though accessed through shared reference.
+    /// so the load-then-store updates never lose a concurrent write.
     #[inline(always)]
     pub(crate) fn quantity_fill(&self, traded: Quantity) -> Quantity {
-        unsafe {
-            *self.quantity.get() -= traded;
-            *self.filled_quantity.get() += traded;
-            *self.quantity.get()
-        }
+        let remaining = self.quantity.load() - traded;
+        self.quantity.store(remaining);
+        self.filled_quantity
+            .store(self.filled_quantity.load() + traded);
+        remaining
     }
 
-    /// SAFETY:
-    /// Only the matching engine thread modifies order status through shared reference,
-    /// ensuring no concurrent modification.
+    /// Returns busted quantity to the order, undoing up to its whole filled quantity.
+    /// Like `quantity_fill`, it runs only while the order is held in the `Matched` lifecycle.
+    #[inline(always)]
+    pub(crate) fn quantity_unfill(&self, busted: Quantity) -> Quantity {
+        let filled = self.filled_quantity.load();
+        let busted = busted.min(filled);
+        self.filled_quantity.store(filled - busted);
+        let remaining = self.quantity.load() + busted;
+        self.quantity.store(remaining);
+        remaining
+    }
+
+    /// Only the matching engine thread amends the quantity, and only while it holds
+    /// the order in the `Matched` lifecycle, so no other writer can race with it.
+    #[inline(always)]
+    pub(crate) fn update_quantity(&self, quantity: Quantity) {
+        self.quantity.store(quantity);
+    }
+
+    /// Only the matching engine thread modifies order status.
     #[inline(always)]
     pub(crate) fn update_status(&self, status: OrderStatus) {
-        unsafe {
-            *self.status.get() = status;
-        }
+        self.status.store(status);
     }
 
-    /// SAFETY:
-    /// Only the matching engine thread modifies cancel_reason,
-    /// ensuring safe access under shared reference.
-    #[allow(dead_code)]
+    /// Only the matching engine thread modifies cancel_reason.
     #[inline(always)]
     pub(crate) fn update_cancel_reason(&self, reason: CancelReason) {
-        unsafe {
-            *self.cancel_reason.get() = Some(reason);
-        }
+        self.cancel_reason.store(Some(reason));
     }
 
-    /// SAFETY:
-    /// Only the matching engine thread modifies reject_reason,
-    /// ensuring safe access under shared reference.
+    /// Only the matching engine thread modifies reject_reason.
     #[inline(always)]
     pub(crate) fn update_reject_reason(&self, reason: RejectReason) {
-        unsafe {
-            *self.reject_reason.get() = Some(reason);
-        }
+        self.reject_reason.store(Some(reason));
     }
 
     /// Returns the worst acceptable execution price under slippage tolerance.
@@ -566,10 +972,11 @@ impl Order {
                     _ => {}
                 }
                 // 4. SlippageTolerance could be None or a valid value
-                if let Some(slippage) = self.slippage_tolerance {
-                    if slippage.0 > MAX_ALLOWED_SLIPPAGE_TOLERANCE.0 {
-                        return Err(OrderValidationError::SlippageExceedsMaximum);
-                    }
+                if self
+                    .slippage_tolerance
+                    .is_some_and(|slippage| slippage.0 > MAX_ALLOWED_SLIPPAGE_TOLERANCE.0)
+                {
+                    return Err(OrderValidationError::SlippageExceedsMaximum);
                 }
 
                 Ok(())
@@ -586,16 +993,19 @@ impl Order {
 }
 
 impl Trade {
-    /// Orders matched then calculate the quantity and trades.
+    /// Orders matched for at most `max_quantity` at `price`, then calculate the quantity and trades.
     #[inline(always)]
     pub(crate) fn matched(
         now_microseconds: u64,
+        trade_id: u64,
         taker: &Order,
         maker: &Order,
+        max_quantity: Quantity,
+        price: Price,
     ) -> Option<(Trade, Trade)> {
         let mut maker_quantity = maker.quantity();
         let mut taker_quantity = taker.quantity();
-        let traded_quantity = taker_quantity.min(maker_quantity);
+        let traded_quantity = taker_quantity.min(maker_quantity).min(max_quantity);
         if traded_quantity.is_zero().into() {
             return None;
         }
@@ -617,20 +1027,43 @@ impl Trade {
         maker.update_status(maker_status);
         taker.update_status(taker_status);
 
+        let liquidation =
+            maker.class == OrderClass::Liquidation || taker.class == OrderClass::Liquidation;
+
         Some((
             Trade {
+                trade_id,
                 role: TradeRole::Maker,
                 order_id: maker.id,
-                price: maker.price,
+                price,
                 quantity: traded_quantity,
                 created_at: now_microseconds,
+                liquidation,
+                off_book: false,
+                tag: maker.tag,
+                source: maker.source,
+                counterparty_source: taker.source,
+                #[cfg(feature = "hierarchy")]
+                account_id: maker.account_id,
+                #[cfg(feature = "hierarchy")]
+                broker_id: maker.broker_id,
             },
             Trade {
+                trade_id,
                 role: TradeRole::Taker,
                 order_id: taker.id,
-                price: maker.price,
+                price,
                 quantity: traded_quantity,
                 created_at: now_microseconds,
+                liquidation,
+                off_book: false,
+                tag: taker.tag,
+                source: taker.source,
+                counterparty_source: maker.source,
+                #[cfg(feature = "hierarchy")]
+                account_id: taker.account_id,
+                #[cfg(feature = "hierarchy")]
+                broker_id: taker.broker_id,
             },
         ))
     }
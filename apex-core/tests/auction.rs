@@ -0,0 +1,83 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+
+fn levels(levels: &[(u64, u64)]) -> Vec<PriceLevel> {
+    levels
+        .iter()
+        .map(|(price, quantity)| PriceLevel {
+            price: Price::from(*price),
+            quantity: Quantity::from(*quantity),
+            orders: 1,
+        })
+        .collect()
+}
+
+#[test]
+fn test_uncross_price_maximizes_volume_then_minimizes_imbalance() {
+    let bids = levels(&[(102, 5), (101, 5)]);
+    let asks = levels(&[(100, 4), (101, 6)]);
+    assert_eq!(
+        uncross_price(&bids, &asks, None),
+        Some(AuctionPrice {
+            price: Price::from(101u64),
+            volume: Quantity::from(10u64),
+            imbalance: Quantity::ZERO,
+            imbalance_side: None,
+            rule: TieBreak::MaxVolume,
+        })
+    );
+
+    // 5 executes at 99, 101 and 102, but only 99 leaves no sell imbalance
+    let bids = levels(&[(102, 5)]);
+    let asks = levels(&[(99, 5), (101, 2)]);
+    let auction = uncross_price(&bids, &asks, None).unwrap();
+    assert_eq!(auction.price, Price::from(99u64));
+    assert_eq!(auction.rule, TieBreak::MinImbalance);
+
+    assert_eq!(uncross_price(&levels(&[(98, 5)]), &asks, None), None);
+}
+
+#[test]
+fn test_uncross_price_ties_go_to_the_reference_price() {
+    let bids = levels(&[(101, 10)]);
+    let asks = levels(&[(99, 5), (100, 5)]);
+    let auction = uncross_price(&bids, &asks, None).unwrap();
+    assert_eq!(
+        (auction.price, auction.rule),
+        (Price::from(100u64), TieBreak::LowestPrice)
+    );
+    let auction = uncross_price(&bids, &asks, Some(Price::from(105u64))).unwrap();
+    assert_eq!(
+        (auction.price, auction.rule),
+        (Price::from(101u64), TieBreak::ReferencePrice)
+    );
+
+    // Equal imbalance on 101 and 102, with 102 closer to the reference
+    let bids = levels(&[(102, 5), (100, 5)]);
+    let asks = levels(&[(99, 5), (101, 3)]);
+    let auction = uncross_price(&bids, &asks, Some(Price::from(104u64))).unwrap();
+    assert_eq!(auction.price, Price::from(102u64));
+    assert_eq!(auction.imbalance_side, Some(Side::Sell));
+    assert_eq!(auction.rule, TieBreak::ReferencePrice);
+}
+
+#[test]
+fn test_engine_reports_the_indicative_auction_price() {
+    let (_book, engine) = TestEngine::new().build();
+    assert_eq!(engine.indicative_auction_price(), None);
+    for (id, side, price, qty) in [
+        (1, Side::Buy, 102, 5),
+        (2, Side::Buy, 101, 5),
+        (3, Side::Sell, 100, 4),
+        (4, Side::Sell, 101, 6),
+    ] {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, qty, 1000 + id))
+            .unwrap();
+    }
+    let auction = engine.indicative_auction_price().unwrap();
+    assert_eq!(auction.price, Price::from(101u64));
+    assert_eq!(auction.volume, Quantity::from(10u64));
+}
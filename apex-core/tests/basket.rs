@@ -0,0 +1,134 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+struct Book {
+    syncer: Arc<Recorder>,
+    book: Arc<DefaultOrderBook>,
+    engine: Arc<DefaultMatchingEngine>,
+}
+
+fn new_book() -> Book {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    Book {
+        syncer,
+        book,
+        engine: Arc::new(engine),
+    }
+}
+
+/// Two books: symbol 1 offers 5 at 100 and 5 at 101, symbol 2 bids 4 at 50
+fn new_router() -> (Book, Book, BasketRouter) {
+    let (first, second) = (new_book(), new_book());
+    first
+        .engine
+        .create_order(&mut make_limit_order(1, Side::Sell, 100, 5, 1000))
+        .unwrap();
+    first
+        .engine
+        .create_order(&mut make_limit_order(2, Side::Sell, 101, 5, 1001))
+        .unwrap();
+    second
+        .engine
+        .create_order(&mut make_limit_order(3, Side::Buy, 50, 4, 1002))
+        .unwrap();
+    let router = BasketRouter::new()
+        .with_book(1, first.engine.clone())
+        .with_book(2, second.engine.clone());
+    (first, second, router)
+}
+
+#[test]
+fn test_basket_fills_every_child_across_books() {
+    let (first, second, router) = new_router();
+    let mut children = [
+        BasketChild::new(1, make_limit_order(10, Side::Buy, 101, 3, 2000)),
+        BasketChild::new(1, make_limit_order(11, Side::Buy, 101, 4, 2001)),
+        BasketChild::new(2, make_market_order(12, Side::Sell, 4, 2002)),
+    ];
+    let fills = router.execute(&mut children).unwrap();
+    let filled: Vec<_> = fills
+        .iter()
+        .map(|fill| (fill.symbol, fill.order_id, fill.trades.len()))
+        .collect();
+    // A resting order claimed by one child is not shared with the next one
+    assert_eq!(filled, vec![(1, 10, 2), (1, 11, 2), (2, 12, 2)]);
+    assert!(children.iter().all(|child| child.order.is_filled()));
+
+    assert_eq!(
+        get_book_state(first.book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(2u64)), (2, Quantity::from(1u64))]
+    );
+    assert!(get_book_state(second.book.as_ref(), Side::Buy).is_empty());
+    // The children of one book are synchronized as a single match
+    assert_eq!(first.syncer.matches().len(), 1);
+    assert_eq!(second.syncer.matches().len(), 1);
+}
+
+#[test]
+fn test_basket_executes_nothing_when_one_child_cannot_fill() {
+    let (first, second, router) = new_router();
+    let mut children = [
+        BasketChild::new(1, make_limit_order(10, Side::Buy, 101, 6, 2000)),
+        BasketChild::new(2, make_limit_order(11, Side::Sell, 50, 5, 2001)),
+    ];
+    assert_eq!(
+        router.execute(&mut children),
+        Err(BasketError::Rejected {
+            order_id: 11,
+            reason: RejectReason::InsufficientLiquidity,
+        })
+    );
+    assert_eq!(
+        children[0].order.reject_reason(),
+        Some(RejectReason::BasketRejected)
+    );
+    assert_eq!(order_ids(&first.syncer.rejected()), vec![10]);
+    assert_eq!(order_ids(&second.syncer.rejected()), vec![11]);
+    assert_eq!(first.syncer.matches().len(), 0);
+
+    // The claims were released, so the resting orders still trade
+    assert_eq!(
+        get_book_state(first.book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(5u64)), (2, Quantity::from(5u64))]
+    );
+    let mut children = [BasketChild::new(
+        1,
+        make_limit_order(12, Side::Buy, 101, 10, 2002),
+    )];
+    assert!(router.execute(&mut children).is_ok());
+    assert!(get_book_state(first.book.as_ref(), Side::Sell).is_empty());
+}
+
+#[test]
+fn test_basket_rejections_before_claiming() {
+    let (first, _second, router) = new_router();
+    let mut children = [BasketChild::new(
+        3,
+        make_limit_order(10, Side::Buy, 100, 1, 2000),
+    )];
+    assert_eq!(
+        router.execute(&mut children),
+        Err(BasketError::UnknownSymbol(3))
+    );
+    assert_eq!(children[0].order.status(), OrderStatus::default());
+
+    first.engine.set_mode(EngineMode::Halted);
+    let mut children = [
+        BasketChild::new(2, make_limit_order(11, Side::Sell, 50, 1, 2001)),
+        BasketChild::new(1, make_limit_order(12, Side::Buy, 100, 1, 2002)),
+    ];
+    assert_eq!(
+        router.execute(&mut children),
+        Err(BasketError::Rejected {
+            order_id: 12,
+            reason: RejectReason::Halted,
+        })
+    );
+    assert_eq!(children[0].order.status(), OrderStatus::Rejected);
+    assert_eq!(BasketError::UnknownSymbol(3).code(), 801);
+    assert_eq!(RejectReason::BasketRejected.code(), 119);
+}
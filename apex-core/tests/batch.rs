@@ -0,0 +1,167 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+#[test]
+fn test_create_orders_single_syncer_batch() {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+
+    let mut orders = vec![
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 101, 10, 1001),
+        make_limit_order(3, Side::Sell, 110, 10, 1002),
+    ];
+    let results = engine.create_orders(&mut orders);
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert!(
+        orders
+            .iter()
+            .all(|order| order.status() == OrderStatus::Placed)
+    );
+
+    assert_eq!(syncer.batches(), vec![3]);
+    assert!(
+        syncer
+            .events()
+            .iter()
+            .all(|(_, event)| matches!(event, SyncEvent::Batch(_)))
+    );
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 2);
+    assert_eq!(get_book_state(book.as_ref(), Side::Sell).len(), 1);
+}
+
+#[test]
+fn test_cancel_orders_reports_per_order_results() {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+
+    let mut orders = vec![
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 101, 10, 1001),
+    ];
+    engine.create_orders(&mut orders);
+
+    let results = engine.cancel_orders(&[1, 99, 2]);
+    assert_eq!(
+        results,
+        vec![Ok(()), Err(CancelOrderError::OrderNotFound), Ok(())]
+    );
+    assert_eq!(syncer.batches(), vec![2, 2]);
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+}
+
+#[test]
+fn test_execute_mixed_batch() {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+
+    let mut commands = vec![
+        Command::Create(make_limit_order(1, Side::Buy, 100, 10, 1000)),
+        Command::Create(make_limit_order(2, Side::Buy, 99, 10, 1001)),
+        Command::Update {
+            order_id: 2,
+            new_price: Price::from(102u64),
+            now_microseconds: 1002,
+        },
+        Command::Cancel(1),
+    ];
+    let results = engine.execute_batch(&mut commands);
+    assert_eq!(
+        results,
+        vec![
+            CommandResult::Created(Ok(())),
+            CommandResult::Created(Ok(())),
+            CommandResult::Updated(Ok(())),
+            CommandResult::Cancelled(Ok(())),
+        ]
+    );
+    assert_eq!(syncer.batches(), vec![4]);
+
+    let buys = get_book_state(book.as_ref(), Side::Buy);
+    assert_eq!(buys, vec![(2, Quantity::from(10u64))]);
+}
+
+#[test]
+fn test_cancel_where_whole_side() {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+
+    let mut orders = vec![
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 101, 10, 1001),
+        make_limit_order(3, Side::Sell, 110, 10, 1002),
+    ];
+    engine.create_orders(&mut orders);
+
+    let cancelled = engine.cancel_where(Side::Buy, None);
+    assert_eq!(cancelled, vec![2, 1]);
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+    assert_eq!(get_book_state(book.as_ref(), Side::Sell).len(), 1);
+
+    // Both cancels were delivered in a single batch, flagged as mass cancels
+    assert_eq!(syncer.batches().len(), 2);
+    assert!(syncer.cancelled().iter().all(|order| {
+        order.status() == OrderStatus::Cancelled
+            && order.cancel_reason() == Some(CancelReason::MassCancel)
+    }));
+}
+
+#[test]
+fn test_cancel_where_price_band() {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+
+    let mut orders = vec![
+        make_limit_order(1, Side::Sell, 100, 10, 1000),
+        make_limit_order(2, Side::Sell, 105, 10, 1001),
+        make_limit_order(3, Side::Sell, 110, 10, 1002),
+        make_limit_order(4, Side::Sell, 115, 10, 1003),
+    ];
+    engine.create_orders(&mut orders);
+
+    let band = Price::from(105u64)..=Price::from(110u64);
+    let cancelled = engine.cancel_where(Side::Sell, Some(band));
+    assert_eq!(cancelled, vec![2, 3]);
+
+    let remaining: Vec<OrderID> = get_book_state(book.as_ref(), Side::Sell)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(remaining, vec![1, 4]);
+}
+
+#[test]
+fn test_idempotent_retry_returns_original_result() {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    let engine = engine.with_idempotency(2);
+
+    let create = Command::Create(make_limit_order(1, Side::Buy, 100, 10, 1000));
+    let first = engine.execute_idempotent(7, create.clone());
+    let retried = engine.execute_idempotent(7, create.clone());
+    assert_eq!(first, CommandResult::Created(Ok(())));
+    assert_eq!(retried, first);
+    assert_eq!(syncer.added().len(), 1);
+
+    let cancel = Command::Cancel(1);
+    assert_eq!(
+        engine.execute_idempotent(8, cancel.clone()),
+        CommandResult::Cancelled(Ok(()))
+    );
+    assert_eq!(
+        engine.execute_idempotent(8, cancel),
+        CommandResult::Cancelled(Ok(()))
+    );
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+
+    // Key 7 was evicted by keys 8 and 9, so it is applied again
+    engine.execute_idempotent(9, Command::Cancel(42));
+    assert_eq!(
+        engine.execute_idempotent(7, create),
+        CommandResult::Created(Ok(()))
+    );
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 1);
+}
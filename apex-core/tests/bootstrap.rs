@@ -0,0 +1,146 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+/// Hands every change recorded since the last drain to a replica, as a live feed would carry it
+fn drain_into(syncer: &Recorder, replica: &mut SnapshotReplica) {
+    for (id, event) in syncer.take() {
+        match event {
+            SyncEvent::Added(order) | SyncEvent::Updated(order) | SyncEvent::Cancelled(order) => {
+                replica.apply(id, &order)
+            }
+            SyncEvent::Matched { updated, .. } => {
+                for order in updated {
+                    replica.apply(id, &order);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn new_engine() -> (Arc<Recorder>, DefaultMatchingEngine) {
+    let syncer = Arc::new(Recorder::default());
+    let (_book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 99, 5, 1000),
+        make_limit_order(2, Side::Buy, 98, 5, 1001),
+        make_limit_order(3, Side::Buy, 98, 5, 1002),
+        make_limit_order(4, Side::Sell, 101, 5, 1003),
+        make_limit_order(5, Side::Sell, 102, 5, 1004),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    (syncer, engine)
+}
+
+#[test]
+fn test_snapshot_stream_chunks_the_book_in_order() {
+    let (_syncer, engine) = new_engine();
+    let chunks: Vec<_> = engine.snapshot_stream(2).collect();
+    let layout: Vec<_> = chunks
+        .iter()
+        .map(|chunk| {
+            let ids: Vec<_> = chunk.orders.iter().map(|order| order.id).collect();
+            (chunk.index, chunk.section, ids, chunk.last)
+        })
+        .collect();
+    assert_eq!(
+        layout,
+        vec![
+            (0, SnapshotSection::Bids, vec![1, 2], false),
+            (1, SnapshotSection::Bids, vec![3], false),
+            (2, SnapshotSection::Asks, vec![4, 5], false),
+            (3, SnapshotSection::MarketOrders, vec![], true),
+        ]
+    );
+    assert!(
+        chunks
+            .iter()
+            .all(|chunk| chunk.sequence == chunks[0].sequence)
+    );
+}
+
+#[test]
+fn test_snapshot_chunk_binary_round_trip() {
+    let (_syncer, engine) = new_engine();
+    let mut chunk = engine.snapshot_stream(3).next().unwrap();
+    chunk.orders[0].time_in_force = TimeInForce::GoodTillDate(5000);
+    let bytes = chunk.encode();
+    assert_eq!(SnapshotChunk::decode(&bytes), Ok(chunk));
+
+    assert_eq!(
+        SnapshotChunk::decode(&bytes[..bytes.len() - 1]),
+        Err(SnapshotError::Truncated {
+            length: bytes.len() - 1
+        })
+    );
+    let mut corrupt = bytes.clone();
+    corrupt[0] = b'X';
+    assert_eq!(
+        SnapshotChunk::decode(&corrupt),
+        Err(SnapshotError::InvalidHeader)
+    );
+}
+
+#[test]
+fn test_replica_bootstraps_while_the_engine_keeps_matching() {
+    let (syncer, engine) = new_engine();
+    // Changes made before the stream starts are already in the snapshot
+    let mut replica = SnapshotReplica::new();
+    let mut stream = engine.snapshot_stream(2);
+    let first = stream.next().unwrap();
+    assert_eq!(
+        replica.load_chunk(SnapshotChunk::decode(&first.encode()).unwrap()),
+        Ok(false)
+    );
+
+    // The book changes between chunks: an order already sent is canceled,
+    // one not yet sent is partially filled, and new orders arrive on both sides
+    engine.cancel_order(1).unwrap();
+    engine
+        .create_order(&mut make_limit_order(6, Side::Sell, 98, 7, 1005))
+        .unwrap();
+    engine.match_orders();
+    engine
+        .create_order(&mut make_limit_order(7, Side::Buy, 97, 1, 1006))
+        .unwrap();
+    drain_into(&syncer, &mut replica);
+    assert!(!replica.is_complete());
+
+    for chunk in stream {
+        replica
+            .load_chunk(SnapshotChunk::decode(&chunk.encode()).unwrap())
+            .unwrap();
+    }
+    assert!(replica.is_complete());
+
+    // Live events after the switch apply directly
+    engine.update_order(5, Price::from(103u64), 1007).unwrap();
+    drain_into(&syncer, &mut replica);
+    assert_eq!(*replica.book(), engine.snapshot());
+}
+
+#[test]
+fn test_replica_refuses_chunks_out_of_order() {
+    let (_syncer, engine) = new_engine();
+    let chunks: Vec<_> = engine.snapshot_stream(2).collect();
+    let mut replica = SnapshotReplica::new();
+    assert_eq!(
+        replica.load_chunk(chunks[1].clone()),
+        Err(SnapshotError::OutOfOrder {
+            expected: 0,
+            received: 1
+        })
+    );
+    for chunk in chunks.iter().cloned() {
+        replica.load_chunk(chunk).unwrap();
+    }
+    assert_eq!(
+        replica.load_chunk(chunks[0].clone()),
+        Err(SnapshotError::AlreadyComplete)
+    );
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::{Arc, Mutex};
+
+fn new_engine(config: BookConfig) -> (Arc<Recorder>, Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new()
+        .with_syncer(syncer.clone())
+        .with_config(config)
+        .build();
+    (syncer, book, engine)
+}
+
+/// Rests five asks of 2 and sends a bid for all of them
+fn giant_cross(engine: &DefaultMatchingEngine) {
+    for id in 1..=5 {
+        engine
+            .create_order(&mut make_limit_order(
+                id,
+                Side::Sell,
+                100 + id,
+                2,
+                1000 + id,
+            ))
+            .unwrap();
+    }
+    engine
+        .create_order(&mut make_limit_order(10, Side::Buy, 110, 10, 2000))
+        .unwrap();
+}
+
+#[test]
+fn test_sweep_resumes_where_the_trade_budget_stopped_it() {
+    let (counter, book, engine) = new_engine(BookConfig {
+        max_cycle_trades: Some(2),
+        ..BookConfig::default()
+    });
+    giant_cross(&engine);
+
+    engine.match_orders();
+    assert!(engine.match_budget_exhausted());
+    assert_eq!(counter.trades().len() / 2, 2);
+    // The partially filled taker keeps resting, and a cancel gets in between cycles
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(10, Quantity::from(6u64))]
+    );
+    engine.cancel_order(4).unwrap();
+
+    // Orders 3 and 5 use up the budget exactly, with nothing left to match
+    engine.match_orders();
+    assert!(!engine.match_budget_exhausted());
+    assert_eq!(counter.trades().len() / 2, 4);
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(10, Quantity::from(2u64))]
+    );
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
+
+#[test]
+fn test_spent_time_budget_defers_matching() {
+    let (counter, book, engine) = new_engine(BookConfig {
+        max_cycle_micros: Some(0),
+        ..BookConfig::default()
+    });
+    giant_cross(&engine);
+    engine
+        .create_order(&mut make_market_order(11, Side::Sell, 1, 2001))
+        .unwrap();
+
+    engine.match_orders();
+    assert!(engine.match_budget_exhausted());
+    assert_eq!(counter.trades().len() / 2, 0);
+    assert_eq!(get_book_state(book.as_ref(), Side::Sell).len(), 5);
+
+    engine.reload_config(BookConfig::default());
+    engine.match_orders();
+    assert!(!engine.match_budget_exhausted());
+    assert_eq!(counter.trades().len() / 2, 6);
+}
+
+/// Pauses or halts the engine after its first match
+struct Interrupter {
+    engine: Mutex<Option<Arc<DefaultMatchingEngine>>>,
+    halt: bool,
+}
+
+impl OrderBookSyncer for Interrupter {
+    fn add_order(&self, _id: u64, _order: &Order) {}
+
+    fn update_order(&self, _id: u64, _order: &Order) {}
+
+    fn cancel_order(&self, _id: u64, _order: &Order) {}
+
+    fn matched(&self, _id: u64, _updated: &[Order], _trades: &[Trade]) {
+        if let Some(engine) = self.engine.lock().unwrap().take() {
+            if self.halt {
+                engine.set_mode(EngineMode::Halted);
+            } else {
+                engine.pause_matching();
+            }
+        }
+    }
+}
+
+fn interrupted_cycle(halt: bool) -> (Arc<DefaultOrderBook>, Arc<DefaultMatchingEngine>) {
+    let interrupter = Arc::new(Interrupter {
+        engine: Mutex::new(None),
+        halt,
+    });
+    let (book, engine) = TestEngine::new().with_syncer(interrupter.clone()).build();
+    let engine = Arc::new(engine);
+    for (id, side, price, ts) in [
+        (1, Side::Sell, 100, 1000),
+        (2, Side::Sell, 100, 1001),
+        (3, Side::Buy, 101, 1002),
+        (4, Side::Buy, 101, 1003),
+    ] {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, 5, ts))
+            .unwrap();
+    }
+    *interrupter.engine.lock().unwrap() = Some(engine.clone());
+    engine.match_orders();
+    // The cycle stopped after the first taker, with the second still crossing
+    assert!(engine.match_budget_exhausted());
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(2, Quantity::from(5u64))]
+    );
+    (book, engine)
+}
+
+#[test]
+fn test_pause_stops_a_cycle_between_takers_until_resumed() {
+    let (book, engine) = interrupted_cycle(false);
+    assert!(engine.is_matching_paused());
+    engine.match_orders();
+    assert_eq!(get_book_state(book.as_ref(), Side::Sell).len(), 1);
+
+    assert!(engine.resume_matching());
+    assert!(!engine.resume_matching());
+    engine.match_orders();
+    assert!(!engine.match_budget_exhausted());
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+}
+
+#[test]
+fn test_halt_stops_a_running_cycle() {
+    let (book, engine) = interrupted_cycle(true);
+    engine.set_mode(EngineMode::Normal);
+    engine.match_orders();
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
@@ -0,0 +1,59 @@
+use apex_core::prelude::*;
+
+#[test]
+fn test_limit_and_market_constructors_are_valid() {
+    let limit = Order::limit(
+        1,
+        7,
+        Side::Buy,
+        Price::from(100u64),
+        Quantity::from(5u64),
+        1000,
+    );
+    assert_eq!(limit.order_type, OrderType::Limit);
+    assert_eq!(limit.time_in_force, TimeInForce::GoodTillCancelled);
+    assert_eq!(limit.quantity(), Quantity::from(5u64));
+    assert_eq!(limit.created_at, 1000);
+    assert!(limit.validate().is_ok());
+
+    let market = Order::market(2, 7, Side::Sell, Quantity::from(3u64), 1001);
+    assert_eq!(market.order_type, OrderType::Market);
+    assert_eq!(market.match_strategy, MatchStrategy::ImmediateOrCancel);
+    assert!(market.validate().is_ok());
+}
+
+#[test]
+fn test_builder_sets_fields_and_validates() {
+    let order = Order::builder(3, Side::Buy)
+        .user_id(9)
+        .market()
+        .match_strategy(MatchStrategy::FillOrKill)
+        .slippage_tolerance(SlippageTolerance(50))
+        .quantity(Quantity::from(10u64))
+        .timestamp(2000)
+        .build()
+        .unwrap();
+    assert_eq!(order.user_id, 9);
+    assert_eq!(order.match_strategy, MatchStrategy::FillOrKill);
+    assert_eq!(order.slippage_tolerance, Some(SlippageTolerance(50)));
+    assert_eq!(order.updated_at, 2000);
+
+    let invalid = Order::builder(4, Side::Sell)
+        .limit(Price::from(100u64))
+        .time_in_force(TimeInForce::None)
+        .quantity(Quantity::from(1u64))
+        .build();
+    assert_eq!(
+        invalid.unwrap_err(),
+        OrderValidationError::InvalidTimeInForce
+    );
+
+    let invalid = Order::builder(5, Side::Sell)
+        .limit(Price::from(100u64))
+        .slippage_tolerance(SlippageTolerance(5))
+        .build();
+    assert_eq!(
+        invalid.unwrap_err(),
+        OrderValidationError::SlippageNotApplicable
+    );
+}
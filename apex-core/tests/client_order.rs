@@ -0,0 +1,41 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+
+fn make_client_order(id: u64, user_id: u64, client_order_id: ClientOrderID) -> Order {
+    let mut order = make_limit_order(id, Side::Buy, 100, 10, 1000 + id);
+    order.user_id = user_id;
+    order.client_order_id = Some(client_order_id);
+    order
+}
+
+#[test]
+fn test_cancel_by_client_order_id() {
+    let (book, engine) = TestEngine::new().build();
+    for (id, user_id, client_order_id) in [(1, 1, 7), (2, 2, 7), (3, 1, 8)] {
+        engine
+            .create_order(&mut make_client_order(id, user_id, client_order_id))
+            .unwrap();
+    }
+
+    assert_eq!(engine.cancel_client_order(2, 7), Ok(2));
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(10u64)), (3, Quantity::from(10u64))]
+    );
+    // User 2 has no order 8, and its order 7 is gone
+    assert_eq!(
+        engine.cancel_client_order(2, 8),
+        Err(CancelOrderError::OrderNotFound)
+    );
+    assert_eq!(
+        engine.cancel_client_order(2, 7),
+        Err(CancelOrderError::OrderNotFound)
+    );
+    assert_eq!(engine.cancel_client_order(1, 8), Ok(3));
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(10u64))]
+    );
+}
@@ -2,28 +2,25 @@ use apex_core::prelude::*;
 use crossbeam::epoch;
 use crossbeam::epoch::default_collector;
 use crossbeam_skiplist::SkipList;
-use std::cell::UnsafeCell;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
 
 /// Quickly generate a simple limit order for testing
+#[allow(dead_code)]
 pub fn make_limit_order(id: u64, side: Side, price: u64, qty: u64, ts: u64) -> Order {
-    Order {
-        id,
-        user_id: 1,
-        side,
-        price: Price::from(price),
-        quantity: UnsafeCell::new(Quantity::from(qty)),
-        created_at: ts,
-        updated_at: ts,
-        ..Order::default()
-    }
+    Order::limit(id, 1, side, Price::from(price), Quantity::from(qty), ts)
 }
 
 /// Quickly generate a market order for testing
 #[allow(dead_code)]
 pub fn make_market_order(id: u64, side: Side, qty: u64, ts: u64) -> Order {
-    let mut value = make_limit_order(id, side, 0, qty, ts);
-    value.order_type = OrderType::Market;
-    value
+    Order::market(id, 1, side, Quantity::from(qty), ts)
+}
+
+/// Get the ids of a list of orders
+#[allow(dead_code)]
+pub fn order_ids(orders: &[Order]) -> Vec<OrderID> {
+    orders.iter().map(|order| order.id).collect()
 }
 
 /// Get the current state of a side of the book
@@ -36,6 +33,235 @@ pub fn get_book_state(book: &dyn OrderBookWalker, side: Side) -> Vec<(OrderID, Q
         .collect()
 }
 
+/// Builds the book and engine a test starts from
+#[allow(dead_code)]
+pub struct TestEngine {
+    syncer: Arc<dyn OrderBookSyncer>,
+    config: BookConfig,
+    invariant_checks: bool,
+}
+
+#[allow(dead_code)]
+impl TestEngine {
+    /// Starts from an empty book without a syncer and with the default configuration
+    pub fn new() -> Self {
+        Self {
+            syncer: Arc::new(EmptyOrderBookSyncer {}),
+            config: BookConfig::default(),
+            invariant_checks: false,
+        }
+    }
+
+    /// Sets the syncer the book reports its changes to
+    pub fn with_syncer(mut self, syncer: Arc<dyn OrderBookSyncer>) -> Self {
+        self.syncer = syncer;
+        self
+    }
+
+    /// Sets the configuration the engine starts with
+    pub fn with_config(mut self, config: BookConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Checks the book invariants at the end of every match cycle
+    pub fn with_invariant_checks(mut self) -> Self {
+        self.invariant_checks = true;
+        self
+    }
+
+    /// Builds the book and an engine over it
+    pub fn build(self) -> (Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+        let book = Arc::new(
+            DefaultOrderBook::new(Arc::new(AtomicU64::new(1)), self.syncer)
+                .with_invariant_checks(self.invariant_checks),
+        );
+        let engine = DefaultMatchingEngine::new(book.clone()).with_config(self.config);
+        (book, engine)
+    }
+}
+
+impl Default for TestEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A syncer that keeps every callback it receives, for assertions.
+/// Batches are kept whole; the order accessors look into them as well.
+#[derive(Default)]
+pub struct Recorder {
+    events: Mutex<Vec<(u64, SyncEvent)>>,
+}
+
+#[allow(dead_code)]
+impl Recorder {
+    fn push(&self, id: u64, event: SyncEvent) {
+        self.events.lock().unwrap().push((id, event));
+    }
+
+    /// Gets every callback received so far, with the id of the book change it belongs to
+    pub fn events(&self) -> Vec<(u64, SyncEvent)> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Takes every callback received so far
+    pub fn take(&self) -> Vec<(u64, SyncEvent)> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+
+    /// Gets the single-order events, with the events of batches in place
+    pub fn book_events(&self) -> Vec<(u64, BookEvent)> {
+        let mut book_events = Vec::new();
+        for (id, event) in self.events() {
+            match event {
+                SyncEvent::Added(order) => book_events.push((id, BookEvent::Added(order))),
+                SyncEvent::Updated(order) => book_events.push((id, BookEvent::Updated(order))),
+                SyncEvent::Cancelled(order) => book_events.push((id, BookEvent::Cancelled(order))),
+                SyncEvent::Rejected(order) => book_events.push((id, BookEvent::Rejected(order))),
+                SyncEvent::Batch(events) => {
+                    book_events.extend(events.into_iter().map(|event| (id, event)))
+                }
+                _ => {}
+            }
+        }
+        book_events
+    }
+
+    /// Gets the orders accepted into the book
+    pub fn added(&self) -> Vec<Order> {
+        self.orders(|event| matches!(event, BookEvent::Added(_)))
+    }
+
+    /// Gets the orders updated outside of matches
+    pub fn updated(&self) -> Vec<Order> {
+        self.orders(|event| matches!(event, BookEvent::Updated(_)))
+    }
+
+    /// Gets the orders canceled outside of matches
+    pub fn cancelled(&self) -> Vec<Order> {
+        self.orders(|event| matches!(event, BookEvent::Cancelled(_)))
+    }
+
+    /// Gets the orders rejected before they reached the book
+    pub fn rejected(&self) -> Vec<Order> {
+        self.orders(|event| matches!(event, BookEvent::Rejected(_)))
+    }
+
+    fn orders(&self, filter: impl Fn(&BookEvent) -> bool) -> Vec<Order> {
+        self.book_events()
+            .into_iter()
+            .filter(|(_, event)| filter(event))
+            .map(|(_, event)| match event {
+                BookEvent::Added(order)
+                | BookEvent::Updated(order)
+                | BookEvent::Cancelled(order)
+                | BookEvent::Rejected(order) => order,
+            })
+            .collect()
+    }
+
+    /// Gets the updated orders and trades of every match
+    pub fn matches(&self) -> Vec<(Vec<Order>, Vec<Trade>)> {
+        self.events()
+            .into_iter()
+            .filter_map(|(_, event)| match event {
+                SyncEvent::Matched { updated, trades } => Some((updated, trades)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Gets the orders updated by matches
+    pub fn matched_orders(&self) -> Vec<Order> {
+        self.matches()
+            .into_iter()
+            .flat_map(|(updated, _)| updated)
+            .collect()
+    }
+
+    /// Gets the trades of every match
+    pub fn trades(&self) -> Vec<Trade> {
+        self.matches()
+            .into_iter()
+            .flat_map(|(_, trades)| trades)
+            .collect()
+    }
+
+    /// Gets the trade corrections
+    pub fn corrections(&self) -> Vec<TradeCorrection> {
+        self.events()
+            .into_iter()
+            .filter_map(|(_, event)| match event {
+                SyncEvent::TradeCorrected(correction) => Some(correction),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Gets the rescales
+    pub fn rescales(&self) -> Vec<Rescale> {
+        self.events()
+            .into_iter()
+            .filter_map(|(_, event)| match event {
+                SyncEvent::Rescaled(rescale) => Some(rescale),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Gets the number of events of every batch
+    pub fn batches(&self) -> Vec<usize> {
+        self.events()
+            .into_iter()
+            .filter_map(|(_, event)| match event {
+                SyncEvent::Batch(events) => Some(events.len()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl OrderBookSyncer for Recorder {
+    fn add_order(&self, id: u64, order: &Order) {
+        self.push(id, SyncEvent::Added(order.clone()));
+    }
+
+    fn update_order(&self, id: u64, order: &Order) {
+        self.push(id, SyncEvent::Updated(order.clone()));
+    }
+
+    fn cancel_order(&self, id: u64, order: &Order) {
+        self.push(id, SyncEvent::Cancelled(order.clone()));
+    }
+
+    fn reject_order(&self, id: u64, order: &Order) {
+        self.push(id, SyncEvent::Rejected(order.clone()));
+    }
+
+    fn matched(&self, id: u64, updated: &[Order], trades: &[Trade]) {
+        self.push(
+            id,
+            SyncEvent::Matched {
+                updated: updated.to_vec(),
+                trades: trades.to_vec(),
+            },
+        );
+    }
+
+    fn trade_corrected(&self, id: u64, correction: &TradeCorrection) {
+        self.push(id, SyncEvent::TradeCorrected(correction.clone()));
+    }
+
+    fn rescaled(&self, id: u64, rescale: &Rescale) {
+        self.push(id, SyncEvent::Rescaled(*rescale));
+    }
+
+    fn batch(&self, id: u64, events: &[BookEvent]) {
+        self.push(id, SyncEvent::Batch(events.to_vec()));
+    }
+}
+
 #[test]
 fn test_skiplist_next_when_delete() {
     let list = SkipList::new(default_collector().clone());
@@ -0,0 +1,107 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::num::NonZeroU64;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+#[test]
+fn test_compaction_drops_index_entries_of_filled_orders() {
+    let (book, engine) = TestEngine::new().build();
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 100, 5, 1000),
+        make_limit_order(2, Side::Buy, 100, 5, 1001),
+        make_limit_order(3, Side::Buy, 99, 5, 1002),
+        make_market_order(4, Side::Sell, 2, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.match_orders();
+
+    // Filled ids stay claimed until the book is compacted
+    let mut reused = make_limit_order(2, Side::Buy, 90, 5, 1004);
+    assert_eq!(
+        engine.create_order(&mut reused),
+        Err(RejectReason::DuplicateOrderId)
+    );
+    let report = engine.compact();
+    assert_eq!(
+        report,
+        CompactionReport {
+            book_entries: 0,
+            index_entries: 2,
+        }
+    );
+    assert_eq!(engine.compact(), CompactionReport::default());
+
+    let mut reused = make_limit_order(2, Side::Buy, 90, 5, 1005);
+    assert_eq!(engine.create_order(&mut reused), Ok(()));
+    assert_eq!(
+        book.get_order(1).map(|order| order.quantity),
+        Some(Quantity::from(2u64))
+    );
+    assert!(book.get_order(3).is_some());
+}
+
+#[test]
+fn test_compaction_runs_every_interval() {
+    let (_book, engine) = TestEngine::new()
+        .with_config(BookConfig {
+            compaction_interval: NonZeroU64::new(2),
+            ..BookConfig::default()
+        })
+        .build();
+    let mut maker = make_limit_order(1, Side::Sell, 100, 5, 1000);
+    let mut taker = make_limit_order(2, Side::Buy, 100, 5, 1001);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+
+    let mut reused = make_limit_order(2, Side::Buy, 90, 5, 1002);
+    assert_eq!(
+        engine.create_order(&mut reused),
+        Err(RejectReason::DuplicateOrderId)
+    );
+    engine.match_orders();
+    assert_eq!(engine.create_order(&mut reused), Ok(()));
+}
+
+#[test]
+fn test_compaction_keeps_index_entries_of_concurrent_inserts() {
+    let (book, engine) = TestEngine::new().build();
+    let engine = Arc::new(engine);
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let compactor = {
+        let engine = engine.clone();
+        let stop = stop.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                engine.compact();
+            }
+        })
+    };
+    for id in 1..=2000u64 {
+        let mut order = make_limit_order(id, Side::Buy, 100 - id % 10, 1, 1000 + id);
+        engine.create_order(&mut order).unwrap();
+    }
+    stop.store(true, Ordering::Relaxed);
+    compactor.join().unwrap();
+
+    // Every resting order is still reachable by id and still claims it
+    for id in 1..=2000u64 {
+        assert!(
+            book.get_order(id).is_some(),
+            "order {id} lost its index entry"
+        );
+        let mut duplicate = make_limit_order(id, Side::Buy, 80, 1, 5000 + id);
+        assert_eq!(
+            engine.create_order(&mut duplicate),
+            Err(RejectReason::DuplicateOrderId)
+        );
+    }
+    assert_eq!(engine.compact().index_entries, 0);
+}
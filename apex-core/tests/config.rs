@@ -0,0 +1,119 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use crypto_bigint::NonZero;
+use std::sync::Arc;
+
+fn new_engine(config: BookConfig) -> (Arc<Recorder>, DefaultMatchingEngine) {
+    let syncer = Arc::new(Recorder::default());
+    let (_book, engine) = TestEngine::new()
+        .with_syncer(syncer.clone())
+        .with_config(config)
+        .build();
+    (syncer, engine)
+}
+
+/// Gets the id and config epoch of every order added to the book
+fn added_epochs(syncer: &Recorder) -> Vec<(OrderID, u64)> {
+    syncer
+        .added()
+        .iter()
+        .map(|order| (order.id, order.config_epoch))
+        .collect()
+}
+
+fn tick(size: u64) -> Option<NonZero<Price>> {
+    NonZero::new(Price::from(size)).into()
+}
+
+fn lot(size: u64) -> Option<NonZero<Quantity>> {
+    NonZero::new(Quantity::from(size)).into()
+}
+
+#[test]
+fn test_tick_and_lot_sizes_reject_off_grid_orders() {
+    let (_syncer, engine) = new_engine(BookConfig {
+        tick_size: tick(5),
+        lot_size: lot(10),
+        ..BookConfig::default()
+    });
+    let mut off_tick = make_limit_order(1, Side::Buy, 101, 10, 1000);
+    assert_eq!(
+        engine.create_order(&mut off_tick),
+        Err(RejectReason::OffTick)
+    );
+    let mut off_lot = make_limit_order(2, Side::Buy, 100, 15, 1001);
+    assert_eq!(engine.create_order(&mut off_lot), Err(RejectReason::OffLot));
+    let mut market = make_market_order(3, Side::Sell, 7, 1002);
+    assert_eq!(engine.create_order(&mut market), Err(RejectReason::OffLot));
+
+    let mut on_grid = make_limit_order(4, Side::Buy, 100, 20, 1003);
+    assert_eq!(engine.create_order(&mut on_grid), Ok(()));
+    assert_eq!(RejectReason::OffTick.code(), 117);
+    assert_eq!(RejectReason::OffLot.code(), 118);
+}
+
+#[test]
+fn test_reload_applies_only_to_subsequent_orders() {
+    let (syncer, engine) = new_engine(BookConfig {
+        tick_size: tick(1),
+        max_price: Some(Price::from(200u64)),
+        ..BookConfig::default()
+    });
+    assert_eq!(engine.config_epoch(), 0);
+    let mut resting = make_limit_order(1, Side::Sell, 103, 5, 1000);
+    engine.create_order(&mut resting).unwrap();
+
+    let epoch = engine.reload_config(BookConfig {
+        tick_size: tick(5),
+        max_price: Some(Price::from(110u64)),
+        taker_fee_bps: 7,
+        maker_fee_bps: -2,
+        ..BookConfig::default()
+    });
+    assert_eq!(epoch, 1);
+    assert_eq!(engine.config_epoch(), 1);
+    assert_eq!(engine.config().fee_bps(TradeRole::Taker), 7);
+    assert_eq!(engine.config().fee_bps(TradeRole::Maker), -2);
+
+    // New orders follow the new tick and band
+    let mut off_tick = make_limit_order(2, Side::Sell, 104, 5, 1001);
+    assert_eq!(
+        engine.create_order(&mut off_tick),
+        Err(RejectReason::OffTick)
+    );
+    assert_eq!(off_tick.config_epoch, 1);
+    let mut out_of_band = make_limit_order(3, Side::Sell, 120, 5, 1002);
+    assert_eq!(
+        engine.create_order(&mut out_of_band),
+        Err(RejectReason::PriceOutOfBand)
+    );
+    let mut buy = make_limit_order(4, Side::Buy, 105, 5, 1003);
+    assert_eq!(engine.create_order(&mut buy), Ok(()));
+
+    // The order accepted under the old rules still rests and matches
+    engine.match_orders();
+    assert_eq!(engine.open_orders(1), vec![]);
+    assert_eq!(added_epochs(&syncer), vec![(1, 0), (4, 1)]);
+}
+
+#[test]
+fn test_batch_orders_carry_the_epoch_they_were_admitted_under() {
+    let (syncer, engine) = new_engine(BookConfig::default());
+    engine.reload_config(BookConfig::default());
+    let epoch = engine.reload_config(BookConfig {
+        lot_size: lot(2),
+        ..BookConfig::default()
+    });
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 100, 4, 1000),
+        make_limit_order(2, Side::Buy, 99, 3, 1001),
+    ];
+    assert_eq!(
+        engine.create_orders(&mut orders),
+        vec![Ok(()), Err(RejectReason::OffLot)]
+    );
+    assert!(orders.iter().all(|order| order.config_epoch == epoch));
+    assert_eq!(added_epochs(&syncer), vec![(1, 2)]);
+}
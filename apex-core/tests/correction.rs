@@ -0,0 +1,87 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine() -> (Arc<Recorder>, Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let recorder = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(recorder.clone()).build();
+    (recorder, book, engine.with_trade_journal(16))
+}
+
+#[test]
+fn test_bust_restores_resting_quantity() {
+    let (recorder, book, engine) = new_engine();
+    let mut maker = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let mut taker = make_limit_order(2, Side::Buy, 100, 4, 1001);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+    let trade_id = recorder.trades()[0].trade_id;
+
+    let correction = engine.bust_trade(trade_id).unwrap();
+    assert_eq!(correction.kind, TradeCorrectionKind::Bust);
+    assert_eq!(correction.original.len(), 2);
+    // The filled buy left the book, so only the resting sell gets its quantity back
+    assert_eq!(correction.restored, vec![1]);
+    let restored = book.get_order(1).unwrap();
+    assert_eq!(restored.quantity, Quantity::from(10u64));
+    assert_eq!(restored.filled_quantity, Quantity::ZERO);
+    assert_eq!(restored.status, OrderStatus::Placed);
+    assert_eq!(recorder.corrections(), vec![correction]);
+
+    assert_eq!(
+        engine.bust_trade(trade_id),
+        Err(TradeCorrectionError::TradeNotFound)
+    );
+}
+
+#[test]
+fn test_price_correction_keeps_orders() {
+    let (recorder, book, engine) = new_engine();
+    let mut maker = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let mut taker = make_limit_order(2, Side::Buy, 100, 4, 1001);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+    let trade_id = recorder.trades()[0].trade_id;
+
+    assert_eq!(
+        engine.correct_trade_price(trade_id, Price::ZERO),
+        Err(TradeCorrectionError::InvalidPrice)
+    );
+    let correction = engine
+        .correct_trade_price(trade_id, Price::from(99u64))
+        .unwrap();
+    assert_eq!(
+        correction.kind,
+        TradeCorrectionKind::Price(Price::from(99u64))
+    );
+    assert!(
+        correction
+            .original
+            .iter()
+            .all(|trade| trade.price == Price::from(100u64))
+    );
+    assert!(correction.restored.is_empty());
+    assert_eq!(book.get_order(1).unwrap().quantity, Quantity::from(6u64));
+
+    // A later bust reports the corrected price as the original
+    let bust = engine.bust_trade(trade_id).unwrap();
+    assert!(
+        bust.original
+            .iter()
+            .all(|trade| trade.price == Price::from(99u64))
+    );
+}
+
+#[test]
+fn test_corrections_need_a_journal() {
+    let (_book, engine) = TestEngine::new().build();
+    assert_eq!(
+        engine.bust_trade(1),
+        Err(TradeCorrectionError::JournalDisabled)
+    );
+    assert_eq!(TradeCorrectionError::TradeNotFound.code(), 601);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+
+fn maker_only(id: u64, side: Side, price: u64) -> Order {
+    let mut order = make_limit_order(id, side, price, 10, 1000 + id);
+    order.liquidity_directive = LiquidityDirective::MakerOnly;
+    order
+}
+
+#[test]
+fn test_crossed_book_is_reported_with_its_duration() {
+    let (book, engine) = TestEngine::new().build();
+    let monitor = CrossedBookMonitor::new();
+    for mut order in [maker_only(1, Side::Sell, 100), maker_only(2, Side::Buy, 99)] {
+        engine.create_order(&mut order).unwrap();
+    }
+    assert_eq!(monitor.check(book.as_ref(), 5000), None);
+
+    engine
+        .create_order(&mut maker_only(3, Side::Buy, 100))
+        .unwrap();
+    engine.match_orders();
+    let locked = monitor.check(book.as_ref(), 6000).unwrap();
+    assert_eq!(locked.condition, BookCondition::Locked);
+    assert_eq!(locked.bid_order_ids, vec![3]);
+    assert_eq!(locked.ask_order_ids, vec![1]);
+    assert_eq!(locked.duration_micros, 0);
+
+    engine
+        .create_order(&mut maker_only(4, Side::Buy, 102))
+        .unwrap();
+    let crossed = monitor.check(book.as_ref(), 9000).unwrap();
+    assert_eq!(crossed.condition, BookCondition::Crossed);
+    assert_eq!(crossed.bid_order_ids, vec![4, 3]);
+    assert_eq!(crossed.since, 6000);
+    assert_eq!(crossed.duration_micros, 3000);
+
+    engine.cancel_order(1).unwrap();
+    assert_eq!(monitor.check(book.as_ref(), 9500), None);
+}
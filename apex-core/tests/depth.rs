@@ -0,0 +1,267 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+fn new_engine() -> (Arc<DepthSyncer>, DefaultMatchingEngine) {
+    let syncer = Arc::new(DepthSyncer::new(Arc::new(EmptyOrderBookSyncer {})));
+    let (_book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    (syncer, engine)
+}
+
+/// Rebuilds the levels of a side from a stream of updates
+fn rebuild(updates: &[DepthUpdate], side: Side) -> Vec<PriceLevel> {
+    let mut levels = BTreeMap::new();
+    for update in updates {
+        if let DepthUpdate::Level {
+            side: level_side,
+            level,
+        } = update
+        {
+            if *level_side != side {
+                continue;
+            }
+            if level.orders == 0 {
+                levels.remove(&level.price);
+            } else {
+                levels.insert(level.price, *level);
+            }
+        }
+    }
+    let levels: Vec<_> = levels.into_values().collect();
+    match side {
+        Side::Buy => levels.into_iter().rev().collect(),
+        Side::Sell => levels,
+    }
+}
+
+#[test]
+fn test_buffered_subscription_receives_every_change() {
+    let (syncer, engine) = new_engine();
+    let subscription = syncer.subscribe(DeliveryMode::Buffered);
+    engine
+        .create_order(&mut make_limit_order(1, Side::Sell, 100, 5, 1000))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(2, Side::Sell, 100, 3, 1001))
+        .unwrap();
+    let updates = subscription.drain();
+    assert_eq!(
+        updates,
+        vec![
+            DepthUpdate::Level {
+                side: Side::Sell,
+                level: PriceLevel {
+                    price: Price::from(100u64),
+                    quantity: Quantity::from(5u64),
+                    orders: 1,
+                },
+            },
+            DepthUpdate::Bbo {
+                bid: None,
+                ask: Some(PriceLevel {
+                    price: Price::from(100u64),
+                    quantity: Quantity::from(5u64),
+                    orders: 1,
+                }),
+            },
+            DepthUpdate::Level {
+                side: Side::Sell,
+                level: PriceLevel {
+                    price: Price::from(100u64),
+                    quantity: Quantity::from(8u64),
+                    orders: 2,
+                },
+            },
+            DepthUpdate::Bbo {
+                bid: None,
+                ask: Some(PriceLevel {
+                    price: Price::from(100u64),
+                    quantity: Quantity::from(8u64),
+                    orders: 2,
+                }),
+            },
+        ]
+    );
+    assert_eq!(subscription.conflated(), 0);
+}
+
+#[test]
+fn test_conflated_subscription_keeps_the_latest_state_per_level() {
+    let (syncer, engine) = new_engine();
+    let buffered = syncer.subscribe(DeliveryMode::Buffered);
+    let conflated = syncer.subscribe(DeliveryMode::Conflated);
+    for (id, price) in [(1, 100), (2, 101), (3, 100), (4, 102), (5, 101)] {
+        engine
+            .create_order(&mut make_limit_order(id, Side::Sell, price, 2, 1000 + id))
+            .unwrap();
+    }
+    engine
+        .create_order(&mut make_limit_order(6, Side::Buy, 100, 3, 1006))
+        .unwrap();
+    engine.match_orders();
+    engine.cancel_order(4).unwrap();
+
+    // The lagging consumer gets one update per level, in the order they first changed
+    assert_eq!(conflated.pending(), 5);
+    assert!(conflated.conflated() > 0);
+    let updates = conflated.drain();
+    let keys: Vec<_> = updates
+        .iter()
+        .map(|update| match update {
+            DepthUpdate::Level { side, level } => Some((*side, level.price)),
+            DepthUpdate::Bbo { .. } => None,
+        })
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            Some((Side::Sell, Price::from(100u64))),
+            None,
+            Some((Side::Sell, Price::from(101u64))),
+            Some((Side::Sell, Price::from(102u64))),
+            Some((Side::Buy, Price::from(100u64))),
+        ]
+    );
+    assert_eq!(
+        updates[1],
+        DepthUpdate::Bbo {
+            bid: None,
+            ask: Some(PriceLevel {
+                price: Price::from(100u64),
+                quantity: Quantity::from(1u64),
+                orders: 1,
+            }),
+        }
+    );
+
+    // Both consumers end up with the engine's book
+    let book = engine.depth(Side::Sell, 10);
+    assert_eq!(rebuild(&updates, Side::Sell), book);
+    assert_eq!(rebuild(&buffered.drain(), Side::Sell), book);
+    assert!(rebuild(&updates, Side::Buy).is_empty());
+}
+
+#[test]
+fn test_dropped_subscription_is_removed() {
+    let (syncer, engine) = new_engine();
+    let subscription = syncer.subscribe(DeliveryMode::Conflated);
+    assert_eq!(subscription.mode(), DeliveryMode::Conflated);
+    assert_eq!(syncer.subscribers(), 1);
+    drop(subscription);
+    assert_eq!(syncer.subscribers(), 0);
+    engine
+        .create_order(&mut make_limit_order(1, Side::Buy, 100, 5, 1000))
+        .unwrap();
+    assert_eq!(syncer.subscribers(), 0);
+}
+
+#[test]
+fn test_depth_ladder_accumulates_from_the_best_price() {
+    let (syncer, engine) = new_engine();
+    for (id, side, price, qty) in [
+        (1, Side::Buy, 99, 4),
+        (2, Side::Buy, 100, 3),
+        (3, Side::Buy, 99, 1),
+        (4, Side::Buy, 97, 2),
+        (5, Side::Sell, 101, 6),
+    ] {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, qty, 1000 + id))
+            .unwrap();
+    }
+    let ladder = engine.depth_ladder(Side::Buy, 10);
+    let prices: Vec<_> = [100u64, 99, 97].into_iter().map(Price::from).collect();
+    let quantities: Vec<_> = [3u64, 5, 2].into_iter().map(Quantity::from).collect();
+    let cumulative: Vec<_> = [3u64, 8, 10].into_iter().map(Quantity::from).collect();
+    assert_eq!(ladder.prices, prices);
+    assert_eq!(ladder.quantities, quantities);
+    assert_eq!(ladder.cumulative, cumulative);
+    assert_eq!(ladder.total(), Quantity::from(10u64));
+    // The syncer's levels give the same ladder
+    assert_eq!(syncer.depth_ladder(Side::Buy, 10), ladder);
+    assert_eq!(syncer.depth_ladder(Side::Buy, 2).len(), 2);
+    assert_eq!(syncer.depth_ladder(Side::Sell, 10).len(), 1);
+
+    // Selling 4 reaches 99, selling 8 clears 99 exactly, and 11 cannot be filled
+    assert_eq!(
+        ladder.price_for(Quantity::from(3u64)),
+        Some(Price::from(100u64))
+    );
+    assert_eq!(
+        ladder.price_for(Quantity::from(4u64)),
+        Some(Price::from(99u64))
+    );
+    assert_eq!(
+        ladder.price_for(Quantity::from(8u64)),
+        Some(Price::from(99u64))
+    );
+    assert_eq!(ladder.price_for(Quantity::from(11u64)), None);
+}
+
+#[test]
+fn test_top_of_book_returns_both_sides() {
+    let (_syncer, engine) = new_engine();
+    for (id, side, price, qty) in [
+        (1, Side::Buy, 99, 4),
+        (2, Side::Buy, 100, 3),
+        (3, Side::Buy, 98, 1),
+        (4, Side::Sell, 102, 2),
+        (5, Side::Sell, 101, 6),
+        (6, Side::Sell, 101, 1),
+    ] {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, qty, 1000 + id))
+            .unwrap();
+    }
+    let top = engine.top_of_book(2);
+    assert_eq!(top.bids, engine.depth(Side::Buy, 2));
+    assert_eq!(top.asks, engine.depth(Side::Sell, 2));
+    assert_eq!(
+        top.asks[0],
+        PriceLevel {
+            price: Price::from(101u64),
+            quantity: Quantity::from(7u64),
+            orders: 2,
+        }
+    );
+    assert_eq!(top.bids[1].price, Price::from(99u64));
+    assert_eq!(engine.top_of_book(0), TopOfBook::default());
+}
+
+#[test]
+fn test_resting_totals_follow_inserts_fills_and_cancels() {
+    let (syncer, engine) = new_engine();
+    for (id, side, price, qty) in [
+        (1, Side::Buy, 99, 4),
+        (2, Side::Buy, 100, 3),
+        (3, Side::Sell, 101, 6),
+    ] {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, qty, 1000 + id))
+            .unwrap();
+    }
+    assert_eq!(
+        syncer.resting(Side::Buy),
+        RestingTotals {
+            quantity: Quantity::from(7u64),
+            orders: 2,
+        }
+    );
+
+    engine
+        .create_order(&mut make_market_order(4, Side::Sell, 5, 2000))
+        .unwrap();
+    engine.match_orders();
+    engine.cancel_order(3).unwrap();
+    assert_eq!(
+        syncer.resting(Side::Buy),
+        RestingTotals {
+            quantity: Quantity::from(2u64),
+            orders: 1,
+        }
+    );
+    assert_eq!(syncer.resting(Side::Sell), RestingTotals::default());
+}
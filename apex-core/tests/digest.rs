@@ -0,0 +1,75 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine(interval: u64) -> (Arc<DigestSyncer>, DefaultMatchingEngine) {
+    let syncer =
+        Arc::new(DigestSyncer::new(Arc::new(EmptyOrderBookSyncer {})).with_interval(interval));
+    let (_book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    (syncer, engine)
+}
+
+fn run(engine: &DefaultMatchingEngine, orders: &mut [Order]) {
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.match_orders();
+}
+
+#[test]
+fn test_replicas_fed_the_same_events_agree() {
+    let (primary, primary_engine) = new_engine(2);
+    let (replica, replica_engine) = new_engine(2);
+    let orders = [
+        make_limit_order(1, Side::Sell, 100, 5, 1000),
+        make_limit_order(2, Side::Sell, 101, 5, 1001),
+        make_limit_order(3, Side::Buy, 100, 3, 1002),
+    ];
+    run(&primary_engine, &mut orders.clone());
+    run(&replica_engine, &mut orders.clone());
+
+    assert_eq!(primary.digest(), replica.digest());
+    assert_eq!(primary.digest().events, 4);
+    assert_eq!(primary.checkpoint().map(|digest| digest.events), Some(4));
+    // The rolling digest matches one computed from scratch
+    assert_eq!(
+        primary.digest().value,
+        snapshot_digest(&primary_engine.snapshot())
+    );
+}
+
+#[test]
+fn test_digest_detects_divergence_and_is_order_independent() {
+    let (primary, primary_engine) = new_engine(0);
+    let (replica, replica_engine) = new_engine(0);
+    run(
+        &primary_engine,
+        &mut [
+            make_limit_order(1, Side::Sell, 100, 5, 1000),
+            make_limit_order(2, Side::Buy, 99, 5, 1001),
+        ],
+    );
+    run(
+        &replica_engine,
+        &mut [
+            make_limit_order(2, Side::Buy, 99, 5, 1001),
+            make_limit_order(1, Side::Sell, 100, 5, 1000),
+        ],
+    );
+    assert_eq!(primary.digest().value, replica.digest().value);
+    assert_eq!(primary.checkpoint(), None);
+
+    replica_engine
+        .amend_quantity(1, Quantity::from(4u64), 1002)
+        .unwrap();
+    assert_ne!(primary.digest().value, replica.digest().value);
+    replica_engine.cancel_order(1).unwrap();
+    primary_engine.cancel_order(1).unwrap();
+    assert_eq!(primary.digest().value, replica.digest().value);
+    assert_eq!(
+        replica.digest().value,
+        snapshot_digest(&replica_engine.snapshot())
+    );
+}
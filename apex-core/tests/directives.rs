@@ -62,8 +62,8 @@ fn test_taker_only_match_when_cross() {
     sell.liquidity_directive = LiquidityDirective::AllowTaker;
     engine.create_order(&mut sell).unwrap();
 
-    // Insert a buy order with TakerOnly
-    let mut buy = make_limit_order(2, Side::Buy, 110, 10, 1001);
+    // Insert a market buy order with TakerOnly
+    let mut buy = make_market_order(2, Side::Buy, 10, 1001);
     buy.liquidity_directive = LiquidityDirective::TakerOnly;
     engine.create_order(&mut buy).unwrap();
 
@@ -86,21 +86,24 @@ fn test_taker_only_match_when_cross() {
 }
 
 #[test]
-fn test_taker_only_stays_when_no_cross() {
+fn test_taker_only_limit_order_is_rejected() {
     let syncer = Arc::new(EmptyOrderBookSyncer {});
     let id = Arc::new(AtomicU64::new(1));
     let book = Arc::new(DefaultOrderBook::new(id, syncer));
     let engine = DefaultMatchingEngine::new(book.clone());
 
-    // No sell orders
-    // Insert a buy order with TakerOnly
+    // A TakerOnly limit order could rest, so it never reaches the book
     let mut buy = make_limit_order(1, Side::Buy, 100, 10, 1000);
     buy.liquidity_directive = LiquidityDirective::TakerOnly;
-    engine.create_order(&mut buy).unwrap();
+    assert_eq!(
+        engine.create_order(&mut buy),
+        Err(RejectReason::InvalidOrder(
+            OrderValidationError::InvalidLiquidityDirective
+        ))
+    );
 
     engine.match_orders();
 
     let remaining_buy = get_book_state(book.as_ref(), Side::Buy);
-    assert_eq!(remaining_buy.len(), 1);
-    assert_eq!(remaining_buy[0].0, 1);
+    assert!(remaining_buy.is_empty());
 }
@@ -0,0 +1,94 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct RecordCollector {
+    records: Mutex<Vec<DropCopyRecord>>,
+}
+
+impl DropCopySink for RecordCollector {
+    fn record(&self, record: &DropCopyRecord) {
+        self.records.lock().unwrap().push(record.clone());
+    }
+}
+
+#[test]
+fn test_drop_copy_records_every_event() {
+    let sink = Arc::new(RecordCollector::default());
+    let primary = Arc::new(Recorder::default());
+    let syncer = Arc::new(DropCopySyncer::new(primary.clone(), sink.clone()));
+    let (_book, engine) = TestEngine::new().with_syncer(syncer).build();
+
+    let mut sell = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let mut buy = make_limit_order(2, Side::Buy, 100, 4, 1001);
+    let mut zero = make_limit_order(3, Side::Buy, 100, 0, 1002);
+    engine.create_order(&mut sell).unwrap();
+    engine.create_order(&mut buy).unwrap();
+    engine.create_order(&mut zero).unwrap_err();
+    engine.match_orders();
+    engine.cancel_order(1).unwrap();
+
+    assert_eq!(primary.added().len(), 2);
+    let records = sink.records.lock().unwrap();
+    assert_eq!(
+        records
+            .iter()
+            .map(|record| (record.kind, record.order.id))
+            .collect::<Vec<_>>(),
+        vec![
+            (DropCopyKind::New, 1),
+            (DropCopyKind::New, 2),
+            (DropCopyKind::Reject, 3),
+            (DropCopyKind::Execution, 2),
+            (DropCopyKind::Execution, 1),
+            (DropCopyKind::Update, 2),
+            (DropCopyKind::Update, 1),
+            (DropCopyKind::Cancel, 1),
+        ]
+    );
+    assert!(
+        records
+            .iter()
+            .enumerate()
+            .all(|(i, record)| record.sequence == i as u64 + 1 && record.timestamp > 0)
+    );
+    let execution = records[3].execution.as_ref().unwrap();
+    assert_eq!(execution.quantity, Quantity::from(4u64));
+    assert_eq!(execution.role, TradeRole::Maker);
+    assert_eq!(records[3].sync_id, records[4].sync_id);
+}
+
+#[test]
+fn test_drop_copy_delivers_in_sequence_order_with_the_given_clock() {
+    let sink = Arc::new(RecordCollector::default());
+    let syncer = Arc::new(
+        DropCopySyncer::new(Arc::new(EmptyOrderBookSyncer {}), sink.clone())
+            .with_clock(Arc::new(ManualClock::new(1234))),
+    );
+    let threads: Vec<_> = (0..4)
+        .map(|thread| {
+            let syncer = syncer.clone();
+            std::thread::spawn(move || {
+                for id in 0..250 {
+                    let order = make_limit_order(thread * 1000 + id, Side::Buy, 100, 1, 1000);
+                    syncer.add_order(id, &order);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let records = sink.records.lock().unwrap();
+    assert_eq!(records.len(), 1000);
+    assert!(
+        records
+            .iter()
+            .enumerate()
+            .all(|(i, record)| record.sequence == i as u64 + 1 && record.timestamp == 1234)
+    );
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use crossbeam::epoch;
+use crypto_bigint::NonZero;
+use std::error::Error;
+
+#[test]
+fn test_engine_error_display_and_code() {
+    let error = EngineError::from(CancelOrderError::OrderNotCancellable {
+        status: OrderStatus::PartiallyFilled,
+        filled_quantity: Quantity::from(3u64),
+    })
+    .with_order_id(7);
+    assert_eq!(error.code(), 302);
+    assert_eq!(
+        error.to_string(),
+        "[302] cancel failed: order is not cancellable (order 7, status PartiallyFilled)"
+    );
+
+    let boxed: Box<dyn Error> = Box::new(error);
+    assert_eq!(
+        boxed.source().map(|source| source.to_string()),
+        Some("order is not cancellable".to_string())
+    );
+}
+
+#[test]
+fn test_rejected_order_error_chain() {
+    let (_book, engine) = TestEngine::new().build();
+
+    let mut order = make_limit_order(3, Side::Buy, 100, 10, 1000);
+    order.slippage_tolerance = Some(SlippageTolerance(5));
+    let reason = engine.create_order(&mut order).unwrap_err();
+
+    let error = EngineError::rejected(&order, reason);
+    assert_eq!(error.order_id, Some(3));
+    assert_eq!(error.status, Some(OrderStatus::Rejected));
+    assert_eq!(
+        error.code(),
+        OrderValidationError::SlippageNotApplicable.code()
+    );
+
+    let reason = error.source().unwrap();
+    assert_eq!(
+        reason.to_string(),
+        "invalid order: slippage tolerance is not applicable to the order type"
+    );
+    assert_eq!(
+        reason.source().unwrap().to_string(),
+        "slippage tolerance is not applicable to the order type"
+    );
+}
+
+#[test]
+fn test_cancel_error_carries_order_state() {
+    let (book, engine) = TestEngine::new().build();
+
+    let sell = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    engine.create_order(&mut sell.clone()).unwrap();
+    engine
+        .create_order(&mut make_market_order(2, Side::Buy, 4, 1001))
+        .unwrap();
+    engine.match_orders();
+
+    // Claim the resting order as if the matching thread were filling it
+    let guard = &epoch::pin();
+    let entry = book
+        .get_book(Side::Sell)
+        .get(&sell.book_key(), guard)
+        .unwrap();
+    assert!(entry.value().lifecycle.enter_matched());
+
+    let error = engine.cancel_order(1).unwrap_err();
+    assert_eq!(
+        error,
+        CancelOrderError::OrderNotCancellable {
+            status: OrderStatus::PartiallyFilled,
+            filled_quantity: Quantity::from(4u64),
+        }
+    );
+    let error = EngineError::from(error).with_order_id(1);
+    assert_eq!(error.status, Some(OrderStatus::PartiallyFilled));
+}
+
+#[test]
+fn test_update_error_carries_reason_and_snapshot() {
+    let (book, engine) = TestEngine::new()
+        .with_config(BookConfig {
+            tick_size: NonZero::new(Price::from(5u64)).into_option(),
+            ..Default::default()
+        })
+        .build();
+
+    let sell = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    engine.create_order(&mut sell.clone()).unwrap();
+    let error = engine
+        .update_order(1, Price::from(102u64), 1001)
+        .unwrap_err();
+    assert_eq!(error.reason(), Some(AmendFailure::InvalidPrice));
+    assert_eq!(error.order().map(|order| order.price), Some(sell.price));
+
+    // Claim the resting order as if the matching thread were filling it
+    let guard = &epoch::pin();
+    let entry = book
+        .get_book(Side::Sell)
+        .get(&sell.book_key(), guard)
+        .unwrap();
+    assert!(entry.value().lifecycle.enter_matched());
+
+    let error = engine
+        .update_order(1, Price::from(105u64), 1002)
+        .unwrap_err();
+    assert_eq!(error.reason(), Some(AmendFailure::MatchInFlight));
+    assert_eq!(
+        error.order().map(|order| order.quantity),
+        Some(Quantity::from(10u64))
+    );
+    let error = EngineError::from(error);
+    assert_eq!(error.code(), 202);
+    assert_eq!(error.order_id, Some(1));
+    assert_eq!(error.status, Some(OrderStatus::Placed));
+}
+
+#[test]
+fn test_engine_error_wraps_every_operation_error() {
+    let error = EngineError::from(TradeCorrectionError::TradeNotFound);
+    assert_eq!(error.code(), 601);
+    assert_eq!(
+        error.to_string(),
+        "[601] trade correction failed: trade not found"
+    );
+
+    let error = EngineError::from(RescaleError::Inexact(4));
+    assert_eq!(error.code(), 702);
+    assert_eq!(error.order_id, Some(4));
+
+    let error = EngineError::from(BasketError::Rejected {
+        order_id: 5,
+        reason: RejectReason::InsufficientLiquidity,
+    });
+    assert_eq!(error.code(), 802);
+    assert_eq!(error.order_id, Some(5));
+
+    let error = EngineError::from(QuoteError::SideMismatch);
+    assert_eq!(error.kind, EngineErrorKind::Quote(QuoteError::SideMismatch));
+    assert_eq!(error.code(), 901);
+
+    let failover = FailoverError::SequenceGap {
+        expected: 3,
+        received: 5,
+    };
+    let error = EngineError::from(failover);
+    assert_eq!(error.code(), 1002);
+    assert!(error.source().is_some());
+    assert_eq!(
+        error.to_string(),
+        "[1002] failover failed: expected sequence 3, received 5"
+    );
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct ReportCollector {
+    reports: Mutex<Vec<ExecutionReport>>,
+}
+
+impl ExecutionReportSink for ReportCollector {
+    fn report(&self, report: &ExecutionReport) {
+        self.reports.lock().unwrap().push(report.clone());
+    }
+}
+
+fn new_engine() -> (
+    Arc<ReportCollector>,
+    Arc<ExecutionReportSyncer>,
+    DefaultMatchingEngine,
+) {
+    let sink = Arc::new(ReportCollector::default());
+    let syncer = Arc::new(ExecutionReportSyncer::new(
+        Arc::new(EmptyOrderBookSyncer {}),
+        sink.clone(),
+    ));
+    let (_book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    (sink, syncer, engine)
+}
+
+fn reports_for(sink: &ReportCollector, order_id: u64) -> Vec<ExecutionReport> {
+    let reports = sink.reports.lock().unwrap();
+    reports
+        .iter()
+        .filter(|report| report.order.id == order_id)
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn test_reports_aggregate_fills() {
+    let (sink, syncer, engine) = new_engine();
+    let mut maker = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    engine.create_order(&mut maker).unwrap();
+    for (id, price, quantity, timestamp) in [(2, 100, 4, 1001), (3, 102, 6, 1002)] {
+        let mut taker = make_limit_order(id, Side::Buy, price, quantity, timestamp);
+        engine.create_order(&mut taker).unwrap();
+        engine.match_orders();
+    }
+
+    let reports = reports_for(&sink, 1);
+    assert_eq!(
+        reports
+            .iter()
+            .map(|report| (report.kind, report.status))
+            .collect::<Vec<_>>(),
+        vec![
+            (ExecutionKind::New, OrderStatus::Placed),
+            (ExecutionKind::Trade, OrderStatus::PartiallyFilled),
+            (ExecutionKind::Trade, OrderStatus::Filled),
+        ]
+    );
+    let partial = &reports[1];
+    assert_eq!(partial.filled_quantity, Quantity::from(4u64));
+    assert_eq!(partial.remaining_quantity, Quantity::from(6u64));
+    assert_eq!(partial.average_price, Some(Price::from(100u64)));
+    let filled = &reports[2];
+    assert_eq!(filled.filled_quantity, Quantity::from(10u64));
+    assert_eq!(filled.remaining_quantity, Quantity::ZERO);
+    // (4 * 100 + 6 * 102) / 10, rounded down
+    assert_eq!(filled.average_price, Some(Price::from(101u64)));
+    let fill = filled.last_fill.as_ref().unwrap();
+    assert_eq!(fill.price, Price::from(102u64));
+    assert_eq!(fill.quantity, Quantity::from(6u64));
+    assert_eq!(syncer.tracked_orders(), 0);
+}
+
+#[test]
+fn test_reports_for_unfilled_remainders() {
+    let (sink, syncer, engine) = new_engine();
+    let mut maker = make_limit_order(1, Side::Sell, 100, 3, 1000);
+    let mut partial = make_market_order(2, Side::Buy, 5, 1001);
+    let mut unfilled = make_market_order(3, Side::Buy, 5, 1002);
+    let mut resting = make_limit_order(4, Side::Buy, 90, 5, 1003);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut partial).unwrap();
+    engine.match_orders();
+    engine.create_order(&mut unfilled).unwrap();
+    engine.match_orders();
+    engine.create_order(&mut resting).unwrap();
+    engine.cancel_order(4).unwrap();
+
+    let reports = reports_for(&sink, 2);
+    let last = reports.last().unwrap();
+    assert_eq!(last.kind, ExecutionKind::Cancelled);
+    assert_eq!(last.filled_quantity, Quantity::from(3u64));
+    assert_eq!(last.remaining_quantity, Quantity::from(2u64));
+    assert_eq!(last.average_price, Some(Price::from(100u64)));
+
+    let last = reports_for(&sink, 3).pop().unwrap();
+    assert_eq!(last.kind, ExecutionKind::Rejected);
+    assert_eq!(
+        last.reject_reason,
+        Some(RejectReason::InsufficientLiquidity)
+    );
+    assert_eq!(last.average_price, None);
+
+    let last = reports_for(&sink, 4).pop().unwrap();
+    assert_eq!(last.kind, ExecutionKind::Cancelled);
+    assert_eq!(last.cancel_reason, Some(CancelReason::UserRequest));
+    assert_eq!(syncer.tracked_orders(), 0);
+}
+
+#[test]
+fn test_reports_for_orders_canceled_during_matching() {
+    let (sink, syncer, engine) = new_engine();
+    let engine = engine.with_config(BookConfig {
+        max_sweep_levels: Some(1),
+        self_trade_prevention: SelfTradePrevention::CancelResting,
+        ..BookConfig::default()
+    });
+    let mut own = make_limit_order(1, Side::Sell, 100, 2, 1000);
+    own.user_id = 3;
+    let mut maker = make_limit_order(2, Side::Sell, 100, 3, 1001);
+    maker.user_id = 2;
+    let mut deeper = make_limit_order(3, Side::Sell, 101, 3, 1002);
+    deeper.user_id = 2;
+    let mut taker = make_market_order(4, Side::Buy, 5, 1003);
+    taker.user_id = 3;
+    for order in [&mut own, &mut maker, &mut deeper, &mut taker] {
+        engine.create_order(order).unwrap();
+    }
+    engine.match_orders();
+
+    let last = reports_for(&sink, 1).pop().unwrap();
+    assert_eq!(last.kind, ExecutionKind::Cancelled);
+    assert_eq!(last.cancel_reason, Some(CancelReason::SelfTradePrevention));
+
+    let last = reports_for(&sink, 4).pop().unwrap();
+    assert_eq!(last.kind, ExecutionKind::Cancelled);
+    assert_eq!(last.cancel_reason, Some(CancelReason::SweepLimit));
+    assert_eq!(last.filled_quantity, Quantity::from(3u64));
+    assert_eq!(last.remaining_quantity, Quantity::from(2u64));
+    assert_eq!(syncer.tracked_orders(), 0);
+}
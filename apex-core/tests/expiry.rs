@@ -0,0 +1,62 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::{Arc, Mutex};
+
+fn new_engine() -> (Arc<ExpirySyncer>, DefaultMatchingEngine) {
+    let syncer = Arc::new(ExpirySyncer::new(Arc::new(EmptyOrderBookSyncer {})));
+    let (_book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    (syncer, engine)
+}
+
+fn make_gtd_order(id: u64, deadline: u64) -> Order {
+    let mut order = make_limit_order(id, Side::Buy, 100, 10, 1000 + id);
+    order.time_in_force = TimeInForce::GoodTillDate(deadline);
+    order
+}
+
+#[test]
+fn test_expiry_callback_fires_only_on_expiry() {
+    let (syncer, engine) = new_engine();
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    for id in [1, 2] {
+        engine.create_order(&mut make_gtd_order(id, 5000)).unwrap();
+        let fired = fired.clone();
+        syncer.on_expiry(id, move |event| {
+            fired.lock().unwrap().push((event.order.id, event.reason))
+        });
+    }
+    engine.cancel_order(2).unwrap();
+    assert_eq!(syncer.registered(), 1);
+
+    assert!(engine.expire_orders(4999).is_empty());
+    assert!(fired.lock().unwrap().is_empty());
+    assert_eq!(engine.expire_orders(5000), vec![1]);
+    assert_eq!(
+        *fired.lock().unwrap(),
+        vec![(1, CancelReason::TimeInForceExpired)]
+    );
+    assert_eq!(syncer.registered(), 0);
+}
+
+#[test]
+fn test_expiry_channel_notification() {
+    let (syncer, engine) = new_engine();
+    engine.create_order(&mut make_gtd_order(1, 5000)).unwrap();
+    engine.create_order(&mut make_gtd_order(2, 5000)).unwrap();
+    let filled = syncer.notify_expiry(1);
+    let expired = syncer.notify_expiry(2);
+    engine
+        .create_order(&mut make_market_order(3, Side::Sell, 10, 2000))
+        .unwrap();
+    engine.match_orders();
+
+    // The older order fills and the other one expires
+    assert_eq!(engine.expire_orders(5000), vec![2]);
+    let event = expired.try_recv().unwrap();
+    assert_eq!(event.order.status, OrderStatus::Expired);
+    assert_eq!(event.reason, CancelReason::TimeInForceExpired);
+    // The filled order dropped its registration, disconnecting the channel
+    assert!(filled.recv().is_err());
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct ReportCollector {
+    reports: Mutex<Vec<ExecutionReport>>,
+}
+
+impl ExecutionReportSink for ReportCollector {
+    fn report(&self, report: &ExecutionReport) {
+        self.reports.lock().unwrap().push(report.clone());
+    }
+}
+
+#[test]
+fn test_fix_codes() {
+    assert_eq!(FixExecType::from(ExecutionKind::New).code(), '0');
+    assert_eq!(FixExecType::from(ExecutionKind::Trade).code(), 'F');
+    assert_eq!(FixExecType::from(ExecutionKind::Expired).code(), 'C');
+    assert_eq!(
+        FixExecType::from_cancel_reason(CancelReason::LifetimeExceeded),
+        FixExecType::Expired
+    );
+    assert_eq!(
+        FixExecType::from_cancel_reason(CancelReason::MassCancel),
+        FixExecType::Canceled
+    );
+    assert_eq!(FixOrdStatus::from(OrderStatus::Pending).code(), 'A');
+    assert_eq!(FixOrdStatus::from(OrderStatus::PartiallyFilled).code(), '1');
+    assert_eq!(FixOrdStatus::from(OrderStatus::Rejected).code(), '8');
+}
+
+#[test]
+fn test_fix_ord_status_uses_full_order_state() {
+    assert_eq!(
+        FixOrdStatus::from_order_state(
+            OrderStatus::PartiallyFilled,
+            OrderLifecycle::Finished,
+            None
+        ),
+        FixOrdStatus::Canceled
+    );
+    assert_eq!(
+        FixOrdStatus::from_order_state(OrderStatus::PartiallyFilled, OrderLifecycle::Active, None),
+        FixOrdStatus::PartiallyFilled
+    );
+    assert_eq!(
+        FixOrdStatus::from_order_state(
+            OrderStatus::Cancelled,
+            OrderLifecycle::Finished,
+            Some(CancelReason::TimeInForceExpired)
+        ),
+        FixOrdStatus::Expired
+    );
+}
+
+#[test]
+fn test_execution_reports_carry_fix_codes() {
+    let sink = Arc::new(ReportCollector::default());
+    let syncer = Arc::new(ExecutionReportSyncer::new(
+        Arc::new(EmptyOrderBookSyncer {}),
+        sink.clone(),
+    ));
+    let (_book, engine) = TestEngine::new().with_syncer(syncer).build();
+    let mut maker = make_limit_order(1, Side::Sell, 100, 3, 1000);
+    let mut market = make_market_order(2, Side::Buy, 5, 1001);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut market).unwrap();
+    engine.match_orders();
+
+    let reports = sink.reports.lock().unwrap();
+    assert_eq!(
+        reports
+            .iter()
+            .map(|report| (
+                report.order.id,
+                report.exec_type.code(),
+                report.ord_status.code()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, '0', '0'),
+            (2, '0', '0'),
+            (1, 'F', '2'),
+            (2, 'F', '1'),
+            (2, '4', '4'),
+        ]
+    );
+}
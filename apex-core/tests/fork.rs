@@ -0,0 +1,134 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+use std::thread;
+
+/// Rejects every order of one user
+struct BlockedUser(u64);
+
+impl RiskChecker for BlockedUser {
+    fn check(&self, order: &Order) -> RiskDecision {
+        if order.user_id == self.0 {
+            RiskDecision::Reject(RejectReason::RiskRejected(1))
+        } else {
+            RiskDecision::Allow
+        }
+    }
+}
+
+fn new_engine() -> (Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    TestEngine::new()
+        .with_config(BookConfig {
+            max_order_quantity: Some(Quantity::from(100u64)),
+            ..Default::default()
+        })
+        .build()
+}
+
+#[test]
+fn test_forks_run_independent_futures() {
+    let (book, engine) = new_engine();
+    for (id, side, price, qty) in [
+        (1, Side::Buy, 99, 5),
+        (2, Side::Sell, 101, 4),
+        (3, Side::Sell, 102, 6),
+    ] {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, qty, 1000 + id))
+            .unwrap();
+    }
+    engine.freeze_user(7);
+
+    let branches: Vec<_> = [4u64, 10]
+        .into_iter()
+        .map(|qty| {
+            let (fork_book, fork) = engine.fork(Arc::new(EmptyOrderBookSyncer {}));
+            thread::spawn(move || {
+                fork.create_order(&mut make_market_order(10, Side::Buy, qty, 2000))
+                    .unwrap();
+                fork.match_orders();
+                (
+                    fork.last_trade_price(),
+                    get_book_state(fork_book.as_ref(), Side::Sell),
+                    fork.is_frozen(7),
+                    fork.config().max_order_quantity,
+                )
+            })
+        })
+        .collect();
+    let results: Vec<_> = branches
+        .into_iter()
+        .map(|branch| branch.join().unwrap())
+        .collect();
+
+    let limit = Some(Quantity::from(100u64));
+    assert_eq!(
+        results,
+        vec![
+            (
+                Some(Price::from(101u64)),
+                vec![(3, Quantity::from(6u64))],
+                true,
+                limit
+            ),
+            (Some(Price::from(102u64)), vec![], true, limit),
+        ]
+    );
+
+    // The live book is untouched by either branch
+    assert_eq!(engine.last_trade_price(), None);
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(2, Quantity::from(4u64)), (3, Quantity::from(6u64))]
+    );
+}
+
+#[test]
+fn test_fork_carries_on_from_the_live_sequence() {
+    let (book, engine) = new_engine();
+    engine
+        .create_order(&mut make_limit_order(1, Side::Buy, 99, 5, 1000))
+        .unwrap();
+
+    let (fork_book, fork) = engine.fork(Arc::new(EmptyOrderBookSyncer {}));
+    assert_eq!(fork_book.sequence(), book.sequence());
+    assert_eq!(fork_book.snapshot(), book.snapshot());
+
+    engine.cancel_order(1).unwrap();
+    assert!(fork_book.get_order(1).is_some());
+    fork.cancel_order(1).unwrap();
+    assert_eq!(fork_book.sequence(), book.sequence());
+}
+
+#[test]
+fn test_fork_has_its_own_ids_and_no_risk_checker() {
+    let ids = Arc::new(IdGenerator::new(3).with_clock(Arc::new(ManualClock::new(0))));
+    let (_book, engine) = new_engine();
+    let engine = engine
+        .with_id_generator(ids.clone())
+        .with_risk_checker(Arc::new(BlockedUser(9)));
+    engine
+        .create_order(&mut make_limit_order(1, Side::Sell, 100, 5, 1000))
+        .unwrap();
+    let before = ids.next_id();
+
+    let recorder = Arc::new(Recorder::default());
+    let (_fork_book, fork) = engine.fork(recorder.clone());
+    let mut blocked = make_limit_order(2, Side::Buy, 100, 5, 2000);
+    blocked.user_id = 9;
+    fork.create_order(&mut blocked).unwrap();
+    fork.match_orders();
+
+    // The fork's trade took the next id of its own generator, not of the live one
+    let trade_id = recorder.trades()[0].trade_id;
+    assert!(trade_id > before);
+    assert_eq!(ids.next_id(), trade_id);
+    let mut blocked = make_limit_order(3, Side::Buy, 100, 5, 2001);
+    blocked.user_id = 9;
+    assert_eq!(
+        engine.create_order(&mut blocked),
+        Err(RejectReason::RiskRejected(1))
+    );
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+
+fn user_order(id: u64, user_id: u64, side: Side, price: u64, qty: u64, ts: u64) -> Order {
+    let mut order = make_limit_order(id, side, price, qty, ts);
+    order.user_id = user_id;
+    order
+}
+
+#[test]
+fn test_frozen_user_orders_are_skipped_and_rejected() {
+    let (book, engine) = TestEngine::new().build();
+    let mut frozen = user_order(1, 7, Side::Sell, 100, 5, 1000);
+    let mut other = user_order(2, 8, Side::Sell, 101, 5, 1001);
+    engine.create_order(&mut frozen).unwrap();
+    engine.create_order(&mut other).unwrap();
+    assert!(engine.freeze_user(7));
+    assert!(!engine.freeze_user(7));
+    assert!(engine.is_frozen(7));
+
+    let mut new_order = user_order(3, 7, Side::Buy, 90, 5, 1002);
+    assert_eq!(
+        engine.create_order(&mut new_order),
+        Err(RejectReason::UserFrozen)
+    );
+
+    // The buy skips the frozen ask and trades with the next one
+    let mut buy = user_order(4, 9, Side::Buy, 101, 5, 1003);
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(5u64))]
+    );
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+
+    // Once unfrozen, the resting order trades again
+    assert!(engine.unfreeze_user(7));
+    assert!(!engine.unfreeze_user(7));
+    let mut buy = user_order(5, 9, Side::Buy, 100, 5, 1004);
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
+
+#[test]
+fn test_frozen_taker_does_not_match() {
+    let (book, engine) = TestEngine::new().build();
+    let mut sell = user_order(1, 8, Side::Sell, 100, 5, 1000);
+    let mut buy = user_order(2, 7, Side::Buy, 100, 5, 1001);
+    let mut market = make_market_order(3, Side::Buy, 5, 1002);
+    market.user_id = 7;
+    engine.create_order(&mut sell).unwrap();
+    engine.create_order(&mut buy).unwrap();
+    engine.create_order(&mut market).unwrap();
+    engine.freeze_user(7);
+    engine.match_orders();
+
+    // Both of the frozen user's orders are left alone by the crossing ask,
+    // and the queued market order is rejected
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(5u64))]
+    );
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(2, Quantity::from(5u64))]
+    );
+    // Cancels are still accepted
+    assert_eq!(engine.cancel_order(2), Ok(()));
+}
+
+#[test]
+fn test_frozen_user_cannot_change_orders() {
+    let (book, engine) = TestEngine::new().build();
+    let mut resting = user_order(1, 7, Side::Buy, 100, 5, 1000);
+    engine.create_order(&mut resting).unwrap();
+    engine.freeze_user(7);
+
+    let frozen = Err(UpdateOrderError::UserFrozen);
+    assert_eq!(engine.update_order(1, Price::from(101u64), 1001), frozen);
+    assert_eq!(
+        engine.amend_quantity(1, Quantity::from(10u64), 1002),
+        frozen
+    );
+    let request = AmendRequest {
+        price: Some(Price::from(99u64)),
+        ..Default::default()
+    };
+    assert_eq!(engine.amend_order(1, request, 1003), frozen);
+    let mut commands = [Command::Amend {
+        order_id: 1,
+        new_quantity: Quantity::from(1u64),
+        now_microseconds: 1004,
+    }];
+    assert_eq!(
+        engine.execute_batch(&mut commands),
+        vec![CommandResult::Updated(frozen)]
+    );
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(5u64))]
+    );
+    assert_eq!(UpdateOrderError::UserFrozen.code(), 206);
+
+    // Frozen users may still pull their orders
+    assert_eq!(engine.cancel_order(1), Ok(()));
+}
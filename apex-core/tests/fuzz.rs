@@ -0,0 +1,66 @@
+use apex_core::prelude::*;
+
+#[test]
+fn test_decode_never_fails() {
+    assert!(decode_commands(&[]).is_empty());
+    for len in 0..64u8 {
+        let data = (0..len).map(|i| i.wrapping_mul(37)).collect::<Vec<_>>();
+        let _ = decode_commands(&data);
+    }
+    // A create whose option byte is missing is dropped.
+    assert!(decode_commands(&[0, 0, 5, 10]).is_empty());
+    assert_eq!(decode_commands(&[0, 0, 5, 10, 0]).len(), 1);
+}
+
+#[test]
+fn test_decode_limit_and_match() {
+    let commands = decode_commands(&[0, 0, 5, 10, 0, 0, 1, 5, 10, 0, 9]);
+    assert_eq!(commands.len(), 3);
+    match &commands[1] {
+        FuzzCommand::Execute(Command::Create(order)) => {
+            assert_eq!(order.id, 2);
+            assert_eq!(order.side, Side::Sell);
+            assert_eq!(order.price, Price::from(10u64));
+            assert_eq!(order.quantity(), Quantity::from(5u64));
+        }
+        other => panic!("unexpected command {other:?}"),
+    }
+    assert!(matches!(commands[2], FuzzCommand::Match));
+}
+
+#[test]
+fn test_fuzz_apply_random_inputs() {
+    let mut rng = SimRng::new(2626);
+    for _ in 0..300 {
+        let len = rng.below(512) as usize;
+        let data = (0..len).map(|_| rng.next_u64() as u8).collect::<Vec<_>>();
+        fuzz_apply(&data);
+    }
+}
+
+#[test]
+fn test_book_invariants_detect_bad_priority() {
+    let order = |id: u64, price: u64| OrderView {
+        id,
+        user_id: 1,
+        side: Side::Buy,
+        order_type: OrderType::Limit,
+        status: OrderStatus::Placed,
+        time_in_force: TimeInForce::GoodTillCancelled,
+        price: Price::from(price),
+        quantity: Quantity::from(1u64),
+        filled_quantity: Quantity::ZERO,
+        created_at: 0,
+        updated_at: 0,
+    };
+    let mut snapshot = BookSnapshot {
+        bids: vec![order(1, 10), order(2, 9)],
+        ..BookSnapshot::default()
+    };
+    assert!(check_book_invariants(&snapshot).is_ok());
+    snapshot.bids.reverse();
+    assert!(check_book_invariants(&snapshot).is_err());
+    snapshot.bids.reverse();
+    snapshot.bids[1].id = 1;
+    assert!(check_book_invariants(&snapshot).is_err());
+}
@@ -0,0 +1,42 @@
+//! Run with `cargo test --features hierarchy --test hierarchy`.
+#![cfg(feature = "hierarchy")]
+
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+#[test]
+fn test_account_and_broker_are_carried_to_trades() {
+    let recorder = Arc::new(Recorder::default());
+    let (_book, engine) = TestEngine::new().with_syncer(recorder.clone()).build();
+
+    let mut maker = Order::builder(1, Side::Sell)
+        .limit(Price::from(100u64))
+        .quantity(Quantity::from(5u64))
+        .account_id(11)
+        .broker_id(21)
+        .timestamp(1000)
+        .build()
+        .unwrap();
+    engine.create_order(&mut maker).unwrap();
+    let mut taker = make_limit_order(2, Side::Buy, 100, 5, 1001);
+    taker.account_id = Some(12);
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+
+    // The older order takes, and each side of the trade carries its own order's ids
+    let trades = recorder.trades();
+    let parties: Vec<_> = trades
+        .iter()
+        .map(|trade| (trade.role, trade.account_id, trade.broker_id))
+        .collect();
+    assert_eq!(
+        parties,
+        vec![
+            (TradeRole::Maker, Some(12), None),
+            (TradeRole::Taker, Some(11), Some(21)),
+        ]
+    );
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine(history: Arc<TradeHistory>) -> DefaultMatchingEngine {
+    let (_book, engine) = TestEngine::new().with_syncer(history).build();
+    engine
+}
+
+fn user_order(id: u64, user_id: u64, side: Side, price: u64, qty: u64, ts: u64) -> Order {
+    let mut order = make_limit_order(id, side, price, qty, ts);
+    order.user_id = user_id;
+    order
+}
+
+#[test]
+fn test_trades_are_indexed_by_user() {
+    let history = Arc::new(TradeHistory::new(10));
+    let engine = new_engine(history.clone());
+    let mut orders = [
+        user_order(1, 8, Side::Sell, 100, 10, 1000),
+        user_order(2, 7, Side::Buy, 100, 4, 1001),
+        user_order(3, 7, Side::Buy, 100, 6, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+        engine.match_orders();
+    }
+
+    let fills = history.trades_for_user(7, 10);
+    assert_eq!(
+        fills
+            .iter()
+            .map(|fill| (fill.trade.order_id, fill.side, fill.trade.quantity))
+            .collect::<Vec<_>>(),
+        vec![
+            (3, Side::Buy, Quantity::from(6u64)),
+            (2, Side::Buy, Quantity::from(4u64)),
+        ]
+    );
+    let fills = history.trades_for_user(8, 1);
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].side, Side::Sell);
+    assert_eq!(fills[0].trade.quantity, Quantity::from(6u64));
+    assert!(history.trades_for_user(9, 10).is_empty());
+}
+
+#[test]
+fn test_retention_drops_oldest_trades() {
+    let history = Arc::new(TradeHistory::new(2));
+    let engine = new_engine(history.clone());
+    let mut maker = user_order(1, 8, Side::Sell, 100, 10, 1000);
+    engine.create_order(&mut maker).unwrap();
+    for id in 2..=4 {
+        let mut taker = user_order(id, 7, Side::Buy, 100, 1, 1000 + id);
+        engine.create_order(&mut taker).unwrap();
+        engine.match_orders();
+    }
+
+    let fills = history.trades_for_user(7, 10);
+    assert_eq!(
+        fills
+            .iter()
+            .map(|fill| fill.trade.order_id)
+            .collect::<Vec<_>>(),
+        vec![4, 3]
+    );
+    assert_eq!(history.trades_for_user(8, 10).len(), 2);
+}
+
+#[test]
+fn test_corrections_and_rescales_reach_the_history() {
+    let history = Arc::new(TradeHistory::new(10));
+    let engine = new_engine(history.clone()).with_trade_journal(16);
+    let mut orders = [
+        user_order(1, 8, Side::Sell, 100, 10, 1000),
+        user_order(2, 7, Side::Buy, 100, 4, 1001),
+        user_order(3, 7, Side::Buy, 100, 6, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+        engine.match_orders();
+    }
+    let trade_ids = history
+        .trades_for_user(7, 10)
+        .iter()
+        .map(|fill| fill.trade.trade_id)
+        .collect::<Vec<_>>();
+
+    engine.bust_trade(trade_ids[1]).unwrap();
+    engine
+        .correct_trade_price(trade_ids[0], Price::from(110u64))
+        .unwrap();
+    engine.set_mode(EngineMode::Halted);
+    engine
+        .rescale(Rescale {
+            price: ScaleFactor::new(1, 10).unwrap(),
+            quantity: ScaleFactor::new(10, 1).unwrap(),
+        })
+        .unwrap();
+
+    for user_id in [7, 8] {
+        let fills = history.trades_for_user(user_id, 10);
+        assert_eq!(
+            fills
+                .iter()
+                .map(|fill| (fill.trade.trade_id, fill.trade.price, fill.trade.quantity))
+                .collect::<Vec<_>>(),
+            vec![(trade_ids[0], Price::from(11u64), Quantity::from(60u64))]
+        );
+    }
+}
+
+#[test]
+fn test_fan_out_feeds_several_trade_modules() {
+    let history = Arc::new(TradeHistory::new(10));
+    let limit = PositionLimit::new(Quantity::from(100u64), Quantity::from(100u64));
+    let positions = Arc::new(PositionLimits::new(limit));
+    let syncer = FanOutSyncer::new(vec![history.clone()]).with_syncer(positions.clone());
+    let (_book, engine) = TestEngine::new().with_syncer(Arc::new(syncer)).build();
+    engine
+        .create_order(&mut user_order(1, 8, Side::Sell, 100, 10, 1000))
+        .unwrap();
+    engine
+        .create_order(&mut user_order(2, 7, Side::Buy, 100, 4, 1001))
+        .unwrap();
+    engine.match_orders();
+
+    assert_eq!(history.trades_for_user(7, 10).len(), 1);
+    assert_eq!(history.trades_for_user(8, 10).len(), 1);
+    assert_eq!(positions.position(7).bought, Quantity::from(4u64));
+    assert_eq!(positions.position(8).sold, Quantity::from(4u64));
+}
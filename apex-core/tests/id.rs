@@ -0,0 +1,60 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[test]
+fn test_ids_are_unique_and_increasing_across_threads() {
+    let ids = Arc::new(IdGenerator::new(5));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let ids = ids.clone();
+            std::thread::spawn(move || {
+                let generated: Vec<u64> = (0..10_000).map(|_| ids.next_id()).collect();
+                assert!(generated.windows(2).all(|pair| pair[0] < pair[1]));
+                generated
+            })
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    for handle in handles {
+        for id in handle.join().unwrap() {
+            assert!(seen.insert(id), "duplicate id {id}");
+            assert_eq!(IdGenerator::decompose(id).1, 5);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_worker_id_out_of_range() {
+    let _ = IdGenerator::new(1024);
+}
+
+#[test]
+fn test_trades_carry_generated_ids() {
+    let syncer = Arc::new(Recorder::default());
+    let (_book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    let engine = engine.with_id_generator(Arc::new(IdGenerator::new(3)));
+
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 100, 5, 1000),
+        make_limit_order(2, Side::Sell, 100, 5, 1001),
+        make_limit_order(3, Side::Buy, 100, 10, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.match_orders();
+
+    let trades = syncer.trades();
+    assert_eq!(trades.len(), 4);
+    // Maker and taker legs of one match share an id; different matches do not
+    assert_eq!(trades[0].trade_id, trades[1].trade_id);
+    assert_eq!(trades[2].trade_id, trades[3].trade_id);
+    assert_ne!(trades[0].trade_id, trades[2].trade_id);
+    assert_eq!(IdGenerator::decompose(trades[0].trade_id).1, 3);
+}
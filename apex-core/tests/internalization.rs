@@ -0,0 +1,76 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine(prevent_internalization: bool) -> (Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let (book, engine) = TestEngine::new()
+        .with_config(BookConfig {
+            prevent_internalization,
+            ..Default::default()
+        })
+        .build();
+    (book, engine)
+}
+
+fn firm_order(id: u64, side: Side, price: u64, qty: u64, ts: u64, firm_id: u64) -> Order {
+    let mut order = make_limit_order(id, side, price, qty, ts);
+    order.user_id = id;
+    order.firm_id = Some(firm_id);
+    order
+}
+
+#[test]
+fn test_taker_skips_makers_of_its_own_firm() {
+    let (book, engine) = new_engine(true);
+    engine
+        .create_order(&mut firm_order(10, Side::Buy, 101, 5, 500, 7))
+        .unwrap();
+    engine
+        .create_order(&mut firm_order(1, Side::Sell, 100, 5, 1000, 7))
+        .unwrap();
+    engine
+        .create_order(&mut firm_order(2, Side::Sell, 101, 3, 1001, 8))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(3, Side::Sell, 101, 1, 1002))
+        .unwrap();
+    engine.match_orders();
+
+    // The better priced ask of the same firm is passed over, not canceled
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(5u64))]
+    );
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(10, Quantity::from(1u64))]
+    );
+
+    // Nor can a fill-or-kill market order count on its own firm's liquidity
+    let mut market = make_market_order(20, Side::Buy, 5, 2000);
+    market.match_strategy = MatchStrategy::FillOrKill;
+    market.firm_id = Some(7);
+    assert_eq!(engine.preview(&market).filled_quantity, Quantity::ZERO);
+    engine.create_order(&mut market).unwrap();
+    engine.match_orders();
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(5u64))]
+    );
+}
+
+#[test]
+fn test_same_firm_matches_when_allowed() {
+    let (book, engine) = new_engine(false);
+    engine
+        .create_order(&mut firm_order(1, Side::Sell, 100, 5, 1000, 7))
+        .unwrap();
+    engine
+        .create_order(&mut firm_order(2, Side::Buy, 100, 5, 1001, 7))
+        .unwrap();
+    engine.match_orders();
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use crossbeam::epoch;
+
+#[test]
+fn test_invariant_checks_pass_through_a_session() {
+    let (book, engine) = TestEngine::new().with_invariant_checks().build();
+    for (id, side, price, qty) in [
+        (1, Side::Sell, 101, 5),
+        (2, Side::Sell, 100, 5),
+        (3, Side::Buy, 99, 5),
+        (4, Side::Buy, 98, 5),
+    ] {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, qty, 1000 + id))
+            .unwrap();
+    }
+    engine
+        .create_order(&mut make_market_order(5, Side::Buy, 7, 2000))
+        .unwrap();
+    engine.match_orders();
+
+    engine.update_order(3, Price::from(100u64), 3000).unwrap();
+    engine
+        .amend_quantity(4, Quantity::from(2u64), 3001)
+        .unwrap();
+    engine.cancel_order(1).unwrap();
+    engine.compact();
+
+    assert_eq!(book.check_invariants(), Ok(()));
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(3, Quantity::from(5u64)), (4, Quantity::from(2u64))]
+    );
+}
+
+#[test]
+#[should_panic(expected = "order 1 is finished but still reachable")]
+fn test_invariant_checks_panic_on_a_reachable_finished_order() {
+    let (book, engine) = TestEngine::new().with_invariant_checks().build();
+    let order = make_limit_order(1, Side::Buy, 100, 5, 1000);
+    engine.create_order(&mut order.clone()).unwrap();
+
+    // Finish the order in place, as a walk that never unlinks it would
+    let guard = &epoch::pin();
+    let entry = book
+        .get_book(Side::Buy)
+        .get(&order.book_key(), guard)
+        .unwrap();
+    assert!(entry.value().lifecycle.enter_finished_from_active());
+    assert!(book.check_invariants().is_err());
+
+    // Single-order changes are not checked; the next match cycle is
+    engine
+        .create_order(&mut make_limit_order(2, Side::Buy, 99, 5, 1001))
+        .unwrap();
+    engine.match_orders();
+}
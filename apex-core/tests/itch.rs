@@ -0,0 +1,131 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+
+const BASE_MICROS: u64 = 1_700_000_000_000_000;
+
+fn header(message_type: u8, nanos: u64, order_ref: u64) -> Vec<u8> {
+    let mut message = vec![message_type, 0, 1, 0, 0];
+    message.extend_from_slice(&nanos.to_be_bytes()[2..]);
+    message.extend_from_slice(&order_ref.to_be_bytes());
+    message
+}
+
+fn add(nanos: u64, order_ref: u64, side: u8, shares: u32, stock: &[u8; 8], price: u32) -> Vec<u8> {
+    let mut message = header(b'A', nanos, order_ref);
+    message.push(side);
+    message.extend_from_slice(&shares.to_be_bytes());
+    message.extend_from_slice(stock);
+    message.extend_from_slice(&price.to_be_bytes());
+    message
+}
+
+fn with_shares(message_type: u8, nanos: u64, order_ref: u64, shares: u32) -> Vec<u8> {
+    let mut message = header(message_type, nanos, order_ref);
+    message.extend_from_slice(&shares.to_be_bytes());
+    if message_type == b'E' {
+        message.extend_from_slice(&7u64.to_be_bytes());
+    }
+    message
+}
+
+fn stream(messages: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for message in messages {
+        data.extend_from_slice(&(message.len() as u16).to_be_bytes());
+        data.extend_from_slice(message);
+    }
+    data
+}
+
+#[test]
+fn test_itch_messages_become_commands() {
+    let data = stream(&[
+        add(5_000, 10, b'B', 300, b"AAPL    ", 1_500_000),
+        add(6_000, 11, b'S', 200, b"MSFT    ", 3_000_000),
+        vec![b'S', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, b'O'],
+        with_shares(b'X', 7_000, 10, 100),
+        with_shares(b'E', 8_000, 10, 50),
+        header(b'D', 9_000, 10),
+        header(b'D', 9_500, 11),
+    ]);
+    let mut loader = ItchLoader::new(BASE_MICROS).with_stock("AAPL");
+    let commands = loader.load(&data).unwrap();
+    assert_eq!(commands.len(), 4);
+    match &commands[0] {
+        Command::Create(order) => {
+            assert_eq!(order.id, 10);
+            assert_eq!(order.side, Side::Buy);
+            assert_eq!(order.price, Price::from(1_500_000u64));
+            assert_eq!(order.quantity(), Quantity::from(300u64));
+            assert_eq!(order.created_at, BASE_MICROS + 5);
+        }
+        other => panic!("unexpected command {other:?}"),
+    }
+    assert!(matches!(
+        commands[1],
+        Command::Amend { order_id: 10, new_quantity, .. } if new_quantity == Quantity::from(200u64)
+    ));
+    assert!(matches!(
+        commands[2],
+        Command::Amend { order_id: 10, new_quantity, .. } if new_quantity == Quantity::from(150u64)
+    ));
+    assert!(matches!(commands[3], Command::Cancel(10)));
+    assert_eq!(loader.open_orders(), 0);
+}
+
+#[test]
+fn test_itch_replace_and_replay() {
+    let mut replace = header(b'U', 3_000, 1);
+    replace.extend_from_slice(&2u64.to_be_bytes());
+    replace.extend_from_slice(&40u32.to_be_bytes());
+    replace.extend_from_slice(&101u32.to_be_bytes());
+    let data = stream(&[
+        add(1_000, 1, b'B', 50, b"AAPL    ", 100),
+        add(2_000, 3, b'S', 30, b"AAPL    ", 105),
+        replace,
+        with_shares(b'E', 4_000, 3, 10),
+    ]);
+    let mut loader = ItchLoader::new(BASE_MICROS).with_execution(ItchExecution::Aggress);
+    let commands = loader.load(&data).unwrap();
+
+    let (_book, engine) = TestEngine::new().build();
+    for command in commands {
+        command.apply(&engine);
+        engine.match_orders();
+    }
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.bids.len(), 1);
+    assert_eq!(snapshot.bids[0].id, 2);
+    assert_eq!(snapshot.bids[0].price, Price::from(101u64));
+    assert_eq!(snapshot.asks[0].quantity, Quantity::from(20u64));
+    assert_eq!(snapshot.asks[0].filled_quantity, Quantity::from(10u64));
+}
+
+#[test]
+fn test_itch_errors() {
+    let mut loader = ItchLoader::new(BASE_MICROS);
+    let mut data = stream(&[add(1_000, 1, b'B', 50, b"AAPL    ", 100)]);
+    data.truncate(data.len() - 1);
+    assert_eq!(
+        loader.load(&data).unwrap_err(),
+        ItchError::Truncated { offset: 0 }
+    );
+    assert_eq!(
+        loader.parse_message(&[b'D', 0, 0]).unwrap_err(),
+        ItchError::MessageTooShort {
+            message_type: b'D',
+            length: 3
+        }
+    );
+    assert_eq!(
+        loader
+            .parse_message(&add(1_000, 9, b'Z', 50, b"AAPL    ", 100))
+            .unwrap_err(),
+        ItchError::InvalidSide {
+            order_ref: 9,
+            side: b'Z'
+        }
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_log_linear_histogram_percentiles() {
+    let histogram = Histogram::log_linear();
+    assert_eq!(histogram.value_at_percentile(50.0), 0);
+    for nanos in 1..=1000u64 {
+        histogram.observe(nanos * 1000);
+    }
+    assert_eq!(histogram.count(), 1000);
+    assert_eq!(histogram.max(), 1_000_000);
+    for (percentile, expected) in [(50.0, 500_000u64), (99.0, 990_000), (100.0, 1_000_000)] {
+        let value = histogram.value_at_percentile(percentile);
+        assert!(value >= expected, "p{percentile} = {value}");
+        assert!(value <= expected + expected / 16, "p{percentile} = {value}");
+    }
+    histogram.observe(3);
+    assert_eq!(histogram.value_at_percentile(0.0), 3);
+}
+
+#[test]
+fn test_tracker_records_first_fill_and_completion() {
+    let tracker = LatencyTracker::new();
+    let start = Instant::now();
+    tracker.record_accept(1, start);
+    tracker.record_accept(2, start);
+
+    let maker = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let taker = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    let partial = Trade {
+        trade_id: 7,
+        role: TradeRole::Maker,
+        order_id: 1,
+        price: Price::from(100u64),
+        quantity: Quantity::from(4u64),
+        created_at: 0,
+        liquidation: false,
+        off_book: false,
+        tag: OrderTag::default(),
+        source: OrderSource::Api,
+        counterparty_source: OrderSource::Api,
+        #[cfg(feature = "hierarchy")]
+        account_id: None,
+        #[cfg(feature = "hierarchy")]
+        broker_id: None,
+    };
+    maker.status.store(OrderStatus::PartiallyFilled);
+    tracker.record_matched(
+        std::slice::from_ref(&maker),
+        std::slice::from_ref(&partial),
+        start + Duration::from_micros(5),
+    );
+    assert_eq!(tracker.accept_to_first_fill().count(), 1);
+    assert_eq!(tracker.accept_to_completion().count(), 0);
+
+    maker.status.store(OrderStatus::Filled);
+    tracker.record_matched(
+        &[maker, taker],
+        &[partial],
+        start + Duration::from_micros(9),
+    );
+    assert_eq!(tracker.accept_to_first_fill().count(), 1);
+    assert_eq!(tracker.accept_to_completion().count(), 1);
+    assert_eq!(tracker.accept_to_completion().max(), 9_000);
+    assert_eq!(tracker.pending(), 1);
+}
+
+#[test]
+fn test_tracker_as_book_syncer() {
+    let tracker = Arc::new(LatencyTracker::new());
+    let (_book, engine) = TestEngine::new().with_syncer(tracker.clone()).build();
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 100, 10, 1001),
+        make_limit_order(3, Side::Buy, 90, 10, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.match_orders();
+    assert_eq!(tracker.accept_to_first_fill().count(), 2);
+    assert_eq!(tracker.accept_to_completion().count(), 2);
+    assert_eq!(tracker.pending(), 1);
+
+    engine.cancel_order(3).unwrap();
+    assert_eq!(tracker.pending(), 0);
+}
@@ -16,10 +16,7 @@ fn test_lifecycle_initial_state_active() {
     let mut buy = make_limit_order(1, Side::Buy, 100, 10, 1000);
     engine.create_order(&mut buy).unwrap();
 
-    assert_eq!(
-        OrderLifecycle::from(buy.lifecycle.load(std::sync::atomic::Ordering::Acquire)),
-        OrderLifecycle::Active
-    );
+    assert_eq!(buy.lifecycle.load(), OrderLifecycle::Active);
 }
 
 #[test]
@@ -101,3 +98,119 @@ fn test_lifecycle_transition_to_finished_after_cancel() {
         "Order should be removed from book after cancel"
     );
 }
+
+#[test]
+fn test_duplicate_order_id_rejected() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    let mut first = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut duplicate = make_limit_order(1, Side::Sell, 105, 5, 1001);
+    engine.create_order(&mut first).unwrap();
+
+    assert_eq!(
+        engine.create_order(&mut duplicate),
+        Err(RejectReason::DuplicateOrderId)
+    );
+    assert_eq!(duplicate.status(), OrderStatus::Rejected);
+    assert_eq!(
+        duplicate.reject_reason(),
+        Some(RejectReason::DuplicateOrderId)
+    );
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+
+    // The original order is still reachable through the index
+    engine.cancel_order(1).unwrap();
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+}
+
+#[test]
+fn test_concurrent_reads_during_matching() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    let mut maker = make_limit_order(1, Side::Sell, 100, 1000, 1000);
+    engine.create_order(&mut maker).unwrap();
+
+    let reader_book = book.clone();
+    let reader = std::thread::spawn(move || {
+        for _ in 0..10_000 {
+            let guard = &epoch::pin();
+            if let Some(entry) = reader_book.get_book(Side::Sell).front(guard) {
+                let order = entry.value();
+                assert!(order.quantity() <= Quantity::from(1000u64));
+                assert!(order.filled_quantity() <= Quantity::from(1000u64));
+            }
+        }
+    });
+
+    for i in 0..100 {
+        let mut taker = make_limit_order(2 + i, Side::Buy, 100, 10, 2000 + i);
+        engine.create_order(&mut taker).unwrap();
+        engine.match_orders();
+    }
+    reader.join().unwrap();
+
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
+
+#[test]
+fn test_priority_collision_rejected_with_timestamp_conflict() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    // Same price and time, and ids equal modulo 100, give the same book key
+    let mut first = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut colliding = make_limit_order(101, Side::Buy, 100, 5, 1000);
+    engine.create_order(&mut first).unwrap();
+    assert_eq!(
+        engine.create_order(&mut colliding),
+        Err(RejectReason::TimestampConflict)
+    );
+    assert_eq!(colliding.status(), OrderStatus::Rejected);
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(10u64))]
+    );
+
+    // The rejected order's id was released
+    let mut retried = make_limit_order(101, Side::Buy, 100, 5, 1001);
+    assert_eq!(engine.create_order(&mut retried), Ok(()));
+
+    let mut market = make_market_order(2, Side::Sell, 1, 2000);
+    let mut market_colliding = make_market_order(102, Side::Sell, 1, 2000);
+    engine.create_order(&mut market).unwrap();
+    assert_eq!(
+        engine.create_order(&mut market_colliding),
+        Err(RejectReason::TimestampConflict)
+    );
+}
+
+#[test]
+fn test_reprice_collision_is_moved_back() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    let mut resting = make_limit_order(1, Side::Buy, 100, 10, 3000);
+    let mut moving = make_limit_order(101, Side::Buy, 99, 5, 1000);
+    engine.create_order(&mut resting).unwrap();
+    engine.create_order(&mut moving).unwrap();
+
+    // Re-pricing onto the resting order's key must not overwrite it
+    engine.update_order(101, Price::from(100u64), 3000).unwrap();
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(10u64)), (101, Quantity::from(5u64))]
+    );
+    assert_eq!(book.get_order(101).unwrap().updated_at, 3001);
+    engine.cancel_order(1).unwrap();
+    engine.cancel_order(101).unwrap();
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine(
+    max_resting_micros: Option<u64>,
+) -> (Arc<ManualClock>, Arc<Recorder>, DefaultMatchingEngine) {
+    let clock = Arc::new(ManualClock::new(0));
+    let recorder = Arc::new(Recorder::default());
+    let (_book, engine) = TestEngine::new()
+        .with_syncer(recorder.clone())
+        .with_config(BookConfig {
+            max_resting_micros,
+            ..BookConfig::default()
+        })
+        .build();
+    (clock.clone(), recorder, engine.with_clock(clock))
+}
+
+#[test]
+fn test_sweeper_expires_orders_past_lifetime() {
+    let (clock, recorder, engine) = new_engine(Some(10_000));
+    let mut old = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut young = make_limit_order(2, Side::Sell, 110, 10, 5000);
+    let mut dated = make_limit_order(3, Side::Buy, 99, 10, 2000);
+    dated.time_in_force = TimeInForce::GoodTillDate(8000);
+    for order in [&mut old, &mut young, &mut dated] {
+        clock.set(order.created_at);
+        engine.create_order(order).unwrap();
+    }
+
+    assert!(engine.expire_orders(7999).is_empty());
+    assert_eq!(engine.expire_orders(8000), vec![3]);
+    assert_eq!(engine.expire_orders(11_000), vec![1]);
+    assert_eq!(engine.expire_orders(15_000), vec![2]);
+    assert_eq!(
+        recorder
+            .cancelled()
+            .iter()
+            .map(|order| (order.id, order.status(), order.cancel_reason()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                3,
+                OrderStatus::Expired,
+                Some(CancelReason::TimeInForceExpired)
+            ),
+            (
+                1,
+                OrderStatus::Expired,
+                Some(CancelReason::LifetimeExceeded)
+            ),
+            (
+                2,
+                OrderStatus::Expired,
+                Some(CancelReason::LifetimeExceeded)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_orders_rest_without_lifetime_cap() {
+    let (_clock, recorder, engine) = new_engine(None);
+    let mut order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    engine.create_order(&mut order).unwrap();
+    assert!(engine.expire_orders(u64::MAX).is_empty());
+    assert!(recorder.cancelled().is_empty());
+    assert_eq!(engine.open_orders(1).len(), 1);
+}
+
+#[test]
+fn test_lifetime_counts_from_acceptance() {
+    let (clock, _recorder, engine) = new_engine(Some(10_000));
+    clock.set(10_000);
+    // A client timestamp far in the past does not shorten the order's life
+    let mut forged = make_limit_order(1, Side::Buy, 100, 10, 0);
+    engine.create_order(&mut forged).unwrap();
+    assert!(engine.expire_orders(15_000).is_empty());
+    assert_eq!(engine.expire_orders(20_000), vec![1]);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+struct RejectAll {}
+
+impl RiskChecker for RejectAll {
+    fn check(&self, _order: &Order) -> RiskDecision {
+        RiskDecision::Reject(RejectReason::RiskRejected(1))
+    }
+}
+
+fn liquidation(id: u64, side: Side, price: u64, qty: u64, ts: u64) -> Order {
+    let mut order = make_limit_order(id, side, price, qty, ts);
+    order.class = OrderClass::Liquidation;
+    order
+}
+
+fn new_engine() -> (Arc<Recorder>, DefaultMatchingEngine) {
+    let recorder = Arc::new(Recorder::default());
+    let (_book, engine) = TestEngine::new().with_syncer(recorder.clone()).build();
+    (recorder, engine)
+}
+
+#[test]
+fn test_liquidation_bypasses_user_risk_checks() {
+    let (_book, engine) = TestEngine::new().build();
+    let engine = engine
+        .with_risk_checker(Arc::new(RejectAll {}))
+        .with_rate_limiter(RateLimiter::new(1, 1));
+
+    let mut regular = make_limit_order(1, Side::Buy, 100, 1, 1000);
+    assert_eq!(
+        engine.create_order(&mut regular),
+        Err(RejectReason::RiskRejected(1))
+    );
+    for id in 2..5 {
+        let mut forced = liquidation(id, Side::Buy, 100, 1, 1000 + id);
+        assert_eq!(engine.create_order(&mut forced), Ok(()));
+    }
+    let mut zero = liquidation(5, Side::Buy, 100, 0, 1005);
+    assert_eq!(
+        engine.create_order(&mut zero),
+        Err(RejectReason::ZeroQuantity)
+    );
+}
+
+#[test]
+fn test_liquidation_rests_ahead_at_same_price() {
+    let (recorder, engine) = new_engine();
+    let mut regular = make_limit_order(1, Side::Sell, 100, 5, 1000);
+    let mut forced = liquidation(2, Side::Sell, 100, 5, 1001);
+    engine.create_order(&mut regular).unwrap();
+    engine.create_order(&mut forced).unwrap();
+    let asks = engine.snapshot().asks;
+    assert_eq!(
+        asks.iter().map(|order| order.id).collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+
+    let mut buyer = make_market_order(3, Side::Buy, 5, 1002);
+    engine.create_order(&mut buyer).unwrap();
+    engine.match_orders();
+    let trades = recorder.trades();
+    assert_eq!(trades.len(), 2);
+    assert!(trades.iter().all(|trade| trade.liquidation));
+    assert_eq!(trades[0].order_id, 2);
+    assert_eq!(engine.snapshot().asks[0].id, 1);
+}
+
+#[test]
+fn test_liquidation_takes_when_crossing() {
+    let (recorder, engine) = new_engine();
+    let mut bid = make_limit_order(1, Side::Buy, 100, 5, 1000);
+    let mut forced = liquidation(2, Side::Sell, 90, 2, 1001);
+    engine.create_order(&mut bid).unwrap();
+    engine.create_order(&mut forced).unwrap();
+    engine.match_orders();
+
+    let trades = recorder.trades();
+    assert_eq!(trades.len(), 2);
+    let taker = trades
+        .iter()
+        .find(|trade| trade.role == TradeRole::Taker)
+        .unwrap();
+    assert_eq!(taker.order_id, 2);
+    assert_eq!(taker.price, Price::from(100u64));
+    assert!(taker.liquidation);
+}
+
+#[test]
+fn test_trades_carry_the_source_of_both_sides() {
+    let (recorder, engine) = new_engine();
+    let mut maker = make_limit_order(1, Side::Buy, 100, 5, 1000);
+    maker.source = OrderSource::Fix;
+    engine.create_order(&mut maker).unwrap();
+    let mut taker = Order::builder(2, Side::Sell)
+        .market()
+        .quantity(Quantity::from(5u64))
+        .class(OrderClass::Liquidation)
+        .source(OrderSource::Liquidation)
+        .timestamp(1001)
+        .build()
+        .unwrap();
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+
+    let trades = recorder.trades();
+    let sources: Vec<_> = trades
+        .iter()
+        .map(|trade| (trade.order_id, trade.source, trade.counterparty_source))
+        .collect();
+    assert_eq!(
+        sources,
+        vec![
+            (1, OrderSource::Fix, OrderSource::Liquidation),
+            (2, OrderSource::Liquidation, OrderSource::Fix),
+        ]
+    );
+    assert_eq!(
+        make_limit_order(3, Side::Buy, 100, 1, 1000).source,
+        OrderSource::Api
+    );
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine() -> (Arc<OrderListenerSyncer>, DefaultMatchingEngine) {
+    let listener = Arc::new(OrderListenerSyncer::new(Arc::new(EmptyOrderBookSyncer {})));
+    let (_book, engine) = TestEngine::new().with_syncer(listener.clone()).build();
+    (listener, engine)
+}
+
+#[test]
+fn test_listener_tracks_fills_of_one_order() {
+    let (listener, engine) = new_engine();
+    let receiver = listener.listen(1);
+    let mut maker = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    engine.create_order(&mut maker).unwrap();
+    for (id, quantity, timestamp) in [(2, 4, 1001), (3, 6, 1002)] {
+        let mut taker = make_limit_order(id, Side::Buy, 100, quantity, timestamp);
+        engine.create_order(&mut taker).unwrap();
+        engine.match_orders();
+    }
+
+    let events = receiver
+        .try_iter()
+        .map(|update| update.event)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            OrderStatusEvent::Accepted,
+            OrderStatusEvent::PartiallyFilled {
+                quantity: Quantity::from(4u64),
+                remaining: Quantity::from(6u64),
+            },
+            OrderStatusEvent::Filled {
+                quantity: Quantity::from(6u64),
+            },
+        ]
+    );
+    // The listener is dropped after the terminal event
+    assert_eq!(listener.listeners(), 0);
+}
+
+#[test]
+fn test_listener_reports_cancel_expiry_and_rejection() {
+    let (listener, engine) = new_engine();
+    let engine = engine
+        .with_clock(Arc::new(ManualClock::new(1000)))
+        .with_config(BookConfig {
+            max_resting_micros: Some(1000),
+            ..BookConfig::default()
+        });
+    let cancelled = listener.listen(1);
+    let expired = listener.listen(2);
+    let rejected = listener.listen(3);
+    let mut resting = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut lifetime = make_limit_order(2, Side::Buy, 99, 10, 1000);
+    let mut market = make_market_order(3, Side::Sell, 50, 1001);
+    engine.create_order(&mut resting).unwrap();
+    engine.create_order(&mut lifetime).unwrap();
+    engine.cancel_order(1).unwrap();
+    engine.expire_orders(5000);
+    engine.create_order(&mut market).unwrap();
+    engine.match_orders();
+
+    let last = |receiver: &crossbeam::channel::Receiver<OrderStatusUpdate>| {
+        receiver.try_iter().last().map(|update| update.event)
+    };
+    assert_eq!(
+        last(&cancelled),
+        Some(OrderStatusEvent::Cancelled(CancelReason::UserRequest))
+    );
+    assert_eq!(
+        last(&expired),
+        Some(OrderStatusEvent::Expired(CancelReason::LifetimeExceeded))
+    );
+    assert_eq!(
+        last(&rejected),
+        Some(OrderStatusEvent::Rejected(
+            RejectReason::InsufficientLiquidity
+        ))
+    );
+}
+
+#[test]
+fn test_unlistened_orders_are_not_delivered() {
+    let (listener, engine) = new_engine();
+    let receiver = listener.listen(1);
+    let other = listener.listen(2);
+    listener.unlisten(2);
+    let mut first = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut second = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    engine.create_order(&mut first).unwrap();
+    engine.create_order(&mut second).unwrap();
+
+    let update = receiver.try_recv().unwrap();
+    assert_eq!(update.order.id, 1);
+    assert_eq!(update.event, OrderStatusEvent::Accepted);
+    assert!(receiver.try_recv().is_err());
+    assert!(other.try_recv().is_err());
+    assert_eq!(listener.listeners(), 1);
+}
+
+#[test]
+fn test_listener_reports_the_end_of_partially_filled_market_orders() {
+    let (listener, engine) = new_engine();
+    let engine = engine.with_config(BookConfig {
+        max_sweep_levels: Some(1),
+        self_trade_prevention: SelfTradePrevention::CancelTaker,
+        ..BookConfig::default()
+    });
+    let swept = listener.listen(10);
+    let ioc = listener.listen(11);
+    let self_traded = listener.listen(12);
+    for (id, price) in [(1, 100), (2, 101), (3, 102)] {
+        let mut maker = make_limit_order(id, Side::Sell, price, 4, 1000 + id);
+        maker.user_id = 2;
+        engine.create_order(&mut maker).unwrap();
+    }
+    // The first market order stops at the sweep limit after one level
+    let mut taker = make_market_order(10, Side::Buy, 6, 2000);
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+    engine.cancel_order(2).unwrap();
+    engine.cancel_order(3).unwrap();
+    let mut maker = make_limit_order(4, Side::Sell, 100, 2, 2001);
+    maker.user_id = 2;
+    engine.create_order(&mut maker).unwrap();
+
+    let mut sweeper = make_market_order(11, Side::Buy, 5, 2002);
+    engine.create_order(&mut sweeper).unwrap();
+    engine.match_orders();
+
+    // The last one stops at an order of its own owner
+    for (id, user_id, quantity) in [(5, 4, 1), (6, 3, 3)] {
+        let mut maker = make_limit_order(id, Side::Sell, 100, quantity, 3000 + id);
+        maker.user_id = user_id;
+        engine.create_order(&mut maker).unwrap();
+    }
+    let mut own = make_market_order(12, Side::Buy, 4, 3010);
+    own.user_id = 3;
+    engine.create_order(&mut own).unwrap();
+    engine.match_orders();
+
+    let events = |receiver: &crossbeam::channel::Receiver<OrderStatusUpdate>| {
+        receiver
+            .try_iter()
+            .map(|update| update.event)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        events(&swept),
+        vec![
+            OrderStatusEvent::Accepted,
+            OrderStatusEvent::PartiallyFilled {
+                quantity: Quantity::from(4u64),
+                remaining: Quantity::from(2u64),
+            },
+            OrderStatusEvent::Cancelled(CancelReason::SweepLimit),
+        ]
+    );
+    assert_eq!(
+        events(&ioc),
+        vec![
+            OrderStatusEvent::Accepted,
+            OrderStatusEvent::PartiallyFilled {
+                quantity: Quantity::from(2u64),
+                remaining: Quantity::from(3u64),
+            },
+            OrderStatusEvent::Expired(CancelReason::TimeInForceExpired),
+        ]
+    );
+    assert_eq!(
+        events(&self_traded),
+        vec![
+            OrderStatusEvent::Accepted,
+            OrderStatusEvent::PartiallyFilled {
+                quantity: Quantity::from(1u64),
+                remaining: Quantity::from(3u64),
+            },
+            OrderStatusEvent::Cancelled(CancelReason::SelfTradePrevention),
+        ]
+    );
+    assert_eq!(listener.listeners(), 0);
+}
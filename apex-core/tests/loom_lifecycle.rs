@@ -0,0 +1,69 @@
+//! Model checks of the order lifecycle protocol.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --release --test loom_lifecycle`.
+#![cfg(loom)]
+
+use apex_core::prelude::*;
+use loom::sync::Arc;
+use loom::thread;
+
+#[test]
+fn test_cancel_and_match_claim_an_active_order_once() {
+    loom::model(|| {
+        let state = Arc::new(LifecycleState::default());
+        let matcher = {
+            let state = state.clone();
+            thread::spawn(move || state.enter_matched())
+        };
+        let cancelled = state.enter_finished_from_active();
+        let matched = matcher.join().unwrap();
+
+        assert!(matched != cancelled);
+        let expected = if matched {
+            OrderLifecycle::Matched
+        } else {
+            OrderLifecycle::Finished
+        };
+        assert_eq!(state.load(), expected);
+    });
+}
+
+#[test]
+fn test_cancel_waits_out_a_partial_fill() {
+    loom::model(|| {
+        let state = Arc::new(LifecycleState::default());
+        let matcher = {
+            let state = state.clone();
+            thread::spawn(move || {
+                if state.enter_matched() {
+                    assert!(state.exit_matched());
+                }
+            })
+        };
+        let cancelled = state.enter_finished_from_active();
+        matcher.join().unwrap();
+
+        if cancelled {
+            assert_eq!(state.load(), OrderLifecycle::Finished);
+        } else {
+            // The matcher held the order; it is active again and can now be canceled
+            assert!(state.enter_finished_from_active());
+        }
+    });
+}
+
+#[test]
+fn test_completed_order_cannot_be_canceled() {
+    loom::model(|| {
+        let state = Arc::new(LifecycleState::default());
+        let matcher = {
+            let state = state.clone();
+            thread::spawn(move || state.enter_matched() && state.enter_finished_from_matched())
+        };
+        let cancelled = state.enter_finished_from_active();
+        let completed = matcher.join().unwrap();
+
+        assert!(completed != cancelled);
+        assert_eq!(state.load(), OrderLifecycle::Finished);
+    });
+}
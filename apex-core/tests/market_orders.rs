@@ -173,3 +173,162 @@ fn test_market_order_on_empty_book() {
     let remaining = get_book_state(book.as_ref(), Side::Buy);
     assert_eq!(remaining.len(), 0);
 }
+
+#[test]
+fn test_stale_market_order_is_rejected() {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new()
+        .with_syncer(syncer.clone())
+        .with_config(BookConfig {
+            max_market_order_age_micros: Some(500),
+            ..BookConfig::default()
+        })
+        .build();
+    let clock = Arc::new(ManualClock::new(1000));
+    let engine = engine.with_clock(clock.clone());
+
+    let mut sell = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    engine.create_order(&mut sell).unwrap();
+    // Ages are measured from acceptance, whatever time the client claims
+    let mut stale = make_market_order(2, Side::Buy, 4, 9000);
+    engine.create_order(&mut stale).unwrap();
+    clock.advance(400);
+    let mut fresh = make_market_order(3, Side::Buy, 4, 0);
+    engine.create_order(&mut fresh).unwrap();
+
+    clock.advance(101);
+    engine.match_orders();
+
+    let rejected: Vec<_> = syncer
+        .matched_orders()
+        .iter()
+        .filter(|order| order.status() == OrderStatus::Rejected)
+        .map(|order| (order.id, order.reject_reason()))
+        .collect();
+    assert_eq!(rejected, vec![(2, Some(RejectReason::StaleMarketOrder))]);
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(6u64))]
+    );
+    assert_eq!(RejectReason::StaleMarketOrder.code(), 115);
+}
+
+/// Gets the id, status, cancel reason and filled quantity of every taker that finished matching
+fn finished_takers(
+    syncer: &Recorder,
+) -> Vec<(OrderID, OrderStatus, Option<CancelReason>, Quantity)> {
+    syncer
+        .matched_orders()
+        .iter()
+        .filter(|order| order.order_type == OrderType::Market)
+        .map(|order| {
+            (
+                order.id,
+                order.status(),
+                order.cancel_reason(),
+                order.filled_quantity(),
+            )
+        })
+        .collect()
+}
+
+fn new_sweep_engine(
+    config: BookConfig,
+) -> (Arc<Recorder>, Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new()
+        .with_syncer(syncer.clone())
+        .with_config(config)
+        .build();
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 100, 2, 1000),
+        make_limit_order(2, Side::Sell, 100, 2, 1001),
+        make_limit_order(3, Side::Sell, 101, 2, 1002),
+        make_limit_order(4, Side::Sell, 102, 2, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    (syncer, book, engine)
+}
+
+#[test]
+fn test_market_order_stops_at_the_sweep_level_cap() {
+    let (syncer, book, engine) = new_sweep_engine(BookConfig {
+        max_sweep_levels: Some(2),
+        ..BookConfig::default()
+    });
+    let mut buy = make_market_order(5, Side::Buy, 8, 1004);
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+
+    // Both orders at 100 count as one level
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(4, Quantity::from(2u64))]
+    );
+    assert_eq!(
+        finished_takers(&syncer),
+        vec![(
+            5,
+            OrderStatus::Cancelled,
+            Some(CancelReason::SweepLimit),
+            Quantity::from(6u64)
+        )]
+    );
+}
+
+#[test]
+fn test_market_order_stops_before_the_sweep_notional_cap() {
+    let (syncer, book, engine) = new_sweep_engine(BookConfig {
+        max_sweep_notional: Some(Price::from(500u64)),
+        ..BookConfig::default()
+    });
+    let mut buy = make_market_order(5, Side::Buy, 8, 1004);
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+
+    // 400 is traded at 100; the 202 at 101 would exceed the cap
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(3, Quantity::from(2u64)), (4, Quantity::from(2u64))]
+    );
+    let finished = finished_takers(&syncer);
+    assert_eq!(finished[0].2, Some(CancelReason::SweepLimit));
+    assert_eq!(finished[0].3, Quantity::from(4u64));
+
+    // An order filled within the caps is not canceled
+    let (syncer, _book, engine) = new_sweep_engine(BookConfig {
+        max_sweep_levels: Some(2),
+        ..BookConfig::default()
+    });
+    let mut buy = make_market_order(6, Side::Buy, 6, 1004);
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+    assert_eq!(finished_takers(&syncer)[0].1, OrderStatus::Filled);
+}
+
+#[test]
+fn test_queued_market_order_is_not_amendable() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    let mut buy = make_market_order(1, Side::Buy, 10, 1000);
+    engine.create_order(&mut buy).unwrap();
+
+    assert_eq!(
+        engine.update_order(buy.id, Price::from(100u64), 1001),
+        Err(UpdateOrderError::MarketOrderNotAmendable)
+    );
+    assert_eq!(
+        engine.amend_quantity(buy.id, Quantity::from(5u64), 1001),
+        Err(UpdateOrderError::MarketOrderNotAmendable)
+    );
+    assert_eq!(
+        engine.update_order(42, Price::from(100u64), 1001),
+        Err(UpdateOrderError::OrderNotFound)
+    );
+    assert_eq!(UpdateOrderError::MarketOrderNotAmendable.code(), 205);
+}
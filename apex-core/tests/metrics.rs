@@ -0,0 +1,66 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine() -> (Arc<EngineMetrics>, DefaultMatchingEngine) {
+    let metrics = Arc::new(EngineMetrics::new());
+    let (_book, engine) = TestEngine::new().build();
+    let engine = engine.with_metrics(metrics.clone());
+    (metrics, engine)
+}
+
+#[test]
+fn test_engine_reports_counters_and_gauges() {
+    let (metrics, engine) = new_engine();
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 100, 4, 1001),
+        make_limit_order(3, Side::Buy, 98, 4, 1002),
+        make_limit_order(4, Side::Buy, 97, 4, 1003),
+        make_limit_order(5, Side::Buy, 97, 0, 1004),
+    ];
+    let results = engine.create_orders(&mut orders);
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 4);
+    let mut market = make_market_order(6, Side::Sell, 6, 1005);
+    engine.create_order(&mut market).unwrap();
+    engine.match_orders();
+    engine.cancel_order(4).unwrap();
+
+    assert_eq!(metrics.inserts(), 5);
+    assert_eq!(metrics.rejects(), 1);
+    assert_eq!(metrics.trades(), 2);
+    assert_eq!(metrics.cancels(), 1);
+    assert_eq!(metrics.resting_orders(Side::Sell), 1);
+    assert_eq!(metrics.depth(Side::Sell), 1);
+    assert_eq!(metrics.resting_orders(Side::Buy), 2);
+    assert_eq!(metrics.depth(Side::Buy), 2);
+    assert_eq!(metrics.match_cycle().count(), 1);
+}
+
+#[test]
+fn test_histogram_buckets_are_cumulative() {
+    let histogram = Histogram::new(&[10, 100]);
+    for value in [1, 10, 11, 500] {
+        histogram.observe(value);
+    }
+    assert_eq!(histogram.cumulative_counts(), vec![2, 3, 4]);
+    assert_eq!(histogram.sum(), 522);
+    assert_eq!(histogram.count(), 4);
+}
+
+#[cfg(feature = "prometheus")]
+#[test]
+fn test_prometheus_exporter_renders_text_format() {
+    let (metrics, engine) = new_engine();
+    let mut order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    engine.create_order(&mut order).unwrap();
+    engine.match_orders();
+
+    let text = PrometheusExporter::new(metrics, "apex").render();
+    assert!(text.contains("# TYPE apex_inserts_total counter\napex_inserts_total 1\n"));
+    assert!(text.contains("apex_resting_orders{side=\"buy\"} 1\n"));
+    assert!(text.contains("apex_match_cycle_microseconds_bucket{le=\"+Inf\"} 1\n"));
+    assert!(text.contains("apex_match_cycle_microseconds_count 1\n"));
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+
+#[test]
+fn test_cancel_only_rejects_new_orders_and_updates() {
+    let (book, engine) = TestEngine::new().build();
+    let mut resting = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    engine.create_order(&mut resting).unwrap();
+
+    engine.set_mode(EngineMode::CancelOnly);
+    assert_eq!(engine.mode(), EngineMode::CancelOnly);
+
+    let mut order = make_limit_order(2, Side::Buy, 101, 10, 1001);
+    assert_eq!(
+        engine.create_order(&mut order),
+        Err(RejectReason::CancelOnly)
+    );
+    assert_eq!(order.status(), OrderStatus::Rejected);
+    assert_eq!(order.reject_reason(), Some(RejectReason::CancelOnly));
+    assert_eq!(
+        engine.update_order(1, Price::from(99u64), 1002),
+        Err(UpdateOrderError::TradingSuspended)
+    );
+
+    assert_eq!(engine.cancel_order(1), Ok(()));
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+
+    engine.set_mode(EngineMode::Normal);
+    let mut order = make_limit_order(3, Side::Buy, 101, 10, 1003);
+    assert_eq!(engine.create_order(&mut order), Ok(()));
+}
+
+#[test]
+fn test_cancel_only_batch_applies_cancels_only() {
+    let (book, engine) = TestEngine::new().build();
+    let mut resting = make_limit_order(1, Side::Sell, 110, 10, 1000);
+    engine.create_order(&mut resting).unwrap();
+
+    engine.set_mode(EngineMode::CancelOnly);
+    let mut commands = vec![
+        Command::Create(make_limit_order(2, Side::Buy, 100, 10, 1001)),
+        Command::Cancel(1),
+        Command::Cancel(42),
+    ];
+    let results = engine.execute_batch(&mut commands);
+    assert_eq!(
+        results,
+        vec![
+            CommandResult::Created(Err(RejectReason::CancelOnly)),
+            CommandResult::Cancelled(Ok(())),
+            CommandResult::Cancelled(Err(CancelOrderError::OrderNotFound)),
+        ]
+    );
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
+
+#[test]
+fn test_expiry_sweeper_runs_in_cancel_only() {
+    let (book, engine) = TestEngine::new().build();
+    let mut expiring = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    expiring.time_in_force = TimeInForce::GoodTillDate(5000);
+    let mut lasting = make_limit_order(2, Side::Buy, 99, 10, 1001);
+    lasting.time_in_force = TimeInForce::GoodTillDate(9000);
+    engine.create_order(&mut expiring).unwrap();
+    engine.create_order(&mut lasting).unwrap();
+
+    engine.set_mode(EngineMode::CancelOnly);
+    assert!(engine.expire_orders(4999).is_empty());
+    assert_eq!(engine.expire_orders(5000), vec![1]);
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy)[0].0, 2);
+}
+
+#[test]
+fn test_halted_rejects_everything_and_stops_matching() {
+    let (book, engine) = TestEngine::new().build();
+    let mut sell = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let mut buy = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    engine.create_order(&mut sell).unwrap();
+    engine.create_order(&mut buy).unwrap();
+
+    engine.set_mode(EngineMode::Halted);
+    engine.match_orders();
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 1);
+    assert_eq!(get_book_state(book.as_ref(), Side::Sell).len(), 1);
+
+    let mut order = make_limit_order(3, Side::Buy, 100, 10, 1002);
+    assert_eq!(engine.create_order(&mut order), Err(RejectReason::Halted));
+    assert_eq!(engine.cancel_order(1), Err(CancelOrderError::EngineHalted));
+
+    engine.set_mode(EngineMode::Normal);
+    engine.match_orders();
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
@@ -169,3 +169,136 @@ fn test_cancelled_order_not_in_book() {
         "Sell side should be empty after cancel"
     );
 }
+
+#[test]
+fn test_amend_quantity_decrease_keeps_priority() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    let mut buy1 = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut buy2 = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    engine.create_order(&mut buy1).unwrap();
+    engine.create_order(&mut buy2).unwrap();
+
+    engine
+        .amend_quantity(buy1.id, Quantity::from(4u64), 1002)
+        .unwrap();
+
+    let state = get_book_state(book.as_ref(), Side::Buy);
+    assert_eq!(
+        state,
+        vec![(1, Quantity::from(4u64)), (2, Quantity::from(10u64))],
+        "Decreased order should keep its place at the front"
+    );
+}
+
+#[test]
+fn test_amend_quantity_increase_loses_priority() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    let mut buy1 = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut buy2 = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    engine.create_order(&mut buy1).unwrap();
+    engine.create_order(&mut buy2).unwrap();
+
+    engine
+        .amend_quantity(buy1.id, Quantity::from(15u64), 1002)
+        .unwrap();
+
+    let state = get_book_state(book.as_ref(), Side::Buy);
+    assert_eq!(
+        state,
+        vec![(2, Quantity::from(10u64)), (1, Quantity::from(15u64))],
+        "Increased order should move to the back of its level"
+    );
+    assert_eq!(
+        engine
+            .amend_quantity(buy1.id, Quantity::ZERO, 1003)
+            .unwrap_err()
+            .reason(),
+        Some(AmendFailure::InvalidQuantity)
+    );
+    assert_eq!(
+        engine.amend_quantity(42, Quantity::from(1u64), 1003),
+        Err(UpdateOrderError::OrderNotFound)
+    );
+}
+
+#[test]
+fn test_amend_order_keeps_priority_for_tif_and_decrease() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    let mut buy1 = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut buy2 = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    engine.create_order(&mut buy1).unwrap();
+    engine.create_order(&mut buy2).unwrap();
+
+    let request = AmendRequest {
+        quantity: Some(Quantity::from(6u64)),
+        time_in_force: Some(TimeInForce::GoodTillDate(5000)),
+        ..Default::default()
+    };
+    engine.amend_order(buy1.id, request, 1002).unwrap();
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(6u64)), (2, Quantity::from(10u64))],
+        "A decrease with a new time in force should keep priority"
+    );
+    assert_eq!(
+        book.get_order(buy1.id).unwrap().time_in_force,
+        TimeInForce::GoodTillDate(5000)
+    );
+    assert_eq!(engine.expire_orders(5000), vec![buy1.id]);
+}
+
+#[test]
+fn test_amend_order_price_and_quantity_at_once() {
+    let syncer = Arc::new(EmptyOrderBookSyncer {});
+    let id = Arc::new(AtomicU64::new(1));
+    let book = Arc::new(DefaultOrderBook::new(id, syncer));
+    let engine = DefaultMatchingEngine::new(book.clone());
+
+    let mut buy1 = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut buy2 = make_limit_order(2, Side::Buy, 101, 10, 1001);
+    engine.create_order(&mut buy1).unwrap();
+    engine.create_order(&mut buy2).unwrap();
+
+    let request = AmendRequest {
+        price: Some(Price::from(101u64)),
+        quantity: Some(Quantity::from(4u64)),
+        ..Default::default()
+    };
+    engine.amend_order(buy1.id, request, 1002).unwrap();
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(2, Quantity::from(10u64)), (1, Quantity::from(4u64))],
+        "A price change should move the order to the back of the new level"
+    );
+
+    for (request, reason) in [
+        (AmendRequest::default(), AmendFailure::EmptyRequest),
+        (
+            AmendRequest {
+                time_in_force: Some(TimeInForce::None),
+                ..Default::default()
+            },
+            AmendFailure::InvalidTimeInForce,
+        ),
+    ] {
+        assert_eq!(
+            engine
+                .amend_order(buy1.id, request, 1003)
+                .unwrap_err()
+                .reason(),
+            Some(reason)
+        );
+    }
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct EventRecorder {
+    rejects: Mutex<Vec<RejectEvent>>,
+    cancels: Mutex<Vec<CancelEvent>>,
+}
+
+impl OrderObserver for EventRecorder {
+    fn on_reject(&self, event: &RejectEvent) {
+        self.rejects.lock().unwrap().push(event.clone());
+    }
+
+    fn on_cancel(&self, event: &CancelEvent) {
+        self.cancels.lock().unwrap().push(event.clone());
+    }
+}
+
+fn new_engine() -> (Arc<EventRecorder>, DefaultMatchingEngine) {
+    let observer = Arc::new(EventRecorder::default());
+    let syncer = Arc::new(ObservedSyncer::new(
+        Arc::new(EmptyOrderBookSyncer {}),
+        observer.clone(),
+    ));
+    let (_book, engine) = TestEngine::new().with_syncer(syncer).build();
+    (observer, engine)
+}
+
+#[test]
+fn test_observer_receives_rejections() {
+    let (observer, engine) = new_engine();
+    let mut zero = make_limit_order(1, Side::Buy, 100, 0, 1000);
+    engine.create_order(&mut zero).unwrap_err();
+    let mut first = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    let mut duplicate = make_limit_order(2, Side::Buy, 101, 10, 1002);
+    engine.create_order(&mut first).unwrap();
+    engine.create_order(&mut duplicate).unwrap_err();
+    let mut market = make_market_order(3, Side::Buy, 10, 1003);
+    engine.create_order(&mut market).unwrap();
+    engine.match_orders();
+
+    let rejects = observer.rejects.lock().unwrap();
+    assert_eq!(
+        rejects
+            .iter()
+            .map(|event| (event.order.id, event.reason))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, RejectReason::ZeroQuantity),
+            (2, RejectReason::DuplicateOrderId),
+            (3, RejectReason::InsufficientLiquidity),
+        ]
+    );
+    assert_eq!(rejects[1].order.price, Price::from(101u64));
+    assert_eq!(rejects[2].order.status, OrderStatus::Rejected);
+}
+
+#[test]
+fn test_observer_receives_cancellations() {
+    let (observer, engine) = new_engine();
+    let mut expiring = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    expiring.time_in_force = TimeInForce::GoodTillDate(5000);
+    let mut orders = [
+        expiring,
+        make_limit_order(2, Side::Buy, 99, 10, 1001),
+        make_limit_order(3, Side::Sell, 110, 10, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.cancel_order(2).unwrap();
+    engine.expire_orders(5000);
+    engine.cancel_where(Side::Sell, None);
+
+    let cancels = observer.cancels.lock().unwrap();
+    assert_eq!(
+        cancels
+            .iter()
+            .map(|event| (event.order.id, event.reason))
+            .collect::<Vec<_>>(),
+        vec![
+            (2, CancelReason::UserRequest),
+            (1, CancelReason::TimeInForceExpired),
+            (3, CancelReason::MassCancel),
+        ]
+    );
+    assert_eq!(cancels[1].order.status, OrderStatus::Expired);
+}
+
+#[test]
+fn test_observer_receives_cancellations_during_matching() {
+    let (observer, engine) = new_engine();
+    let engine = engine.with_config(BookConfig {
+        self_trade_prevention: SelfTradePrevention::CancelBoth,
+        ..BookConfig::default()
+    });
+    let mut maker = make_limit_order(1, Side::Sell, 100, 5, 1000);
+    let mut taker = make_market_order(2, Side::Buy, 5, 1001);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+
+    assert_eq!(
+        observer
+            .cancels
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| (event.order.id, event.reason))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, CancelReason::SelfTradePrevention),
+            (2, CancelReason::SelfTradePrevention),
+        ]
+    );
+    assert!(observer.rejects.lock().unwrap().is_empty());
+}
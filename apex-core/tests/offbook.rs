@@ -0,0 +1,67 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn block(price: u64, quantity: u64) -> OffBookTrade {
+    OffBookTrade::new(
+        (901, 7),
+        (902, 8),
+        Price::from(price),
+        Quantity::from(quantity),
+    )
+}
+
+#[test]
+fn test_off_book_trades_reach_trade_consumers() {
+    let history = Arc::new(TradeHistory::new(10));
+    let (_book, engine) = TestEngine::new().with_syncer(history.clone()).build();
+    let metrics = Arc::new(EngineMetrics::default());
+    let engine = engine.with_metrics(metrics.clone()).with_trade_journal(8);
+    let mut resting = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    engine.create_order(&mut resting).unwrap();
+
+    let (sell, buy) = engine.report_off_book_trade(&block(95, 500)).unwrap();
+    assert_eq!(sell.trade_id, buy.trade_id);
+    assert!(sell.off_book && buy.off_book);
+    assert_eq!(sell.role, TradeRole::Maker);
+    assert_eq!(buy.order_id, 901);
+
+    let fills = history.trades_for_user(7, 10);
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].side, Side::Buy);
+    assert_eq!(fills[0].trade.quantity, Quantity::from(500u64));
+    assert_eq!(history.trades_for_user(8, 10)[0].side, Side::Sell);
+    assert_eq!(metrics.trades(), 1);
+
+    // Resting orders and the last trade price are untouched
+    assert_eq!(engine.snapshot().asks[0].quantity, Quantity::from(10u64));
+    assert_eq!(engine.last_trade_price(), None);
+    // Reported trades can be busted like matched ones
+    assert!(
+        engine
+            .bust_trade(sell.trade_id)
+            .unwrap()
+            .restored
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_invalid_off_book_trades_are_rejected() {
+    let (_book, engine) = TestEngine::new().build();
+    assert_eq!(
+        engine.report_off_book_trade(&block(95, 0)),
+        Err(RejectReason::ZeroQuantity)
+    );
+    assert_eq!(
+        engine.report_off_book_trade(&block(0, 5)),
+        Err(RejectReason::InvalidPrice)
+    );
+    engine.set_mode(EngineMode::Halted);
+    assert_eq!(
+        engine.report_off_book_trade(&block(95, 5)),
+        Err(RejectReason::Halted)
+    );
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine(limits: Arc<PositionLimits>) -> (Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let (book, engine) = TestEngine::new().with_syncer(limits.clone()).build();
+    let engine = engine.with_risk_checker(limits);
+    (book, engine)
+}
+
+fn user_order(id: u64, user_id: u64, side: Side, price: u64, qty: u64, ts: u64) -> Order {
+    let mut order = make_limit_order(id, side, price, qty, ts);
+    order.user_id = user_id;
+    order
+}
+
+fn limit(max_long: u64, max_short: u64) -> PositionLimit {
+    PositionLimit::new(Quantity::from(max_long), Quantity::from(max_short))
+}
+
+#[test]
+fn test_positions_follow_trades() {
+    let limits = Arc::new(PositionLimits::new(limit(100, 100)));
+    let (_book, engine) = new_engine(limits.clone());
+    let mut sell = user_order(1, 8, Side::Sell, 100, 10, 1000);
+    let mut buy = user_order(2, 7, Side::Buy, 100, 6, 1001);
+    engine.create_order(&mut sell).unwrap();
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+
+    assert_eq!(
+        limits.position(7),
+        Position {
+            bought: Quantity::from(6u64),
+            sold: Quantity::ZERO,
+        }
+    );
+    assert_eq!(limits.position(8).sold, Quantity::from(6u64));
+    assert_eq!(limits.position(9), Position::default());
+}
+
+#[test]
+fn test_orders_breaching_limits_are_rejected() {
+    let limits = Arc::new(PositionLimits::new(limit(100, 100)));
+    limits.set_limit(7, limit(10, 10));
+    let (_book, engine) = new_engine(limits.clone());
+    let mut sell = user_order(1, 8, Side::Sell, 100, 6, 1000);
+    let mut buy = user_order(2, 7, Side::Buy, 100, 6, 1001);
+    engine.create_order(&mut sell).unwrap();
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+
+    let mut order = user_order(3, 7, Side::Buy, 90, 5, 1002);
+    assert_eq!(
+        engine.create_order(&mut order),
+        Err(RejectReason::PositionLimitExceeded)
+    );
+    let mut order = user_order(4, 7, Side::Buy, 90, 4, 1003);
+    assert_eq!(engine.create_order(&mut order), Ok(()));
+
+    let mut order = user_order(5, 7, Side::Sell, 110, 17, 1004);
+    assert_eq!(
+        engine.create_order(&mut order),
+        Err(RejectReason::PositionLimitExceeded)
+    );
+    let mut order = user_order(6, 7, Side::Sell, 110, 16, 1005);
+    assert_eq!(engine.create_order(&mut order), Ok(()));
+
+    let mut order = user_order(7, 8, Side::Buy, 90, 50, 1006);
+    assert_eq!(engine.create_order(&mut order), Ok(()));
+}
+
+#[test]
+fn test_busts_and_rescales_reach_the_positions() {
+    let limits = PositionLimits::new(limit(100, 100));
+    let sell = user_order(1, 8, Side::Sell, 100, 10, 1000);
+    let buy = user_order(2, 7, Side::Buy, 100, 10, 1001);
+    let side = |trade_id, role, order_id, quantity: u64| Trade {
+        trade_id,
+        role,
+        order_id,
+        price: Price::from(100u64),
+        quantity: Quantity::from(quantity),
+        ..Trade::default()
+    };
+    let first = [
+        side(1, TradeRole::Maker, 1, 4),
+        side(1, TradeRole::Taker, 2, 4),
+    ];
+    let second = [
+        side(2, TradeRole::Maker, 1, 6),
+        side(2, TradeRole::Taker, 2, 6),
+    ];
+    limits.record(&[sell.clone(), buy.clone()], &first);
+    limits.record(&[sell, buy], &second);
+
+    let bust = |trade_id, original: &[Trade]| TradeCorrection {
+        trade_id,
+        kind: TradeCorrectionKind::Bust,
+        original: original.to_vec(),
+        restored: Vec::new(),
+        created_at: 2000,
+    };
+    limits.trade_corrected(1, &bust(1, &first));
+    // Busting the same trade twice takes it out once
+    limits.trade_corrected(2, &bust(1, &first));
+    assert_eq!(
+        limits.position(7),
+        Position {
+            bought: Quantity::from(6u64),
+            sold: Quantity::ZERO,
+        }
+    );
+    assert_eq!(limits.position(8).sold, Quantity::from(6u64));
+
+    limits.rescaled(
+        3,
+        &Rescale {
+            price: ScaleFactor::new(1, 10).unwrap(),
+            quantity: ScaleFactor::new(10, 1).unwrap(),
+        },
+    );
+    assert_eq!(limits.position(7).bought, Quantity::from(60u64));
+    limits.trade_corrected(4, &bust(2, &second));
+    assert_eq!(limits.position(7), Position::default());
+    assert_eq!(limits.position(8), Position::default());
+}
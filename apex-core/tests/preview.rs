@@ -0,0 +1,102 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine() -> (Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let (book, engine) = TestEngine::new().build();
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 100, 3, 1000),
+        make_limit_order(2, Side::Sell, 101, 4, 1001),
+        make_limit_order(3, Side::Sell, 103, 5, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    (book, engine)
+}
+
+fn fills(preview: &MatchPreview) -> Vec<(OrderID, u64, u64)> {
+    preview
+        .fills
+        .iter()
+        .map(|fill| {
+            (
+                fill.maker_order_id,
+                fill.price.as_words()[0],
+                fill.quantity.as_words()[0],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_preview_limit_order_does_not_change_the_book() {
+    let (book, engine) = new_engine();
+    let order = make_limit_order(10, Side::Buy, 101, 9, 2000);
+    let preview = engine.preview(&order);
+    assert_eq!(fills(&preview), vec![(1, 100, 3), (2, 101, 4)]);
+    assert_eq!(preview.filled_quantity, Quantity::from(7u64));
+    assert_eq!(preview.remaining_quantity, Quantity::from(2u64));
+    // (3 * 100 + 4 * 101) / 7 rounds down to 100
+    assert_eq!(preview.average_price, Some(Price::from(100u64)));
+
+    assert_eq!(order.quantity(), Quantity::from(9u64));
+    assert_eq!(order.status(), OrderStatus::default());
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![
+            (1, Quantity::from(3u64)),
+            (2, Quantity::from(4u64)),
+            (3, Quantity::from(5u64)),
+        ]
+    );
+}
+
+#[test]
+fn test_preview_matches_the_real_execution() {
+    let (_book, engine) = new_engine();
+    let mut order = make_limit_order(10, Side::Buy, 103, 10, 2000);
+    let preview = engine.preview(&order);
+    assert_eq!(fills(&preview), vec![(1, 100, 3), (2, 101, 4), (3, 103, 3)]);
+    assert_eq!(preview.remaining_quantity, Quantity::ZERO);
+
+    engine.create_order(&mut order).unwrap();
+    engine.match_orders();
+    let after = engine.preview(&make_limit_order(11, Side::Buy, 103, 10, 2001));
+    assert_eq!(fills(&after), vec![(3, 103, 2)]);
+}
+
+#[test]
+fn test_preview_without_fills() {
+    let (_book, engine) = new_engine();
+    let preview = engine.preview(&make_limit_order(10, Side::Buy, 99, 5, 2000));
+    assert!(preview.fills.is_empty());
+    assert_eq!(preview.average_price, None);
+    assert_eq!(preview.remaining_quantity, Quantity::from(5u64));
+
+    let preview = engine.preview(&make_market_order(11, Side::Sell, 5, 2001));
+    assert_eq!(preview.filled_quantity, Quantity::ZERO);
+}
+
+#[test]
+fn test_estimate_fill_across_levels() {
+    let (_book, engine) = new_engine();
+    let estimate = engine
+        .estimate_fill(Side::Buy, Quantity::from(8u64))
+        .unwrap();
+    // (3 * 100 + 4 * 101 + 1 * 103) / 8 = 100.875
+    assert_eq!(estimate.average_price, Price::from(100u64));
+    assert_eq!(estimate.worst_price, Price::from(103u64));
+    assert_eq!(estimate.filled_quantity, Quantity::from(8u64));
+    assert_eq!(estimate.unfilled_quantity, Quantity::ZERO);
+
+    // A size larger than the book reports what stays unfilled
+    let estimate = engine
+        .estimate_fill(Side::Buy, Quantity::from(20u64))
+        .unwrap();
+    assert_eq!(estimate.filled_quantity, Quantity::from(12u64));
+    assert_eq!(estimate.unfilled_quantity, Quantity::from(8u64));
+    assert_eq!(engine.estimate_fill(Side::Sell, Quantity::from(1u64)), None);
+}
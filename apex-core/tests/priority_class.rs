@@ -0,0 +1,133 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+
+fn tiered(id: u64, price: u64, ts: u64, priority_class: Option<PriorityClass>) -> Order {
+    let mut order = make_limit_order(id, Side::Buy, price, 1, ts);
+    order.priority_class = priority_class;
+    order
+}
+
+/// Tier of venue-defined orders resting behind regular ones
+const BACK: PriorityClass = PriorityClass(192);
+
+#[test]
+fn test_priority_class_orders_within_price() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut orders = [
+        tiered(1, 100, 1000, Some(BACK)),
+        tiered(2, 100, 1001, None),
+        tiered(3, 100, 1002, Some(PriorityClass(64))),
+        tiered(4, 100, 1003, None),
+        tiered(5, 101, 1004, Some(BACK)),
+    ];
+    for order in orders.iter_mut() {
+        if order.priority_class.is_some() {
+            order.class = OrderClass::Liquidation;
+        }
+        engine.create_order(order).unwrap();
+    }
+    let bids = engine.snapshot().bids;
+    // Price first, then tier, then time
+    assert_eq!(
+        bids.iter().map(|order| order.id).collect::<Vec<_>>(),
+        vec![5, 3, 2, 4, 1]
+    );
+}
+
+#[test]
+fn test_regular_orders_cannot_choose_their_tier() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut orders = [
+        tiered(1, 100, 1000, None),
+        tiered(2, 100, 1001, Some(PriorityClass::LIQUIDATION)),
+        tiered(3, 100, 1002, Some(BACK)),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    assert_eq!(orders[1].priority_class, None);
+    let bids = engine.snapshot().bids;
+    assert_eq!(
+        bids.iter().map(|order| order.id).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn test_priority_class_defaults_and_override() {
+    let regular = make_limit_order(1, Side::Buy, 100, 1, 1000);
+    assert_eq!(regular.priority_class(), PriorityClass::NORMAL);
+
+    let mut forced = make_limit_order(2, Side::Buy, 100, 1, 1000);
+    forced.class = OrderClass::Liquidation;
+    assert_eq!(forced.priority_class(), PriorityClass::LIQUIDATION);
+
+    let order = Order::builder(3, Side::Sell)
+        .limit(Price::from(100u64))
+        .quantity(Quantity::from(1u64))
+        .class(OrderClass::Liquidation)
+        .priority_class(PriorityClass::NORMAL)
+        .build()
+        .unwrap();
+    assert_eq!(order.class, OrderClass::Liquidation);
+    assert_eq!(order.priority_class(), PriorityClass::NORMAL);
+    assert!(PriorityClass::LIQUIDATION < PriorityClass::default());
+}
+
+#[test]
+fn test_stale_quotes_drop_behind_fresher_orders() {
+    let (book, engine) = TestEngine::new()
+        .with_config(BookConfig {
+            max_quote_age_micros: Some(1000),
+            ..Default::default()
+        })
+        .build();
+    let mut forced = tiered(4, 100, 1100, None);
+    forced.class = OrderClass::Liquidation;
+    let mut orders = [
+        tiered(1, 100, 1000, None),
+        tiered(2, 100, 1500, None),
+        tiered(3, 100, 2500, None),
+        forced,
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    let bid_ids = || {
+        engine
+            .snapshot()
+            .bids
+            .iter()
+            .map(|order| order.id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(bid_ids(), vec![4, 1, 2, 3]);
+
+    // Liquidation orders are never demoted, and the stale ones keep their relative time
+    assert_eq!(engine.demote_stale_quotes(2600), vec![1, 2]);
+    assert_eq!(bid_ids(), vec![4, 3, 1, 2]);
+    assert_eq!(engine.demote_stale_quotes(2600), Vec::<OrderID>::new());
+
+    // Refreshing a quote restores its tier at the back of the fresh orders
+    engine.update_order(2, Price::from(100u64), 2700).unwrap();
+    assert_eq!(bid_ids(), vec![4, 3, 2, 1]);
+
+    // The book can re-key any order, keeping its time within the new tier
+    book.reprioritize(4, PriorityClass::STALE).unwrap();
+    assert_eq!(bid_ids(), vec![3, 2, 1, 4]);
+    assert_eq!(
+        book.reprioritize(9, PriorityClass::STALE),
+        Err(UpdateOrderError::OrderNotFound)
+    );
+}
+
+#[test]
+fn test_stale_quotes_are_kept_without_a_policy() {
+    let (_book, engine) = TestEngine::new().build();
+    engine
+        .create_order(&mut tiered(1, 100, 1000, None))
+        .unwrap();
+    assert!(engine.demote_stale_quotes(u64::MAX).is_empty());
+}
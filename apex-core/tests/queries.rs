@@ -0,0 +1,233 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::ops::ControlFlow;
+
+fn user_order(id: u64, user_id: u64, side: Side, price: u64, qty: u64, ts: u64) -> Order {
+    let mut order = make_limit_order(id, side, price, qty, ts);
+    order.user_id = user_id;
+    order
+}
+
+#[test]
+fn test_open_orders_by_user() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut orders = [
+        user_order(1, 7, Side::Buy, 100, 10, 1000),
+        user_order(2, 8, Side::Buy, 101, 10, 1001),
+        user_order(3, 7, Side::Sell, 110, 5, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+
+    let open = engine.open_orders(7);
+    assert_eq!(
+        open.iter().map(|view| view.id).collect::<Vec<_>>(),
+        vec![1, 3]
+    );
+    assert_eq!(open[1].side, Side::Sell);
+    assert_eq!(open[1].quantity, Quantity::from(5u64));
+    assert_eq!(open[1].status, OrderStatus::Placed);
+    assert!(engine.open_orders(9).is_empty());
+
+    engine.cancel_order(1).unwrap();
+    assert_eq!(
+        engine
+            .open_orders(7)
+            .iter()
+            .map(|view| view.id)
+            .collect::<Vec<_>>(),
+        vec![3]
+    );
+}
+
+#[test]
+fn test_open_orders_follow_matching() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut maker = user_order(1, 7, Side::Sell, 100, 10, 1000);
+    let mut taker = user_order(2, 8, Side::Buy, 100, 4, 1001);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+
+    let open = engine.open_orders(7);
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].quantity, Quantity::from(6u64));
+    assert_eq!(open[0].filled_quantity, Quantity::from(4u64));
+    assert!(engine.open_orders(8).is_empty());
+
+    engine.update_order(1, Price::from(101u64), 1002).unwrap();
+    assert_eq!(engine.open_orders(7)[0].price, Price::from(101u64));
+}
+
+#[test]
+fn test_depth_aggregates_levels() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 101, 5, 1001),
+        make_limit_order(3, Side::Buy, 100, 7, 1002),
+        make_limit_order(4, Side::Buy, 98, 1, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+
+    let levels = engine.depth(Side::Buy, 2);
+    assert_eq!(
+        levels,
+        vec![
+            PriceLevel {
+                price: Price::from(101u64),
+                quantity: Quantity::from(5u64),
+                orders: 1,
+            },
+            PriceLevel {
+                price: Price::from(100u64),
+                quantity: Quantity::from(17u64),
+                orders: 2,
+            },
+        ]
+    );
+    assert_eq!(engine.depth(Side::Buy, 10).len(), 3);
+    assert!(engine.depth(Side::Sell, 10).is_empty());
+}
+
+#[test]
+fn test_quantity_at_and_cumulative() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 110, 3, 1000),
+        make_limit_order(2, Side::Sell, 111, 4, 1001),
+        make_limit_order(3, Side::Sell, 111, 2, 1002),
+        make_limit_order(4, Side::Sell, 115, 8, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+
+    assert_eq!(
+        engine.quantity_at(Side::Sell, Price::from(111u64)),
+        Quantity::from(6u64)
+    );
+    assert_eq!(
+        engine.quantity_at(Side::Sell, Price::from(112u64)),
+        Quantity::ZERO
+    );
+    assert_eq!(
+        engine.cumulative_quantity_to(Side::Sell, Price::from(111u64)),
+        Quantity::from(9u64)
+    );
+    assert_eq!(
+        engine.cumulative_quantity_to(Side::Sell, Price::from(109u64)),
+        Quantity::ZERO
+    );
+    assert_eq!(
+        engine.cumulative_quantity_to(Side::Sell, Price::from(200u64)),
+        Quantity::from(17u64)
+    );
+}
+
+#[test]
+fn test_level_info_and_queue_position() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 101, 5, 1000),
+        make_limit_order(2, Side::Buy, 100, 4, 1001),
+        make_limit_order(3, Side::Buy, 100, 2, 1002),
+        make_limit_order(4, Side::Buy, 100, 7, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+
+    let info = engine.level_info(Side::Buy, Price::from(100u64), Some(4));
+    assert_eq!(
+        info.level,
+        PriceLevel {
+            price: Price::from(100u64),
+            quantity: Quantity::from(13u64),
+            orders: 3,
+        }
+    );
+    assert_eq!(
+        info.queue_position,
+        Some(QueuePosition {
+            orders_ahead: 2,
+            quantity_ahead: Quantity::from(6u64),
+        })
+    );
+    // Order 1 rests at another price, and nothing rests at 99
+    let info = engine.level_info(Side::Buy, Price::from(100u64), Some(1));
+    assert_eq!(info.queue_position, None);
+    let info = engine.level_info(Side::Buy, Price::from(99u64), None);
+    assert_eq!(info.level.orders, 0);
+    assert_eq!(info.level.quantity, Quantity::ZERO);
+}
+
+#[test]
+fn test_book_stats() {
+    let (_book, engine) = TestEngine::new().build();
+    assert_eq!(engine.stats(), BookStats::default());
+
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 99, 30, 1000),
+        make_limit_order(2, Side::Buy, 98, 30, 1001),
+        make_limit_order(3, Side::Sell, 102, 20, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+
+    let stats = engine.stats();
+    assert_eq!(stats.best_bid, Some(Price::from(99u64)));
+    assert_eq!(stats.best_ask, Some(Price::from(102u64)));
+    assert_eq!(stats.spread, Some(Price::from(3u64)));
+    assert_eq!(stats.midpoint, Some(Price::from(100u64)));
+    assert_eq!((stats.bid_orders, stats.ask_orders), (2, 1));
+    assert_eq!(stats.bid_volume, Quantity::from(60u64));
+    assert_eq!(stats.ask_volume, Quantity::from(20u64));
+    assert_eq!(stats.imbalance_bps, Some(5000));
+}
+
+#[test]
+fn test_level_and_order_visitors() {
+    let (book, engine) = TestEngine::new().build();
+    let mut taker_only = make_limit_order(3, Side::Buy, 101, 2, 1002);
+    taker_only.liquidity_directive = LiquidityDirective::TakerOnly;
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 101, 5, 1001),
+        make_limit_order(4, Side::Buy, 99, 1, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    // The engine rejects TakerOnly limit orders, but the book itself still holds them
+    book.insert(&mut taker_only).unwrap();
+
+    let mut prices = Vec::new();
+    book.for_each_level(Side::Buy, &mut |level| {
+        prices.push((level.price, level.orders));
+        if prices.len() < 2 {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    });
+    assert_eq!(
+        prices,
+        vec![(Price::from(101u64), 2), (Price::from(100u64), 1)]
+    );
+
+    let mut makers = Vec::new();
+    book.for_each_order(Side::Buy, &mut |order| {
+        if order.provides_liquidity() {
+            makers.push(order.id);
+        }
+        ControlFlow::Continue(())
+    });
+    assert_eq!(makers, vec![2, 1, 4]);
+}
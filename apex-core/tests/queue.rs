@@ -0,0 +1,147 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn new_queued_engine(capacity: usize) -> (Arc<DefaultOrderBook>, QueuedMatchingEngine) {
+    let (book, engine) = TestEngine::new().build();
+    let engine = Arc::new(engine);
+    let capacity = NonZeroUsize::new(capacity).unwrap();
+    (book, QueuedMatchingEngine::new(engine, capacity))
+}
+
+#[test]
+fn test_queue_full_returns_typed_error() {
+    let (_book, engine) = new_queued_engine(2);
+
+    engine
+        .create_order(make_limit_order(1, Side::Buy, 100, 10, 1000))
+        .unwrap();
+    engine
+        .create_order(make_limit_order(2, Side::Buy, 100, 10, 1001))
+        .unwrap();
+
+    let result = engine.create_order(make_limit_order(3, Side::Buy, 100, 10, 1002));
+    assert_eq!(result, Err(SubmitError::QueueFull));
+
+    let metrics = engine.metrics();
+    assert_eq!(metrics.depth, 2);
+    assert_eq!(metrics.capacity, 2);
+    assert_eq!(metrics.submitted, 2);
+    assert_eq!(metrics.rejected, 1);
+}
+
+#[test]
+fn test_submit_timeout_when_queue_stays_full() {
+    let (_book, engine) = new_queued_engine(1);
+
+    engine
+        .create_order(make_limit_order(1, Side::Buy, 100, 10, 1000))
+        .unwrap();
+    let result = engine.create_order_timeout(
+        make_limit_order(2, Side::Buy, 100, 10, 1001),
+        Duration::from_millis(5),
+    );
+    assert_eq!(result, Err(SubmitError::Timeout));
+}
+
+#[test]
+fn test_process_drains_queue_and_matches() {
+    let (book, engine) = new_queued_engine(8);
+
+    engine
+        .create_order(make_limit_order(1, Side::Sell, 100, 10, 1000))
+        .unwrap();
+    engine
+        .create_order(make_limit_order(2, Side::Buy, 100, 4, 1001))
+        .unwrap();
+    engine
+        .create_order(make_limit_order(3, Side::Buy, 90, 5, 1002))
+        .unwrap();
+    engine.cancel_order(3).unwrap();
+
+    assert_eq!(engine.process(16), 4);
+    assert_eq!(engine.depth(), 0);
+    assert_eq!(engine.metrics().high_watermark, 4);
+
+    let sells = get_book_state(book.as_ref(), Side::Sell);
+    assert_eq!(sells, vec![(1, Quantity::from(6u64))]);
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+}
+
+#[test]
+fn test_snapshot_waits_for_the_commands_submitted_before_it() {
+    let (book, engine) = new_queued_engine(16);
+    engine
+        .create_order(make_limit_order(1, Side::Sell, 100, 5, 1000))
+        .unwrap();
+    engine
+        .create_order(make_limit_order(2, Side::Buy, 99, 5, 1001))
+        .unwrap();
+    let snapshot = engine.request_snapshot();
+    engine
+        .create_order(make_limit_order(3, Side::Buy, 100, 2, 1002))
+        .unwrap();
+
+    assert_eq!(engine.process(1), 1);
+    assert!(snapshot.try_recv().is_err());
+
+    // The snapshot is taken after the whole batch is applied and matched
+    assert_eq!(engine.process(10), 2);
+    let snapshot = snapshot.try_recv().unwrap();
+    assert_eq!(snapshot.sequence, book.sequence());
+    assert_eq!(snapshot.book, book.snapshot());
+    let orders: Vec<_> = snapshot
+        .orders()
+        .map(|order| (order.id, order.quantity))
+        .collect();
+    assert_eq!(
+        orders,
+        vec![(2, Quantity::from(5u64)), (1, Quantity::from(3u64))]
+    );
+}
+
+#[test]
+fn test_snapshots_reflect_a_single_sequence_under_load() {
+    let (_book, engine) = new_queued_engine(1024);
+    let engine = Arc::new(engine);
+    let producer = {
+        let engine = engine.clone();
+        std::thread::spawn(move || {
+            for id in 1..=2000u64 {
+                let side = if id % 2 == 0 { Side::Buy } else { Side::Sell };
+                let order = make_limit_order(id, side, 95 + id % 10, 1 + id % 3, 1000 + id);
+                while engine.create_order(order.clone()).is_err() {
+                    std::thread::yield_now();
+                }
+            }
+        })
+    };
+
+    let mut snapshots = Vec::new();
+    while !producer.is_finished() || engine.depth() > 0 {
+        let snapshot = engine.request_snapshot();
+        loop {
+            engine.process(64);
+            if let Ok(snapshot) = snapshot.try_recv() {
+                snapshots.push(snapshot);
+                break;
+            }
+        }
+    }
+    producer.join().unwrap();
+
+    for snapshot in snapshots {
+        // A fully matched book never holds a crossed pair of resting orders
+        if let (Some(bid), Some(ask)) = (
+            snapshot.book.best_price(Side::Buy),
+            snapshot.book.best_price(Side::Sell),
+        ) {
+            assert!(bid < ask, "crossed at sequence {}", snapshot.sequence);
+        }
+        assert_eq!(check_book_invariants(&snapshot.book), Ok(()));
+    }
+}
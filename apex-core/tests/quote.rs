@@ -0,0 +1,157 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine() -> (Arc<Recorder>, Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    (syncer, book, engine)
+}
+
+fn entry(order_id: OrderID, side: Side, price: u64, quantity: u64) -> QuoteEntry {
+    QuoteEntry {
+        order_id,
+        side,
+        price: Price::from(price),
+        quantity: Quantity::from(quantity),
+    }
+}
+
+fn quotes(book: &DefaultOrderBook, side: Side) -> Vec<(OrderID, Price, Quantity)> {
+    let mut quotes: Vec<_> = book
+        .open_orders(7)
+        .into_iter()
+        .filter(|order| order.side == side)
+        .map(|order| (order.id, order.price, order.quantity))
+        .collect();
+    quotes.sort();
+    quotes
+}
+
+#[test]
+fn test_mass_quote_places_and_replaces_quotes() {
+    let (syncer, book, engine) = new_engine();
+    let placed = MassQuote::new(
+        7,
+        vec![
+            entry(1, Side::Buy, 99, 5),
+            entry(2, Side::Buy, 98, 5),
+            entry(3, Side::Sell, 101, 5),
+        ],
+        1000,
+    );
+    assert_eq!(placed.execute(&engine), vec![Ok(QuoteAction::Created); 3]);
+    assert_eq!(syncer.batches().len(), 1);
+
+    let replaced = MassQuote::new(
+        7,
+        vec![
+            entry(1, Side::Buy, 99, 5),
+            entry(2, Side::Buy, 97, 8),
+            entry(3, Side::Sell, 101, 0),
+            entry(4, Side::Sell, 102, 6),
+        ],
+        1001,
+    );
+    assert_eq!(
+        replaced.execute(&engine),
+        vec![
+            Ok(QuoteAction::Unchanged),
+            Ok(QuoteAction::Replaced),
+            Ok(QuoteAction::Cancelled),
+            Ok(QuoteAction::Created),
+        ]
+    );
+    assert_eq!(syncer.batches().len(), 2);
+    assert_eq!(
+        quotes(&book, Side::Buy),
+        vec![
+            (1, Price::from(99u64), Quantity::from(5u64)),
+            (2, Price::from(97u64), Quantity::from(8u64)),
+        ]
+    );
+    assert_eq!(
+        quotes(&book, Side::Sell),
+        vec![(4, Price::from(102u64), Quantity::from(6u64))]
+    );
+}
+
+#[test]
+fn test_mass_quote_reports_each_rejected_entry() {
+    let (_syncer, book, engine) = new_engine();
+    engine
+        .create_order(&mut make_limit_order(5, Side::Sell, 200, 1, 900))
+        .unwrap();
+    MassQuote::new(7, vec![entry(1, Side::Buy, 99, 5)], 1000).execute(&engine);
+
+    let results = MassQuote::new(
+        7,
+        vec![
+            entry(1, Side::Sell, 99, 5),
+            entry(2, Side::Buy, 0, 5),
+            entry(3, Side::Buy, 95, 0),
+            entry(5, Side::Sell, 150, 1),
+            entry(6, Side::Sell, 105, 2),
+        ],
+        1001,
+    )
+    .execute(&engine);
+    assert_eq!(
+        results,
+        vec![
+            Err(QuoteError::SideMismatch),
+            Err(QuoteError::Rejected(RejectReason::InvalidPrice)),
+            Err(QuoteError::Cancel(CancelOrderError::OrderNotFound)),
+            Err(QuoteError::Rejected(RejectReason::DuplicateOrderId)),
+            Ok(QuoteAction::Created),
+        ]
+    );
+    assert_eq!(QuoteError::SideMismatch.code(), 901);
+    assert_eq!(
+        QuoteError::Rejected(RejectReason::DuplicateOrderId).code(),
+        103
+    );
+
+    // The accepted entries took effect and the rejected ones left the book alone
+    assert_eq!(
+        quotes(&book, Side::Buy),
+        vec![(1, Price::from(99u64), Quantity::from(5u64))]
+    );
+    assert_eq!(
+        quotes(&book, Side::Sell),
+        vec![(6, Price::from(105u64), Quantity::from(2u64))]
+    );
+}
+
+#[test]
+fn test_mass_quote_in_cancel_only_mode_pulls_but_does_not_quote() {
+    let (_syncer, book, engine) = new_engine();
+    MassQuote::new(
+        7,
+        vec![entry(1, Side::Buy, 99, 5), entry(2, Side::Sell, 101, 5)],
+        1000,
+    )
+    .execute(&engine);
+    engine.set_mode(EngineMode::CancelOnly);
+
+    let results = MassQuote::new(
+        7,
+        vec![entry(1, Side::Buy, 98, 5), entry(2, Side::Sell, 101, 0)],
+        1001,
+    )
+    .execute(&engine);
+    assert_eq!(
+        results,
+        vec![
+            Err(QuoteError::Update(UpdateOrderError::TradingSuspended)),
+            Ok(QuoteAction::Cancelled),
+        ]
+    );
+    assert_eq!(
+        quotes(&book, Side::Buy),
+        vec![(1, Price::from(99u64), Quantity::from(5u64))]
+    );
+    assert!(quotes(&book, Side::Sell).is_empty());
+}
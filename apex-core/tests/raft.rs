@@ -0,0 +1,289 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// An in-memory group whose transport drops every message to or from a down node
+struct Cluster {
+    nodes: Vec<RaftNode>,
+    /// The clock each node stamps entries with while it leads, a little apart from the others.
+    clocks: Vec<Arc<ManualClock>>,
+    down: HashSet<NodeID>,
+}
+
+impl Cluster {
+    fn new(size: u64) -> Self {
+        Self::with_config(size, BookConfig::default())
+    }
+
+    fn with_config(size: u64, config: BookConfig) -> Self {
+        let ids: Vec<NodeID> = (1..=size).collect();
+        let clocks: Vec<_> = ids
+            .iter()
+            .map(|id| Arc::new(ManualClock::new(1_000_000 + id * 137)))
+            .collect();
+        let nodes = ids
+            .iter()
+            .zip(&clocks)
+            .map(|(id, clock)| {
+                let (_book, engine) = TestEngine::new().with_config(config.clone()).build();
+                let engine = Arc::new(engine.with_log_clock(Arc::new(ManualClock::default())));
+                RaftNode::new(*id, ids.clone(), RaftConfig::default(), engine)
+                    .with_clock(clock.clone())
+            })
+            .collect();
+        Self {
+            nodes,
+            clocks,
+            down: HashSet::new(),
+        }
+    }
+
+    /// Moves the clock of every node forward
+    fn advance(&self, micros: u64) {
+        for clock in &self.clocks {
+            clock.advance(micros);
+        }
+    }
+
+    fn node(&mut self, id: NodeID) -> &mut RaftNode {
+        &mut self.nodes[id as usize - 1]
+    }
+
+    /// Delivers messages until no node has anything left to send
+    fn deliver(&mut self) {
+        loop {
+            let mut messages = Vec::new();
+            for node in self.nodes.iter_mut() {
+                let from = node.id();
+                for (to, message) in node.take_messages() {
+                    messages.push((from, to, message));
+                }
+            }
+            if messages.is_empty() {
+                return;
+            }
+            for (from, to, message) in messages {
+                if !self.down.contains(&from) && !self.down.contains(&to) {
+                    self.node(to).step(from, message);
+                }
+            }
+        }
+    }
+
+    /// Ticks every live node until one of them leads
+    fn elect(&mut self) -> NodeID {
+        for _ in 0..100 {
+            for node in self.nodes.iter_mut() {
+                if !self.down.contains(&node.id()) {
+                    node.tick();
+                }
+            }
+            self.deliver();
+            let leader = self
+                .nodes
+                .iter()
+                .find(|node| node.role() == RaftRole::Leader && !self.down.contains(&node.id()));
+            if let Some(leader) = leader {
+                return leader.id();
+            }
+        }
+        panic!("no leader elected");
+    }
+
+    fn create(&mut self, leader: NodeID, order: Order) -> u64 {
+        let index = self.node(leader).propose(Command::Create(order)).unwrap();
+        self.deliver();
+        index
+    }
+
+    /// Lets the leader send a round of heartbeats, which carry its commit index
+    fn heartbeat(&mut self, leader: NodeID) {
+        for _ in 0..RaftConfig::default().heartbeat_ticks {
+            self.node(leader).tick();
+        }
+        self.deliver();
+    }
+}
+
+#[test]
+fn test_committed_commands_reach_every_replica() {
+    let mut cluster = Cluster::new(3);
+    let leader = cluster.elect();
+    // The node with the shortest election timeout wins the first election
+    assert_eq!(leader, 1);
+    cluster.create(leader, make_limit_order(1, Side::Sell, 100, 5, 1000));
+    let index = cluster.create(leader, make_limit_order(2, Side::Buy, 100, 3, 1001));
+    // Followers learn the new commit index from the next heartbeat
+    assert_eq!(cluster.node(2).commit_index(), index - 1);
+    cluster.heartbeat(leader);
+
+    for node in &cluster.nodes {
+        assert_eq!(node.commit_index(), index);
+        assert_eq!(node.last_applied(), index);
+        assert_eq!(node.leader(), Some(1));
+    }
+    let book = cluster.nodes[0].engine().snapshot();
+    assert_eq!(book.asks.len(), 1);
+    assert_eq!(book.asks[0].quantity, Quantity::from(2u64));
+    for node in &cluster.nodes[1..] {
+        assert_eq!(node.engine().snapshot(), book);
+    }
+}
+
+#[test]
+fn test_only_the_leader_accepts_proposals() {
+    let mut cluster = Cluster::new(3);
+    let leader = cluster.elect();
+    let command = Command::Create(make_limit_order(1, Side::Sell, 100, 5, 1000));
+    assert_eq!(
+        cluster.node(2).propose(command),
+        Err(RaftError::NotLeader {
+            leader: Some(leader)
+        })
+    );
+}
+
+#[test]
+fn test_commands_without_a_majority_are_never_applied() {
+    let mut cluster = Cluster::new(3);
+    let leader = cluster.elect();
+    cluster.create(leader, make_limit_order(1, Side::Sell, 100, 5, 1000));
+
+    // Cut the leader off: its next proposal cannot commit
+    cluster.down.insert(leader);
+    cluster.create(leader, make_limit_order(2, Side::Sell, 101, 5, 1001));
+    assert_eq!(cluster.node(leader).engine().snapshot().asks.len(), 1);
+
+    // The rest of the group elects a new leader and moves on
+    let new_leader = cluster.elect();
+    assert_ne!(new_leader, leader);
+    cluster.create(new_leader, make_limit_order(3, Side::Sell, 102, 5, 1002));
+
+    // Once reconnected, the old leader drops its uncommitted entry and catches up
+    cluster.down.clear();
+    cluster.heartbeat(new_leader);
+    assert_eq!(cluster.node(leader).role(), RaftRole::Follower);
+    let ids: Vec<_> = cluster
+        .node(leader)
+        .engine()
+        .snapshot()
+        .asks
+        .iter()
+        .map(|order| order.id)
+        .collect();
+    assert_eq!(ids, vec![1, 3]);
+    assert_eq!(
+        cluster.node(leader).last_applied(),
+        cluster.node(new_leader).commit_index()
+    );
+}
+
+#[test]
+fn test_restarted_node_keeps_its_term_vote_and_log() {
+    let engine = || {
+        let (_book, engine) = TestEngine::new().build();
+        Arc::new(engine)
+    };
+    let storage = Arc::new(MemoryRaftStorage::new());
+    let mut node = RaftNode::new(2, vec![1, 2, 3], RaftConfig::default(), engine())
+        .with_storage(storage.clone());
+    node.step(
+        1,
+        RaftMessage::RequestVote {
+            term: 1,
+            last_log_index: 0,
+            last_log_term: 0,
+        },
+    );
+    node.step(
+        1,
+        RaftMessage::AppendEntries {
+            term: 1,
+            prev_log_index: 0,
+            prev_log_term: 0,
+            entries: vec![LogEntry {
+                term: 1,
+                timestamp: 1000,
+                command: Some(
+                    Command::Create(make_limit_order(1, Side::Sell, 100, 5, 1000)).into(),
+                ),
+            }],
+            leader_commit: 0,
+        },
+    );
+
+    let mut restarted =
+        RaftNode::new(2, vec![1, 2, 3], RaftConfig::default(), engine()).with_storage(storage);
+    assert_eq!(restarted.term(), 1);
+    // The vote of term 1 went to node 1 before the restart
+    restarted.step(
+        3,
+        RaftMessage::RequestVote {
+            term: 1,
+            last_log_index: 1,
+            last_log_term: 1,
+        },
+    );
+    assert!(matches!(
+        restarted.take_messages()[..],
+        [(3, RaftMessage::Vote { granted: false, .. })]
+    ));
+
+    // The acknowledged entry survived and is applied once committed
+    restarted.step(
+        1,
+        RaftMessage::AppendEntries {
+            term: 1,
+            prev_log_index: 1,
+            prev_log_term: 1,
+            entries: Vec::new(),
+            leader_commit: 1,
+        },
+    );
+    assert_eq!(restarted.last_applied(), 1);
+    assert_eq!(restarted.engine().snapshot().asks.len(), 1);
+}
+
+#[test]
+fn test_replicas_agree_on_expiry_and_staleness() {
+    let mut cluster = Cluster::with_config(
+        3,
+        BookConfig {
+            max_resting_micros: Some(5000),
+            max_market_order_age_micros: Some(1000),
+            ..BookConfig::default()
+        },
+    );
+    let leader = cluster.elect();
+    cluster.create(leader, make_limit_order(5, Side::Buy, 90, 3, 0));
+    // Nothing to buy yet, so the market order waits and goes stale
+    cluster.create(leader, make_market_order(2, Side::Buy, 4, 0));
+    cluster.advance(2000);
+    cluster.create(leader, make_limit_order(1, Side::Sell, 100, 10, 0));
+    cluster.advance(4000);
+    cluster
+        .node(leader)
+        .propose(ReplicatedCommand::ExpireOrders)
+        .unwrap();
+    cluster.deliver();
+    cluster.create(leader, make_limit_order(4, Side::Buy, 100, 2, 0));
+    cluster.heartbeat(leader);
+
+    let book = cluster.node(leader).engine().snapshot();
+    assert!(book.bids.is_empty());
+    let asks: Vec<_> = book
+        .asks
+        .iter()
+        .map(|order| (order.id, order.quantity))
+        .collect();
+    assert_eq!(asks, vec![(1, Quantity::from(8u64))]);
+    for node in &cluster.nodes {
+        assert_eq!(node.last_applied(), cluster.nodes[0].commit_index());
+        let snapshot = node.engine().snapshot();
+        assert_eq!(snapshot_digest(&snapshot), snapshot_digest(&book));
+        assert_eq!(snapshot, book);
+    }
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn new_engine(rate_limiter: RateLimiter) -> (Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let (book, engine) = TestEngine::new().build();
+    let engine = engine.with_rate_limiter(rate_limiter);
+    (book, engine)
+}
+
+#[test]
+fn test_token_bucket_refills_over_time() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::new_at(2, 10, start);
+    assert!(bucket.try_acquire_at(start));
+    assert!(bucket.try_acquire_at(start));
+    assert!(!bucket.try_acquire_at(start));
+
+    assert!(!bucket.try_acquire_at(start + Duration::from_millis(50)));
+    assert!(bucket.try_acquire_at(start + Duration::from_millis(100)));
+    assert!(bucket.try_acquire_at(start + Duration::from_secs(10)));
+    assert!(bucket.try_acquire_at(start + Duration::from_secs(10)));
+    assert!(!bucket.try_acquire_at(start + Duration::from_secs(10)));
+}
+
+#[test]
+fn test_create_order_is_rate_limited_per_user() {
+    let (book, engine) = new_engine(RateLimiter::new(2, 0));
+    for id in 1..=2 {
+        let mut order = make_limit_order(id, Side::Buy, 100, 10, 1000 + id);
+        assert_eq!(engine.create_order(&mut order), Ok(()));
+    }
+    let mut order = make_limit_order(3, Side::Buy, 100, 10, 1003);
+    assert_eq!(
+        engine.create_order(&mut order),
+        Err(RejectReason::RateLimited)
+    );
+    assert_eq!(order.status(), OrderStatus::Rejected);
+
+    let mut order = make_limit_order(4, Side::Buy, 100, 10, 1004);
+    order.user_id = 2;
+    assert_eq!(engine.create_order(&mut order), Ok(()));
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 3);
+}
+
+#[test]
+fn test_cancel_order_is_rate_limited_by_owner() {
+    let (book, engine) = new_engine(RateLimiter::new(2, 0));
+    let mut first = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut second = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    engine.create_order(&mut first).unwrap();
+    engine.create_order(&mut second).unwrap();
+
+    assert_eq!(engine.cancel_order(1), Err(CancelOrderError::RateLimited));
+    assert_eq!(
+        engine.cancel_order(42),
+        Err(CancelOrderError::OrderNotFound)
+    );
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 2);
+    assert_eq!(book.get_order(1).map(|view| view.user_id), Some(1));
+}
+
+#[test]
+fn test_every_cancel_path_is_rate_limited() {
+    let (book, engine) = new_engine(RateLimiter::new(2, 0));
+    for (id, user_id) in [(1, 1), (2, 2), (3, 2)] {
+        let mut order = make_limit_order(id, Side::Buy, 100, 10, 1000 + id);
+        order.user_id = user_id;
+        engine.create_order(&mut order).unwrap();
+    }
+
+    // Unknown ids share one bucket, so a flood of them is limited too
+    assert_eq!(
+        engine.cancel_orders(&[2, 1, 42, 43, 44]),
+        vec![
+            Err(CancelOrderError::RateLimited),
+            Ok(()),
+            Err(CancelOrderError::OrderNotFound),
+            Err(CancelOrderError::OrderNotFound),
+            Err(CancelOrderError::RateLimited),
+        ]
+    );
+    assert_eq!(
+        engine.execute_batch(&mut [Command::Cancel(3), Command::Cancel(45)]),
+        vec![
+            CommandResult::Cancelled(Err(CancelOrderError::RateLimited)),
+            CommandResult::Cancelled(Err(CancelOrderError::RateLimited)),
+        ]
+    );
+    assert_eq!(engine.cancel_order(46), Err(CancelOrderError::RateLimited));
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(2, Quantity::from(10u64)), (3, Quantity::from(10u64))]
+    );
+}
+
+#[test]
+fn test_throttle_limits_each_session_and_spares_cancels_globally() {
+    let now = Instant::now();
+    let throttle = Throttle::new()
+        .with_session_limit(3, 0)
+        .with_global_limit(2, 0);
+    let create = Command::Create(make_limit_order(1, Side::Buy, 100, 1, 1000));
+    let cancel = Command::Cancel(1);
+
+    assert!(throttle.try_admit_at(1, &create, now));
+    assert!(throttle.try_admit_at(2, &create, now));
+    // The global budget is spent, but cancels do not need it
+    assert!(!throttle.try_admit_at(3, &create, now));
+    assert!(throttle.try_admit_at(3, &cancel, now));
+    assert!(throttle.try_admit_at(1, &cancel, now));
+    assert!(throttle.try_admit_at(1, &cancel, now));
+    // Session 1 has used its burst of three
+    assert!(!throttle.try_admit_at(1, &cancel, now));
+    assert!(throttle.try_admit_at(2, &cancel, now));
+}
+
+#[test]
+fn test_queued_engine_rejects_throttled_sessions() {
+    let (_book, engine) = TestEngine::new().build();
+    let engine = QueuedMatchingEngine::new(Arc::new(engine), NonZeroUsize::new(16).unwrap())
+        .with_throttle(Throttle::new().with_session_limit(1, 0));
+
+    let create = |id| Command::Create(make_limit_order(id, Side::Buy, 100, 1, 1000));
+    assert_eq!(engine.submit(7, create(1)), Ok(()));
+    assert_eq!(engine.submit(7, create(2)), Err(SubmitError::Throttled));
+    assert_eq!(engine.submit(8, create(3)), Ok(()));
+    assert_eq!(engine.depth(), 2);
+    assert_eq!(SubmitError::Throttled.code(), 406);
+}
+
+#[test]
+fn test_queued_engine_throttles_commands_without_a_session() {
+    let (_book, engine) = TestEngine::new().build();
+    let engine = QueuedMatchingEngine::new(Arc::new(engine), NonZeroUsize::new(16).unwrap())
+        .with_throttle(Throttle::new().with_session_limit(2, 0));
+
+    let order = make_limit_order(1, Side::Buy, 100, 1, 1000);
+    assert_eq!(engine.create_order(order), Ok(()));
+    assert_eq!(engine.update_order(1, Price::from(101u64), 1001), Ok(()));
+    assert_eq!(engine.cancel_order(1), Err(SubmitError::Throttled));
+    let order = make_limit_order(2, Side::Buy, 100, 1, 1002);
+    assert_eq!(
+        engine.create_order_timeout(order, Duration::from_millis(1)),
+        Err(SubmitError::Throttled)
+    );
+    // Other sessions have their own budget
+    assert_eq!(
+        engine.submit(DEFAULT_SESSION + 1, Command::Cancel(1)),
+        Ok(())
+    );
+    assert_eq!(engine.depth(), 3);
+}
+
+#[test]
+fn test_rate_limit_follows_the_engine_clock() {
+    let clock = Arc::new(ManualClock::new(0));
+    let (book, engine) = new_engine(RateLimiter::new(1, 1));
+    let engine = engine.with_clock(clock.clone());
+    let mut first = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut second = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    engine.create_order(&mut first).unwrap();
+    assert_eq!(
+        engine.create_order(&mut second),
+        Err(RejectReason::RateLimited)
+    );
+    clock.advance(1_000_000);
+    let mut third = make_limit_order(3, Side::Buy, 100, 10, 1002);
+    assert_eq!(engine.create_order(&mut third), Ok(()));
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 2);
+}
+
+#[test]
+fn test_updates_and_amends_are_rate_limited_by_owner() {
+    let (book, engine) = new_engine(RateLimiter::new(3, 0));
+    let mut order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    engine.create_order(&mut order).unwrap();
+    engine.update_order(1, Price::from(101u64), 1001).unwrap();
+    engine
+        .amend_quantity(1, Quantity::from(5u64), 1002)
+        .unwrap();
+
+    let request = AmendRequest {
+        price: Some(Price::from(99u64)),
+        ..Default::default()
+    };
+    assert_eq!(
+        engine.amend_order(1, request, 1003),
+        Err(UpdateOrderError::RateLimited)
+    );
+    assert_eq!(
+        engine.execute_batch(&mut [Command::Update {
+            order_id: 1,
+            new_price: Price::from(98u64),
+            now_microseconds: 1004,
+        }]),
+        vec![CommandResult::Updated(Err(UpdateOrderError::RateLimited))]
+    );
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(5u64))]
+    );
+    assert_eq!(UpdateOrderError::RateLimited.code(), 207);
+}
+
+#[test]
+fn test_rate_limiter_drops_refilled_buckets() {
+    let limiter = RateLimiter::new(1, 10);
+    assert!(limiter.try_acquire_at_micros(1, 0));
+    assert!(limiter.try_acquire_at_micros(2, 50_000));
+    assert_eq!(limiter.tracked_users(), 2);
+
+    // User 1 refilled by then, user 2 not yet
+    assert!(limiter.try_acquire_at_micros(3, 120_000));
+    assert_eq!(limiter.tracked_users(), 2);
+    assert!(!limiter.try_acquire_at_micros(3, 120_000));
+    assert!(limiter.try_acquire_at_micros(1, 120_000));
+
+    // Buckets that never refill are never dropped
+    let limiter = RateLimiter::new(1, 0);
+    assert!(limiter.try_acquire_at_micros(1, 0));
+    assert!(!limiter.try_acquire_at_micros(1, u64::MAX));
+    assert_eq!(limiter.tracked_users(), 1);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine(config: BookConfig) -> (Arc<ManualReferencePrice>, DefaultMatchingEngine) {
+    let reference = Arc::new(ManualReferencePrice::new());
+    let (_book, engine) = TestEngine::new().with_config(config).build();
+    let engine = engine.with_reference_price(reference.clone());
+    (reference, engine)
+}
+
+#[test]
+fn test_reference_price_sources() {
+    let (reference, engine) = new_engine(BookConfig::default());
+    assert_eq!(engine.reference_price(PriceSource::LastTrade), None);
+    assert_eq!(engine.reference_price(PriceSource::Mark), None);
+
+    reference.set_mark(Some(Price::from(1000u64)));
+    reference.set_index(Some(Price::from(998u64)));
+    assert_eq!(
+        engine.reference_price(PriceSource::Mark),
+        Some(Price::from(1000u64))
+    );
+    assert_eq!(
+        engine.reference_price(PriceSource::Index),
+        Some(Price::from(998u64))
+    );
+
+    let mut maker = make_limit_order(1, Side::Sell, 1001, 5, 1000);
+    let mut taker = make_limit_order(2, Side::Buy, 1001, 2, 1001);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+    assert_eq!(engine.last_trade_price(), Some(Price::from(1001u64)));
+    assert_eq!(
+        engine.reference_price(PriceSource::LastTrade),
+        Some(Price::from(1001u64))
+    );
+}
+
+#[test]
+fn test_price_band_follows_mark_price() {
+    let (reference, engine) = new_engine(BookConfig {
+        reference_band_bps: Some(500),
+        reference_source: PriceSource::Mark,
+        ..BookConfig::default()
+    });
+    // Unbanded while the mark price is unknown
+    let mut far = make_limit_order(1, Side::Buy, 10, 1, 1000);
+    assert_eq!(engine.create_order(&mut far), Ok(()));
+
+    reference.set_mark(Some(Price::from(1000u64)));
+    let mut low = make_limit_order(2, Side::Buy, 949, 1, 1001);
+    let mut edge = make_limit_order(3, Side::Buy, 950, 1, 1002);
+    let mut high = make_limit_order(4, Side::Sell, 1051, 1, 1003);
+    assert_eq!(
+        engine.create_order(&mut low),
+        Err(RejectReason::PriceOutOfBand)
+    );
+    assert_eq!(engine.create_order(&mut edge), Ok(()));
+    assert_eq!(
+        engine.create_order(&mut high),
+        Err(RejectReason::PriceOutOfBand)
+    );
+
+    reference.set_mark(Some(Price::from(1100u64)));
+    let mut moved = make_limit_order(5, Side::Sell, 1051, 1, 1004);
+    assert_eq!(engine.create_order(&mut moved), Ok(()));
+}
+
+#[test]
+fn test_price_in_reference_band() {
+    let config = BookConfig {
+        reference_band_bps: Some(100),
+        ..BookConfig::default()
+    };
+    let reference = Price::from(10_000u64);
+    assert!(config.price_in_reference_band(Price::from(9_900u64), reference));
+    assert!(config.price_in_reference_band(Price::from(10_100u64), reference));
+    assert!(!config.price_in_reference_band(Price::from(10_101u64), reference));
+    assert!(BookConfig::default().price_in_reference_band(Price::ONE, reference));
+}
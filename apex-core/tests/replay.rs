@@ -0,0 +1,88 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+const CSV: &str = "\
+# recorded session
+timestamp,action,order_id,user_id,side,type,price,quantity
+1000,new,1,7,buy,limit,100,10
+1500,new,2,8,sell,limit,101,5
+2000,update,2,,,,100,
+2500,new,3,9,sell,market,,4
+3000,cancel,1,,,,,
+";
+
+#[test]
+fn test_csv_replay_with_recorded_clock() {
+    let events = load_csv(CSV.as_bytes()).unwrap();
+    assert_eq!(events.len(), 5);
+    assert_eq!(events[2].timestamp, 2000);
+    assert!(matches!(events[4].command, Command::Cancel(1)));
+
+    let sink = Arc::new(Recorder::default());
+    let clock = Arc::new(ManualClock::new(0));
+    let (_book, engine) = TestEngine::new().with_syncer(sink.clone()).build();
+    let engine = engine.with_clock(clock.clone());
+    let report = ReplayDriver::new(&engine).with_clock(clock).run(&events);
+    assert_eq!((report.events, report.failed), (5, 0));
+
+    let trades = sink.trades();
+    assert_eq!(trades.len(), 4);
+    assert_eq!(trades[0].created_at, 2000);
+    assert_eq!(trades[2].created_at, 2500);
+    assert!(engine.snapshot().bids.is_empty());
+}
+
+#[test]
+fn test_jsonl_matches_csv() {
+    let jsonl = r#"
+{"timestamp": 1000, "action": "new", "order_id": 1, "user_id": 7, "side": "buy", "type": "limit", "price": 100, "quantity": 10}
+{"timestamp":1500,"action":"new","order_id":2,"user_id":8,"side":"sell","price":"101","quantity":5}
+{"timestamp": 2000, "action": "update", "order_id": 2, "price": 100, "quantity": null}
+"#;
+    let events = load_jsonl(jsonl.as_bytes()).unwrap();
+    assert_eq!(events.len(), 3);
+    match &events[1].command {
+        Command::Create(order) => {
+            assert_eq!(order.order_type, OrderType::Limit);
+            assert_eq!(order.price, Price::from(101u64));
+        }
+        other => panic!("unexpected command {other:?}"),
+    }
+    assert!(matches!(
+        events[2].command,
+        Command::Update { order_id: 2, new_price, now_microseconds: 2000 } if new_price == Price::from(100u64)
+    ));
+}
+
+#[test]
+fn test_replay_parse_errors() {
+    let error = load_csv("timestamp,action,order_id\n1,new,1\n".as_bytes()).unwrap_err();
+    assert!(matches!(error, ReplayError::Parse { line: 2, .. }));
+    assert_eq!(error.to_string(), "line 2: missing field `user_id`");
+
+    let error =
+        load_jsonl(r#"{"timestamp": 1, "action": "fly", "order_id": 1}"#.as_bytes()).unwrap_err();
+    assert_eq!(error.to_string(), "line 1: unknown action: fly");
+    assert!(load_jsonl(r#"{"timestamp": 1,"#.as_bytes()).is_err());
+}
+
+#[test]
+fn test_accelerated_replay_is_paced() {
+    let events = load_csv(
+        "timestamp,action,order_id,user_id,side,price,quantity\n\
+         0,new,1,1,buy,100,1\n\
+         40000,new,2,1,buy,100,1\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    let (_book, engine) = TestEngine::new().build();
+    let report = ReplayDriver::new(&engine)
+        .with_speed(ReplaySpeed::Accelerated(2.0))
+        .run(&events);
+    assert_eq!(report.events, 2);
+    assert!(report.elapsed >= Duration::from_millis(20));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine() -> (Arc<Recorder>, Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    (syncer, book, engine)
+}
+
+/// A 1:10 split: prices divided by ten, quantities multiplied by ten
+fn split() -> Rescale {
+    Rescale {
+        price: ScaleFactor::new(1, 10).unwrap(),
+        quantity: ScaleFactor::new(10, 1).unwrap(),
+    }
+}
+
+#[test]
+fn test_rescale_requires_a_halted_engine() {
+    let (syncer, _book, engine) = new_engine();
+    assert_eq!(engine.rescale(split()), Err(RescaleError::NotHalted));
+    engine.set_mode(EngineMode::CancelOnly);
+    assert_eq!(engine.rescale(split()), Err(RescaleError::NotHalted));
+    engine.set_mode(EngineMode::Halted);
+    assert_eq!(engine.rescale(split()), Ok(0));
+    assert_eq!(syncer.rescales().len(), 1);
+    assert_eq!(RescaleError::NotHalted.code(), 701);
+}
+
+#[test]
+fn test_rescale_scales_orders_and_keeps_priority() {
+    let (syncer, book, engine) = new_engine();
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 1000, 3, 1000),
+        make_limit_order(2, Side::Sell, 1000, 4, 1001),
+        make_limit_order(3, Side::Sell, 1010, 5, 1002),
+        make_limit_order(4, Side::Buy, 990, 2, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.set_mode(EngineMode::Halted);
+    assert_eq!(engine.rescale(split()), Ok(4));
+    assert_eq!(syncer.rescales(), vec![split()]);
+    assert_eq!(syncer.updated().len(), 0);
+
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![
+            (1, Quantity::from(30u64)),
+            (2, Quantity::from(40u64)),
+            (3, Quantity::from(50u64)),
+        ]
+    );
+    let order = book.get_order(4).unwrap();
+    assert_eq!(order.price, Price::from(99u64));
+    assert_eq!(order.quantity, Quantity::from(20u64));
+
+    // The index follows the new keys and the time priority is unchanged
+    engine.set_mode(EngineMode::Normal);
+    assert_eq!(engine.cancel_order(3), Ok(()));
+    let mut taker = make_limit_order(5, Side::Buy, 100, 35, 1004);
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(2, Quantity::from(35u64))]
+    );
+}
+
+#[test]
+fn test_inexact_rescale_leaves_the_book_unchanged() {
+    let (syncer, book, engine) = new_engine();
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 1000, 3, 1000),
+        make_limit_order(2, Side::Sell, 1005, 3, 1001),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.set_mode(EngineMode::Halted);
+    assert_eq!(engine.rescale(split()), Err(RescaleError::Inexact(2)));
+    assert!(syncer.rescales().is_empty());
+    assert_eq!(
+        book.get_order(1).map(|order| order.price),
+        Some(Price::from(1000u64))
+    );
+
+    // The claims were released, so the orders still match
+    engine.set_mode(EngineMode::Normal);
+    let mut taker = make_limit_order(3, Side::Buy, 1005, 6, 1002);
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
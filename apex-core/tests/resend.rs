@@ -0,0 +1,73 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine(capacity: usize) -> (Arc<RetentionSyncer>, DefaultMatchingEngine) {
+    let syncer = Arc::new(RetentionSyncer::new(
+        Arc::new(EmptyOrderBookSyncer {}),
+        capacity,
+    ));
+    let (_book, engine) = TestEngine::new().with_syncer(syncer.clone()).build();
+    (syncer, engine)
+}
+
+fn populate(engine: &DefaultMatchingEngine) {
+    engine
+        .create_order(&mut make_limit_order(1, Side::Sell, 100, 5, 1000))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(2, Side::Buy, 100, 3, 1001))
+        .unwrap();
+    engine.match_orders();
+    engine.cancel_order(1).unwrap();
+}
+
+#[test]
+fn test_consumer_heals_a_gap_from_the_retention_buffer() {
+    let (syncer, engine) = new_engine(16);
+    populate(&engine);
+    assert_eq!(syncer.oldest(), Some(1));
+    assert_eq!(syncer.latest(), Some(4));
+
+    // The consumer lost events 2 and 3 on the way
+    let mut detector = GapDetector::new(1);
+    assert_eq!(detector.observe(1), SequenceCheck::InOrder);
+    assert_eq!(detector.observe(4), SequenceCheck::Gap { from: 2, to: 4 });
+
+    let events = syncer.replay_from(2).unwrap();
+    let sequences: Vec<_> = events.iter().map(|event| event.sequence).collect();
+    assert_eq!(sequences, vec![2, 3, 4]);
+    assert!(matches!(events[1].event, SyncEvent::Matched { ref trades, .. } if trades.len() == 2));
+
+    let consumer = Recorder::default();
+    assert_eq!(syncer.replay_into(2, &consumer), Ok(3));
+    let ids: Vec<_> = consumer.events().iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, vec![2, 3, 4]);
+    assert!(syncer.replay_from(5).unwrap().is_empty());
+}
+
+#[test]
+fn test_evicted_events_cannot_be_resent() {
+    let (syncer, engine) = new_engine(2);
+    populate(&engine);
+    assert_eq!(syncer.oldest(), Some(3));
+    assert_eq!(
+        syncer.replay_from(2).unwrap_err(),
+        ResendError::Evicted {
+            requested: 2,
+            oldest: 3
+        }
+    );
+    assert_eq!(syncer.replay_from(3).unwrap().len(), 2);
+}
+
+#[test]
+fn test_gap_detector_ignores_duplicates() {
+    let mut detector = GapDetector::new(5);
+    assert_eq!(detector.observe(4), SequenceCheck::Duplicate);
+    assert_eq!(detector.observe(5), SequenceCheck::InOrder);
+    assert_eq!(detector.observe(5), SequenceCheck::Duplicate);
+    assert_eq!(detector.next(), 6);
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+
+/// Takes the id of every event recorded so far and the trades of every match
+fn take_log(log: &Recorder) -> Vec<(u64, Vec<Trade>)> {
+    log.take()
+        .into_iter()
+        .map(|(id, event)| match event {
+            SyncEvent::Matched { trades, .. } => (id, trades),
+            _ => (id, Vec::new()),
+        })
+        .collect()
+}
+
+fn new_engine(book: Arc<DefaultOrderBook>, clock: Arc<ManualClock>) -> DefaultMatchingEngine {
+    DefaultMatchingEngine::new(book)
+        .with_clock(clock.clone())
+        .with_id_generator(Arc::new(IdGenerator::new(1).with_clock(clock)))
+}
+
+/// Trades through a level whose orders only differ in time priority and priority class
+fn continue_trading(engine: &DefaultMatchingEngine) {
+    engine
+        .create_order(&mut make_limit_order(20, Side::Buy, 101, 12, 3000))
+        .unwrap();
+    engine.match_orders();
+    engine.cancel_order(3).unwrap();
+    engine
+        .create_order(&mut make_limit_order(21, Side::Sell, 99, 4, 3001))
+        .unwrap();
+    engine.match_orders();
+}
+
+#[test]
+fn test_restored_book_matches_like_the_original() {
+    let log = Arc::new(Recorder::default());
+    let book = Arc::new(DefaultOrderBook::new(
+        Arc::new(AtomicU64::new(1)),
+        log.clone(),
+    ));
+    let clock = Arc::new(ManualClock::new(1_750_000_000_000_000));
+    let engine = new_engine(book.clone(), clock.clone());
+    let mut liquidation = make_limit_order(4, Side::Sell, 101, 3, 1003);
+    liquidation.class = OrderClass::Liquidation;
+    let mut orders = [
+        make_limit_order(1, Side::Sell, 100, 5, 1000),
+        make_limit_order(2, Side::Sell, 101, 5, 1001),
+        make_limit_order(3, Side::Sell, 101, 5, 1002),
+        liquidation,
+        make_limit_order(5, Side::Buy, 100, 2, 1004),
+        make_limit_order(6, Side::Buy, 98, 5, 1005),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.match_orders();
+
+    // Restore a second book from the checkpoint and run both the same way
+    let checkpoint = book.checkpoint();
+    let restored_log = Arc::new(Recorder::default());
+    let restored = Arc::new(DefaultOrderBook::restore(
+        Arc::new(AtomicU64::new(1)),
+        restored_log.clone(),
+        &checkpoint,
+    ));
+    assert_eq!(checkpoint.verify(&restored.checkpoint()), Ok(()));
+    assert_eq!(restored.snapshot(), book.snapshot());
+
+    log.take();
+    clock.advance(1_000_000);
+    let restored_clock = Arc::new(ManualClock::new(clock.now_micros()));
+    let restored_engine = new_engine(restored.clone(), restored_clock);
+    continue_trading(&engine);
+    continue_trading(&restored_engine);
+
+    let events = take_log(&log);
+    let trades: Vec<_> = events.iter().flat_map(|(_, trades)| trades).collect();
+    // Order 20 fills 1, then the liquidation order 4 ahead of 2 and 3, then 2 and 3 in time
+    let makers: Vec<_> = trades
+        .iter()
+        .filter(|trade| trade.role == TradeRole::Taker)
+        .map(|trade| trade.order_id)
+        .collect();
+    assert_eq!(makers[..4], [1, 4, 2, 3]);
+    assert_eq!(take_log(&restored_log), events);
+    assert_eq!(book.checkpoint().verify(&restored.checkpoint()), Ok(()));
+}
+
+#[test]
+fn test_verify_reports_the_first_difference() {
+    let book = DefaultOrderBook::new(
+        Arc::new(AtomicU64::new(1)),
+        Arc::new(EmptyOrderBookSyncer {}),
+    );
+    book.insert(&mut make_limit_order(1, Side::Buy, 100, 5, 1000))
+        .unwrap();
+    book.insert(&mut make_limit_order(2, Side::Buy, 100, 5, 1001))
+        .unwrap();
+    let checkpoint = book.checkpoint();
+
+    let mut other = checkpoint.clone();
+    other.sequence += 1;
+    assert_eq!(
+        checkpoint.verify(&other),
+        Err(CheckpointMismatch::Sequence {
+            expected: 3,
+            actual: 4
+        })
+    );
+
+    // Same orders, but the second one re-timed behind where it was
+    let mut other = checkpoint.clone();
+    other.bids[1].0.priority += 1;
+    assert_eq!(
+        checkpoint.verify(&other),
+        Err(CheckpointMismatch::Entry {
+            side: Some(Side::Buy),
+            position: 1
+        })
+    );
+    other.bids.pop();
+    assert!(checkpoint.verify(&other).is_err());
+}
+
+#[test]
+fn test_normalize_resets_interrupted_lifecycles() {
+    let book = DefaultOrderBook::new(
+        Arc::new(AtomicU64::new(1)),
+        Arc::new(EmptyOrderBookSyncer {}),
+    );
+    for id in 1..=4 {
+        book.insert(&mut make_limit_order(id, Side::Sell, 100, 5, 1000 + id))
+            .unwrap();
+    }
+    // Taken while order 1 was being partially filled, order 2 completed, and order 3 canceled
+    let mut checkpoint = book.checkpoint();
+    let orders: Vec<_> = checkpoint.asks.iter().map(|(_, order)| order).collect();
+    orders[0].lifecycle.enter_matched();
+    orders[0].quantity.store(Quantity::from(2u64));
+    orders[0].status.store(OrderStatus::PartiallyFilled);
+    orders[1].lifecycle.enter_matched();
+    orders[1].quantity.store(Quantity::ZERO);
+    orders[1].status.store(OrderStatus::Filled);
+    orders[2].lifecycle.enter_finished_from_active();
+
+    let report = checkpoint.normalize();
+    assert_eq!(
+        report,
+        NormalizationReport {
+            reactivated: vec![1],
+            removed: vec![2, 3],
+        }
+    );
+    assert!(checkpoint.clone().normalize().is_empty());
+
+    let restored = DefaultOrderBook::restore(
+        Arc::new(AtomicU64::new(1)),
+        Arc::new(EmptyOrderBookSyncer {}),
+        &checkpoint,
+    );
+    let ids: Vec<_> = restored
+        .snapshot()
+        .asks
+        .iter()
+        .map(|view| view.id)
+        .collect();
+    assert_eq!(ids, vec![1, 4]);
+    assert_eq!(restored.remove(1), Ok(()));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+struct BlockedUser {
+    user_id: u64,
+    block_matching: AtomicBool,
+}
+
+impl RiskChecker for BlockedUser {
+    fn check(&self, order: &Order) -> RiskDecision {
+        if order.user_id == self.user_id {
+            RiskDecision::Reject(RejectReason::RiskRejected(7))
+        } else {
+            RiskDecision::Allow
+        }
+    }
+
+    fn check_match(&self, _taker: &Order) -> RiskDecision {
+        if self.block_matching.load(Ordering::Relaxed) {
+            RiskDecision::Reject(RejectReason::RiskRejected(8))
+        } else {
+            RiskDecision::Allow
+        }
+    }
+}
+
+/// Counts the pre-match checks
+#[derive(Default)]
+struct MatchCounter(AtomicU64);
+
+impl RiskChecker for MatchCounter {
+    fn check(&self, _order: &Order) -> RiskDecision {
+        RiskDecision::Allow
+    }
+
+    fn check_match(&self, _taker: &Order) -> RiskDecision {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        RiskDecision::Allow
+    }
+}
+
+fn new_engine(checker: Arc<dyn RiskChecker>) -> (Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let (book, engine) = TestEngine::new().build();
+    let engine = engine.with_risk_checker(checker);
+    (book, engine)
+}
+
+fn blocked_user(user_id: u64) -> Arc<BlockedUser> {
+    Arc::new(BlockedUser {
+        user_id,
+        block_matching: AtomicBool::new(false),
+    })
+}
+
+#[test]
+fn test_risk_check_rejects_before_insert() {
+    let (book, engine) = new_engine(blocked_user(13));
+    let mut order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    order.user_id = 13;
+    assert_eq!(
+        engine.create_order(&mut order),
+        Err(RejectReason::RiskRejected(7))
+    );
+    assert_eq!(order.status(), OrderStatus::Rejected);
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+
+    let mut order = make_limit_order(2, Side::Buy, 100, 10, 1001);
+    assert_eq!(engine.create_order(&mut order), Ok(()));
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 1);
+}
+
+#[test]
+fn test_risk_check_rejects_taker_before_match() {
+    let checker = blocked_user(13);
+    let (book, engine) = new_engine(checker.clone());
+    let mut older = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let mut newer = make_limit_order(2, Side::Buy, 100, 4, 1001);
+    engine.create_order(&mut older).unwrap();
+    engine.create_order(&mut newer).unwrap();
+
+    checker.block_matching.store(true, Ordering::Relaxed);
+    engine.match_orders();
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(2, Quantity::from(4u64))]
+    );
+    assert_eq!(
+        engine
+            .open_orders(1)
+            .iter()
+            .map(|view| view.id)
+            .collect::<Vec<_>>(),
+        vec![2]
+    );
+}
+
+#[test]
+fn test_risk_check_rejects_market_taker() {
+    let checker = blocked_user(13);
+    let (book, engine) = new_engine(checker.clone());
+    let mut maker = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let mut taker = make_market_order(2, Side::Buy, 4, 1001);
+    engine.create_order(&mut maker).unwrap();
+    engine.create_order(&mut taker).unwrap();
+
+    checker.block_matching.store(true, Ordering::Relaxed);
+    engine.match_orders();
+    assert!(book.snapshot().market_orders.is_empty());
+    assert_eq!(get_book_state(book.as_ref(), Side::Sell).len(), 1);
+}
+
+#[test]
+fn test_risk_check_runs_once_per_taker_walk() {
+    let counter = Arc::new(MatchCounter::default());
+    let (book, engine) = new_engine(counter.clone());
+    for (id, price) in [(1, 100), (2, 101)] {
+        engine
+            .create_order(&mut make_limit_order(id, Side::Sell, price, 5, 1000 + id))
+            .unwrap();
+    }
+    engine
+        .create_order(&mut make_market_order(3, Side::Buy, 10, 2000))
+        .unwrap();
+    engine.match_orders();
+
+    // Both makers were crossed under the single check of the taker
+    assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
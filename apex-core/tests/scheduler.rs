@@ -0,0 +1,166 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn new_scheduler(start: u64) -> (Arc<ManualClock>, Scheduler) {
+    let clock = Arc::new(ManualClock::new(start));
+    (clock.clone(), Scheduler::new(clock))
+}
+
+#[test]
+fn test_manual_ticks_run_due_tasks_in_order() {
+    let (clock, mut scheduler) = new_scheduler(0);
+    let runs = Arc::new(Mutex::new(Vec::new()));
+    let log = runs.clone();
+    scheduler.schedule("flush", Duration::from_micros(300), move |now| {
+        log.lock().unwrap().push(("flush", now))
+    });
+    let log = runs.clone();
+    let snapshot = scheduler.schedule("snapshot", Duration::from_micros(200), move |now| {
+        log.lock().unwrap().push(("snapshot", now))
+    });
+    assert_eq!(scheduler.next_due(), Some(200));
+    assert_eq!(scheduler.tick(), 0);
+
+    clock.set(200);
+    assert_eq!(scheduler.tick(), 1);
+    // Both are due, and the snapshot at 400 was due before the flush at 300 ran late
+    clock.set(450);
+    assert_eq!(scheduler.tick(), 2);
+    assert_eq!(
+        *runs.lock().unwrap(),
+        vec![("snapshot", 200), ("flush", 450), ("snapshot", 450)]
+    );
+
+    // Missed periods are skipped instead of run back to back
+    clock.set(2000);
+    assert_eq!(scheduler.tick(), 2);
+    assert_eq!(scheduler.next_due(), Some(2100));
+    assert!(scheduler.cancel(snapshot));
+    assert!(!scheduler.cancel(snapshot));
+    let tasks = scheduler.tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!((tasks[0].name.as_str(), tasks[0].runs), ("flush", 2));
+}
+
+#[test]
+fn test_jitter_is_bounded_and_reproducible() {
+    let schedule = |seed: u64| {
+        let clock = Arc::new(ManualClock::new(0));
+        let mut scheduler = Scheduler::new(clock.clone()).with_seed(seed);
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let log = runs.clone();
+        scheduler.schedule_with_jitter(
+            "metrics",
+            Duration::from_micros(1000),
+            Duration::from_micros(100),
+            move |now| log.lock().unwrap().push(now),
+        );
+        for _ in 0..550 {
+            clock.advance(10);
+            scheduler.tick();
+        }
+        runs.lock().unwrap().clone()
+    };
+    let runs = schedule(7);
+    assert_eq!(runs.len(), 5);
+    // Every run falls in the jitter window of its own period, so there is no drift
+    for (period, run) in runs.iter().enumerate() {
+        let start = (period as u64 + 1) * 1000;
+        assert!((start..=start + 100).contains(run), "{run} outside {start}");
+    }
+    assert_eq!(schedule(7), runs);
+}
+
+#[test]
+fn test_background_thread_runs_the_expiry_sweep() {
+    let (book, engine) = TestEngine::new().build();
+    let engine = Arc::new(engine);
+    let mut order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    order.time_in_force = TimeInForce::GoodTillDate(5000);
+    engine.create_order(&mut order).unwrap();
+
+    let (clock, mut scheduler) = new_scheduler(1000);
+    scheduler.schedule_expiry(engine.clone(), Duration::from_micros(1000));
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = scheduler.spawn(stop.clone());
+    clock.set(6000);
+    while book.get_order(1).is_some() {
+        std::thread::yield_now();
+    }
+    stop.store(true, std::sync::atomic::Ordering::Release);
+    let scheduler = handle.join().unwrap();
+    assert!(scheduler.tasks()[0].runs >= 1);
+}
+
+#[test]
+fn test_adaptive_cadence_backs_off_until_woken() {
+    let (clock, mut scheduler) = new_scheduler(0);
+    let busy = Arc::new(AtomicBool::new(false));
+    let found = busy.clone();
+    let cadence = Cadence::new(Duration::from_micros(100), Duration::from_micros(500));
+    let id = scheduler.schedule_adaptive("sweep", cadence, move |_| {
+        found.swap(false, std::sync::atomic::Ordering::AcqRel)
+    });
+
+    // Idle runs double the interval up to the maximum
+    let mut intervals = Vec::new();
+    for _ in 0..5 {
+        clock.set(scheduler.next_due().unwrap());
+        assert_eq!(scheduler.tick(), 1);
+        intervals.push(scheduler.tasks()[0].interval);
+    }
+    assert_eq!(intervals, vec![200, 400, 500, 500, 500]);
+
+    // Finding work goes back to the minimum
+    busy.store(true, std::sync::atomic::Ordering::Release);
+    clock.set(scheduler.next_due().unwrap());
+    scheduler.tick();
+    assert_eq!(scheduler.tasks()[0].interval, 100);
+    clock.set(scheduler.next_due().unwrap());
+    scheduler.tick();
+    assert_eq!(scheduler.tasks()[0].interval, 200);
+
+    // A woken task is due right away, however long its interval
+    clock.advance(1);
+    assert_eq!(scheduler.tick(), 0);
+    scheduler.waker(id).unwrap().wake();
+    assert_eq!(scheduler.next_due(), Some(0));
+    assert_eq!(scheduler.tick(), 1);
+    assert_eq!(scheduler.tasks()[0].interval, 100);
+}
+
+#[test]
+fn test_inserted_orders_wake_the_matching_sweep() {
+    let (clock, mut scheduler) = new_scheduler(0);
+    let waker = scheduler.new_waker();
+    let (book, engine) = TestEngine::new()
+        .with_syncer(Arc::new(WakeSyncer::new(
+            Arc::new(EmptyOrderBookSyncer {}),
+            waker.clone(),
+        )))
+        .build();
+    let engine = Arc::new(engine);
+    let cadence = Cadence::new(Duration::from_millis(1), Duration::from_secs(1));
+    scheduler.schedule_matching(engine.clone(), cadence, &waker);
+    for _ in 0..12 {
+        clock.set(scheduler.next_due().unwrap());
+        scheduler.tick();
+    }
+    assert_eq!(scheduler.tasks()[0].interval, 1_000_000);
+
+    engine
+        .create_order(&mut make_limit_order(1, Side::Sell, 100, 5, 1000))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(2, Side::Buy, 100, 5, 1001))
+        .unwrap();
+    // Matched on the next tick rather than after the idle interval
+    assert_eq!(scheduler.tick(), 1);
+    assert!(book.get_order(1).is_none());
+    assert_eq!(scheduler.tasks()[0].interval, 1000);
+}
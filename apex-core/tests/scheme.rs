@@ -0,0 +1,135 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine(
+    config: BookConfig,
+) -> (
+    Arc<TradeHistory>,
+    Arc<DefaultOrderBook>,
+    DefaultMatchingEngine,
+) {
+    let tape = Arc::new(TradeHistory::new(100));
+    let (book, engine) = TestEngine::new()
+        .with_syncer(tape.clone())
+        .with_config(config)
+        .build();
+    (tape, book, engine)
+}
+
+fn pro_rata() -> BookConfig {
+    BookConfig {
+        matching_scheme: MatchingScheme::ProRata,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_pro_rata_splits_a_level_by_size() {
+    let (_tape, book, engine) = new_engine(pro_rata());
+    for (id, qty) in [(1, 1), (2, 3), (3, 6)] {
+        engine
+            .create_order(&mut make_limit_order(id, Side::Sell, 100, qty, 1000 + id))
+            .unwrap();
+    }
+    engine
+        .create_order(&mut make_market_order(10, Side::Buy, 5, 2000))
+        .unwrap();
+    engine.match_orders();
+
+    // 5 of 10 is 0, 1 and 3; the unit lost to rounding goes to the oldest order
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(2, Quantity::from(2u64)), (3, Quantity::from(3u64))]
+    );
+}
+
+#[test]
+fn test_pro_rata_fills_better_prices_first() {
+    let (tape, book, engine) = new_engine(pro_rata());
+    engine
+        .create_order(&mut make_limit_order(10, Side::Buy, 101, 9, 500))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(1, Side::Sell, 100, 4, 1000))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(2, Side::Sell, 101, 2, 1001))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(3, Side::Sell, 101, 6, 1002))
+        .unwrap();
+    engine.match_orders();
+
+    // 100 fills in full, and the 5 left split 1 and 3 at 101 plus a remainder to order 2
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(3, Quantity::from(3u64))]
+    );
+    let prints = tape.prints(TapeWindow::Last(10));
+    let fills: Vec<_> = prints
+        .iter()
+        .map(|print| (print.price, print.quantity))
+        .collect();
+    assert_eq!(
+        fills,
+        vec![
+            (Price::from(100u64), Quantity::from(4u64)),
+            (Price::from(101u64), Quantity::from(2u64)),
+            (Price::from(101u64), Quantity::from(3u64)),
+        ]
+    );
+}
+
+#[test]
+fn test_batch_auction_executes_at_a_single_price() {
+    let (tape, book, engine) = new_engine(BookConfig {
+        matching_scheme: MatchingScheme::BatchAuction,
+        ..Default::default()
+    });
+    let orders = [
+        (1, Side::Buy, 102, 5),
+        (2, Side::Buy, 101, 5),
+        (3, Side::Sell, 100, 4),
+        (4, Side::Sell, 101, 6),
+    ];
+    for (id, side, price, qty) in orders {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, qty, 1000 + id))
+            .unwrap();
+    }
+    let mut market = make_market_order(5, Side::Buy, 1, 2000);
+    assert_eq!(
+        engine.create_order(&mut market),
+        Err(RejectReason::OrderTypeNotAllowed)
+    );
+    engine.match_orders();
+
+    let prints = tape.prints(TapeWindow::Last(10));
+    assert_eq!(prints.len(), 3);
+    assert!(
+        prints
+            .iter()
+            .all(|print| print.price == Price::from(101u64))
+    );
+    assert_eq!(tape.vwap(TapeWindow::Last(10)), Some(Price::from(101u64)));
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
+
+#[test]
+fn test_order_type_whitelist() {
+    let (_tape, _book, engine) = new_engine(BookConfig {
+        allowed_order_types: Some(vec![OrderType::Market]),
+        ..Default::default()
+    });
+    let mut order = make_limit_order(1, Side::Buy, 100, 1, 1000);
+    assert_eq!(
+        engine.create_order(&mut order),
+        Err(RejectReason::OrderTypeNotAllowed)
+    );
+    assert_eq!(order.status.load(), OrderStatus::Rejected);
+    assert_eq!(RejectReason::OrderTypeNotAllowed.code(), 120);
+}
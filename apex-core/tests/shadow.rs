@@ -0,0 +1,89 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::ops::ControlFlow;
+
+#[test]
+fn test_shadow_absorbs_inserts_and_cancels() {
+    let (book, engine) = TestEngine::new().build();
+    for (id, side, price, qty) in [
+        (1, Side::Buy, 99, 5),
+        (2, Side::Buy, 98, 3),
+        (3, Side::Sell, 101, 4),
+        (4, Side::Sell, 102, 6),
+    ] {
+        engine
+            .create_order(&mut make_limit_order(id, side, price, qty, 1000 + id))
+            .unwrap();
+    }
+
+    let mut shadow = ShadowBook::new(book.as_ref());
+    assert!(shadow.is_unchanged());
+    shadow
+        .insert(&make_limit_order(10, Side::Buy, 99, 2, 2000))
+        .unwrap();
+    shadow
+        .insert(&make_limit_order(11, Side::Buy, 100, 1, 2001))
+        .unwrap();
+    assert!(shadow.cancel(3));
+    assert!(!shadow.cancel(3));
+
+    let mut bids = Vec::new();
+    shadow.for_each_order(Side::Buy, &mut |order| {
+        bids.push(order.id);
+        ControlFlow::Continue(())
+    });
+    assert_eq!(bids, vec![11, 1, 10, 2]);
+    assert_eq!(
+        shadow.quantity_at(Side::Buy, Price::from(99u64)),
+        Quantity::from(7u64)
+    );
+    assert_eq!(shadow.get_best_price(Side::Sell), Some(Price::from(102u64)));
+    assert!(shadow.get_order(3).is_none());
+    assert_eq!(
+        shadow.get_order(10).map(|order| order.status),
+        Some(OrderStatus::Placed)
+    );
+
+    // The base book never sees the hypothetical changes
+    assert_eq!(book.get_best_price(Side::Buy), Some(Price::from(99u64)));
+    assert!(book.get_order(3).is_some());
+    assert!(book.get_order(10).is_none());
+
+    shadow.reset();
+    assert_eq!(shadow.depth(Side::Sell, 10), book.depth(Side::Sell, 10));
+}
+
+#[test]
+fn test_shadow_rejects_duplicates_and_market_orders() {
+    let (book, engine) = TestEngine::new().build();
+    engine
+        .create_order(&mut make_limit_order(1, Side::Buy, 99, 5, 1000))
+        .unwrap();
+
+    let mut shadow = ShadowBook::new(book.as_ref());
+    assert_eq!(
+        shadow.insert(&make_limit_order(1, Side::Buy, 98, 1, 2000)),
+        Err(RejectReason::DuplicateOrderId)
+    );
+    assert_eq!(
+        shadow.insert(&make_market_order(2, Side::Buy, 1, 2001)),
+        Err(RejectReason::OrderTypeNotAllowed)
+    );
+
+    // A canceled base order frees its id in the shadow
+    assert!(shadow.cancel(1));
+    assert_eq!(
+        shadow.insert(&make_limit_order(1, Side::Buy, 98, 1, 2002)),
+        Ok(())
+    );
+    assert_eq!(
+        shadow.depth(Side::Buy, 10),
+        vec![PriceLevel {
+            price: Price::from(98u64),
+            quantity: Quantity::from(1u64),
+            orders: 1,
+        }]
+    );
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::num::NonZeroUsize;
+
+#[test]
+fn test_sharded_engine_routes_by_symbol() {
+    let (book_a, engine_a) = TestEngine::new().build();
+    let (book_b, engine_b) = TestEngine::new().build();
+    let config = ShardConfig {
+        shards: 2,
+        queue_capacity: NonZeroUsize::new(64).unwrap(),
+        affinity: None,
+    };
+    let engine = ShardedEngine::new(config, vec![(10, engine_a), (11, engine_b)]);
+    assert_eq!(engine.shard_count(), 2);
+    assert_ne!(engine.shard_of(10), engine.shard_of(11));
+
+    let sell = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let buy = make_limit_order(2, Side::Buy, 100, 4, 1001);
+    let other = make_limit_order(3, Side::Buy, 50, 7, 1002);
+    engine.submit(10, Command::Create(sell)).unwrap();
+    engine.submit(10, Command::Create(buy)).unwrap();
+    engine.submit(11, Command::Create(other)).unwrap();
+    engine.flush();
+
+    assert_eq!(
+        get_book_state(book_a.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(6u64))]
+    );
+    assert!(get_book_state(book_a.as_ref(), Side::Buy).is_empty());
+    assert_eq!(
+        get_book_state(book_b.as_ref(), Side::Buy),
+        vec![(3, Quantity::from(7u64))]
+    );
+}
+
+#[test]
+fn test_sharded_engine_rejects_unknown_symbol() {
+    let (_book, engine_a) = TestEngine::new().build();
+    let engine = ShardedEngine::new(ShardConfig::default(), vec![(1, engine_a)]);
+
+    let order = make_limit_order(1, Side::Sell, 100, 10, 1000);
+    let result = engine.submit(2, Command::Create(order));
+    assert_eq!(result, Err(SubmitError::UnknownSymbol));
+}
+
+#[test]
+fn test_sharded_engine_cancel_through_shard() {
+    let (book, engine_a) = TestEngine::new().build();
+    let engine = ShardedEngine::new(ShardConfig::default(), vec![(7, engine_a)]);
+
+    let order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    engine.submit(7, Command::Create(order)).unwrap();
+    engine.submit(7, Command::Cancel(1)).unwrap();
+    engine.flush();
+
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+    assert_eq!(engine.shard_depths(), vec![0]);
+}
+
+#[test]
+fn test_sharded_engine_pins_to_configured_cores() {
+    let cores = available_cores();
+    assert!(!cores.is_empty());
+
+    let (book, engine_a) = TestEngine::new().build();
+    let config = ShardConfig {
+        shards: 1,
+        queue_capacity: NonZeroUsize::new(16).unwrap(),
+        affinity: Some(AffinityConfig {
+            matching_cores: vec![cores[0]],
+            ..AffinityConfig::default()
+        }),
+    };
+    let engine = ShardedEngine::new(config, vec![(1, engine_a)]);
+
+    let order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    engine.submit(1, Command::Create(order)).unwrap();
+    engine.flush();
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 1);
+}
+
+#[test]
+fn test_affinity_core_resolution() {
+    assert_eq!(parse_cpu_list("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+    assert!(parse_cpu_list("").is_empty());
+
+    let config = AffinityConfig {
+        matching_cores: vec![2, 3],
+        syncer_core: Some(1),
+        numa_node: None,
+    };
+    assert_eq!(config.matching_core(0), Some(2));
+    assert_eq!(config.matching_core(3), Some(3));
+    assert_eq!(AffinityConfig::default().matching_core(0), None);
+    assert!(!AffinityConfig::default().pin_syncer_thread());
+}
+
+#[test]
+fn test_symbols_listed_at_runtime() {
+    let (_book, engine_a) = TestEngine::new().build();
+    let engine = ShardedEngine::new(
+        ShardConfig {
+            shards: 2,
+            queue_capacity: NonZeroUsize::new(16).unwrap(),
+            affinity: None,
+        },
+        vec![(1, engine_a)],
+    );
+    let (book, engine_b) = TestEngine::new().build();
+    assert_eq!(engine.list(2, engine_b), Ok(()));
+    assert!(engine.is_listed(2));
+    let (_other, engine_c) = TestEngine::new().build();
+    assert_eq!(engine.list(2, engine_c), Err(SubmitError::SymbolListed));
+
+    let order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    engine.submit(2, Command::Create(order)).unwrap();
+    engine.flush();
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 1);
+}
+
+#[test]
+fn test_delisting_cancels_and_stops_accepting() {
+    let (book, engine_a) = TestEngine::new().build();
+    let engine = ShardedEngine::new(ShardConfig::default(), vec![(1, engine_a)]);
+    let orders = [
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        make_limit_order(2, Side::Sell, 110, 5, 1001),
+        make_limit_order(3, Side::Sell, 100, 4, 1002),
+    ];
+    for order in orders {
+        engine.submit(1, Command::Create(order)).unwrap();
+    }
+
+    // Queued commands are applied and matched before the book closes
+    let delisting = engine.delist(1).unwrap();
+    assert_eq!(delisting.snapshot.bids[0].quantity, Quantity::from(6u64));
+    assert_eq!(delisting.snapshot.asks.len(), 1);
+    let mut cancelled = delisting.cancelled;
+    cancelled.sort();
+    assert_eq!(cancelled, vec![1, 2]);
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+
+    assert!(!engine.is_listed(1));
+    let order = make_limit_order(4, Side::Buy, 100, 10, 1003);
+    assert_eq!(
+        engine.submit(1, Command::Create(order)),
+        Err(SubmitError::UnknownSymbol)
+    );
+    assert_eq!(engine.delist(1), Err(SubmitError::UnknownSymbol));
+}
+
+#[test]
+fn test_delisting_keeps_commands_accepted_while_it_runs() {
+    let (_book, engine_a) = TestEngine::new().build();
+    let engine = ShardedEngine::new(ShardConfig::default(), vec![(1, engine_a)]);
+
+    let (accepted, delisting) = std::thread::scope(|scope| {
+        let submitter = scope.spawn(|| {
+            let mut accepted = 0;
+            for id in 1.. {
+                let order = make_limit_order(id, Side::Sell, 100 + id, 1, 1000 + id);
+                match engine.submit(1, Command::Create(order)) {
+                    Ok(()) => accepted += 1,
+                    Err(SubmitError::QueueFull) => std::thread::yield_now(),
+                    Err(_) => break,
+                }
+            }
+            accepted
+        });
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let delisting = engine.delist(1).unwrap();
+        (submitter.join().unwrap(), delisting)
+    });
+
+    // Every command the engine accepted reached the book before it closed
+    assert_eq!(delisting.cancelled.len(), accepted);
+}
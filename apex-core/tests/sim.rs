@@ -0,0 +1,73 @@
+use apex_core::prelude::*;
+
+#[test]
+fn test_scenario_is_reproducible() {
+    let scenario = Scenario {
+        seed: 42,
+        steps: 300,
+        ..Scenario::default()
+    };
+    let first = Simulation::run_scenario(&scenario);
+    let second = Simulation::run_scenario(&scenario);
+    assert!(!first.trades.is_empty());
+    assert_eq!(first.books.len(), 300);
+    assert_eq!(first, second);
+
+    let other = Simulation::run_scenario(&Scenario {
+        seed: 43,
+        ..scenario
+    });
+    assert_ne!(first.trades, other.trades);
+}
+
+#[test]
+fn test_simulation_timestamps_follow_the_clock() {
+    let scenario = Scenario {
+        seed: 7,
+        steps: 200,
+        tick_micros: 500,
+        ..Scenario::default()
+    };
+    let report = Simulation::run_scenario(&scenario);
+    let end = scenario.start_micros + 200 * 500;
+    assert!(
+        report
+            .trades
+            .iter()
+            .all(|trade| { trade.created_at > scenario.start_micros && trade.created_at <= end })
+    );
+    assert!(
+        report
+            .trades
+            .windows(2)
+            .all(|pair| pair[0].created_at <= pair[1].created_at)
+    );
+}
+
+#[test]
+fn test_manual_events() {
+    let sim = Simulation::new(1_000_000);
+    let sell = Order::limit(
+        1,
+        1,
+        Side::Sell,
+        Price::from(100u64),
+        Quantity::from(5u64),
+        1,
+    );
+    let buy = Order::limit(
+        2,
+        2,
+        Side::Buy,
+        Price::from(100u64),
+        Quantity::from(5u64),
+        2,
+    );
+    assert!(sim.apply(&SimEvent::Create(sell)));
+    assert!(sim.apply(&SimEvent::Advance(250)));
+    assert!(sim.apply(&SimEvent::Create(buy)));
+    assert!(!sim.apply(&SimEvent::Cancel(9)));
+    assert!(sim.apply(&SimEvent::Match));
+    assert_eq!(sim.clock().now_micros(), 1_000_250);
+    assert!(sim.engine().snapshot().is_empty());
+}
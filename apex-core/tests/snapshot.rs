@@ -0,0 +1,96 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+
+#[test]
+fn test_snapshot_orders_by_priority() {
+    let (_book, engine) = TestEngine::new().build();
+    assert!(engine.snapshot().is_empty());
+
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 101, 10, 1001),
+        make_limit_order(3, Side::Sell, 105, 10, 1002),
+        make_limit_order(4, Side::Sell, 104, 10, 1003),
+        make_market_order(5, Side::Buy, 3, 1004),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+
+    let snapshot = engine.snapshot();
+    let ids = |views: &[OrderView]| views.iter().map(|view| view.id).collect::<Vec<_>>();
+    assert_eq!(ids(&snapshot.bids), vec![2, 1]);
+    assert_eq!(ids(&snapshot.asks), vec![4, 3]);
+    assert_eq!(ids(&snapshot.market_orders), vec![5]);
+    assert_eq!(snapshot.best_price(Side::Sell), Some(Price::from(104u64)));
+    assert_eq!(snapshot.order(1).map(|view| view.side), Some(Side::Buy));
+    assert_eq!(
+        snapshot.order(5).map(|view| view.order_type),
+        Some(OrderType::Market)
+    );
+    assert!(snapshot.order(6).is_none());
+}
+
+#[test]
+fn test_snapshot_is_detached_from_book() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    engine.create_order(&mut order).unwrap();
+
+    let snapshot = engine.snapshot();
+    engine.cancel_order(1).unwrap();
+
+    assert_eq!(snapshot.side(Side::Buy).len(), 1);
+    assert_eq!(snapshot.bids[0].status, OrderStatus::Placed);
+    assert!(engine.snapshot().is_empty());
+}
+
+#[test]
+fn test_diff_of_identical_books_is_empty() {
+    let (_book, engine) = TestEngine::new().build();
+    engine
+        .create_order(&mut make_limit_order(1, Side::Buy, 100, 10, 1000))
+        .unwrap();
+    let snapshot = engine.snapshot();
+    assert!(snapshot.diff(&snapshot.clone()).is_empty());
+    assert!(
+        BookSnapshot::default()
+            .diff(&BookSnapshot::default())
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_diff_reports_every_kind_of_difference() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        make_limit_order(2, Side::Buy, 100, 10, 1001),
+        make_limit_order(3, Side::Buy, 99, 10, 1002),
+        make_limit_order(4, Side::Sell, 105, 10, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    let reference = engine.snapshot();
+
+    let mut replica = reference.clone();
+    // Order 4 never reached the replica, and it holds an order the reference does not
+    let stray = replica.asks.remove(0);
+    replica.asks.push(OrderView { id: 9, ..stray });
+    // Order 3 is stale, and orders 1 and 2 swapped their time priority
+    replica.bids[2].quantity = Quantity::from(4u64);
+    replica.bids.swap(0, 1);
+
+    let diff = reference.diff(&replica);
+    let ids = |views: &[OrderView]| views.iter().map(|view| view.id).collect::<Vec<_>>();
+    assert_eq!(ids(&diff.missing), vec![4]);
+    assert_eq!(ids(&diff.unexpected), vec![9]);
+    assert_eq!(diff.mismatched.len(), 1);
+    assert_eq!(diff.mismatched[0].expected, reference.bids[2]);
+    assert_eq!(diff.mismatched[0].actual.quantity, Quantity::from(4u64));
+    assert_eq!(diff.reordered, vec![1, 2]);
+    assert!(!diff.is_empty());
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn new_engine(
+    seed: u64,
+) -> (
+    Arc<ManualClock>,
+    Arc<DefaultOrderBook>,
+    DefaultMatchingEngine,
+) {
+    let clock = Arc::new(ManualClock::new(1_000_000));
+    let (book, engine) = TestEngine::new().build();
+    let engine = engine
+        .with_clock(clock.clone())
+        .with_speed_bump(SpeedBump::new(Duration::from_micros(500), seed));
+    (clock, book, engine)
+}
+
+#[test]
+fn test_orders_match_only_once_their_delay_passed() {
+    let (clock, book, engine) = new_engine(42);
+    engine
+        .create_order(&mut make_limit_order(1, Side::Sell, 100, 5, 1000))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(2, Side::Buy, 100, 5, 1001))
+        .unwrap();
+    let speed_bump = engine.speed_bump().unwrap();
+    let eligible_at = speed_bump
+        .eligible_at(1)
+        .max(speed_bump.eligible_at(2))
+        .unwrap();
+    assert!((1_000_000..=1_000_500).contains(&eligible_at));
+
+    // Both rest and show in the book while either is still delayed
+    clock.set(eligible_at - 1);
+    engine.match_orders();
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 1);
+    assert_eq!(get_book_state(book.as_ref(), Side::Sell).len(), 1);
+
+    clock.set(eligible_at);
+    engine.match_orders();
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+    assert_eq!(speed_bump.pending(), 0);
+}
+
+#[test]
+fn test_delays_replay_with_the_same_seed() {
+    let delays = |seed: u64| {
+        let (_clock, _book, engine) = new_engine(seed);
+        (1..=20)
+            .map(|id| {
+                engine
+                    .create_order(&mut make_limit_order(id, Side::Buy, 100, 1, id))
+                    .unwrap();
+                engine.speed_bump().unwrap().eligible_at(id).unwrap()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(delays(7), delays(7));
+    assert_ne!(delays(7), delays(8));
+
+    // Liquidations are not delayed, and canceled orders are forgotten
+    let (_clock, _book, engine) = new_engine(7);
+    let mut forced = make_limit_order(1, Side::Buy, 100, 1, 1000);
+    forced.class = OrderClass::Liquidation;
+    engine.create_order(&mut forced).unwrap();
+    assert_eq!(engine.speed_bump().unwrap().eligible_at(1), None);
+    engine
+        .create_order(&mut make_limit_order(2, Side::Buy, 100, 1, 1001))
+        .unwrap();
+    engine.cancel_order(2).unwrap();
+    assert_eq!(engine.speed_bump().unwrap().pending(), 0);
+}
+
+#[test]
+fn test_delays_are_kept_and_forgotten_with_their_orders() {
+    let (clock, _book, engine) = new_engine(7);
+    let speed_bump = engine.speed_bump().unwrap();
+    engine
+        .create_order(&mut make_limit_order(1, Side::Buy, 100, 1, 1000))
+        .unwrap();
+    let eligible_at = speed_bump.eligible_at(1).unwrap();
+
+    // A duplicate id is rejected without touching the resting order's delay
+    clock.advance(100);
+    assert_eq!(
+        engine.create_order(&mut make_limit_order(1, Side::Buy, 101, 1, 1001)),
+        Err(RejectReason::DuplicateOrderId)
+    );
+    assert_eq!(speed_bump.eligible_at(1), Some(eligible_at));
+
+    let mut expiring = make_limit_order(2, Side::Buy, 99, 1, 1002);
+    expiring.time_in_force = TimeInForce::GoodTillDate(2_000_000);
+    engine.create_order(&mut expiring).unwrap();
+    engine
+        .create_order(&mut make_limit_order(3, Side::Sell, 120, 1, 1003))
+        .unwrap();
+    let mut commands = [
+        Command::Create(make_limit_order(4, Side::Sell, 121, 1, 1004)),
+        Command::Cancel(3),
+    ];
+    engine.execute_batch(&mut commands);
+    assert_eq!(speed_bump.pending(), 3);
+
+    assert_eq!(engine.expire_orders(2_000_000), vec![2]);
+    assert_eq!(engine.cancel_where(Side::Buy, None), vec![1]);
+    assert_eq!(engine.cancel_orders(&[4]), vec![Ok(())]);
+    assert_eq!(speed_bump.pending(), 0);
+}
+
+#[test]
+fn test_longest_delays_saturate() {
+    let clock = Arc::new(ManualClock::new(u64::MAX - 10));
+    let (_book, engine) = TestEngine::new().build();
+    let engine = engine
+        .with_clock(clock)
+        .with_speed_bump(SpeedBump::new(Duration::MAX, 1));
+    engine
+        .create_order(&mut make_limit_order(1, Side::Buy, 100, 1, 1000))
+        .unwrap();
+    assert!(engine.speed_bump().unwrap().eligible_at(1).unwrap() >= u64::MAX - 10);
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_leg() -> (Arc<DefaultOrderBook>, Arc<DefaultMatchingEngine>) {
+    let (book, engine) = TestEngine::new().build();
+    let engine = Arc::new(engine);
+    (book, engine)
+}
+
+fn definition() -> SpreadDefinition {
+    SpreadDefinition {
+        front: 1,
+        back: 2,
+        price_offset: Price::from(1000u64),
+    }
+}
+
+fn user_order(id: u64, user_id: u64, side: Side, price: u64, qty: u64, ts: u64) -> Order {
+    Order::limit(
+        id,
+        user_id,
+        side,
+        Price::from(price),
+        Quantity::from(qty),
+        ts,
+    )
+}
+
+#[test]
+fn test_spread_prices_and_leg_prices() {
+    let definition = definition();
+    assert_eq!(
+        definition.spread_price(Price::from(105u64), Price::from(100u64)),
+        Some(Price::from(1005u64))
+    );
+    assert_eq!(
+        definition.spread_price(Price::from(1u64), Price::from(2000u64)),
+        None
+    );
+    assert_eq!(
+        definition.leg_prices(Price::from(1003u64), Price::from(104u64)),
+        (Price::from(104u64), Price::from(101u64))
+    );
+    // The front leg moves up when the back leg would not be positive
+    assert_eq!(
+        definition.leg_prices(Price::from(1500u64), Price::from(100u64)),
+        (Price::from(501u64), Price::from(1u64))
+    );
+}
+
+#[test]
+fn test_spread_order_executes_against_implied_leg_prices() {
+    let (front_book, front) = new_leg();
+    let (back_book, back) = new_leg();
+    front
+        .create_order(&mut user_order(1, 2, Side::Sell, 105, 5, 1000))
+        .unwrap();
+    back.create_order(&mut user_order(2, 3, Side::Buy, 100, 5, 1001))
+        .unwrap();
+    let spread = SpreadEngine::new(
+        definition(),
+        Arc::new(EmptyOrderBookSyncer {}),
+        front.clone(),
+        back.clone(),
+    );
+    assert_eq!(
+        spread.implied_quote(Side::Buy),
+        Some(ImpliedQuote {
+            price: Price::from(1005u64),
+            quantity: Quantity::from(5u64),
+            front_price: Price::from(105u64),
+            back_price: Price::from(100u64),
+        })
+    );
+
+    let mut resting = user_order(10, 1, Side::Buy, 1004, 3, 1002);
+    spread.create_order(&mut resting).unwrap();
+    assert!(spread.match_orders().is_empty());
+
+    let mut crossing = user_order(11, 1, Side::Buy, 1010, 3, 1003);
+    spread.create_order(&mut crossing).unwrap();
+    let executions = spread.match_orders();
+    assert_eq!(executions.len(), 1);
+    let execution = &executions[0];
+    assert_eq!(execution.kind, SpreadMatch::Implied);
+    assert_eq!(execution.price, Price::from(1005u64));
+    assert_eq!(execution.quantity, Quantity::from(3u64));
+    assert_eq!(execution.spread_trades[0].order_id, 11);
+    let leg_trade = |trades: &[Trade], role: TradeRole| {
+        trades
+            .iter()
+            .find(|trade| trade.role == role)
+            .map(|trade| (trade.order_id, trade.price))
+    };
+    assert_eq!(
+        leg_trade(&execution.front_trades, TradeRole::Taker),
+        Some((11, Price::from(105u64)))
+    );
+    assert_eq!(
+        leg_trade(&execution.back_trades, TradeRole::Maker),
+        Some((2, Price::from(100u64)))
+    );
+
+    assert_eq!(
+        get_book_state(front_book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(2u64))]
+    );
+    assert_eq!(
+        get_book_state(back_book.as_ref(), Side::Buy),
+        vec![(2, Quantity::from(2u64))]
+    );
+    let open: Vec<_> = spread
+        .spread()
+        .open_orders(1)
+        .iter()
+        .map(|order| order.id)
+        .collect();
+    assert_eq!(open, vec![10]);
+}
+
+#[test]
+fn test_implied_execution_never_fills_a_single_leg() {
+    let (front_book, front) = new_leg();
+    let (_back_book, back) = new_leg();
+    front
+        .create_order(&mut user_order(1, 2, Side::Sell, 105, 5, 1000))
+        .unwrap();
+    back.create_order(&mut user_order(2, 3, Side::Buy, 100, 5, 1001))
+        .unwrap();
+    // The back maker cannot trade, so the front leg must not trade either
+    back.freeze_user(3);
+    let spread = SpreadEngine::new(
+        definition(),
+        Arc::new(EmptyOrderBookSyncer {}),
+        front.clone(),
+        back.clone(),
+    );
+    let mut order = user_order(10, 1, Side::Buy, 1010, 3, 1002);
+    spread.create_order(&mut order).unwrap();
+    assert!(spread.match_orders().is_empty());
+    assert_eq!(
+        get_book_state(front_book.as_ref(), Side::Sell),
+        vec![(1, Quantity::from(5u64))]
+    );
+    assert_eq!(spread.spread().open_orders(1).len(), 1);
+
+    // The released front order still trades
+    front
+        .create_order(&mut user_order(3, 4, Side::Buy, 105, 5, 1003))
+        .unwrap();
+    front.match_orders();
+    assert!(get_book_state(front_book.as_ref(), Side::Sell).is_empty());
+}
+
+#[test]
+fn test_direct_spread_match_reports_both_legs() {
+    let (_front_book, front) = new_leg();
+    let (_back_book, back) = new_leg();
+    front
+        .create_order(&mut make_limit_order(1, Side::Buy, 104, 1, 1000))
+        .unwrap();
+    let spread = SpreadEngine::new(
+        definition(),
+        Arc::new(EmptyOrderBookSyncer {}),
+        front.clone(),
+        back.clone(),
+    );
+    spread
+        .create_order(&mut user_order(10, 1, Side::Buy, 1003, 4, 1001))
+        .unwrap();
+    spread
+        .create_order(&mut user_order(11, 2, Side::Sell, 1003, 4, 1002))
+        .unwrap();
+
+    let executions = spread.match_orders();
+    assert_eq!(executions.len(), 1);
+    let execution = &executions[0];
+    assert_eq!(execution.kind, SpreadMatch::Direct);
+    assert_eq!(execution.spread_trades.len(), 2);
+    // The spread buyer buys the front leg and sells the back leg
+    let legs = |trades: &[Trade]| {
+        trades
+            .iter()
+            .map(|trade| (trade.role, trade.order_id, trade.price, trade.off_book))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        legs(&execution.front_trades),
+        vec![
+            (TradeRole::Maker, 11, Price::from(104u64), true),
+            (TradeRole::Taker, 10, Price::from(104u64), true),
+        ]
+    );
+    assert_eq!(
+        legs(&execution.back_trades),
+        vec![
+            (TradeRole::Maker, 10, Price::from(101u64), true),
+            (TradeRole::Taker, 11, Price::from(101u64), true),
+        ]
+    );
+    assert!(spread.spread().open_orders(1).is_empty());
+}
+
+#[test]
+fn test_direct_spread_match_waits_for_both_legs() {
+    let (_front_book, front) = new_leg();
+    let (_back_book, back) = new_leg();
+    let spread = SpreadEngine::new(
+        definition(),
+        Arc::new(EmptyOrderBookSyncer {}),
+        front.clone(),
+        back.clone(),
+    );
+    spread
+        .create_order(&mut user_order(10, 1, Side::Buy, 1003, 4, 1001))
+        .unwrap();
+    spread
+        .create_order(&mut user_order(11, 2, Side::Sell, 1003, 4, 1002))
+        .unwrap();
+
+    // A leg that cannot book its side keeps the spread orders from matching at all
+    back.set_mode(EngineMode::CancelOnly);
+    assert!(spread.match_orders().is_empty());
+    assert_eq!(spread.spread().open_orders(1).len(), 1);
+    assert_eq!(spread.spread().open_orders(2).len(), 1);
+
+    back.set_mode(EngineMode::Normal);
+    let executions = spread.match_orders();
+    assert_eq!(executions.len(), 1);
+    assert_eq!(executions[0].front_trades.len(), 2);
+    assert_eq!(executions[0].back_trades.len(), 2);
+}
@@ -0,0 +1,230 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine() -> Arc<DefaultMatchingEngine> {
+    engine_with_config(BookConfig::default())
+}
+
+fn engine_with_config(config: BookConfig) -> Arc<DefaultMatchingEngine> {
+    let (_book, engine) = TestEngine::new().with_config(config).build();
+    Arc::new(engine.with_log_clock(Arc::new(ManualClock::default())))
+}
+
+/// Promotes another node, through the shared fence, as soon as the book accepts an order
+struct PromoteOnAdd(Arc<EpochFence>);
+
+impl OrderBookSyncer for PromoteOnAdd {
+    fn add_order(&self, _id: u64, _order: &Order) {
+        let _ = self.0.advance(self.0.current());
+    }
+
+    fn update_order(&self, _id: u64, _order: &Order) {}
+
+    fn cancel_order(&self, _id: u64, _order: &Order) {}
+
+    fn matched(&self, _id: u64, _updated: &[Order], _trades: &[Trade]) {}
+}
+
+fn create(primary: &mut PrimaryEngine, order: Order) -> SequencedCommand {
+    primary.execute(Command::Create(order)).unwrap().1
+}
+
+#[test]
+fn test_standby_keeps_an_identical_book() {
+    let fence = Arc::new(EpochFence::new(1));
+    let mut primary = PrimaryEngine::new(new_engine(), fence.clone());
+    let mut standby = StandbyEngine::new(new_engine(), fence);
+
+    let records = [
+        create(&mut primary, make_limit_order(1, Side::Sell, 100, 5, 1000)),
+        create(&mut primary, make_limit_order(2, Side::Sell, 101, 5, 1001)),
+        create(&mut primary, make_limit_order(3, Side::Buy, 101, 7, 1002)),
+        primary.execute(Command::Cancel(2)).unwrap().1,
+    ];
+    for record in &records {
+        standby.apply(record).unwrap();
+    }
+    // Records resent after a reconnect are ignored
+    standby.apply(&records[1]).unwrap();
+
+    assert_eq!(standby.sequence(), 4);
+    assert_eq!(standby.engine().snapshot(), primary.engine().snapshot());
+    assert!(standby.engine().snapshot().asks.is_empty());
+}
+
+#[test]
+fn test_standby_refuses_a_gap() {
+    let fence = Arc::new(EpochFence::new(1));
+    let mut primary = PrimaryEngine::new(new_engine(), fence.clone());
+    let mut standby = StandbyEngine::new(new_engine(), fence);
+    create(&mut primary, make_limit_order(1, Side::Sell, 100, 5, 1000));
+    let record = create(&mut primary, make_limit_order(2, Side::Sell, 101, 5, 1001));
+    assert_eq!(
+        standby.apply(&record),
+        Err(FailoverError::SequenceGap {
+            expected: 1,
+            received: 2
+        })
+    );
+    assert!(standby.engine().snapshot().asks.is_empty());
+}
+
+#[test]
+fn test_promotion_fences_the_old_primary() {
+    let fence = Arc::new(EpochFence::new(1));
+    let mut primary = PrimaryEngine::new(new_engine(), fence.clone());
+    let mut standby = StandbyEngine::new(new_engine(), fence.clone());
+    let mut observer = StandbyEngine::new(new_engine(), fence.clone());
+    for record in [
+        create(&mut primary, make_limit_order(1, Side::Sell, 100, 5, 1000)),
+        create(&mut primary, make_limit_order(2, Side::Buy, 99, 5, 1001)),
+    ] {
+        standby.apply(&record).unwrap();
+        observer.apply(&record).unwrap();
+    }
+
+    let mut promoted = standby.promote().unwrap();
+    assert_eq!(promoted.epoch(), 2);
+    assert_eq!(fence.current(), 2);
+
+    // The old primary can no longer change its book
+    assert_eq!(
+        primary
+            .execute(Command::Cancel(1))
+            .map(|(_, record)| record.sequence)
+            .unwrap_err(),
+        FailoverError::Fenced {
+            epoch: 1,
+            current: 2
+        }
+    );
+
+    // The new primary continues the sequence and the other standby follows it
+    let record = create(&mut promoted, make_limit_order(3, Side::Buy, 100, 2, 1002));
+    assert_eq!(record.sequence, 3);
+    observer.apply(&record).unwrap();
+    assert_eq!(observer.epoch(), 2);
+    assert_eq!(observer.engine().snapshot(), promoted.engine().snapshot());
+
+    // A record the old primary shipped before it noticed the fence is refused
+    let stale = SequencedCommand {
+        epoch: 1,
+        sequence: 4,
+        timestamp: record.timestamp,
+        command: Command::Cancel(2).into(),
+    };
+    assert_eq!(
+        observer.apply(&stale),
+        Err(FailoverError::StaleEpoch {
+            epoch: 1,
+            current: 2
+        })
+    );
+}
+
+#[test]
+fn test_only_one_standby_is_promoted() {
+    let fence = Arc::new(EpochFence::new(1));
+    let first = StandbyEngine::new(new_engine(), fence.clone());
+    let second = StandbyEngine::new(new_engine(), fence.clone());
+    assert!(first.promote().is_ok());
+    assert_eq!(
+        second.promote().err(),
+        Some(FailoverError::PromotionConflict {
+            expected: 1,
+            current: 2
+        })
+    );
+}
+
+#[test]
+fn test_command_applied_across_a_promotion_is_unreplicated() {
+    let fence = Arc::new(EpochFence::new(1));
+    let (_book, engine) = TestEngine::new()
+        .with_syncer(Arc::new(PromoteOnAdd(fence.clone())))
+        .build();
+    let mut primary = PrimaryEngine::new(Arc::new(engine), fence);
+
+    assert_eq!(
+        primary
+            .execute(Command::Create(make_limit_order(
+                1,
+                Side::Sell,
+                100,
+                5,
+                1000
+            )))
+            .map(|(_, record)| record.sequence)
+            .unwrap_err(),
+        FailoverError::Unreplicated {
+            epoch: 1,
+            current: 2
+        }
+    );
+    // The order reached the primary's book, which is now ahead of every standby
+    assert_eq!(primary.engine().snapshot().asks.len(), 1);
+}
+
+#[test]
+fn test_standby_follows_maintenance_at_the_primary_time() {
+    let config = BookConfig {
+        max_resting_micros: Some(5000),
+        max_market_order_age_micros: Some(1000),
+        ..BookConfig::default()
+    };
+    let fence = Arc::new(EpochFence::new(1));
+    let clock = Arc::new(ManualClock::new(1_000_000));
+    let mut primary = PrimaryEngine::new(engine_with_config(config.clone()), fence.clone())
+        .with_clock(clock.clone());
+    let mut standby = StandbyEngine::new(engine_with_config(config), fence);
+
+    let mut records = vec![
+        create(&mut primary, make_limit_order(5, Side::Buy, 90, 3, 0)),
+        create(&mut primary, make_limit_order(6, Side::Buy, 80, 3, 0)),
+        // Nothing to buy yet, so the market order waits and goes stale
+        create(&mut primary, make_market_order(2, Side::Buy, 4, 0)),
+    ];
+    clock.advance(2000);
+    records.push(create(
+        &mut primary,
+        make_limit_order(1, Side::Sell, 100, 10, 0),
+    ));
+    records.push(
+        primary
+            .execute_replicated(ReplicatedCommand::CancelWhere {
+                side: Side::Buy,
+                price_range: Some(Price::from(80u64)..=Price::from(80u64)),
+            })
+            .unwrap()
+            .1,
+    );
+    clock.advance(4000);
+    let (expired, record) = primary
+        .execute_replicated(ReplicatedCommand::ExpireOrders)
+        .unwrap();
+    assert_eq!(expired, ReplicatedOutcome::Orders(vec![5]));
+    assert_eq!(record.timestamp, 1_006_000);
+    records.push(record);
+    records.push(create(
+        &mut primary,
+        make_limit_order(4, Side::Buy, 100, 2, 0),
+    ));
+
+    for record in &records {
+        standby.apply(record).unwrap();
+    }
+    let book = primary.engine().snapshot();
+    assert!(book.bids.is_empty());
+    let asks: Vec<_> = book
+        .asks
+        .iter()
+        .map(|order| (order.id, order.quantity))
+        .collect();
+    assert_eq!(asks, vec![(1, Quantity::from(8u64))]);
+    let snapshot = standby.engine().snapshot();
+    assert_eq!(snapshot_digest(&snapshot), snapshot_digest(&book));
+    assert_eq!(snapshot, book);
+}
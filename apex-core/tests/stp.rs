@@ -0,0 +1,117 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+/// Gets the cancel reason of the last match update of an order
+fn reason(recorder: &Recorder, id: OrderID) -> Option<CancelReason> {
+    recorder
+        .matched_orders()
+        .iter()
+        .rev()
+        .find(|order| order.id == id)
+        .and_then(|order| order.cancel_reason.load())
+}
+
+fn new_engine(
+    policy: SelfTradePrevention,
+) -> (Arc<Recorder>, Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let recorder = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new()
+        .with_syncer(recorder.clone())
+        .with_config(BookConfig {
+            self_trade_prevention: policy,
+            ..Default::default()
+        })
+        .build();
+    (recorder, book, engine)
+}
+
+fn owned(mut order: Order, user_id: u64, stp_group: Option<u64>) -> Order {
+    order.user_id = user_id;
+    order.stp_group = stp_group;
+    order
+}
+
+#[test]
+fn test_group_members_cancel_the_resting_order() {
+    let (recorder, book, engine) = new_engine(SelfTradePrevention::CancelResting);
+    let mut orders = [
+        owned(make_limit_order(1, Side::Sell, 100, 5, 1000), 1, Some(9)),
+        owned(make_limit_order(2, Side::Sell, 101, 5, 1001), 3, None),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    let mut taker = owned(make_market_order(3, Side::Buy, 5, 2000), 2, Some(9));
+    assert_eq!(engine.preview(&taker).fills[0].maker_order_id, 2);
+    engine.create_order(&mut taker).unwrap();
+    engine.match_orders();
+
+    // Different users of one group never trade; the taker fills further down the book
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+    assert_eq!(
+        reason(&recorder, 1),
+        Some(CancelReason::SelfTradePrevention)
+    );
+    assert_eq!(reason(&recorder, 3), None);
+}
+
+#[test]
+fn test_cancel_taker_keeps_the_resting_order() {
+    let (recorder, book, engine) = new_engine(SelfTradePrevention::CancelTaker);
+    engine
+        .create_order(&mut make_limit_order(1, Side::Buy, 101, 5, 500))
+        .unwrap();
+    engine
+        .create_order(&mut make_limit_order(2, Side::Sell, 100, 5, 1000))
+        .unwrap();
+    engine.match_orders();
+
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Sell),
+        vec![(2, Quantity::from(5u64))]
+    );
+    assert_eq!(
+        reason(&recorder, 1),
+        Some(CancelReason::SelfTradePrevention)
+    );
+}
+
+#[test]
+fn test_cancel_both_and_unrelated_owners() {
+    let (recorder, book, engine) = new_engine(SelfTradePrevention::CancelBoth);
+    let mut orders = [
+        owned(make_limit_order(1, Side::Buy, 101, 5, 500), 1, Some(9)),
+        owned(make_limit_order(2, Side::Sell, 100, 5, 1000), 2, Some(9)),
+        owned(make_limit_order(3, Side::Buy, 99, 5, 1001), 3, Some(8)),
+        owned(make_limit_order(4, Side::Sell, 99, 2, 1002), 4, None),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    engine.match_orders();
+
+    // The better ask of another owner trades first, then the group's ask stops both
+    assert_eq!(
+        reason(&recorder, 1),
+        Some(CancelReason::SelfTradePrevention)
+    );
+    assert_eq!(
+        reason(&recorder, 2),
+        Some(CancelReason::SelfTradePrevention)
+    );
+    let filled = recorder
+        .matched_orders()
+        .iter()
+        .find(|order| order.id == 1)
+        .map(|order| order.filled_quantity.load());
+    assert_eq!(filled, Some(Quantity::from(2u64)));
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(3, Quantity::from(5u64))]
+    );
+    assert!(get_book_state(book.as_ref(), Side::Sell).is_empty());
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+fn sweep(chunk: Option<usize>, taker: &mut Order) -> Vec<(Vec<OrderID>, usize)> {
+    let log = Arc::new(Recorder::default());
+    let (_book, engine) = TestEngine::new()
+        .with_syncer(log.clone())
+        .with_config(BookConfig {
+            match_chunk_trades: chunk.and_then(NonZeroUsize::new),
+            ..BookConfig::default()
+        })
+        .build();
+    for id in 1..=5 {
+        engine
+            .create_order(&mut make_limit_order(
+                id,
+                Side::Sell,
+                100 + id,
+                2,
+                1000 + id,
+            ))
+            .unwrap();
+    }
+    engine.create_order(taker).unwrap();
+    engine.match_orders();
+    // The updated orders and the number of trades of every matched event
+    log.matches()
+        .iter()
+        .map(|(updated, trades)| (order_ids(updated), trades.len() / 2))
+        .collect()
+}
+
+#[test]
+fn test_large_sweep_streams_fills_in_chunks() {
+    // Older than the asks, so the bid takes and sweeps all five
+    let events = sweep(Some(2), &mut make_limit_order(10, Side::Buy, 110, 12, 500));
+    assert_eq!(
+        events,
+        vec![
+            // Every chunk carries the taker, so each trade joins to its order
+            (vec![1, 2, 10], 2),
+            (vec![3, 4, 10], 2),
+            (vec![5, 10], 1),
+        ]
+    );
+    assert_eq!(
+        sweep(None, &mut make_limit_order(10, Side::Buy, 110, 12, 500)),
+        vec![(vec![1, 2, 3, 4, 5, 10], 5)]
+    );
+}
+
+#[test]
+fn test_market_order_fills_are_streamed() {
+    let events = sweep(Some(2), &mut make_market_order(10, Side::Buy, 8, 2000));
+    assert_eq!(
+        events,
+        vec![(vec![1, 2, 10], 2), (vec![3, 4, 10], 2), (vec![10], 0)]
+    );
+    let trades: usize = events.iter().map(|(_, trades)| trades).sum();
+    assert_eq!(trades, 4);
+}
+
+#[test]
+fn test_streamed_sweep_records_the_whole_position() {
+    let positions = Arc::new(PositionLimits::new(PositionLimit::new(
+        Quantity::from(1000u64),
+        Quantity::from(1000u64),
+    )));
+    let (_book, engine) = TestEngine::new()
+        .with_syncer(positions.clone())
+        .with_config(BookConfig {
+            match_chunk_trades: NonZeroUsize::new(3),
+            ..BookConfig::default()
+        })
+        .build();
+    for id in 1..=20 {
+        let mut ask = make_limit_order(id, Side::Sell, 100 + id, 2, 1000 + id);
+        ask.user_id = 2;
+        engine.create_order(&mut ask).unwrap();
+    }
+    let mut bid = make_limit_order(100, Side::Buy, 200, 35, 500);
+    bid.user_id = 3;
+    engine.create_order(&mut bid).unwrap();
+    engine.match_orders();
+
+    // 17 fills of 2 and one of 1, spread over six chunks
+    assert_eq!(positions.position(3).bought, Quantity::from(35u64));
+    assert_eq!(positions.position(2).sold, Quantity::from(35u64));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct AlertRecorder {
+    alerts: Mutex<Vec<WashTradeAlert>>,
+}
+
+impl SurveillanceSink for AlertRecorder {
+    fn alert(&self, alert: &WashTradeAlert) {
+        self.alerts.lock().unwrap().push(alert.clone());
+    }
+}
+
+fn new_engine() -> (
+    Arc<AlertRecorder>,
+    Arc<WashTradeMonitor>,
+    DefaultMatchingEngine,
+) {
+    let sink = Arc::new(AlertRecorder::default());
+    let monitor = Arc::new(WashTradeMonitor::new(sink.clone()).with_pattern(10, 3));
+    let (_book, engine) = TestEngine::new().with_syncer(monitor.clone()).build();
+    (sink, monitor, engine)
+}
+
+fn cross(engine: &DefaultMatchingEngine, id: u64, seller: u64, buyer: u64) {
+    let mut sell = make_limit_order(id, Side::Sell, 100, 5, id * 10);
+    sell.user_id = seller;
+    let mut buy = make_limit_order(id + 1, Side::Buy, 100, 5, id * 10 + 1);
+    buy.user_id = buyer;
+    engine.create_order(&mut sell).unwrap();
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+}
+
+#[test]
+fn test_same_user_and_group_trades_raise_alerts() {
+    let (sink, monitor, engine) = new_engine();
+    monitor.set_group(4, 40);
+    monitor.set_group(5, 40);
+    monitor.set_group(6, 60);
+
+    cross(&engine, 1, 7, 7);
+    cross(&engine, 3, 4, 5);
+    cross(&engine, 5, 4, 6);
+
+    let alerts = sink.alerts.lock().unwrap();
+    assert_eq!(alerts.len(), 2);
+    assert_eq!(alerts[0].kind, WashTradeKind::SameUser);
+    assert_eq!((alerts[0].sell_order_id, alerts[0].buy_order_id), (1, 2));
+    assert_eq!(alerts[0].quantity, Quantity::from(5u64));
+    assert_eq!(alerts[1].kind, WashTradeKind::SameGroup(40));
+    assert_eq!((alerts[1].sell_user_id, alerts[1].buy_user_id), (4, 5));
+}
+
+#[test]
+fn test_repeated_crossing_between_two_users() {
+    let (sink, _monitor, engine) = new_engine();
+    cross(&engine, 1, 2, 3);
+    cross(&engine, 3, 2, 3);
+    assert!(sink.alerts.lock().unwrap().is_empty());
+
+    cross(&engine, 5, 3, 2);
+    let alerts = sink.alerts.lock().unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(
+        alerts[0].kind,
+        WashTradeKind::RepeatedCrossing { trades: 3 }
+    );
+    assert_eq!((alerts[0].sell_user_id, alerts[0].buy_user_id), (3, 2));
+}
+
+#[test]
+fn test_busted_trades_leave_the_crossing_window() {
+    let (sink, monitor, _engine) = new_engine();
+    let inspect = |trade_id: u64, seller: u64, buyer: u64| {
+        let mut sell = make_limit_order(trade_id * 2, Side::Sell, 100, 5, trade_id);
+        sell.user_id = seller;
+        let mut buy = make_limit_order(trade_id * 2 + 1, Side::Buy, 100, 5, trade_id);
+        buy.user_id = buyer;
+        let side = |role, order_id| Trade {
+            trade_id,
+            role,
+            order_id,
+            ..Trade::default()
+        };
+        monitor.inspect(
+            &[sell, buy],
+            &[
+                side(TradeRole::Maker, trade_id * 2),
+                side(TradeRole::Taker, trade_id * 2 + 1),
+            ],
+        );
+    };
+    inspect(1, 2, 3);
+    inspect(2, 2, 3);
+    monitor.trade_corrected(
+        1,
+        &TradeCorrection {
+            trade_id: 1,
+            kind: TradeCorrectionKind::Bust,
+            original: Vec::new(),
+            restored: Vec::new(),
+            created_at: 0,
+        },
+    );
+    inspect(3, 3, 2);
+    assert!(sink.alerts.lock().unwrap().is_empty());
+
+    inspect(4, 3, 2);
+    assert_eq!(
+        sink.alerts.lock().unwrap()[0].kind,
+        WashTradeKind::RepeatedCrossing { trades: 3 }
+    );
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+#[test]
+fn test_tags_round_trip_through_events_and_trades() {
+    let recorder = Arc::new(Recorder::default());
+    let (_book, engine) = TestEngine::new().with_syncer(recorder.clone()).build();
+    let (sell_tag, buy_tag) = (OrderTag::from(0xabcd_u128), OrderTag([7; 16]));
+
+    let mut sell = Order::builder(1, Side::Sell)
+        .limit(Price::from(100u64))
+        .quantity(Quantity::from(10u64))
+        .tag(sell_tag)
+        .timestamp(1000)
+        .build()
+        .unwrap();
+    engine.create_order(&mut sell).unwrap();
+    let mut buy = make_limit_order(2, Side::Buy, 100, 4, 1001);
+    buy.tag = buy_tag;
+    engine.create_order(&mut buy).unwrap();
+    engine.match_orders();
+    engine.update_order(1, Price::from(101u64), 2000).unwrap();
+    engine.cancel_order(1).unwrap();
+
+    let mut orders = recorder.added();
+    orders.extend(recorder.matched_orders());
+    orders.extend(recorder.updated());
+    orders.extend(recorder.cancelled());
+    assert_eq!(orders.len(), 6);
+    for order in orders.iter() {
+        assert_eq!(order.tag, if order.id == 1 { sell_tag } else { buy_tag });
+    }
+    let trades: Vec<_> = recorder
+        .trades()
+        .iter()
+        .map(|trade| (trade.order_id, trade.tag))
+        .collect();
+    assert_eq!(trades, vec![(2, buy_tag), (1, sell_tag)]);
+    assert_eq!(sell_tag.0[15], 0xcd);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+/// Trades 4 at 100 at 1s, 1 at 110 at 2s, and 5 at 104 at 5s
+fn new_tape() -> Arc<TradeHistory> {
+    let tape = Arc::new(TradeHistory::new(10));
+    let (_book, engine) = TestEngine::new().with_syncer(tape.clone()).build();
+    let clock = Arc::new(ManualClock::new(0));
+    let engine = engine.with_clock(clock.clone());
+    for (id, price, qty, at) in [
+        (1, 100, 4, 1_000_000),
+        (3, 110, 1, 2_000_000),
+        (5, 104, 5, 5_000_000),
+    ] {
+        clock.set(at);
+        engine
+            .create_order(&mut make_limit_order(id, Side::Sell, price, qty, id))
+            .unwrap();
+        engine
+            .create_order(&mut make_limit_order(id + 1, Side::Buy, price, qty, id + 1))
+            .unwrap();
+        engine.match_orders();
+    }
+    tape
+}
+
+#[test]
+fn test_vwap_weights_prints_by_volume() {
+    let tape = new_tape();
+    assert_eq!(tape.prints(TapeWindow::Last(10)).len(), 3);
+    // (400 + 110 + 520) / 10
+    assert_eq!(tape.vwap(TapeWindow::Last(3)), Some(Price::from(103u64)));
+    assert_eq!(tape.vwap(TapeWindow::Last(2)), Some(Price::from(105u64)));
+    assert_eq!(
+        tape.vwap(TapeWindow::Range {
+            from: 1_500_000,
+            to: 2_500_000
+        }),
+        Some(Price::from(110u64))
+    );
+    assert_eq!(tape.vwap(TapeWindow::Range { from: 0, to: 10 }), None);
+}
+
+#[test]
+fn test_twap_weights_prices_by_how_long_they_held() {
+    let tape = new_tape();
+    // 100 for 1s and 110 for 3s
+    assert_eq!(tape.twap(TapeWindow::Last(3)), Some(Price::from(107u64)));
+    // 100 carried in for 1s, 110 for 3s, then 104 for 4s
+    assert_eq!(
+        tape.twap(TapeWindow::Range {
+            from: 1_000_000,
+            to: 9_000_000
+        }),
+        Some(Price::from(105u64))
+    );
+    assert_eq!(
+        tape.twap(TapeWindow::Range {
+            from: 1_000_001,
+            to: 2_000_000
+        }),
+        Some(Price::from(100u64))
+    );
+    // A single print holds no time and is its own average
+    assert_eq!(tape.twap(TapeWindow::Last(1)), Some(Price::from(104u64)));
+    assert_eq!(TradeHistory::new(0).twap(TapeWindow::Last(5)), None);
+}
+
+#[test]
+fn test_corrections_and_rescales_reach_the_tape() {
+    let tape = Arc::new(TradeHistory::new(10));
+    let (_book, engine) = TestEngine::new().with_syncer(tape.clone()).build();
+    let engine = engine.with_trade_journal(16);
+    for (id, price, qty) in [(1, 100, 4), (3, 110, 1), (5, 120, 5)] {
+        engine
+            .create_order(&mut make_limit_order(id, Side::Sell, price, qty, id))
+            .unwrap();
+        engine
+            .create_order(&mut make_limit_order(id + 1, Side::Buy, price, qty, id + 1))
+            .unwrap();
+        engine.match_orders();
+    }
+    let prints = tape.prints(TapeWindow::Last(10));
+
+    engine.bust_trade(prints[1].trade_id).unwrap();
+    engine
+        .correct_trade_price(prints[2].trade_id, Price::from(100u64))
+        .unwrap();
+    // (400 + 500) / 9
+    assert_eq!(tape.vwap(TapeWindow::Last(10)), Some(Price::from(100u64)));
+
+    engine.set_mode(EngineMode::Halted);
+    engine
+        .rescale(Rescale {
+            price: ScaleFactor::new(1, 10).unwrap(),
+            quantity: ScaleFactor::new(10, 1).unwrap(),
+        })
+        .unwrap();
+    assert_eq!(
+        tape.prints(TapeWindow::Last(10))
+            .iter()
+            .map(|print| (print.trade_id, print.price, print.quantity))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                prints[0].trade_id,
+                Price::from(10u64),
+                Quantity::from(40u64)
+            ),
+            (
+                prints[2].trade_id,
+                Price::from(10u64),
+                Quantity::from(50u64)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_tape_retention_is_separate_from_user_retention() {
+    let history = Arc::new(TradeHistory::new(1).with_tape_retention(3));
+    let (_book, engine) = TestEngine::new().with_syncer(history.clone()).build();
+    for id in [1, 3, 5] {
+        engine
+            .create_order(&mut make_limit_order(id, Side::Sell, 100, 1, id))
+            .unwrap();
+        engine
+            .create_order(&mut make_limit_order(id + 1, Side::Buy, 100, 1, id + 1))
+            .unwrap();
+        engine.match_orders();
+    }
+
+    assert_eq!(history.trades_for_user(1, 10).len(), 1);
+    assert_eq!(history.prints(TapeWindow::Last(10)).len(), 3);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn user_order(id: u64, user_id: u64, side: Side, price: u64, qty: u64, ts: u64) -> Order {
+    let mut order = make_limit_order(id, side, price, qty, ts);
+    order.user_id = user_id;
+    order
+}
+
+#[test]
+fn test_transfer_keeps_priority() {
+    let recorder = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new().with_syncer(recorder.clone()).build();
+    let mut orders = [
+        user_order(1, 7, Side::Buy, 100, 5, 1000),
+        user_order(2, 8, Side::Buy, 100, 5, 1001),
+        user_order(3, 7, Side::Sell, 110, 5, 1002),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+
+    let mut transferred = engine.transfer_orders(7, 9);
+    transferred.sort();
+    assert_eq!(transferred, vec![1, 3]);
+    assert!(engine.open_orders(7).is_empty());
+    assert_eq!(engine.open_orders(9).len(), 2);
+    let updated = recorder.updated();
+    assert_eq!(updated.len(), 2);
+    assert!(updated.iter().all(|order| order.user_id == 9));
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(5u64)), (2, Quantity::from(5u64))]
+    );
+
+    // The transferred order keeps its place at the front of the level
+    let mut sell = user_order(4, 10, Side::Sell, 100, 5, 2000);
+    engine.create_order(&mut sell).unwrap();
+    engine.match_orders();
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(2, Quantity::from(5u64))]
+    );
+}
+
+#[test]
+fn test_transfer_of_user_without_orders() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut order = user_order(1, 7, Side::Buy, 100, 5, 1000);
+    engine.create_order(&mut order).unwrap();
+    assert!(engine.transfer_orders(8, 9).is_empty());
+    assert_eq!(engine.open_orders(7).len(), 1);
+}
+
+#[test]
+fn test_transfer_refused_when_halted_or_frozen() {
+    let (_book, engine) = TestEngine::new().build();
+    let mut order = user_order(1, 7, Side::Buy, 100, 5, 1000);
+    engine.create_order(&mut order).unwrap();
+
+    engine.freeze_user(7);
+    assert!(engine.transfer_orders(7, 9).is_empty());
+    engine.unfreeze_user(7);
+    engine.freeze_user(9);
+    assert!(engine.transfer_orders(7, 9).is_empty());
+    engine.unfreeze_user(9);
+    engine.set_mode(EngineMode::Halted);
+    assert!(engine.transfer_orders(7, 9).is_empty());
+    assert_eq!(engine.open_orders(7).len(), 1);
+
+    engine.set_mode(EngineMode::Normal);
+    assert_eq!(engine.transfer_orders(7, 9), vec![1]);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use std::sync::Arc;
+
+fn new_engine() -> (Arc<ManualReferencePrice>, DefaultMatchingEngine) {
+    let reference = Arc::new(ManualReferencePrice::new());
+    let (_book, engine) = TestEngine::new().build();
+    let engine = engine.with_reference_price(reference.clone());
+    (reference, engine)
+}
+
+#[test]
+fn test_stops_evaluate_their_own_source() {
+    let (_reference, engine) = new_engine();
+    let triggers = TriggerEngine::new();
+    let mut ask = make_limit_order(1, Side::Sell, 105, 10, 1000);
+    engine.create_order(&mut ask).unwrap();
+
+    triggers.submit(StopOrder::new(
+        make_limit_order(10, Side::Buy, 110, 1, 1001),
+        Price::from(105u64),
+        PriceSource::LastTrade,
+    ));
+    triggers.submit(StopOrder::new(
+        make_limit_order(11, Side::Buy, 110, 1, 1002),
+        Price::from(105u64),
+        PriceSource::BestAsk,
+    ));
+    let released = triggers.evaluate(&engine);
+    assert_eq!(released, vec![(11, Ok(()))]);
+    assert_eq!(triggers.pending(), 1);
+
+    engine.match_orders();
+    assert!(engine.last_trade_price().is_some());
+    assert_eq!(triggers.evaluate(&engine), vec![(10, Ok(()))]);
+    assert_eq!(triggers.pending(), 0);
+}
+
+#[test]
+fn test_sell_stop_on_mark_price() {
+    let (reference, engine) = new_engine();
+    let triggers = TriggerEngine::new();
+    triggers.submit(StopOrder::new(
+        make_market_order(20, Side::Sell, 5, 1000),
+        Price::from(95u64),
+        PriceSource::Mark,
+    ));
+    assert!(triggers.evaluate(&engine).is_empty());
+    reference.set_mark(Some(Price::from(96u64)));
+    assert!(triggers.evaluate(&engine).is_empty());
+    reference.set_mark(Some(Price::from(95u64)));
+    assert_eq!(triggers.evaluate(&engine), vec![(20, Ok(()))]);
+    assert_eq!(engine.snapshot().market_orders[0].id, 20);
+}
+
+#[test]
+fn test_cancel_pending_stop() {
+    let (_reference, engine) = new_engine();
+    let triggers = TriggerEngine::new();
+    triggers.submit(StopOrder::new(
+        make_limit_order(30, Side::Buy, 100, 1, 1000),
+        Price::from(90u64),
+        PriceSource::BestBid,
+    ));
+    assert_eq!(triggers.cancel(30).map(|stop| stop.order.id), Some(30));
+    assert!(triggers.cancel(30).is_none());
+    let mut bid = make_limit_order(1, Side::Buy, 95, 1, 1001);
+    engine.create_order(&mut bid).unwrap();
+    assert!(triggers.evaluate(&engine).is_empty());
+}
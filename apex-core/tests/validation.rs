@@ -0,0 +1,273 @@
+mod common;
+
+use crate::common::*;
+use apex_core::prelude::*;
+use crypto_bigint::NonZero;
+use std::sync::Arc;
+
+fn new_engine(config: BookConfig) -> (Arc<Recorder>, Arc<DefaultOrderBook>, DefaultMatchingEngine) {
+    let syncer = Arc::new(Recorder::default());
+    let (book, engine) = TestEngine::new()
+        .with_syncer(syncer.clone())
+        .with_config(config)
+        .build();
+    (syncer, book, engine)
+}
+
+#[test]
+fn test_create_order_rejects_invalid_order() {
+    let (syncer, book, engine) = new_engine(BookConfig::default());
+
+    let mut order = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    order.time_in_force = TimeInForce::None;
+    let reason = RejectReason::InvalidOrder(OrderValidationError::InvalidTimeInForce);
+    assert_eq!(engine.create_order(&mut order), Err(reason));
+    assert_eq!(order.status(), OrderStatus::Rejected);
+    assert_eq!(order.reject_reason(), Some(reason));
+
+    let mut market = make_market_order(2, Side::Buy, 10, 1001);
+    market.match_strategy = MatchStrategy::Standard;
+    assert_eq!(
+        engine.create_order(&mut market),
+        Err(RejectReason::InvalidOrder(
+            OrderValidationError::InvalidMatchStrategy
+        ))
+    );
+
+    assert!(get_book_state(book.as_ref(), Side::Buy).is_empty());
+    assert_eq!(order_ids(&syncer.rejected()), vec![1, 2]);
+    assert_eq!(syncer.added().len(), 0);
+}
+
+#[test]
+fn test_create_orders_rejects_invalid_orders_in_place() {
+    let (syncer, book, engine) = new_engine(BookConfig::default());
+
+    let mut invalid = make_limit_order(2, Side::Buy, 101, 10, 1001);
+    invalid.slippage_tolerance = Some(SlippageTolerance(5));
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 100, 10, 1000),
+        invalid,
+        make_limit_order(3, Side::Buy, 102, 10, 1002),
+    ];
+    let results = engine.create_orders(&mut orders);
+    assert_eq!(
+        results,
+        vec![
+            Ok(()),
+            Err(RejectReason::InvalidOrder(
+                OrderValidationError::SlippageNotApplicable
+            )),
+            Ok(()),
+        ]
+    );
+    assert_eq!(orders[0].status(), OrderStatus::Placed);
+    assert_eq!(orders[1].status(), OrderStatus::Rejected);
+    assert_eq!(orders[2].status(), OrderStatus::Placed);
+
+    assert_eq!(get_book_state(book.as_ref(), Side::Buy).len(), 2);
+    assert_eq!(order_ids(&syncer.rejected()), vec![2]);
+}
+
+#[test]
+fn test_duplicate_order_id_rejection_is_synced() {
+    let (syncer, _book, engine) = new_engine(BookConfig::default());
+
+    let mut first = make_limit_order(1, Side::Buy, 100, 10, 1000);
+    let mut duplicate = make_limit_order(1, Side::Buy, 100, 10, 1001);
+    engine.create_order(&mut first).unwrap();
+    assert!(engine.create_order(&mut duplicate).is_err());
+    assert_eq!(order_ids(&syncer.rejected()), vec![1]);
+}
+
+#[test]
+fn test_create_order_rejects_zero_quantity_and_price() {
+    let (syncer, _book, engine) = new_engine(BookConfig::default());
+
+    let mut empty = make_limit_order(1, Side::Buy, 100, 0, 1000);
+    assert_eq!(
+        engine.create_order(&mut empty),
+        Err(RejectReason::ZeroQuantity)
+    );
+    assert_eq!(empty.reject_reason(), Some(RejectReason::ZeroQuantity));
+
+    let mut free = make_limit_order(2, Side::Sell, 0, 10, 1001);
+    assert_eq!(
+        engine.create_order(&mut free),
+        Err(RejectReason::InvalidPrice)
+    );
+    assert_eq!(order_ids(&syncer.rejected()), vec![1, 2]);
+}
+
+#[test]
+fn test_create_order_enforces_book_config() {
+    let (syncer, _book, engine) = new_engine(BookConfig {
+        min_price: Some(Price::from(90u64)),
+        max_price: Some(Price::from(110u64)),
+        max_resting_orders: Some(2),
+        ..BookConfig::default()
+    });
+
+    let mut low = make_limit_order(1, Side::Buy, 89, 10, 1000);
+    let mut high = make_limit_order(2, Side::Sell, 111, 10, 1001);
+    assert_eq!(
+        engine.create_order(&mut low),
+        Err(RejectReason::PriceOutOfBand)
+    );
+    assert_eq!(
+        engine.create_order(&mut high),
+        Err(RejectReason::PriceOutOfBand)
+    );
+
+    let mut orders = [
+        make_limit_order(3, Side::Buy, 90, 10, 1002),
+        make_limit_order(4, Side::Sell, 110, 10, 1003),
+    ];
+    for order in orders.iter_mut() {
+        engine.create_order(order).unwrap();
+    }
+    let mut overflow = make_limit_order(5, Side::Buy, 95, 10, 1004);
+    assert_eq!(
+        engine.create_order(&mut overflow),
+        Err(RejectReason::CapacityExceeded)
+    );
+    assert_eq!(overflow.status(), OrderStatus::Rejected);
+    assert_eq!(order_ids(&syncer.rejected()), vec![1, 2, 5]);
+}
+
+#[test]
+fn test_create_order_rejects_fat_finger_orders() {
+    let (syncer, _book, engine) = new_engine(BookConfig {
+        max_order_quantity: Some(Quantity::from(100u64)),
+        max_notional: Some(Quantity::from(5000u64)),
+        ..BookConfig::default()
+    });
+
+    let mut large = make_limit_order(1, Side::Buy, 10, 101, 1000);
+    assert_eq!(
+        engine.create_order(&mut large),
+        Err(RejectReason::QuantityTooLarge)
+    );
+    let mut costly = make_limit_order(2, Side::Buy, 51, 100, 1001);
+    assert_eq!(
+        engine.create_order(&mut costly),
+        Err(RejectReason::NotionalTooLarge)
+    );
+    let mut at_limit = make_limit_order(3, Side::Sell, 50, 100, 1002);
+    assert_eq!(engine.create_order(&mut at_limit), Ok(()));
+
+    let mut market = make_market_order(4, Side::Buy, 100, 1003);
+    assert_eq!(engine.create_order(&mut market), Ok(()));
+    let mut market = make_market_order(5, Side::Sell, 100, 1004);
+    assert_eq!(engine.create_order(&mut market), Ok(()));
+    let mut resting = make_limit_order(6, Side::Buy, 60, 50, 1005);
+    engine.create_order(&mut resting).unwrap();
+    let mut market = make_market_order(7, Side::Sell, 90, 1006);
+    assert_eq!(
+        engine.create_order(&mut market),
+        Err(RejectReason::NotionalTooLarge)
+    );
+    assert_eq!(order_ids(&syncer.rejected()), vec![1, 2, 7]);
+}
+
+#[test]
+fn test_amends_respect_size_lot_and_notional_limits() {
+    let (_syncer, book, engine) = new_engine(BookConfig {
+        lot_size: NonZero::new(Quantity::from(10u64)).into(),
+        max_order_quantity: Some(Quantity::from(100u64)),
+        max_notional: Some(Quantity::from(5000u64)),
+        ..BookConfig::default()
+    });
+    let mut resting = make_limit_order(1, Side::Buy, 40, 50, 1000);
+    engine.create_order(&mut resting).unwrap();
+
+    let reason = |result: Result<(), UpdateOrderError>| result.unwrap_err().reason();
+    assert_eq!(
+        reason(engine.amend_quantity(1, Quantity::from(110u64), 1001)),
+        Some(AmendFailure::QuantityTooLarge)
+    );
+    assert_eq!(
+        reason(engine.amend_quantity(1, Quantity::from(55u64), 1002)),
+        Some(AmendFailure::OffLot)
+    );
+    let request = AmendRequest {
+        price: Some(Price::from(60u64)),
+        quantity: Some(Quantity::from(90u64)),
+        ..Default::default()
+    };
+    assert_eq!(
+        reason(engine.amend_order(1, request, 1003)),
+        Some(AmendFailure::NotionalTooLarge)
+    );
+    // A new price alone is valued at the resting quantity
+    assert_eq!(
+        reason(engine.update_order(1, Price::from(110u64), 1004)),
+        Some(AmendFailure::NotionalTooLarge)
+    );
+
+    // Batched amends are checked the same way
+    let mut commands = [
+        Command::Amend {
+            order_id: 1,
+            new_quantity: Quantity::from(110u64),
+            now_microseconds: 1005,
+        },
+        Command::Amend {
+            order_id: 1,
+            new_quantity: Quantity::from(100u64),
+            now_microseconds: 1006,
+        },
+    ];
+    let results = engine.execute_batch(&mut commands);
+    assert!(matches!(
+        &results[0],
+        CommandResult::Updated(Err(error)) if error.reason() == Some(AmendFailure::QuantityTooLarge)
+    ));
+    assert_eq!(results[1], CommandResult::Updated(Ok(())));
+    assert_eq!(
+        get_book_state(book.as_ref(), Side::Buy),
+        vec![(1, Quantity::from(100u64))]
+    );
+}
+
+#[test]
+fn test_batches_respect_max_resting_orders() {
+    let (syncer, book, engine) = new_engine(BookConfig {
+        max_resting_orders: Some(3),
+        ..BookConfig::default()
+    });
+
+    // Market orders never rest, so they take no room in the batch
+    let mut orders = [
+        make_limit_order(1, Side::Buy, 90, 10, 1000),
+        make_market_order(2, Side::Buy, 10, 1001),
+        make_limit_order(3, Side::Sell, 110, 10, 1002),
+    ];
+    assert_eq!(engine.create_orders(&mut orders), vec![Ok(()); 3]);
+
+    let mut commands = [
+        Command::Create(make_limit_order(4, Side::Buy, 91, 10, 1003)),
+        Command::Create(make_limit_order(5, Side::Buy, 92, 10, 1004)),
+    ];
+    assert_eq!(
+        engine.execute_batch(&mut commands),
+        vec![
+            CommandResult::Created(Ok(())),
+            CommandResult::Created(Err(RejectReason::CapacityExceeded))
+        ]
+    );
+    assert_eq!(
+        book.get_book(Side::Buy).len() + book.get_book(Side::Sell).len(),
+        3
+    );
+
+    let mut orders = [
+        make_limit_order(6, Side::Sell, 111, 10, 1005),
+        make_limit_order(7, Side::Sell, 112, 10, 1006),
+    ];
+    assert_eq!(
+        engine.create_orders(&mut orders),
+        vec![Err(RejectReason::CapacityExceeded); 2]
+    );
+    assert_eq!(order_ids(&syncer.rejected()), vec![5, 6, 7]);
+}